@@ -0,0 +1,23 @@
+return {
+  name = "player",
+  size = mk.structure.Size.new(16, 16),
+  components = {
+    glyph_renderer = {
+      layer = mk.gfx.Layer.new(1),
+      order = 1,
+      color = mk.gfx.Color.from_rgb(0 / 255, 150 / 255, 105 / 255),
+      shader = mk.asset.load_shader("glyph"),
+      font = mk.asset.load_font("Courier Prime Sans"),
+      font_size = 16,
+      thickness = 0.5,
+      smoothness = 2 / 16,
+      text = "@",
+      config = mk.gfx.GlyphLayoutConfig.new(
+        mk.gfx.HorizontalAlign.Center,
+        mk.gfx.VerticalAlign.Middle,
+        mk.gfx.WrapStyle.Word,
+        true
+      ),
+    },
+  },
+}
@@ -1,5 +1,5 @@
 
-local theme = require("assets/scripts/editor/theme")
+local theme = require("editor.theme")
 
 local camera = mk.entity.Entity.find_by_name("camera")
 camera.camera.clear_color = theme.background
@@ -23,7 +23,11 @@ print(game.map)
 
 -- event.unlisten(listener)
 
-local camera = require("assets/scripts/camera")
+local camera = require("camera")
+
+mk.input.load_bindings("default")
+mk.input.set_default_bindings()
+mk.input.load_user_bindings("bindings")
 
 local shader = mk.asset.load_shader("sprite")
 local sprite = mk.asset.load_sprite("arrow")
@@ -81,10 +85,12 @@ sprite_renderer:emit("test", {
 --     :build()
 -- end
 
--- local ui_status_indicator = require("assets/scripts/ui/ui-status-indicator")
-require("assets/scripts/utils/fps-counter")
+-- local ui_status_indicator = require("ui.ui-status-indicator")
+require("utils.fps-counter")
+require("utils.error-overlay")
+require("utils.console")
 
--- local generate_map = require("assets/scripts/map-gen/generate")
+-- local generate_map = require("map-gen.generate")
 -- local map = generate_map(100, 100)
 
 -- mk.entity.EntityBuilder.new()
@@ -111,7 +117,7 @@ require("assets/scripts/utils/fps-counter")
 --   })
 --   :build()
 
--- local player = require("assets/scripts/player")
+-- local player = require("player")
 
 -- player.entity.transform.position = mk.structure.Vec2.new(
 --   (map.rooms[1].x + map.rooms[1].width / 2) * 16,
@@ -119,54 +125,3 @@ require("assets/scripts/utils/fps-counter")
 -- )
 
 -- camera.transform.parent = player.entity.transform
-
--- local keys = require("assets/scripts/keys")
-
--- local player_movement = mk.input.Action.new({
---   name = "PlayerMovement",
---   triggers = {}
--- })
--- player_movement.add_trigger(mk.input.Keyboard.key_down("ArrowLeft"))
-
--- local player_movement = mk.input.Action.new({
---   name = "PlayerMovement",
---   triggers = {
---     mk.input.Keyboard.key_down("ArrowLeft"),
---     mk.input.Keyboard.key_stay("ArrowLeft"),
---     mk.input.Keyboard.key_up("ArrowLeft"),
-
---     mk.input.Gamepad.button_down("DPadLeft"),
---     mk.input.Gamepad.button_stay("DPadLeft"),
---     mk.input.Gamepad.button_up("DPadLeft"),
---     mk.input.Gamepad.axis_1d("DPadHorizontal"),
---     mk.input.Gamepad.axis_2d("DPad"),
---     mk.input.Gamepad.axis_2d("DPadHorizontal", "DPadVertical"),
-
---     mk.input.Touch.tap(),
---     mk.input.Touch.long_tap(),
---     mk.input.Touch.double_tap(),
---     mk.input.Touch.slide(),
-
---     mk.input.Complex.all({
---       mk.input.Keyboard.key_down("ArrowLeft"),
---       mk.input.Keyboard.key_down("ArrowRight"),
---     }),
---     mk.input.Complex.any({
---       mk.input.Keyboard.key_down("ArrowLeft"),
---       mk.input.Keyboard.key_down("ArrowRight"),
---     }),
---     mk.input.Complex.sequence({
---       mk.input.Keyboard.key_down("ArrowLeft"),
---       mk.input.Keyboard.key_down("ArrowRight"),
---     }),
---   },
--- })
-
--- local action_map = mk.input.ActionMap.new()
--- action_map.add_action(player_movement)
-
--- local input = mk.input.Input.new()
--- input.bind_action_map("Field", action_map)
-
--- input.activate("Field")
--- input.activate({"Field", "Menu"})
@@ -1,5 +1,5 @@
 
-local camera = require("assets/scripts/camera")
+local camera = require("camera")
 local ui_root = mk.entity.EntityBuilder.new()
   :name("ui-root")
   :transform_parent(camera.transform)
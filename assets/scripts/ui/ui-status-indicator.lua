@@ -12,7 +12,7 @@ local font_size = 16
 --   })
 --   :build()
 
-local ui_root = require("assets/scripts/ui/ui-root")
+local ui_root = require("ui.ui-root")
 local background = mk.entity.EntityBuilder.new()
   :transform_parent(ui_root.transform)
   :ui_element({
@@ -0,0 +1,136 @@
+-- Developer console. Press ` to toggle it, Tab to complete, Up and Down to recall lines.
+
+local font_size = 14
+local output_lines = 30
+
+local ui_root = require("ui.ui-root")
+
+local function glyph_renderer(color)
+  return {
+    layer = mk.gfx.Layer.new(1),
+    order = 1000001,
+    color = color,
+    shader = mk.asset.load_shader("glyph"),
+    font = mk.asset.load_font("Courier Prime Sans"),
+    font_size = font_size,
+    thickness = 0.5,
+    smoothness = 2 / font_size,
+    config = mk.gfx.GlyphLayoutConfig.new(
+      mk.gfx.HorizontalAlign.Left,
+      mk.gfx.VerticalAlign.Bottom,
+      mk.gfx.WrapStyle.Word,
+      true
+    ),
+  }
+end
+
+local output = mk.entity.EntityBuilder.new()
+  :name("console-output")
+  :transform_parent(ui_root.transform)
+  :diagnostic(true)
+  :ui_element({
+    anchor = mk.ui.Anchor.new(
+      mk.structure.Vec2.new(0, 0.5),
+      mk.structure.Vec2.new(1, 1)
+    ),
+    margin = mk.ui.Margin.new(15, 15, 15, font_size * 2),
+    is_interactible = false,
+    order_index = 1000001,
+  })
+  :glyph_renderer(glyph_renderer(mk.gfx.Color.white()))
+  :build()
+
+local input = mk.entity.EntityBuilder.new()
+  :name("console-input")
+  :transform_parent(ui_root.transform)
+  :diagnostic(true)
+  :ui_element({
+    anchor = mk.ui.Anchor.new(
+      mk.structure.Vec2.new(0, 0.5),
+      mk.structure.Vec2.new(1, 0.5)
+    ),
+    margin = mk.ui.Margin.new(15, -font_size * 2, 15, 0),
+    is_interactible = false,
+    order_index = 1000002,
+  })
+  :glyph_renderer(glyph_renderer(mk.gfx.Color.from_rgb(1, 1, 0.6)))
+  :text_field({ max_length = 256 })
+  :build()
+
+local function refresh()
+  if mk.console.is_open() then
+    output.glyph_renderer.text = mk.console.output(output_lines)
+  else
+    output.glyph_renderer.text = ""
+  end
+end
+
+local function set_line(line)
+  input.text_field.text = line
+  input.text_field.caret_index = #line
+end
+
+local function toggle()
+  local is_open = mk.console.toggle()
+  input.ui_element.is_interactible = is_open
+  set_line("")
+
+  if is_open then
+    input.text_field:focus()
+  else
+    input.text_field:blur()
+  end
+
+  refresh()
+end
+
+input:listen("ui-text-change", function(entity, name, event)
+  -- The toggle key types its character into the field before it is handled.
+  local line = event.text:gsub("`", "")
+  if line ~= event.text then
+    set_line(line)
+  end
+end)
+
+input:listen("ui-text-submit", function(entity, name, event)
+  set_line("")
+  mk.console.execute(event.text)
+  refresh()
+end)
+
+-- Handled before gameplay, which does not see the keys typed into the console.
+mk.event.KeyDown.listen(function(name, event)
+  if event.key == "grave" then
+    toggle()
+    mk.event.consume()
+    return
+  end
+
+  if not mk.console.is_open() then
+    return
+  end
+
+  mk.event.consume()
+
+  if event.key == "tab" then
+    local completed, candidates = mk.console.complete(input.text_field.text)
+    if 1 < #candidates then
+      mk.console.print(table.concat(candidates, "  "))
+    end
+    set_line(completed)
+  elseif event.key == "up" then
+    local line = mk.console.previous_history()
+    if line ~= nil then
+      set_line(line)
+    end
+  elseif event.key == "down" then
+    set_line(mk.console.next_history() or "")
+  end
+
+  refresh()
+end, { priority = 1000 })
+
+-- Diagnostics are printed to the console as they are emitted.
+mk.event.Diagnostic.listen(function(name, event)
+  refresh()
+end)
@@ -0,0 +1,60 @@
+-- Shows the script error that paused the game. Press F5 to resume.
+
+local font_size = 14
+
+mk.diagnostic.set_pause_on_script_error(true)
+
+local ui_root = require("ui.ui-root")
+local overlay = mk.entity.EntityBuilder.new()
+  :name("error-overlay")
+  :transform_parent(ui_root.transform)
+  :diagnostic(true)
+  :ui_element({
+    anchor = mk.ui.Anchor.full(),
+    margin = mk.ui.Margin.new(15, 15, 15, 15),
+    is_interactible = false,
+    order_index = 1000000,
+  })
+  :glyph_renderer({
+    layer = mk.gfx.Layer.new(1),
+    order = 1000000,
+    color = mk.gfx.Color.from_rgb(1, 0.4, 0.4),
+    shader = mk.asset.load_shader("glyph"),
+    font = mk.asset.load_font("Courier Prime Sans"),
+    font_size = font_size,
+    thickness = 0.5,
+    smoothness = 2 / font_size,
+    config = mk.gfx.GlyphLayoutConfig.new(
+      mk.gfx.HorizontalAlign.Left,
+      mk.gfx.VerticalAlign.Top,
+      mk.gfx.WrapStyle.Word,
+      true
+    ),
+  })
+  :build()
+
+local function show(diagnostic)
+  if diagnostic == nil then
+    overlay.glyph_renderer.text = ""
+    return
+  end
+
+  local text = diagnostic.message
+  if diagnostic.file ~= "" then
+    text = text .. "\n  at " .. diagnostic.file .. ":" .. diagnostic.line
+  end
+  if diagnostic.traceback ~= nil then
+    text = text .. "\n\n" .. diagnostic.traceback
+  end
+  overlay.glyph_renderer.text = text .. "\n\n[F5] resume"
+end
+
+mk.event.Diagnostic.listen(function(name, event)
+  show(mk.diagnostic.script_error())
+end)
+
+mk.event.KeyDown.listen(function(name, event)
+  if event.key == "f5" and mk.diagnostic.resume() then
+    show(nil)
+  end
+end)
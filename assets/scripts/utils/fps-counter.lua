@@ -1,8 +1,8 @@
-local Queue = require('assets/scripts/collections/queue')
+local Queue = require('collections.queue')
 
 local font_size = 16
 
-local ui_root = require("assets/scripts/ui/ui-root")
+local ui_root = require("ui.ui-root")
 local indicator = mk.entity.EntityBuilder.new()
   :name("fps-indicator")
   :transform_parent(ui_root.transform)
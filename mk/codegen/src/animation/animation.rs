@@ -1,7 +1,7 @@
 use convert_case::{Case, Casing};
 use proc_macro::TokenStream;
 use proc_macro_error::*;
-use quote::{format_ident, quote, ToTokens, __private::TokenStream as QuoteTokenStream};
+use quote::{__private::TokenStream as QuoteTokenStream, format_ident, quote, ToTokens};
 use std::collections::HashSet;
 use syn::parse::{Parse, ParseStream};
 use syn::token::Comma;
@@ -1,3 +1,4 @@
+use crate::lua::{doc_string, lua_type_name};
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
@@ -16,6 +17,7 @@ pub fn event(item: TokenStream) -> TokenStream {
 
     let ty_name = &derive.ident;
     let ty_name_str = ty_name.to_string();
+    let ty_doc = doc_string(&derive.attrs);
     let event_name = &derive
         .attrs
         .iter()
@@ -25,10 +27,11 @@ pub fn event(item: TokenStream) -> TokenStream {
                 .expect("invalid event_name")
                 .name
         })
-        .unwrap_or_else(|| ty_name_str);
+        .unwrap_or_else(|| ty_name_str.clone());
 
     let mut event_field_impls = Vec::new();
     let mut lua_event_field_impls = Vec::new();
+    let mut field_metas = Vec::new();
 
     for field in &input.fields {
         if let Some(ident) = &field.ident {
@@ -39,6 +42,17 @@ pub fn event(item: TokenStream) -> TokenStream {
             lua_event_field_impls.push(quote! {
                 table.set(#ident_str, self.#ident.clone())?;
             });
+
+            let lua_ty = lua_type_name(&field.ty, &ty_name_str);
+            let doc = doc_string(&field.attrs);
+            field_metas.push(quote! {
+                crate::script::LuaFieldMeta {
+                    name: #ident_str,
+                    ty: #lua_ty,
+                    doc: #doc,
+                    is_readonly: true,
+                }
+            });
         }
     }
 
@@ -69,6 +83,17 @@ pub fn event(item: TokenStream) -> TokenStream {
                 #event_name
             }
         }
+
+        impl crate::script::LuaEventMetaProvider for #ty_name {
+            fn lua_event_meta() -> crate::script::LuaEventMeta {
+                crate::script::LuaEventMeta {
+                    name: #ty_name_str,
+                    event_name: #event_name,
+                    doc: #ty_doc,
+                    fields: &[#(#field_metas),*],
+                }
+            }
+        }
     })
 }
 
@@ -0,0 +1,102 @@
+use syn::{Attribute, GenericArgument, Lit, Meta, PathArguments, ReturnType, Type};
+
+/// Joins the doc comments of the item into a string, one line per comment.
+pub fn doc_string(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(meta)) => match meta.lit {
+                Lit::Str(str) => Some(str.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_owned).unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the name of the type as seen from Lua, in the LuaLS annotation syntax,
+/// e.g. `number?` for `Option<f32>`. `Self` is replaced with the given name.
+pub fn lua_type_name(ty: &Type, self_name: &str) -> String {
+    match ty {
+        Type::Reference(reference) => lua_type_name(&reference.elem, self_name),
+        Type::Paren(paren) => lua_type_name(&paren.elem, self_name),
+        Type::Group(group) => lua_type_name(&group.elem, self_name),
+        Type::Array(array) => format!("{}[]", lua_type_name(&array.elem, self_name)),
+        Type::Slice(slice) => format!("{}[]", lua_type_name(&slice.elem, self_name)),
+        Type::Tuple(tuple) if tuple.elems.is_empty() => "nil".to_owned(),
+        Type::Path(path) => {
+            let segment = match path.path.segments.last() {
+                Some(segment) => segment,
+                None => return "any".to_owned(),
+            };
+            let ident = segment.ident.to_string();
+
+            match ident.as_str() {
+                "f32" | "f64" => "number".to_owned(),
+                "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" => {
+                    "integer".to_owned()
+                }
+                "bool" => "boolean".to_owned(),
+                "char" | "str" | "String" | "SmartString" => "string".to_owned(),
+                "Self" => self_name.to_owned(),
+                "Option" => format!(
+                    "{}?",
+                    first_generic_lua_type_name(&segment.arguments, self_name)
+                ),
+                "Vec" | "VecDeque" => {
+                    format!(
+                        "{}[]",
+                        first_generic_lua_type_name(&segment.arguments, self_name)
+                    )
+                }
+                _ => ident,
+            }
+        }
+        _ => "any".to_owned(),
+    }
+}
+
+/// Returns the name of the type the function returns to Lua, or `None` if it returns nothing.
+/// The error of a fallible function is raised rather than returned.
+pub fn lua_return_type_name(
+    output: &ReturnType,
+    is_fallible: bool,
+    self_name: &str,
+) -> Option<String> {
+    let ty = match output {
+        ReturnType::Default => return None,
+        ReturnType::Type(_, ty) => ty.as_ref(),
+    };
+    let name = match (is_fallible, ty) {
+        (true, Type::Path(path)) => match path.path.segments.last() {
+            Some(segment) if segment.ident.to_string().ends_with("Result") => {
+                first_generic_lua_type_name(&segment.arguments, self_name)
+            }
+            _ => lua_type_name(ty, self_name),
+        },
+        _ => lua_type_name(ty, self_name),
+    };
+
+    if name == "nil" {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+fn first_generic_lua_type_name(arguments: &PathArguments, self_name: &str) -> String {
+    match arguments {
+        PathArguments::AngleBracketed(arguments) => arguments
+            .args
+            .iter()
+            .find_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(lua_type_name(ty, self_name)),
+                _ => None,
+            })
+            .unwrap_or_else(|| "any".to_owned()),
+        _ => "any".to_owned(),
+    }
+}
@@ -33,6 +33,17 @@ impl Parse for LuaMethodParam {
     }
 }
 
+/// Returns the Lua names of the methods listed by the `lua_method` attributes.
+pub fn lua_method_names(input: &DeriveInput) -> Vec<String> {
+    input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("lua_method"))
+        .filter_map(|attr| syn::parse2::<LuaMethodParam>(attr.tokens.clone()).ok())
+        .map(|param| param.as_name.unwrap_or(param.method).to_string())
+        .collect()
+}
+
 pub fn impl_lua_method(input: &DeriveInput, impl_this: Option<TokenStream>) -> TokenStream {
     let impl_this = QuoteTokenStream::from(impl_this.unwrap_or_else(|| TokenStream::new()));
 
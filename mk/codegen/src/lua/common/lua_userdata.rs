@@ -1,4 +1,4 @@
-use crate::lua::impl_lua_method;
+use crate::lua::{doc_string, impl_lua_method, lua_method_names, lua_type_name};
 use proc_macro::TokenStream;
 use proc_macro_error::ResultExt;
 use quote::{__private::TokenStream as QuoteTokenStream, quote};
@@ -89,6 +89,7 @@ pub fn impl_lua_userdata(
 
     let mut field_getters = Vec::with_capacity(input.fields.len());
     let mut field_setters = Vec::with_capacity(input.fields.len());
+    let mut field_metas = Vec::with_capacity(input.fields.len());
 
     for field in fields {
         let field_ty = &field.ty;
@@ -144,6 +145,18 @@ pub fn impl_lua_userdata(
                 .map_or(false, |ident| ident == "lua_readonly")
         });
 
+        let lua_ty = lua_type_name(lua_user_type.as_ref().unwrap_or(field_ty), &ty_name_str);
+        let doc = doc_string(&field.attrs);
+        let is_readonly = lua_readonly || codegen_this_setter.is_none();
+        field_metas.push(quote! {
+            crate::script::LuaFieldMeta {
+                name: #lua_field_name,
+                ty: #lua_ty,
+                doc: #doc,
+                is_readonly: #is_readonly,
+            }
+        });
+
         if codegen_this_getter.is_some() {
             field_getters.push(FieldGetter {
                 lua_field_name: lua_field_name.clone(),
@@ -254,7 +267,32 @@ pub fn impl_lua_userdata(
         })
         .unwrap_or_else(|| quote! {});
 
+    let ty_doc = doc_string(&derive.attrs);
+    // The methods take their arguments as a `LuaMultiValue`, so their parameters are unknown.
+    let method_metas = lua_method_names(derive).into_iter().map(|name| {
+        quote! {
+            crate::script::LuaMethodMeta {
+                name: #name,
+                doc: "",
+                is_static: false,
+                params: &[crate::script::LuaParamMeta { name: "...", ty: "any" }],
+                returns: None,
+            }
+        }
+    });
+
     TokenStream::from(quote! {
+        impl crate::script::LuaUserDataMetaProvider for #ty_name {
+            fn lua_class_meta() -> crate::script::LuaClassMeta {
+                crate::script::LuaClassMeta {
+                    name: #ty_name_str,
+                    doc: #ty_doc,
+                    fields: &[#(#field_metas),*],
+                    methods: &[#(#method_metas),*],
+                }
+            }
+        }
+
         impl mlua::UserData for #ty_name {
             fn add_methods<'lua, M: mlua::UserDataMethods<'lua, Self>>(methods: &mut M) {
                 use mlua::{ExternalError, ToLua};
@@ -1,5 +1,7 @@
+mod lua_meta;
 mod lua_method;
 mod lua_userdata;
 
+pub use lua_meta::*;
 pub use lua_method::*;
 pub use lua_userdata::*;
@@ -1,3 +1,4 @@
+use super::doc_string;
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, Data, DeriveInput};
@@ -11,6 +12,13 @@ pub fn lua_enum(item: TokenStream) -> TokenStream {
     };
 
     let ty_name = &derive.ident;
+    let ty_name_str = ty_name.to_string();
+    let ty_doc = doc_string(&derive.attrs);
+    let variant_names = input
+        .variants
+        .iter()
+        .map(|variant| variant.ident.to_string())
+        .collect::<Vec<_>>();
     let mut to_string_impls = Vec::new();
     let mut api_table_impls = Vec::new();
 
@@ -49,8 +57,20 @@ pub fn lua_enum(item: TokenStream) -> TokenStream {
             }
         }
 
+        impl crate::script::LuaEnumMetaProvider for #ty_name {
+            fn lua_enum_meta() -> crate::script::LuaEnumMeta {
+                crate::script::LuaEnumMeta {
+                    name: #ty_name_str,
+                    doc: #ty_doc,
+                    variants: &[#(#variant_names),*],
+                    are_strings: false,
+                }
+            }
+        }
+
         impl crate::script::LuaApiTable for #ty_name {
             fn create_api_table<'lua>(lua: &'lua mlua::Lua) -> mlua::Result<mlua::Table<'lua>> {
+                crate::script::register_lua_enum::<Self>(lua);
                 let table = lua.create_table()?;
                 #(#api_table_impls)*
                 Ok(table)
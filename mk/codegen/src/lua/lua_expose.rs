@@ -1,3 +1,4 @@
+use super::{doc_string, lua_type_name};
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, Attribute, Data, DataEnum, DataStruct, DeriveInput, Ident};
@@ -67,6 +68,24 @@ fn lua_expose_struct(ident: Ident, attrs: Vec<Attribute>, data: DataStruct) -> T
         })
     });
 
+    let ident_doc = doc_string(&attrs);
+    let field_metas = data.fields.iter().filter_map(|field| {
+        filter_attr!(field, "hidden");
+
+        let field_name_str = get_ident!(field).to_string();
+        let ty = lua_type_name(&field.ty, &ident_str);
+        let doc = doc_string(&field.attrs);
+
+        Some(quote! {
+            crate::script::LuaFieldMeta {
+                name: #field_name_str,
+                ty: #ty,
+                doc: #doc,
+                is_readonly: false,
+            }
+        })
+    });
+
     let from_lua_impls = if !has_attr!("no_from_lua" in attrs) {
         quote! {
             impl<'lua> mlua::FromLua<'lua> for #ident {
@@ -100,13 +119,30 @@ fn lua_expose_struct(ident: Ident, attrs: Vec<Attribute>, data: DataStruct) -> T
     };
 
     TokenStream::from(quote! {
+        impl crate::script::LuaUserDataMetaProvider for #ident {
+            fn lua_class_meta() -> crate::script::LuaClassMeta {
+                crate::script::LuaClassMeta {
+                    name: #ident_str,
+                    doc: #ident_doc,
+                    fields: &[#(#field_metas),*],
+                    methods: &[],
+                }
+            }
+        }
+
         #from_lua_impls
         #to_lua_impls
     })
 }
 
-fn lua_expose_enum(ident: Ident, _attrs: Vec<Attribute>, data: DataEnum) -> TokenStream {
+fn lua_expose_enum(ident: Ident, attrs: Vec<Attribute>, data: DataEnum) -> TokenStream {
     let ident_str = ident.to_string();
+    let ident_doc = doc_string(&attrs);
+    let variant_names = data.variants.iter().filter_map(|variant| {
+        filter_attr!(variant, "hidden");
+
+        Some(variant.ident.to_string())
+    });
     let from_lua_variants = data.variants.iter().filter_map(|variant| {
         filter_attr!(variant, "hidden");
 
@@ -159,6 +195,17 @@ fn lua_expose_enum(ident: Ident, _attrs: Vec<Attribute>, data: DataEnum) -> Toke
     };
 
     TokenStream::from(quote! {
+        impl crate::script::LuaEnumMetaProvider for #ident {
+            fn lua_enum_meta() -> crate::script::LuaEnumMeta {
+                crate::script::LuaEnumMeta {
+                    name: #ident_str,
+                    doc: #ident_doc,
+                    variants: &[#(#variant_names),*],
+                    are_strings: true,
+                }
+            }
+        }
+
         #from_lua_impls
         #to_lua_impls
     })
@@ -64,6 +64,22 @@ pub fn lua_handle(item: TokenStream) -> TokenStream {
             }
         }
 
+        impl crate::script::LuaUserDataMetaProvider for #name {
+            fn lua_class_meta() -> crate::script::LuaClassMeta {
+                crate::script::LuaClassMeta {
+                    name: #name_str,
+                    doc: "",
+                    fields: &[crate::script::LuaFieldMeta {
+                        name: "_type",
+                        ty: "string",
+                        doc: "The name of the handle type.",
+                        is_readonly: true,
+                    }],
+                    methods: &[],
+                }
+            }
+        }
+
         impl mlua::UserData for #name {
             fn add_methods<'lua, M: mlua::UserDataMethods<'lua, Self>>(methods: &mut M) {
                 use mlua::{ExternalError, ToLua};
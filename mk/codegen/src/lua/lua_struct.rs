@@ -1,3 +1,4 @@
+use super::{doc_string, lua_type_name};
 use proc_macro::TokenStream;
 use proc_macro_error::*;
 use quote::quote;
@@ -13,6 +14,7 @@ pub fn lua_struct(item: TokenStream) -> TokenStream {
 
     let name = input.ident;
     let name_str = name.to_string();
+    let doc = doc_string(&input.attrs);
 
     let from_lua_field_expanded = data.fields.iter().map(|field| {
         let field_name = field
@@ -35,7 +37,36 @@ pub fn lua_struct(item: TokenStream) -> TokenStream {
         }
     });
 
+    let field_metas = data.fields.iter().map(|field| {
+        let field_name = field
+            .ident
+            .as_ref()
+            .expect_or_abort("field name is missing")
+            .to_string();
+        let ty = lua_type_name(&field.ty, &name_str);
+        let doc = doc_string(&field.attrs);
+        quote! {
+            crate::script::LuaFieldMeta {
+                name: #field_name,
+                ty: #ty,
+                doc: #doc,
+                is_readonly: false,
+            }
+        }
+    });
+
     TokenStream::from(quote! {
+        impl crate::script::LuaUserDataMetaProvider for #name {
+            fn lua_class_meta() -> crate::script::LuaClassMeta {
+                crate::script::LuaClassMeta {
+                    name: #name_str,
+                    doc: #doc,
+                    fields: &[#(#field_metas),*],
+                    methods: &[],
+                }
+            }
+        }
+
         impl<'lua> mlua::FromLua<'lua> for #name {
             fn from_lua(value: mlua::Value<'lua>, _: &'lua mlua::Lua) -> mlua::Result<Self> {
                 use mlua::ExternalError;
@@ -1,3 +1,4 @@
+use super::{doc_string, lua_type_name};
 use proc_macro::TokenStream;
 use proc_macro_error::abort_call_site;
 use quote::{__private::ext::RepToTokensExt, format_ident, quote};
@@ -12,8 +13,11 @@ pub fn lua_user_data(item: TokenStream) -> TokenStream {
     };
 
     let ty_name = &derive.ident;
+    let ty_name_str = ty_name.to_string();
+    let ty_doc = doc_string(&derive.attrs);
     let mut field_getter_impls = Vec::new();
     let mut field_setter_impls = Vec::new();
+    let mut field_metas = Vec::new();
 
     for field in &input.fields {
         if field
@@ -58,6 +62,21 @@ pub fn lua_user_data(item: TokenStream) -> TokenStream {
             ident.to_string()
         };
 
+        let lua_ty = lua_type_name(&field.ty, &ty_name_str);
+        let doc = doc_string(&field.attrs);
+        let is_readonly = field
+            .attrs
+            .iter()
+            .any(|attr| attr.path.is_ident("readonly"));
+        field_metas.push(quote! {
+            crate::script::LuaFieldMeta {
+                name: #lua_ident,
+                ty: #lua_ty,
+                doc: #doc,
+                is_readonly: #is_readonly,
+            }
+        });
+
         if field
             .attrs
             .iter()
@@ -158,5 +177,16 @@ pub fn lua_user_data(item: TokenStream) -> TokenStream {
                 #into_self_impl
             }
         }
+
+        impl crate::script::LuaUserDataMetaProvider for #ty_name {
+            fn lua_class_meta() -> crate::script::LuaClassMeta {
+                crate::script::LuaClassMeta {
+                    name: #ty_name_str,
+                    doc: #ty_doc,
+                    fields: &[#(#field_metas),*],
+                    methods: <Self as crate::script::LuaMethodMetaProvider>::lua_methods(),
+                }
+            }
+        }
     })
 }
@@ -1,16 +1,20 @@
+use super::{doc_string, lua_return_type_name, lua_type_name};
 use proc_macro::TokenStream;
 use proc_macro_error::abort_call_site;
 use quote::{__private::ext::RepToTokensExt, format_ident, quote};
 use syn::{
-    parse_macro_input, punctuated::Punctuated,  FnArg, Ident, ImplItem, ItemImpl,
-    Lit, Meta, NestedMeta, Token, Type,
+    parse_macro_input, punctuated::Punctuated, FnArg, Ident, ImplItem, ItemImpl, Lit, Meta,
+    NestedMeta, Pat, Token, Type,
 };
 
 pub fn lua_user_data_method(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let impl_block = parse_macro_input!(item as ItemImpl);
+    let self_ty = &impl_block.self_ty;
+    let self_name = quote!(#self_ty).to_string();
 
     let mut method_impls = Vec::new();
     let mut static_method_impls = Vec::new();
+    let mut method_metas = Vec::new();
 
     for item in &impl_block.items {
         let method = if let ImplItem::Method(method) = &item {
@@ -133,7 +137,38 @@ pub fn lua_user_data_method(_attr: TokenStream, item: TokenStream) -> TokenStrea
             }
         };
 
-        match MethodType::from_method_inputs(&method.sig.inputs) {
+        let method_type = MethodType::from_method_inputs(&method.sig.inputs);
+        let param_metas = params
+            .iter()
+            .enumerate()
+            .map(|(i, typed)| {
+                let name = match typed.pat.as_ref() {
+                    Pat::Ident(pat) => pat.ident.to_string(),
+                    _ => format!("arg_{}", i),
+                };
+                let ty = lua_type_name(&typed.ty, &self_name);
+                quote! {
+                    crate::script::LuaParamMeta { name: #name, ty: #ty }
+                }
+            })
+            .collect::<Vec<_>>();
+        let doc = doc_string(&method.attrs);
+        let is_static = method_type == MethodType::Static;
+        let returns = match lua_return_type_name(&method.sig.output, !no_except, &self_name) {
+            Some(returns) => quote! { Some(#returns) },
+            None => quote! { None },
+        };
+        method_metas.push(quote! {
+            crate::script::LuaMethodMeta {
+                name: #lua_ident,
+                doc: #doc,
+                is_static: #is_static,
+                params: &[#(#param_metas),*],
+                returns: #returns,
+            }
+        });
+
+        match method_type {
             MethodType::Static => {
                 static_method_impls.push(quote! {
                     table.set(#lua_ident, lua.create_function(|lua, (#param_idents): (#param_types)| {
@@ -173,7 +208,12 @@ pub fn lua_user_data_method(_attr: TokenStream, item: TokenStream) -> TokenStrea
         }
     }
 
-    let impl_to_string = if impl_block.attrs.iter().find(|attr| attr.path.is_ident("ops_to_string")).is_some() {
+    let impl_to_string = if impl_block
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("ops_to_string"))
+        .is_some()
+    {
         quote! {
             _methods.add_meta_method(mlua::MetaMethod::ToString, |_lua, this, ()| {
                 Ok(<_ as ToString>::to_string(this))
@@ -194,16 +234,29 @@ pub fn lua_user_data_method(_attr: TokenStream, item: TokenStream) -> TokenStrea
             }
         }
     };
-    let ops_impl = if impl_block.attrs.iter().find(|attr| attr.path.is_ident("ops_extra")).is_some() {
+    let ops_impl = if impl_block
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("ops_extra"))
+        .is_some()
+    {
         quote! {}
-    } else {  
+    } else {
         quote! {
             impl #impl_generics crate::script::UserDataOpsProvider for #ty_name #ty_generics #where_clause {}
         }
     };
+    let method_meta_impl = quote! {
+        impl #impl_generics crate::script::LuaMethodMetaProvider for #ty_name #ty_generics #where_clause {
+            fn lua_methods() -> &'static [crate::script::LuaMethodMeta] {
+                &[#(#method_metas),*]
+            }
+        }
+    };
     let static_methods_impl = quote! {
         impl #impl_generics crate::script::LuaApiTable for #ty_name #ty_generics #where_clause {
             fn create_api_table<'lua>(lua: &'lua mlua::Lua) -> mlua::Result<mlua::Table<'lua>> {
+                crate::script::register_lua_class::<Self>(lua);
                 let table = lua.create_table()?;
                 #(#static_method_impls)*
                 Ok(table)
@@ -219,6 +272,8 @@ pub fn lua_user_data_method(_attr: TokenStream, item: TokenStream) -> TokenStrea
         #ops_impl
 
         #static_methods_impl
+
+        #method_meta_impl
     })
 }
 
@@ -0,0 +1,22 @@
+//! Generates the LuaLS definition file and the API reference pages from the `codegen` metadata.
+//!
+//! Usage: `cargo run --manifest-path mk/Cargo.toml -p mk --example lua-api -- [annotations path] [docs dir]`
+
+use mk::script::{lua_api_meta, write_lua_api};
+use std::env;
+
+const DEFAULT_ANNOTATIONS_PATH: &str = "types/mk.lua";
+const DEFAULT_DOCS_DIR: &str = "docs/src/scripting/generated";
+
+fn main() -> mk::anyhow::Result<()> {
+    let mut args = env::args().skip(1);
+    let annotations_path = args
+        .next()
+        .unwrap_or_else(|| DEFAULT_ANNOTATIONS_PATH.to_owned());
+    let docs_dir = args.next().unwrap_or_else(|| DEFAULT_DOCS_DIR.to_owned());
+
+    write_lua_api(&lua_api_meta()?, &annotations_path, &docs_dir)?;
+
+    println!("wrote {} and {}", annotations_path, docs_dir);
+    Ok(())
+}
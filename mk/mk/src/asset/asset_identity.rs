@@ -0,0 +1,10 @@
+use std::any::Any;
+use std::sync::Weak;
+
+/// Identifies the shared data an asset refers to, so that the asset manager can tell which path it was loaded from.
+pub trait AssetIdentity {
+    /// Returns a weak reference to the data shared by the clones of the asset, or `None` if it is not shared.
+    fn identity(&self) -> Option<Weak<dyn Any + Send + Sync>> {
+        None
+    }
+}
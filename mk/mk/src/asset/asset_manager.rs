@@ -1,16 +1,23 @@
 use crate::asset::{
-    AssetCacheManager, AssetLoadError, AssetLoader, BaseAssetCacheManager, BaseAssetLoader,
+    AssetCacheManager, AssetIdentity, AssetLoadError, AssetLoader, BaseAssetCacheManager,
+    BaseAssetLoader,
 };
 use crate::engine::use_context;
 use std::any::{Any, TypeId};
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
+
+/// The path of each loaded asset, by the address of the data it shares.
+type AssetPaths = HashMap<usize, (PathBuf, Weak<dyn Any + Send + Sync>)>;
 
 pub struct AssetManager {
     base: PathBuf,
     types: HashMap<TypeId, (Box<dyn BaseAssetCacheManager>, Box<dyn BaseAssetLoader>)>,
+    /// The weak references keep the addresses from being reused while the entries exist.
+    paths: RefCell<AssetPaths>,
 }
 
 impl AssetManager {
@@ -18,12 +25,13 @@ impl AssetManager {
         AssetManager {
             base,
             types: HashMap::new(),
+            paths: HashMap::new().into(),
         }
     }
 
     pub fn register_loader<T>(&mut self, loader: AssetLoader<T>)
     where
-        T: 'static + Clone + Any + Send + Sync,
+        T: 'static + Clone + Any + Send + Sync + AssetIdentity,
     {
         self.types.insert(
             TypeId::of::<T>(),
@@ -34,7 +42,7 @@ impl AssetManager {
     // TODO: Provide async-way to load assets.
     pub fn load<T>(&self, path: impl AsRef<Path>) -> Result<T, AssetLoadError>
     where
-        T: 'static + Clone + Any + Send + Sync,
+        T: 'static + Clone + Any + Send + Sync + AssetIdentity,
     {
         match self.types.get(&TypeId::of::<T>()) {
             Some((cache, loader)) => {
@@ -50,6 +58,7 @@ impl AssetManager {
                         )?;
 
                         cache.cache(path.as_ref().to_path_buf(), Arc::downgrade(&asset));
+                        self.remember_path(asset.deref(), path.as_ref());
                         Ok(asset.deref().clone())
                     }
                 }
@@ -57,4 +66,31 @@ impl AssetManager {
             None => Err(AssetLoadError::unsupported::<T>()),
         }
     }
+
+    /// Returns the path the asset has been loaded from, as given to [`AssetManager::load`].
+    /// Returns `None` if the asset has not been loaded by the manager.
+    pub fn path_of<T>(&self, asset: &T) -> Option<PathBuf>
+    where
+        T: ?Sized + AssetIdentity,
+    {
+        let identity = asset.identity()?;
+        let paths = self.paths.borrow();
+        let (path, _) = paths.get(&(identity.as_ptr() as *const () as usize))?;
+        Some(path.clone())
+    }
+
+    fn remember_path(&self, asset: &impl AssetIdentity, path: &Path) {
+        let identity = match asset.identity() {
+            Some(identity) => identity,
+            None => return,
+        };
+        let mut paths = self.paths.borrow_mut();
+
+        // Forget the assets that have been dropped since.
+        paths.retain(|_, (_, identity)| identity.strong_count() != 0);
+        paths.insert(
+            identity.as_ptr() as *const () as usize,
+            (path.to_path_buf(), identity),
+        );
+    }
 }
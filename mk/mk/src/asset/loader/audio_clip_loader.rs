@@ -1,12 +1,12 @@
 use crate::asset::AssetLoader;
 use crate::audio::AudioClip;
+use crate::handles::AudioClipHandle;
 use std::{
     fs::{metadata as fs_metadata, OpenOptions},
     io::{BufReader, Error as IOError, ErrorKind as IOErrorKind},
-    sync::Arc,
 };
 
-pub fn audio_clip_loader() -> AssetLoader<Arc<AudioClip>> {
+pub fn audio_clip_loader() -> AssetLoader<AudioClipHandle> {
     AssetLoader::new(|_context, base, path| {
         let path = base.join("audios").join(path);
         let mut audio_clip_path = Err(IOError::new(
@@ -27,7 +27,7 @@ pub fn audio_clip_loader() -> AssetLoader<Arc<AudioClip>> {
             }
         }
 
-        Ok(Arc::new(AudioClip::new(
+        Ok(AudioClipHandle::new(AudioClip::new(
             rodio::Decoder::new(BufReader::with_capacity(
                 1024 * 32,
                 OpenOptions::new().read(true).open(&audio_clip_path?)?,
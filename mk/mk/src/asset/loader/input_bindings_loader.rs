@@ -0,0 +1,14 @@
+use crate::{
+    asset::{AssetLoadError, AssetLoader},
+    input::InputBindings,
+};
+use std::fs::read_to_string;
+
+pub fn input_bindings_loader() -> AssetLoader<InputBindings> {
+    AssetLoader::new(|_context, base, path| {
+        InputBindings::from_json(&read_to_string(
+            base.join("input").join(path).with_extension("json"),
+        )?)
+        .map_err(AssetLoadError::other)
+    })
+}
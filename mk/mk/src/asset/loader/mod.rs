@@ -1,5 +1,7 @@
 mod audio_clip_loader;
 mod font_loader;
+mod input_bindings_loader;
+mod prefab_loader;
 mod shader_loader;
 // mod sprite_atlas_grid_loader;
 // mod sprite_atlas_loader;
@@ -8,6 +10,8 @@ mod sprite_loader;
 
 pub use audio_clip_loader::*;
 pub use font_loader::*;
+pub use input_bindings_loader::*;
+pub use prefab_loader::*;
 pub use shader_loader::*;
 // pub use sprite_atlas_grid_loader::*;
 // pub use sprite_atlas_loader::*;
@@ -0,0 +1,12 @@
+use crate::{asset::AssetLoader, entity::Prefab, handles::*};
+use std::fs::read_to_string;
+
+pub fn prefab_loader() -> AssetLoader<PrefabHandle> {
+    AssetLoader::new(|_context, base, path| {
+        let source = read_to_string(base.join("prefabs").join(path).with_extension("lua"))?;
+        Ok(PrefabHandle::new(Prefab::new(
+            path.to_string_lossy(),
+            source,
+        )))
+    })
+}
@@ -1,4 +1,5 @@
 mod asset_cache_manager;
+mod asset_identity;
 mod asset_load_error;
 mod asset_loader;
 mod asset_manager;
@@ -7,6 +8,7 @@ mod base_asset_loader;
 pub mod loader;
 
 pub use asset_cache_manager::*;
+pub use asset_identity::*;
 pub use asset_load_error::*;
 pub use asset_loader::*;
 pub use asset_manager::*;
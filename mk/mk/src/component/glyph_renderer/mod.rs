@@ -8,10 +8,12 @@ use crate::{
         Color, GlyphLayoutConfig, GlyphManager, GlyphSprite, Layer, RenderManager,
     },
     handles::*,
-    structure::Size,
+    structure::{Size, Vec2},
     GfxContext,
 };
-use fontdue::layout::{CoordinateSystem, Layout, LayoutSettings, TextStyle};
+use fontdue::layout::{
+    CoordinateSystem, HorizontalAlign, Layout, LayoutSettings, TextStyle, VerticalAlign,
+};
 use specs::{prelude::*, Component};
 use std::{mem::size_of, num::NonZeroU64};
 use wgpu::*;
@@ -146,7 +148,69 @@ impl GlyphRenderer {
         Size::new(width, self.layout.height())
     }
 
-    fn update_glyphs(&mut self, glyph_mgr: &mut GlyphManager, render_mgr: &mut RenderManager) {
+    /// Returns the offset of the layout from the transform for the given renderer size.
+    /// Glyphs are placed at `(glyph.x + offset.x, glyph.y - offset.y)`.
+    pub fn layout_offset(&self, size: Size) -> Vec2 {
+        let layout_size = self.compute_size();
+        let (horizontal_align, vertical_align) = (
+            match self.config.horizontal_align {
+                HorizontalAlign::Left => 0f32,
+                HorizontalAlign::Center => 0.5f32,
+                HorizontalAlign::Right => 1f32,
+            },
+            match self.config.vertical_align {
+                VerticalAlign::Top => 0f32,
+                VerticalAlign::Middle => 0.5f32,
+                VerticalAlign::Bottom => 1f32,
+            },
+        );
+        let overflow_offset = Vec2::new(size.width * 0.5f32, size.height * 0.5f32);
+        let alignment_offset = Vec2::new(
+            (size.width - layout_size.width) * horizontal_align,
+            (size.height - layout_size.height) * vertical_align,
+        );
+
+        alignment_offset - overflow_offset
+    }
+
+    /// Returns the height of a single line of text.
+    pub fn line_height(&self) -> f32 {
+        self.font
+            .horizontal_line_metrics(self.font_size)
+            .map(|metrics| metrics.new_line_size)
+            .unwrap_or(self.font_size)
+    }
+
+    /// Returns the horizontal pen position in the layout before the character at the given index.
+    /// Indices past the end of the text return the position after the last character.
+    pub fn caret_x(&self, index: usize) -> f32 {
+        let glyphs = self.layout.glyphs();
+        let pen = |glyph: &fontdue::layout::GlyphPosition| {
+            glyph.x - self.font.metrics(glyph.parent, self.font_size).xmin as f32
+        };
+        let byte_offset = self
+            .text
+            .char_indices()
+            .nth(index)
+            .map(|(offset, _)| offset);
+
+        match byte_offset.and_then(|offset| glyphs.iter().find(|g| g.byte_offset == offset)) {
+            Some(glyph) => pen(glyph),
+            None => match glyphs.last() {
+                Some(glyph) => {
+                    pen(glyph)
+                        + self
+                            .font
+                            .metrics(glyph.parent, self.font_size)
+                            .advance_width
+                }
+                None => 0f32,
+            },
+        }
+    }
+
+    /// Releases the bind groups of the glyphs. Must be called before the renderer is dropped.
+    pub fn deallocate_glyphs(&mut self, render_mgr: &mut RenderManager) {
         let sprites = self
             .glyphs
             .iter()
@@ -154,6 +218,10 @@ impl GlyphRenderer {
             .collect::<Vec<_>>();
         render_mgr.deallocate_glyph_renderer_bind_group(&sprites);
         self.glyphs.clear();
+    }
+
+    fn update_glyphs(&mut self, glyph_mgr: &mut GlyphManager, render_mgr: &mut RenderManager) {
+        self.deallocate_glyphs(render_mgr);
         for glyph in self.layout.glyphs() {
             let sprite = glyph_mgr.glyph(render_mgr, &self.font, glyph.key);
             let bind_group = render_mgr.allocate_glyph_renderer_bind_group(sprite);
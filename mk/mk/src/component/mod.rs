@@ -8,6 +8,7 @@ mod glyph_renderer;
 // mod single_animator;
 mod size;
 mod sprite_renderer;
+mod text_field;
 // mod tilemap_renderer;
 mod transform;
 mod ui_element;
@@ -22,6 +23,7 @@ pub use glyph_renderer::*;
 // pub use single_animator::*;
 pub use size::*;
 pub use sprite_renderer::*;
+pub use text_field::*;
 // pub use tilemap_renderer::*;
 pub use transform::*;
 pub use ui_element::*;
@@ -37,6 +39,7 @@ pub fn register_components(world: &mut World) {
     // world.register::<SingleAnimator>();
     world.register::<Size>();
     world.register::<SpriteRenderer>();
+    world.register::<TextField>();
     // world.register::<TilemapRenderer>();
     world.register::<Transform>();
     world.register::<UIElement>();
@@ -0,0 +1,61 @@
+use crate::ui::TextEditor;
+use specs::{prelude::*, Component};
+
+/// Seconds the caret stays visible, then hidden, while the field is focused.
+pub const TEXT_FIELD_CARET_BLINK_INTERVAL: f32 = 0.5f32;
+
+/// Makes the `GlyphRenderer` of the entity editable while the entity is focused.
+///
+/// `caret` and `selection` are optional child entities, usually with a `SpriteRenderer`,
+/// that are moved and resized to show the caret and the selected range.
+#[derive(Component)]
+pub struct TextField {
+    pub caret: Option<Entity>,
+    pub selection: Option<Entity>,
+    pub caret_width: f32,
+    editor: TextEditor,
+    blink: f32,
+    is_dirty: bool,
+}
+
+impl TextField {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            caret: None,
+            selection: None,
+            caret_width: 2f32,
+            editor: TextEditor::new(text),
+            blink: 0f32,
+            is_dirty: true,
+        }
+    }
+
+    pub fn editor(&self) -> &TextEditor {
+        &self.editor
+    }
+
+    pub fn editor_mut(&mut self) -> &mut TextEditor {
+        self.mark_as_dirty();
+        &mut self.editor
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.is_dirty
+    }
+
+    /// Requests the renderer to be refreshed and restarts the caret blink.
+    pub fn mark_as_dirty(&mut self) {
+        self.is_dirty = true;
+        self.blink = 0f32;
+    }
+
+    pub fn reset_dirty(&mut self) {
+        self.is_dirty = false;
+    }
+
+    /// Advances the caret blink and returns whether the caret is visible.
+    pub fn update_blink(&mut self, dt: f32) -> bool {
+        self.blink = (self.blink + dt) % (2f32 * TEXT_FIELD_CARET_BLINK_INTERVAL);
+        self.blink < TEXT_FIELD_CARET_BLINK_INTERVAL
+    }
+}
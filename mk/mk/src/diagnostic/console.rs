@@ -0,0 +1,456 @@
+use crate::{
+    component::Transform,
+    diagnostic::ScriptError,
+    script::{entity::Entity, FFIFunction},
+    EngineContext,
+};
+use mlua::prelude::*;
+use specs::WorldExt;
+use std::collections::{BTreeMap, VecDeque};
+
+/// Lines entered in the console that are kept for recall.
+pub const CONSOLE_HISTORY_LIMIT: usize = 100;
+/// Lines printed to the console that are kept for display.
+pub const CONSOLE_OUTPUT_LIMIT: usize = 200;
+/// Tables nested deeper than this are not expanded when printed.
+pub const CONSOLE_PRINT_DEPTH: usize = 2;
+/// Entries of a table printed before the rest are elided.
+pub const CONSOLE_PRINT_ENTRIES: usize = 16;
+
+/// A command registered from Lua, entered as `/name arg1 arg2`.
+#[derive(Clone)]
+pub struct ConsoleCommand {
+    pub help: String,
+    pub function: FFIFunction,
+}
+
+/// State of the developer console: entered lines, printed output and registered commands.
+/// Lines are executed with `execute_console_line`.
+#[derive(Default)]
+pub struct Console {
+    history: VecDeque<String>,
+    history_cursor: Option<usize>,
+    output: VecDeque<String>,
+    commands: BTreeMap<String, ConsoleCommand>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn history(&self) -> impl Iterator<Item = &str> {
+        self.history.iter().map(String::as_str)
+    }
+
+    /// Remembers the line and resets the history navigation.
+    pub fn push_history(&mut self, line: impl Into<String>) {
+        let line = line.into();
+        self.history_cursor = None;
+
+        if self.history.back() == Some(&line) {
+            return;
+        }
+
+        if self.history.len() == CONSOLE_HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+
+        self.history.push_back(line);
+    }
+
+    /// Steps back to an older line. Stays on the oldest one.
+    pub fn previous_history(&mut self) -> Option<&str> {
+        let cursor = match self.history_cursor {
+            Some(cursor) => cursor.saturating_sub(1),
+            None => self.history.len().checked_sub(1)?,
+        };
+        self.history_cursor = Some(cursor);
+        self.history.get(cursor).map(String::as_str)
+    }
+
+    /// Steps forward to a newer line. Returns `None` once past the newest one.
+    pub fn next_history(&mut self) -> Option<&str> {
+        let cursor = self.history_cursor? + 1;
+
+        if self.history.len() <= cursor {
+            self.history_cursor = None;
+            return None;
+        }
+
+        self.history_cursor = Some(cursor);
+        self.history.get(cursor).map(String::as_str)
+    }
+
+    pub fn output(&self) -> impl Iterator<Item = &str> {
+        self.output.iter().map(String::as_str)
+    }
+
+    /// Appends the text to the output, one entry per line.
+    pub fn print(&mut self, text: impl AsRef<str>) {
+        for line in text.as_ref().lines() {
+            if self.output.len() == CONSOLE_OUTPUT_LIMIT {
+                self.output.pop_front();
+            }
+
+            self.output.push_back(line.to_owned());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.output.clear();
+    }
+
+    pub fn commands(&self) -> impl Iterator<Item = (&str, &ConsoleCommand)> {
+        self.commands
+            .iter()
+            .map(|(name, command)| (name.as_str(), command))
+    }
+
+    pub fn command(&self, name: &str) -> Option<&ConsoleCommand> {
+        self.commands.get(name)
+    }
+
+    /// Registers the command, replacing any command with the same name.
+    pub fn register_command(&mut self, name: impl Into<String>, command: ConsoleCommand) {
+        self.commands.insert(name.into(), command);
+    }
+
+    pub fn unregister_command(&mut self, name: &str) -> bool {
+        self.commands.remove(name).is_some()
+    }
+}
+
+/// Executes a line entered in the console and prints its outcome.
+///
+/// Lines starting with `/` run a command; `/help` and `/clear` are built in.
+/// Other lines are evaluated as Lua, first as an expression whose values are printed,
+/// then as a statement.
+pub fn execute_console_line(context: &EngineContext, line: &str) {
+    let line = line.trim();
+
+    if line.is_empty() {
+        return;
+    }
+
+    {
+        let mut diagnostic_mgr = context.diagnostic_mgr_mut();
+        diagnostic_mgr.console_mut().push_history(line);
+        diagnostic_mgr.console_mut().print(format!("> {}", line));
+    }
+
+    let lua = context.script_mgr().lua();
+    let result = match line.strip_prefix('/') {
+        Some(command) => run_command(context, lua, command),
+        None => context
+            .script_mgr()
+            .evaluate(line)
+            .map_err(|err| describe_error(&ScriptError::from_error(&err))),
+    };
+    let text = match result {
+        Ok(values) if values.is_empty() => return,
+        Ok(values) => values
+            .iter()
+            .map(|value| pretty_print(Some(context), value, 0))
+            .collect::<Vec<_>>()
+            .join("\t"),
+        Err(err) => format!("error: {}", err),
+    };
+
+    context.diagnostic_mgr_mut().console_mut().print(text);
+}
+
+fn run_command<'lua>(
+    context: &EngineContext,
+    lua: &'lua Lua,
+    command: &str,
+) -> Result<LuaMultiValue<'lua>, String> {
+    let mut args = command.split_whitespace();
+    let name = args.next().unwrap_or_default();
+    let found = context.diagnostic_mgr().console().command(name).cloned();
+
+    match (name, found) {
+        (_, Some(command)) => {
+            let args = args
+                .map(|arg| lua.create_string(arg).map(LuaValue::String))
+                .collect::<LuaResult<Vec<_>>>()
+                .map_err(|err| err.to_string())?;
+            command
+                .function
+                .as_function(lua)
+                .and_then(|function| function.call(LuaMultiValue::from_vec(args)))
+                .map_err(|err| describe_error(&ScriptError::from_lua_error(&err)))
+        }
+        ("help", None) => {
+            let mut diagnostic_mgr = context.diagnostic_mgr_mut();
+            let console = diagnostic_mgr.console_mut();
+            let help = ["/help: lists the commands", "/clear: clears the output"]
+                .iter()
+                .map(|help| help.to_string())
+                .chain(
+                    console
+                        .commands()
+                        .map(|(name, command)| format!("/{}: {}", name, command.help)),
+                )
+                .collect::<Vec<_>>();
+
+            for line in help {
+                console.print(line);
+            }
+
+            Ok(LuaMultiValue::new())
+        }
+        ("clear", None) => {
+            context.diagnostic_mgr_mut().console_mut().clear();
+            Ok(LuaMultiValue::new())
+        }
+        (name, None) => Err(format!("unknown command '{}'", name)),
+    }
+}
+
+fn describe_error(err: &ScriptError) -> String {
+    match &err.location {
+        Some((file, line)) => format!("{}:{}: {}", file, line, err.message),
+        None => err.message.clone(),
+    }
+}
+
+/// Returns the global names or fields completing the last dotted name of the line,
+/// e.g. `mk.entity.Entity` and `mk.entity.EntityBuilder` for `mk.entity.E`, sorted.
+pub fn complete_console_line(lua: &Lua, line: &str) -> LuaResult<Vec<String>> {
+    let start = line
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| !(c.is_alphanumeric() || c == '_' || c == '.'))
+        .map_or(0, |(index, c)| index + c.len_utf8());
+    let (head, word) = line.split_at(start);
+    let (path, prefix) = match word.rfind('.') {
+        Some(index) => (&word[..index], &word[index + 1..]),
+        None => ("", word),
+    };
+
+    let mut table = lua.globals();
+
+    for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+        table = match table.get::<_, LuaValue>(segment)? {
+            LuaValue::Table(table) => table,
+            _ => return Ok(Vec::new()),
+        };
+    }
+
+    let mut completions = Vec::new();
+
+    for pair in table.pairs::<LuaValue, LuaValue>() {
+        let key = match pair?.0 {
+            LuaValue::String(key) => key,
+            _ => continue,
+        };
+        let key = match key.to_str() {
+            Ok(key) if key.starts_with(prefix) => key,
+            _ => continue,
+        };
+
+        completions.push(if path.is_empty() {
+            format!("{}{}", head, key)
+        } else {
+            format!("{}{}.{}", head, path, key)
+        });
+    }
+
+    completions.sort();
+    Ok(completions)
+}
+
+/// Formats the value for the console, expanding tables up to `CONSOLE_PRINT_DEPTH`.
+/// Entities are printed with their name if a context is given.
+pub fn pretty_print(context: Option<&EngineContext>, value: &LuaValue, depth: usize) -> String {
+    match value {
+        LuaValue::Nil => "nil".to_owned(),
+        LuaValue::Boolean(value) => value.to_string(),
+        LuaValue::Integer(value) => value.to_string(),
+        LuaValue::Number(value) => format!("{:?}", value),
+        LuaValue::String(value) => format!("{:?}", value.to_string_lossy()),
+        LuaValue::Table(table) => pretty_print_table(context, table, depth),
+        LuaValue::UserData(userdata) => pretty_print_userdata(context, userdata),
+        value => format!("{}: {:p}", value.type_name(), value.to_pointer()),
+    }
+}
+
+fn pretty_print_table(context: Option<&EngineContext>, table: &LuaTable, depth: usize) -> String {
+    if CONSOLE_PRINT_DEPTH <= depth {
+        return format!("table: {:p}", table.to_pointer());
+    }
+
+    let length = table.raw_len();
+    let mut entries = Vec::new();
+    let mut is_elided = false;
+
+    for pair in table.clone().pairs::<LuaValue, LuaValue>() {
+        let (key, value) = match pair {
+            Ok(pair) => pair,
+            Err(..) => continue,
+        };
+
+        if entries.len() == CONSOLE_PRINT_ENTRIES {
+            is_elided = true;
+            break;
+        }
+
+        let value = pretty_print(context, &value, depth + 1);
+        entries.push(match key {
+            LuaValue::Integer(index) if 1 <= index && index <= length => value,
+            LuaValue::String(key) if is_identifier(&key.to_string_lossy()) => {
+                format!("{} = {}", key.to_string_lossy(), value)
+            }
+            key => format!(
+                "[{}] = {}",
+                pretty_print(context, &key, CONSOLE_PRINT_DEPTH),
+                value
+            ),
+        });
+    }
+
+    if is_elided {
+        entries.push("...".to_owned());
+    }
+
+    if entries.is_empty() {
+        "{}".to_owned()
+    } else {
+        format!("{{ {} }}", entries.join(", "))
+    }
+}
+
+fn pretty_print_userdata(context: Option<&EngineContext>, userdata: &LuaAnyUserData) -> String {
+    let entity = userdata.borrow::<Entity>().map(|entity| *entity);
+
+    if let Ok(entity) = entity {
+        let name = context
+            .and_then(|context| {
+                let index = context
+                    .world()
+                    .read_storage::<Transform>()
+                    .get(entity.0)?
+                    .index();
+                context
+                    .transform_mgr()
+                    .name_manager()
+                    .name(index)
+                    .map(|name| name.to_string())
+            })
+            .unwrap_or_default();
+        return format!("Entity({}, {:?})", entity.0.id(), name);
+    }
+
+    let to_string = userdata
+        .get_metatable()
+        .and_then(|metatable| metatable.get::<_, LuaFunction>(LuaMetaMethod::ToString));
+
+    match to_string.and_then(|to_string| to_string.call::<_, String>(userdata.clone())) {
+        Ok(text) => text,
+        Err(..) => format!(
+            "userdata: {:p}",
+            LuaValue::UserData(userdata.clone()).to_pointer()
+        ),
+    }
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn history_navigation() {
+        let mut console = Console::new();
+
+        assert_eq!(console.previous_history(), None);
+
+        console.push_history("a");
+        console.push_history("b");
+        console.push_history("b");
+        console.push_history("c");
+        assert_eq!(console.history().collect::<Vec<_>>(), ["a", "b", "c"]);
+
+        assert_eq!(console.previous_history(), Some("c"));
+        assert_eq!(console.previous_history(), Some("b"));
+        assert_eq!(console.previous_history(), Some("a"));
+        assert_eq!(console.previous_history(), Some("a"));
+        assert_eq!(console.next_history(), Some("b"));
+        assert_eq!(console.next_history(), Some("c"));
+        assert_eq!(console.next_history(), None);
+        assert_eq!(console.next_history(), None);
+
+        // Entering a line starts the navigation over from the newest line.
+        console.previous_history();
+        console.push_history("d");
+        assert_eq!(console.previous_history(), Some("d"));
+    }
+
+    #[test]
+    fn history_and_output_are_bounded() {
+        let mut console = Console::new();
+
+        for index in 0..=CONSOLE_HISTORY_LIMIT {
+            console.push_history(index.to_string());
+        }
+        console.print("x\n".repeat(CONSOLE_OUTPUT_LIMIT + 1));
+
+        assert_eq!(console.history().count(), CONSOLE_HISTORY_LIMIT);
+        assert_eq!(console.history().next(), Some("1"));
+        assert_eq!(console.output().count(), CONSOLE_OUTPUT_LIMIT);
+    }
+
+    #[test]
+    fn completes_the_last_dotted_name() {
+        let lua = Lua::new();
+        lua.load("mk = { entity = { Entity = 1, EntityBuilder = 2, query = 3 } }; mkx = 4")
+            .exec()
+            .unwrap();
+
+        assert_eq!(
+            complete_console_line(&lua, "mk.entity.E").unwrap(),
+            ["mk.entity.Entity", "mk.entity.EntityBuilder"]
+        );
+        assert_eq!(
+            complete_console_line(&lua, "print(mk").unwrap(),
+            ["print(mk", "print(mkx"]
+        );
+        assert_eq!(
+            complete_console_line(&lua, "x = mk.entity.q").unwrap(),
+            ["x = mk.entity.query"]
+        );
+        assert!(complete_console_line(&lua, "mk.entity.Entity.x")
+            .unwrap()
+            .is_empty());
+        assert!(complete_console_line(&lua, "missing.x").unwrap().is_empty());
+    }
+
+    #[test]
+    fn pretty_prints_values() {
+        let lua = Lua::new();
+        let print = |chunk: &str| {
+            let value = lua.load(chunk).eval::<LuaValue>().unwrap();
+            pretty_print(None, &value, 0)
+        };
+
+        assert_eq!(print("nil"), "nil");
+        assert_eq!(print("1"), "1");
+        assert_eq!(print("1.5"), "1.5");
+        assert_eq!(print("'a\"b'"), "\"a\\\"b\"");
+        assert_eq!(print("{}"), "{}");
+        assert_eq!(print("{ 1, 2 }"), "{ 1, 2 }");
+        assert_eq!(print("{ name = 'x' }"), "{ name = \"x\" }");
+        assert_eq!(print("{ ['not id'] = true }"), "{ [\"not id\"] = true }");
+        assert!(print("{ { { 1 } } }").starts_with("{ { table: "));
+        assert!(print("print").starts_with("function: "));
+
+        let long = print("local t = {} for i = 1, 20 do t[i] = i end return t");
+        assert!(long.ends_with("16, ... }"), "{}", long);
+    }
+}
@@ -8,6 +8,7 @@ macro_rules! emit_diagnostic {
                 level: $level,
                 message: $message,
                 sub_diagnostics: vec![],
+                traceback: None,
                 file: file.to_owned(),
                 line,
                 column,
@@ -23,6 +24,7 @@ macro_rules! emit_diagnostic {
                 level: $level,
                 message: $message,
                 sub_diagnostics: $sub_diagnostics,
+                traceback: None,
                 file: file.to_owned(),
                 line,
                 column,
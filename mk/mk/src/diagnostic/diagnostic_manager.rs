@@ -1,17 +1,127 @@
+use crate::{diagnostic::Console, script::event::Diagnostic};
+use std::collections::HashSet;
+
+/// The script error that paused the game, with what was paused before it.
+#[derive(Debug, Clone)]
+pub struct ScriptErrorPause {
+    pub diagnostic: Diagnostic,
+    pub was_time_paused: bool,
+    pub was_turn_paused: bool,
+}
+
 pub struct DiagnosticManager {
     pub console_enabled: bool,
+    console: Console,
+    pause_on_script_error: bool,
+    script_error_pause: Option<ScriptErrorPause>,
+    queued: Vec<Diagnostic>,
+    reported: HashSet<(String, String, u32)>,
 }
 
 impl DiagnosticManager {
     pub fn new() -> Self {
         Self::default()
     }
+
+    pub fn console(&self) -> &Console {
+        &self.console
+    }
+
+    pub fn console_mut(&mut self) -> &mut Console {
+        &mut self.console
+    }
+
+    /// Returns `true` if a script error pauses the game. Only honored in debug builds.
+    pub fn pause_on_script_error(&self) -> bool {
+        self.pause_on_script_error
+    }
+
+    pub fn set_pause_on_script_error(&mut self, pause_on_script_error: bool) {
+        self.pause_on_script_error = pause_on_script_error;
+    }
+
+    pub fn script_error_pause(&self) -> Option<&ScriptErrorPause> {
+        self.script_error_pause.as_ref()
+    }
+
+    pub fn set_script_error_pause(&mut self, pause: ScriptErrorPause) {
+        self.script_error_pause = Some(pause);
+    }
+
+    pub fn take_script_error_pause(&mut self) -> Option<ScriptErrorPause> {
+        self.script_error_pause.take()
+    }
+
+    /// Queues the diagnostic to be emitted by the next flush.
+    /// Returns `false` if the same diagnostic has already been queued in this frame.
+    pub fn queue(&mut self, diagnostic: Diagnostic) -> bool {
+        let key = (
+            diagnostic.message.clone(),
+            diagnostic.file.clone(),
+            diagnostic.line,
+        );
+
+        if !self.reported.insert(key) {
+            return false;
+        }
+
+        self.queued.push(diagnostic);
+        true
+    }
+
+    pub fn take_queued(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.queued)
+    }
+
+    /// Forgets the diagnostics queued so far, so that they are reported again if repeated.
+    pub fn begin_frame(&mut self) {
+        self.reported.clear();
+    }
 }
 
 impl Default for DiagnosticManager {
     fn default() -> Self {
         Self {
             console_enabled: false,
+            console: Console::new(),
+            pause_on_script_error: false,
+            script_error_pause: None,
+            queued: Vec::new(),
+            reported: HashSet::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::diagnostic::ScriptError;
+
+    fn diagnostic(message: &str, line: u32) -> Diagnostic {
+        ScriptError {
+            message: message.to_owned(),
+            location: Some(("main.lua".to_owned(), line)),
+            traceback: None,
         }
+        .to_diagnostic("timer")
+    }
+
+    #[test]
+    fn repeated_diagnostics_are_queued_once_per_frame() {
+        let mut diagnostic_mgr = DiagnosticManager::new();
+
+        assert!(diagnostic_mgr.queue(diagnostic("boom", 1)));
+        assert!(!diagnostic_mgr.queue(diagnostic("boom", 1)));
+        assert!(diagnostic_mgr.queue(diagnostic("boom", 2)));
+        assert!(diagnostic_mgr.queue(diagnostic("bang", 1)));
+        assert_eq!(diagnostic_mgr.take_queued().len(), 3);
+
+        // Taking the queue does not forget the diagnostics reported in this frame.
+        assert!(!diagnostic_mgr.queue(diagnostic("boom", 1)));
+        assert!(diagnostic_mgr.take_queued().is_empty());
+
+        diagnostic_mgr.begin_frame();
+        assert!(diagnostic_mgr.queue(diagnostic("boom", 1)));
+        assert_eq!(diagnostic_mgr.take_queued().len(), 1);
     }
 }
@@ -1,6 +1,10 @@
+mod console;
 mod diagnostic;
 mod diagnostic_manager;
 mod diagnostic_object_builder;
+mod script_error;
 
+pub use console::*;
 pub use diagnostic::*;
 pub use diagnostic_manager::*;
+pub use script_error::*;
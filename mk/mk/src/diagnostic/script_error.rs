@@ -0,0 +1,302 @@
+use crate::{
+    diagnostic::ScriptErrorPause,
+    engine::try_use_context,
+    log_diagnostic_event,
+    script::event::{Diagnostic, DiagnosticLevel},
+    EngineContext,
+};
+use anyhow::Error as AnyError;
+use mlua::prelude::*;
+use std::fmt::Display;
+
+const TRACEBACK_HEADER: &str = "stack traceback:";
+
+/// An error raised by a script, broken down for reporting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    pub message: String,
+    /// The chunk and line the error was raised at, if known.
+    pub location: Option<(String, u32)>,
+    pub traceback: Option<String>,
+}
+
+impl ScriptError {
+    pub fn from_lua_error(err: &LuaError) -> Self {
+        let (message, traceback) = split_lua_error(err);
+        let (location, message) = match split_location(&message) {
+            Some((file, line, message)) => (Some((file.to_owned(), line)), message.to_owned()),
+            None => (None, message),
+        };
+        // Errors raised by native functions carry no location; take the innermost script frame.
+        let location = location.or_else(|| {
+            traceback
+                .as_deref()
+                .and_then(traceback_location)
+                .map(|(file, line)| (file.to_owned(), line))
+        });
+
+        Self {
+            message,
+            location,
+            traceback,
+        }
+    }
+
+    pub fn from_error(err: &AnyError) -> Self {
+        match err.downcast_ref::<LuaError>() {
+            Some(err) => Self::from_lua_error(err),
+            None => Self {
+                message: format!("{:#}", err),
+                location: None,
+                traceback: None,
+            },
+        }
+    }
+
+    pub fn to_diagnostic(&self, source: impl Display) -> Diagnostic {
+        let (file, line) = match &self.location {
+            Some((file, line)) => (file.clone(), *line),
+            None => (String::new(), 0),
+        };
+
+        Diagnostic {
+            level: DiagnosticLevel::Error,
+            message: format!("error in {}: {}", source, self.message),
+            sub_diagnostics: vec![],
+            traceback: self.traceback.clone(),
+            file,
+            line,
+            column: 0,
+        }
+    }
+}
+
+/// Reports an error raised by a script while running `source`, e.g. `"timer"`.
+///
+/// The error is queued rather than emitted, so this can be called from inside an event handler.
+/// The same error is reported at most once per frame. Without an engine, e.g. in tests, it is logged right away.
+pub fn report_script_error(source: impl Display, err: &ScriptError) {
+    let diagnostic = err.to_diagnostic(source);
+
+    match try_use_context() {
+        Some(context) => {
+            context.diagnostic_mgr_mut().queue(diagnostic);
+        }
+        None => log_diagnostic_event(&diagnostic),
+    }
+}
+
+/// Emits the diagnostics queued since the last flush.
+/// Errors raised while handling them are left for the next flush.
+///
+/// In debug builds, the first error pauses the game if `pause_on_script_error` is set.
+pub fn flush_diagnostics(context: &EngineContext) {
+    let diagnostics = context.diagnostic_mgr_mut().take_queued();
+
+    for diagnostic in diagnostics {
+        if cfg!(debug_assertions) {
+            pause_on_script_error(context, &diagnostic);
+        }
+
+        context
+            .event_mgr()
+            .emit(&diagnostic, context.script_mgr().lua());
+    }
+}
+
+/// Resumes the game paused by a script error. Returns `false` if it was not paused by one.
+pub fn resume_from_script_error(context: &EngineContext) -> bool {
+    let pause = context.diagnostic_mgr_mut().take_script_error_pause();
+    let pause = match pause {
+        Some(pause) => pause,
+        None => return false,
+    };
+
+    context.time_mgr_mut().set_paused(pause.was_time_paused);
+    context.turn_mgr_mut().set_paused(pause.was_turn_paused);
+    true
+}
+
+fn pause_on_script_error(context: &EngineContext, diagnostic: &Diagnostic) {
+    {
+        let diagnostic_mgr = context.diagnostic_mgr();
+        if !diagnostic_mgr.pause_on_script_error() || diagnostic_mgr.script_error_pause().is_some()
+        {
+            return;
+        }
+    }
+
+    let was_time_paused = context.time_mgr().is_paused();
+    let was_turn_paused = context.turn_mgr().is_paused();
+    context.time_mgr_mut().set_paused(true);
+    context.turn_mgr_mut().set_paused(true);
+    context
+        .diagnostic_mgr_mut()
+        .set_script_error_pause(ScriptErrorPause {
+            diagnostic: diagnostic.clone(),
+            was_time_paused,
+            was_turn_paused,
+        });
+}
+
+/// Splits the message of the error from its traceback.
+fn split_lua_error(err: &LuaError) -> (String, Option<String>) {
+    match err {
+        LuaError::CallbackError { traceback, cause } => {
+            // The cause may have been raised deeper, in a script called back by the native function.
+            let (message, cause_traceback) = split_lua_error(cause);
+            (message, cause_traceback.or_else(|| Some(traceback.clone())))
+        }
+        LuaError::SyntaxError { message, .. } => (message.clone(), None),
+        LuaError::RuntimeError(message) => split_traceback(message),
+        err => split_traceback(&err.to_string()),
+    }
+}
+
+fn split_traceback(message: &str) -> (String, Option<String>) {
+    match message.find(TRACEBACK_HEADER) {
+        Some(index) => (
+            message[..index].trim_end().to_owned(),
+            Some(message[index..].to_owned()),
+        ),
+        None => (message.to_owned(), None),
+    }
+}
+
+/// Splits `chunk:line: message` into its parts.
+fn split_location(message: &str) -> Option<(&str, u32, &str)> {
+    let first_line = message.lines().next()?;
+    // Chunks loaded from strings are named `[string "..."]`, and the source may contain colons.
+    let mut offset = if first_line.starts_with("[string \"") {
+        first_line.find("\"]")? + 2
+    } else {
+        0
+    };
+
+    while let Some(index) = first_line[offset..].find(':') {
+        let colon = offset + index;
+        let rest = &first_line[colon + 1..];
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+
+        if digits != 0 && rest[digits..].starts_with(':') {
+            let line = rest[..digits].parse().ok()?;
+            let message = &message[colon + 1 + digits + 1..];
+            return Some((&first_line[..colon], line, message.trim_start()));
+        }
+
+        offset = colon + 1;
+    }
+
+    None
+}
+
+/// Returns the location of the innermost script frame of the traceback.
+fn traceback_location(traceback: &str) -> Option<(&str, u32)> {
+    traceback
+        .lines()
+        .skip(1)
+        .map(str::trim)
+        .filter(|frame| !frame.starts_with("[C]"))
+        .find_map(|frame| split_location(frame).map(|(file, line, _)| (file, line)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(lua: &Lua, source: &str) -> ScriptError {
+        let err = lua
+            .load(source)
+            .set_name("@main.lua")
+            .unwrap()
+            .exec()
+            .unwrap_err();
+        ScriptError::from_lua_error(&err)
+    }
+
+    #[test]
+    fn location_is_split_from_the_message() {
+        assert_eq!(
+            split_location("main.lua:3: attempt to index a nil value"),
+            Some(("main.lua", 3, "attempt to index a nil value"))
+        );
+        assert_eq!(
+            split_location("[string \"a:b\"]:3: boom"),
+            Some(("[string \"a:b\"]", 3, "boom"))
+        );
+        assert_eq!(
+            split_location("C:\\game\\scripts\\main.lua:12: boom: again"),
+            Some(("C:\\game\\scripts\\main.lua", 12, "boom: again"))
+        );
+        assert_eq!(split_location("boom: no location"), None);
+        assert_eq!(split_location("main.lua:x: boom"), None);
+    }
+
+    #[test]
+    fn traceback_is_split_from_the_message() {
+        assert_eq!(
+            split_traceback("main.lua:1: boom\nstack traceback:\n\t[C]: in ?"),
+            (
+                "main.lua:1: boom".to_owned(),
+                Some("stack traceback:\n\t[C]: in ?".to_owned())
+            )
+        );
+        assert_eq!(split_traceback("boom"), ("boom".to_owned(), None));
+    }
+
+    #[test]
+    fn traceback_location_skips_native_frames() {
+        let traceback = "stack traceback:\n\
+            \t[C]: in function 'error'\n\
+            \t[string \"a:b\"]:7: in function 'f'\n\
+            \tmain.lua:2: in main chunk";
+
+        assert_eq!(traceback_location(traceback), Some(("[string \"a:b\"]", 7)));
+        assert_eq!(traceback_location("stack traceback:\n\t[C]: in ?"), None);
+    }
+
+    #[test]
+    fn script_errors_are_located() {
+        let lua = Lua::new();
+        let err = run(&lua, "local x = 1\nerror('boom')");
+
+        assert_eq!(err.message, "boom");
+        assert_eq!(err.location, Some(("main.lua".to_owned(), 2)));
+    }
+
+    #[test]
+    fn native_errors_take_the_location_of_the_calling_script() {
+        let lua = Lua::new();
+        let fail = lua
+            .create_function(|_lua, ()| Err::<(), _>(LuaError::external("native failure")))
+            .unwrap();
+        lua.globals().set("fail", fail).unwrap();
+
+        let err = run(&lua, "\n\nfail()");
+
+        assert_eq!(err.message, "native failure");
+        assert_eq!(err.location, Some(("main.lua".to_owned(), 3)));
+        assert!(err.traceback.unwrap().starts_with(TRACEBACK_HEADER));
+    }
+
+    #[test]
+    fn nested_causes_give_the_innermost_error() {
+        let lua = Lua::new();
+        let call = lua
+            .create_function(|_lua, function: LuaFunction| function.call::<_, ()>(()))
+            .unwrap();
+        lua.globals().set("call", call).unwrap();
+
+        let err = run(&lua, "call(function()\n\terror('deep')\nend)");
+
+        assert_eq!(err.message, "deep");
+        assert_eq!(err.location, Some(("main.lua".to_owned(), 2)));
+    }
+
+    #[test]
+    fn errors_are_logged_without_an_engine() {
+        let lua = Lua::new();
+        assert!(try_use_context().is_none());
+        report_script_error("test", &run(&lua, "error('boom')"));
+    }
+}
@@ -1,41 +1,65 @@
 use crate::asset::*;
+use crate::diagnostic::{flush_diagnostics, report_script_error, ScriptError};
 use crate::emit_diagnostic_info;
+use crate::emit_diagnostic_warn;
+use crate::entity::remove_destroyed_entities;
 use crate::event::*;
+use crate::input::{
+    CursorState, GamepadEvent, InputRecorder, InputReplay, KeyCode, MouseInput, RecordedInput,
+};
 use crate::log_diagnostic_event;
+use crate::script::entity::Entity;
 use crate::script::event::Diagnostic;
+use crate::script::run_tasks;
+use crate::script::DebugServer;
 use crate::structure::Vec2;
 use crate::system::*;
+use crate::turn::MAX_TURNS_PER_FRAME;
+use crate::ui::{clipboard_text, TextComposition};
 use crate::EngineContext;
 use crate::GfxContext;
+use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
 use specs::RunNow;
-use std::fs::read_to_string;
+use std::env::var_os;
 use std::mem::MaybeUninit;
+use std::net::{Ipv4Addr, SocketAddr};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread::sleep;
-use std::time::Duration;
-use winit::dpi::LogicalSize;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use winit::dpi::PhysicalSize;
-use winit::event::{ElementState, Event, MouseButton, WindowEvent};
+use winit::dpi::{LogicalPosition, LogicalSize};
+use winit::event::{DeviceEvent, ElementState, Event, Ime, MouseButton, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::Window;
 use winit::window::WindowBuilder;
+use winit::window::{CursorGrabMode, Window};
 
-static mut CONTEXT: MaybeUninit<Arc<EngineContext>> = MaybeUninit::uninit();
+static mut CONTEXT: MaybeUninit<Rc<EngineContext>> = MaybeUninit::uninit();
+static IS_CONTEXT_READY: AtomicBool = AtomicBool::new(false);
 
 pub fn use_context() -> &'static EngineContext {
     unsafe { CONTEXT.assume_init_ref() }.as_ref()
 }
 
+/// Returns the context, or `None` if the engine has not created it yet, e.g. in tests.
+pub fn try_use_context() -> Option<&'static EngineContext> {
+    if IS_CONTEXT_READY.load(Ordering::Acquire) {
+        Some(use_context())
+    } else {
+        None
+    }
+}
+
 pub async fn run(
     title: &str,
     width: u32,
     height: u32,
     resizable: bool,
     asset_base: impl Into<PathBuf>,
-    entry_script_path: impl AsRef<Path>,
+    entry_module: impl AsRef<str>,
     once_engine_initialized: impl FnOnce(&Window, &EngineContext) -> Result<()>,
 ) -> Result<()> {
     let event_loop = EventLoop::new();
@@ -47,16 +71,23 @@ pub async fn run(
         .build(&event_loop)?;
 
     let gfx_context = GfxContext::new(&window).await?;
-    let context = Arc::new(EngineContext::new(
+    let user_dir = dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(title);
+    let debug_server = bind_debug_server()?;
+    let context = Rc::new(EngineContext::new(
         gfx_context,
         width,
         height,
         asset_base.into(),
+        user_dir,
+        debug_server,
     )?);
 
     unsafe {
         CONTEXT.write(context.clone());
     }
+    IS_CONTEXT_READY.store(true, Ordering::Release);
 
     #[cfg(debug_assertions)]
     {
@@ -71,22 +102,128 @@ pub async fn run(
         );
     }
 
+    // Diagnostics also show up in the developer console.
+    context.event_mgr().add_handler(
+        Diagnostic::name(),
+        EventHandler::native(|event| {
+            if let Some(event) = event.downcast_ref::<Diagnostic>() {
+                use_context()
+                    .diagnostic_mgr_mut()
+                    .console_mut()
+                    .print(format!("[{}] {}", event.level.to_str(), event.message));
+            }
+            Ok(())
+        }),
+    );
+
+    if let Some(address) = context.script_mgr().debug_server_address() {
+        emit_diagnostic_info!(format!("debug server listening on {}.", address));
+    }
+
     emit_diagnostic_info!(format!("configuring built-in systems."));
 
-    let mut audio_system = AudioSystem;
+    {
+        let system_mgr = context.system_mgr();
+        system_mgr.add_system(
+            SystemStage::PreUpdate,
+            AUDIO_SYSTEM,
+            AudioSystem,
+            SystemOptions::new(),
+        )?;
+        system_mgr.add_thread_local_system(
+            SystemStage::PreUpdate,
+            TEXT_FIELD_SYSTEM,
+            TextFieldSystem,
+            SystemOptions::new(),
+        )?;
+    }
+
     let mut render_system = RenderSystem::new(&mut context.render_mgr_mut());
 
-    let mut systems_pre_render = {
+    let systems_pre_render = {
         let context = context.clone();
         move || {
-            context.time_mgr_mut().update();
-            audio_system.run_now(&context.world());
+            // Report the errors raised since the last frame, e.g. while rendering.
+            flush_diagnostics(&context);
+            context.diagnostic_mgr_mut().begin_frame();
+            context.script_mgr().poll_debugger();
+
+            let replay_frame = context.input_mgr_mut().next_replay_frame();
+            match replay_frame {
+                Some(frame) => {
+                    for input in frame.inputs {
+                        handle_input(&context, input);
+                        // The pasted text is replayed from the recording instead of the clipboard.
+                        context.ui_event_mgr_mut().take_paste_request();
+                    }
+                    context.time_mgr_mut().advance(frame.dt);
+
+                    if !context.input_mgr().is_replaying() {
+                        emit_diagnostic_info!("input replay finished.".to_owned());
+                    }
+                }
+                None => {
+                    context.input_mgr_mut().poll_gamepads();
+                    context.time_mgr_mut().update();
+
+                    let dt = context.time_mgr().unscaled_dt_f64();
+                    context.input_mgr_mut().end_recorded_frame(dt);
+                }
+            }
+
+            let is_editing_text = context.ui_event_mgr().is_editing_text();
+            context
+                .input_mgr_mut()
+                .set_keyboard_captured(is_editing_text);
+            context.input_mgr_mut().update();
+
+            let rebind_results = context.input_mgr_mut().take_rebind_results();
+            for result in rebind_results {
+                context.event_mgr().emit(
+                    &crate::script::event::InputRebound::from(result),
+                    context.script_mgr().lua(),
+                );
+            }
+
+            let gamepad_warnings = context.input_mgr_mut().take_gamepad_warnings();
+            for warning in gamepad_warnings {
+                emit_diagnostic_warn!(warning);
+            }
+
+            let gamepad_events = context.input_mgr_mut().take_gamepad_events();
+            for event in gamepad_events {
+                match event {
+                    GamepadEvent::Connected { id, name } => {
+                        context.event_mgr().emit(
+                            &crate::script::event::GamepadConnected { gamepad: id, name },
+                            context.script_mgr().lua(),
+                        );
+                    }
+                    GamepadEvent::Disconnected { id, name } => {
+                        context.event_mgr().emit(
+                            &crate::script::event::GamepadDisconnected { gamepad: id, name },
+                            context.script_mgr().lua(),
+                        );
+                    }
+                }
+            }
+
+            // Events posted from other threads, or deferred by the main thread.
+            context
+                .event_mgr()
+                .dispatch_posted(context.script_mgr().lua());
+            // Entities destroyed while handling input or rendering the last frame.
+            remove_destroyed_entities(&context);
+
             // animate_sigle_animations(
             //     &mut context.world_mut(),
             //     &context.time_mgr(),
             //     &mut context.transform_mgr_mut(),
             // );
             context.ui_mgr_mut().update_elements();
+            context
+                .system_mgr()
+                .run_stage(&context, SystemStage::PreUpdate);
             context.transform_mgr_mut().update_world_matrices();
 
             context.event_mgr().emit(
@@ -95,18 +232,43 @@ pub async fn run(
                 },
                 context.script_mgr().lua(),
             );
+            let (fixed_dt, fixed_steps) = {
+                let time_mgr = context.time_mgr();
+                (time_mgr.fixed_dt(), time_mgr.fixed_steps())
+            };
+            if let Some(fixed_dt) = fixed_dt {
+                for _ in 0..fixed_steps {
+                    context.event_mgr().emit(
+                        &crate::script::event::FixedUpdate { dt: fixed_dt },
+                        context.script_mgr().lua(),
+                    );
+                }
+            }
+            run_timers(&context);
+
             context.event_mgr().emit(
                 &crate::script::event::Update {
                     dt: context.time_mgr().dt_f64(),
                 },
                 context.script_mgr().lua(),
             );
+            context
+                .system_mgr()
+                .run_stage(&context, SystemStage::Update);
+            run_tasks(&context);
+            run_turns(&context);
+
             context.event_mgr().emit(
                 &crate::script::event::PostUpdate {
                     dt: context.time_mgr().dt_f64(),
                 },
                 context.script_mgr().lua(),
             );
+            context
+                .system_mgr()
+                .run_stage(&context, SystemStage::PostUpdate);
+            remove_destroyed_entities(&context);
+            flush_diagnostics(&context);
         }
     };
 
@@ -116,9 +278,13 @@ pub async fn run(
             context.event_mgr().emit(
                 &crate::script::event::PreRender {
                     dt: context.time_mgr().dt_f64(),
+                    alpha: context.time_mgr().alpha(),
                 },
                 context.script_mgr().lua(),
             );
+            context
+                .system_mgr()
+                .run_stage(&context, SystemStage::PreRender);
 
             if !skip_render {
                 context.render_mgr().update_uniforms(&context);
@@ -142,6 +308,8 @@ pub async fn run(
         let mut asset_mgr = context.asset_mgr_mut();
         asset_mgr.register_loader(loader::audio_clip_loader());
         asset_mgr.register_loader(loader::font_loader());
+        asset_mgr.register_loader(loader::input_bindings_loader());
+        asset_mgr.register_loader(loader::prefab_loader());
         asset_mgr.register_loader(loader::shader_loader());
         asset_mgr.register_loader(loader::sprite_loader());
         // asset_mgr.register_loader(loader::sprite_atlas_loader());
@@ -170,14 +338,16 @@ pub async fn run(
         once_engine_initialized(&window, &context).with_context(|| "failed to execute callback")?;
     }
 
+    configure_input_recording(&context).with_context(|| "failed to configure input recording")?;
+
     {
         emit_diagnostic_info!(format!("executing entry script."));
 
-        let path = entry_script_path.as_ref();
-        let script_mgr = context.script_mgr();
-        script_mgr
-            .execute(read_to_string(path).with_context(|| "failed to read entry script")?)
+        context
+            .script_mgr()
+            .execute_module(entry_module)
             .with_context(|| "failed to execute entry script")?;
+        flush_diagnostics(&context);
     }
 
     emit_diagnostic_info!(format!("engine is up and running."));
@@ -186,163 +356,499 @@ pub async fn run(
 
     let window_id = window.id();
     let mut window_occluded = false;
+    let mut ime_allowed = false;
+    let mut next_frame = Instant::now();
 
     event_loop.run(move |event, _, control_flow| {
-        *control_flow = ControlFlow::Poll;
-
         match event {
             Event::MainEventsCleared => {
+                // With a frame rate cap, sleep until the next frame is due instead of spinning.
+                let frame_interval = context.time_mgr().frame_interval();
+                match frame_interval {
+                    Some(frame_interval) => {
+                        let now = Instant::now();
+
+                        if now < next_frame {
+                            *control_flow = ControlFlow::WaitUntil(next_frame);
+                            return;
+                        }
+
+                        next_frame = (next_frame + frame_interval).max(now);
+                        *control_flow = ControlFlow::WaitUntil(next_frame);
+                    }
+                    None => {
+                        *control_flow = ControlFlow::Poll;
+                    }
+                }
+
                 systems_pre_render();
+
+                let cursor_change = context.input_mgr().mouse().take_cursor_change();
+                if let Some(cursor) = cursor_change {
+                    apply_cursor_state(&window, cursor);
+                }
+
+                // Input methods are only enabled while a text field is focused.
+                let is_editing_text = context.ui_event_mgr().is_editing_text();
+                if ime_allowed != is_editing_text {
+                    ime_allowed = is_editing_text;
+                    window.set_ime_allowed(ime_allowed);
+                }
+                if ime_allowed {
+                    let position = context.ui_event_mgr().focus_position_in_screen();
+                    if let Some(position) = position {
+                        window.set_ime_position(LogicalPosition::new(position.x, position.y));
+                    }
+                }
+
                 systems_render(window_occluded);
 
                 if window_occluded {
                     sleep(Duration::from_millis(60));
                 }
-
-                return;
             }
             Event::WindowEvent {
                 event: WindowEvent::Occluded(occluded),
                 window_id: id,
             } if id == window_id => {
                 window_occluded = occluded;
-
-                return;
             }
             Event::WindowEvent {
-                event: WindowEvent::KeyboardInput { input, .. },
+                event: WindowEvent::Resized(inner_size),
                 window_id: id,
             } if id == window_id => {
-                context.input_mgr_mut().handle_event(&input);
-
-                if let Some(key) = input.virtual_keycode {
-                    match input.state {
-                        ElementState::Pressed => {
-                            context.event_mgr().emit(
-                                &crate::script::event::KeyDown::from_key(key),
-                                context.script_mgr().lua(),
-                            );
-                        }
-                        ElementState::Released => {
-                            context.event_mgr().emit(
-                                &crate::script::event::KeyUp::from_key(key),
-                                context.script_mgr().lua(),
-                            );
-                        }
-                    }
-                }
-
-                return;
+                context.screen_mgr_mut().update_size(inner_size);
+                context.render_mgr_mut().resize_gfx(inner_size);
             }
             Event::WindowEvent {
-                event: WindowEvent::CursorEntered { .. },
+                event:
+                    WindowEvent::ScaleFactorChanged {
+                        scale_factor,
+                        new_inner_size,
+                    },
                 window_id: id,
             } if id == window_id => {
-                context.event_mgr().emit(
-                    &crate::script::event::PointerEnter,
-                    context.script_mgr().lua(),
-                );
-
-                return;
+                context
+                    .screen_mgr_mut()
+                    .update_scale_factor(scale_factor, *new_inner_size);
+                context.render_mgr_mut().resize_gfx(*new_inner_size);
             }
             Event::WindowEvent {
-                event: WindowEvent::CursorLeft { .. },
+                event: WindowEvent::CloseRequested,
                 window_id: id,
             } if id == window_id => {
-                context.event_mgr().emit(
-                    &crate::script::event::PointerExit,
-                    context.script_mgr().lua(),
-                );
-                context.ui_event_mgr_mut().handle_mouse_exit();
-
-                return;
+                context
+                    .event_mgr()
+                    .emit(&crate::script::event::Exit, context.script_mgr().lua());
+                *control_flow = ControlFlow::Exit;
             }
             Event::WindowEvent {
-                event: WindowEvent::CursorMoved { position, .. },
+                event,
                 window_id: id,
             } if id == window_id => {
-                let position = position.to_logical(context.screen_mgr().scale_factor());
+                let scale_factor = context.screen_mgr().scale_factor();
 
-                context.event_mgr().emit(
-                    &crate::script::event::PointerMove {
-                        pointer_x: position.x,
-                        pointer_y: position.y,
+                if let Some(input) = recorded_input_from_window_event(event, scale_factor) {
+                    feed_input(&context, input);
+                }
+            }
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } => {
+                feed_input(
+                    &context,
+                    RecordedInput::MouseMotion {
+                        x: delta.0 as f32,
+                        y: delta.1 as f32,
                     },
-                    context.script_mgr().lua(),
                 );
-                context
-                    .ui_event_mgr_mut()
-                    .handle_mouse_move(Vec2::new(position.x as f32, position.y as f32));
+            }
+            _ => {}
+        }
+    });
+}
 
-                return;
+/// Calls the Lua functions of the timers due in this frame.
+fn run_timers(context: &EngineContext) {
+    let due = {
+        let time_mgr = context.time_mgr();
+        context
+            .timer_mgr_mut()
+            .update(time_mgr.dt_f64(), time_mgr.unscaled_dt_f64())
+    };
+
+    for (handle, function) in due {
+        // A timer may have been cancelled by another one due in the same frame.
+        if !context.timer_mgr().is_active(handle) {
+            continue;
+        }
+
+        let lua = context.script_mgr().lua();
+        let result = function
+            .as_function(lua)
+            .and_then(|function| function.call::<_, ()>(handle));
+
+        if let Err(err) = result {
+            report_script_error("timer", &ScriptError::from_lua_error(&err));
+        }
+    }
+
+    context.timer_mgr_mut().finish_update();
+}
+
+/// Begins turns until an actor keeps its turn, e.g. to wait for the player,
+/// reporting every turn ended in between.
+fn run_turns(context: &EngineContext) {
+    let emit_ended_turns = || {
+        let ended_turns = context.turn_mgr_mut().take_ended_turns();
+        for ended in ended_turns {
+            context.event_mgr().emit(
+                &crate::script::event::TurnEnd {
+                    entity: Entity::new(ended.actor),
+                    turn: ended.turn,
+                    cost: ended.cost,
+                },
+                context.script_mgr().lua(),
+            );
+        }
+    };
+
+    for _ in 0..MAX_TURNS_PER_FRAME {
+        emit_ended_turns();
+
+        let actor = context.turn_mgr_mut().begin_turn();
+        let actor = match actor {
+            Some(actor) => actor,
+            None => break,
+        };
+        let turn = context.turn_mgr().turn();
+
+        context.event_mgr().emit(
+            &crate::script::event::TurnBegin {
+                entity: Entity::new(actor),
+                turn,
+            },
+            context.script_mgr().lua(),
+        );
+    }
+
+    emit_ended_turns();
+}
+
+/// Environment variable naming the address the Lua debug server listens on,
+/// e.g. `4711` for `127.0.0.1:4711`. Only loopback addresses are accepted, as a debug client
+/// can run any code in the game.
+pub const DEBUG_SERVER_ENV: &str = "MK_DEBUG_SERVER";
+
+/// Starts the Lua debug server if requested. Called before the Lua state is created,
+/// as the state of a debugged game carries the `debug` library.
+fn bind_debug_server() -> Result<Option<DebugServer>> {
+    let address = match var_os(DEBUG_SERVER_ENV) {
+        Some(address) => address.to_string_lossy().into_owned(),
+        None => return Ok(None),
+    };
+    let address = match address.parse::<u16>() {
+        Ok(port) => SocketAddr::from((Ipv4Addr::LOCALHOST, port)),
+        Err(..) => address.parse::<SocketAddr>().with_context(|| {
+            format!(
+                "invalid debug server address '{}'; expected a port or a loopback address",
+                address
+            )
+        })?,
+    };
+
+    if !address.ip().is_loopback() {
+        bail!(
+            "debug server address {} must be a loopback address",
+            address
+        );
+    }
+
+    DebugServer::bind(address)
+        .with_context(|| format!("failed to start debug server on {}", address))
+        .map(Some)
+}
+
+/// Environment variable naming a file to record every input into.
+pub const RECORD_INPUT_ENV: &str = "MK_RECORD_INPUT";
+/// Environment variable naming a recorded file to replay instead of the live inputs.
+pub const REPLAY_INPUT_ENV: &str = "MK_REPLAY_INPUT";
+
+/// Starts recording or replaying the inputs if requested, before any script runs.
+/// The script RNG is seeded from the recording so that replays generate the same content.
+fn configure_input_recording(context: &EngineContext) -> Result<()> {
+    let seed = if let Some(path) = var_os(REPLAY_INPUT_ENV) {
+        let replay = InputReplay::open(&path)?;
+        let seed = replay.seed();
+
+        emit_diagnostic_info!(format!(
+            "replaying {} frames of inputs from {}.",
+            replay.remaining_frames(),
+            Path::new(&path).display()
+        ));
+        context.input_mgr_mut().start_replay(replay);
+        seed
+    } else if let Some(path) = var_os(RECORD_INPUT_ENV) {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or_default();
+        let recorder = InputRecorder::create(&path, seed)?;
+
+        emit_diagnostic_info!(format!(
+            "recording inputs into {}.",
+            Path::new(&path).display()
+        ));
+        context.input_mgr_mut().start_recording(recorder);
+        seed
+    } else {
+        return Ok(());
+    };
+
+    let lua = context.script_mgr().lua();
+    let math: mlua::Table = lua.globals().get("math")?;
+    let randomseed: mlua::Function = math.get("randomseed")?;
+    randomseed.call::<_, ()>(seed as i64)?;
+
+    Ok(())
+}
+
+fn recorded_input_from_window_event(
+    event: WindowEvent,
+    scale_factor: f64,
+) -> Option<RecordedInput> {
+    Some(match event {
+        WindowEvent::Focused(focused) => RecordedInput::Focused(focused),
+        WindowEvent::KeyboardInput { input, .. } => RecordedInput::Keyboard(input),
+        WindowEvent::ModifiersChanged(modifiers) => RecordedInput::Modifiers(modifiers),
+        WindowEvent::ReceivedCharacter(character) if !character.is_control() => {
+            RecordedInput::Character(character)
+        }
+        WindowEvent::Ime(Ime::Preedit(text, cursor)) => {
+            // The cursor is given in bytes.
+            let caret = cursor.map(|(start, _)| text[..start].chars().count());
+            RecordedInput::Composition { text, caret }
+        }
+        WindowEvent::Ime(Ime::Commit(text)) => RecordedInput::Commit(text),
+        WindowEvent::Ime(Ime::Disabled) => RecordedInput::CompositionEnd,
+        WindowEvent::CursorEntered { .. } => RecordedInput::PointerEnter,
+        WindowEvent::CursorLeft { .. } => RecordedInput::PointerExit,
+        WindowEvent::CursorMoved { position, .. } => {
+            let position = position.to_logical(scale_factor);
+            RecordedInput::PointerMove {
+                x: position.x,
+                y: position.y,
             }
-            Event::WindowEvent {
-                event: WindowEvent::MouseInput { button, state, .. },
-                window_id: id,
-            } if id == window_id => {
-                let button_name = match button {
-                    MouseButton::Left => "left",
-                    MouseButton::Right => "right",
-                    MouseButton::Middle => "middle",
-                    _ => return,
-                };
-
-                match state {
+        }
+        WindowEvent::MouseInput { button, state, .. } => {
+            RecordedInput::PointerButton { button, state }
+        }
+        WindowEvent::MouseWheel { delta, .. } => {
+            match MouseInput::from_winit_wheel(delta, scale_factor) {
+                MouseInput::Wheel(delta) => RecordedInput::PointerWheel {
+                    x: delta.x,
+                    y: delta.y,
+                },
+                _ => return None,
+            }
+        }
+        _ => return None,
+    })
+}
+
+/// Records and handles a live input. Live inputs are dropped while a recording is replayed.
+fn feed_input(context: &EngineContext, input: RecordedInput) {
+    if context.input_mgr().is_replaying() {
+        return;
+    }
+
+    context.input_mgr_mut().record(input.clone());
+    handle_input(context, input);
+
+    // The clipboard is read once, and the pasted text is recorded for replays.
+    if context.ui_event_mgr_mut().take_paste_request() {
+        if let Some(text) = clipboard_text() {
+            feed_input(context, RecordedInput::Paste(text));
+        }
+    }
+}
+
+fn handle_input(context: &EngineContext, input: RecordedInput) {
+    match input {
+        RecordedInput::Focused(focused) => {
+            context.input_mgr().mouse().set_focused(focused);
+        }
+        RecordedInput::MouseMotion { x, y } => {
+            context
+                .input_mgr_mut()
+                .handle_mouse_input(MouseInput::Motion(Vec2::new(x, y)));
+        }
+        RecordedInput::Keyboard(input) => {
+            context.input_mgr_mut().handle_event(&input);
+
+            if input.state == ElementState::Pressed {
+                if let Some(key) = input
+                    .virtual_keycode
+                    .and_then(KeyCode::from_virtual_keycode)
+                {
+                    context.ui_event_mgr_mut().handle_key_down(key);
+                }
+            }
+
+            if let Some(key) = input.virtual_keycode {
+                match input.state {
                     ElementState::Pressed => {
                         context.event_mgr().emit(
-                            &crate::script::event::PointerDown {
-                                button: button_name,
-                            },
+                            &crate::script::event::KeyDown::from_key(key),
                             context.script_mgr().lua(),
                         );
-                        context.ui_event_mgr_mut().handle_mouse_button_down(button);
                     }
                     ElementState::Released => {
                         context.event_mgr().emit(
-                            &crate::script::event::PointerUp {
-                                button: button_name,
-                            },
+                            &crate::script::event::KeyUp::from_key(key),
                             context.script_mgr().lua(),
                         );
-                        context.ui_event_mgr_mut().handle_mouse_button_up(button);
                     }
                 }
-
-                return;
             }
-            Event::WindowEvent {
-                event: WindowEvent::Resized(inner_size),
-                window_id: id,
-            } if id == window_id => {
-                context.screen_mgr_mut().update_size(inner_size);
-                context.render_mgr_mut().resize_gfx(inner_size);
-
+        }
+        RecordedInput::Modifiers(modifiers) => {
+            context
+                .ui_event_mgr_mut()
+                .handle_modifiers_changed(modifiers);
+        }
+        RecordedInput::Character(character) => {
+            // Characters committed by an input method are handled through `Commit`.
+            if context.ui_event_mgr().is_composing_text() {
                 return;
             }
-            Event::WindowEvent {
-                event:
-                    WindowEvent::ScaleFactorChanged {
-                        scale_factor,
-                        new_inner_size,
-                    },
-                window_id: id,
-            } if id == window_id => {
-                context
-                    .screen_mgr_mut()
-                    .update_scale_factor(scale_factor, *new_inner_size);
-                context.render_mgr_mut().resize_gfx(*new_inner_size);
 
-                return;
+            let text = character.to_string();
+            context.event_mgr().emit(
+                &crate::script::event::TextInput { text: text.clone() },
+                context.script_mgr().lua(),
+            );
+            context.ui_event_mgr_mut().handle_text_input(&text);
+        }
+        RecordedInput::Composition { text, caret } => {
+            context.event_mgr().emit(
+                &crate::script::event::TextComposition {
+                    text: text.clone(),
+                    caret,
+                },
+                context.script_mgr().lua(),
+            );
+            context
+                .ui_event_mgr_mut()
+                .handle_text_composition(Some(TextComposition { text, caret }));
+        }
+        RecordedInput::Commit(text) => {
+            context.event_mgr().emit(
+                &crate::script::event::TextInput { text: text.clone() },
+                context.script_mgr().lua(),
+            );
+            context.ui_event_mgr_mut().handle_text_input(&text);
+        }
+        RecordedInput::Paste(text) => {
+            context.ui_event_mgr_mut().handle_text_input(&text);
+        }
+        RecordedInput::Gamepad(input) => {
+            context.input_mgr_mut().handle_gamepad_input(input);
+        }
+        RecordedInput::CompositionEnd => {
+            context.ui_event_mgr_mut().handle_text_composition(None);
+        }
+        RecordedInput::PointerEnter => {
+            context.event_mgr().emit(
+                &crate::script::event::PointerEnter,
+                context.script_mgr().lua(),
+            );
+        }
+        RecordedInput::PointerExit => {
+            context.event_mgr().emit(
+                &crate::script::event::PointerExit,
+                context.script_mgr().lua(),
+            );
+            context.ui_event_mgr_mut().handle_mouse_exit();
+            context.input_mgr_mut().handle_mouse_input(MouseInput::Left);
+        }
+        RecordedInput::PointerMove { x, y } => {
+            context.event_mgr().emit(
+                &crate::script::event::PointerMove {
+                    pointer_x: x,
+                    pointer_y: y,
+                },
+                context.script_mgr().lua(),
+            );
+            context
+                .ui_event_mgr_mut()
+                .handle_mouse_move(Vec2::new(x as f32, y as f32));
+            context
+                .input_mgr_mut()
+                .handle_mouse_input(MouseInput::Moved(Vec2::new(x as f32, y as f32)));
+        }
+        RecordedInput::PointerButton { button, state } => {
+            if let Some(input) = MouseInput::from_winit_button(button, state) {
+                context.input_mgr_mut().handle_mouse_input(input);
             }
-            Event::WindowEvent {
-                event: WindowEvent::CloseRequested,
-                window_id: id,
-            } if id == window_id => {
-                *control_flow = ControlFlow::Exit;
 
-                return;
+            let button_name = match button {
+                MouseButton::Left => "left",
+                MouseButton::Right => "right",
+                MouseButton::Middle => "middle",
+                _ => return,
+            };
+
+            match state {
+                ElementState::Pressed => {
+                    context.event_mgr().emit(
+                        &crate::script::event::PointerDown {
+                            button: button_name,
+                        },
+                        context.script_mgr().lua(),
+                    );
+                    context.ui_event_mgr_mut().handle_mouse_button_down(button);
+                }
+                ElementState::Released => {
+                    context.event_mgr().emit(
+                        &crate::script::event::PointerUp {
+                            button: button_name,
+                        },
+                        context.script_mgr().lua(),
+                    );
+                    context.ui_event_mgr_mut().handle_mouse_button_up(button);
+                }
             }
-            _ => return,
         }
-    });
+        RecordedInput::PointerWheel { x, y } => {
+            context.event_mgr().emit(
+                &crate::script::event::PointerWheel {
+                    delta_x: x,
+                    delta_y: y,
+                },
+                context.script_mgr().lua(),
+            );
+            context
+                .input_mgr_mut()
+                .handle_mouse_input(MouseInput::Wheel(Vec2::new(x, y)));
+        }
+    }
+}
+
+fn apply_cursor_state(window: &Window, cursor: CursorState) {
+    window.set_cursor_visible(cursor.visible);
+
+    let result = if cursor.locked {
+        // Not every platform can lock the cursor in place; confining it is the closest fallback.
+        window
+            .set_cursor_grab(CursorGrabMode::Locked)
+            .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
+    } else {
+        window.set_cursor_grab(CursorGrabMode::None)
+    };
+
+    if let Err(err) = result {
+        emit_diagnostic_warn!(format!("failed to change cursor grab mode: {}", err));
+    }
 }
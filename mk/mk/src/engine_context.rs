@@ -1,34 +1,48 @@
 use crate::asset::AssetManager;
 use crate::audio::AudioManager;
 use crate::component::register_components;
+use crate::diagnostic::DiagnosticManager;
+use crate::entity::EntityManager;
 use crate::event::{EntityEventManager, EventManager};
 use crate::gfx::{GlyphManager, RenderManager, ScreenManager};
 use crate::input::InputManager;
-use crate::script::ScriptManager;
-use crate::time::TimeManager;
+use crate::script::{
+    DebugServer, Debugger, DirModuleSource, ResModuleSource, ScriptManager, TaskManager,
+};
+use crate::system::SystemManager;
+use crate::time::{TimeManager, TimerManager};
 use crate::transform::TransformManager;
+use crate::turn::TurnManager;
 use crate::ui::{UIEventManager, UIManager};
 use crate::GfxContext;
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use res::{asset_loader::ResourceLoader, ResourcesMeta};
 use specs::prelude::*;
 use std::cell::{Ref, RefCell, RefMut};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub struct EngineContext {
     world: RefCell<World>,
     time_mgr: RefCell<TimeManager>,
+    timer_mgr: RefCell<TimerManager>,
     input_mgr: RefCell<InputManager>,
+    turn_mgr: RefCell<TurnManager>,
     screen_mgr: RefCell<ScreenManager>,
     audio_mgr: AudioManager,
     asset_mgr: RefCell<AssetManager>,
     transform_mgr: RefCell<TransformManager>,
+    entity_mgr: RefCell<EntityManager>,
     event_mgr: EventManager,
     entity_event_mgr: EntityEventManager,
     script_mgr: ScriptManager,
+    diagnostic_mgr: RefCell<DiagnosticManager>,
+    task_mgr: RefCell<TaskManager>,
     glyph_mgr: RefCell<GlyphManager>,
     render_mgr: RefCell<RenderManager>,
     ui_mgr: RefCell<UIManager>,
     ui_event_mgr: RefCell<UIEventManager>,
+    system_mgr: SystemManager,
+    user_dir: PathBuf,
 }
 
 impl EngineContext {
@@ -37,6 +51,8 @@ impl EngineContext {
         screen_width: u32,
         screen_height: u32,
         asset_mgr_base: PathBuf,
+        user_dir: PathBuf,
+        debug_server: Option<DebugServer>,
     ) -> Result<Self> {
         let mut world = World::new();
 
@@ -45,20 +61,29 @@ impl EngineContext {
         Ok(Self {
             world: world.into(),
             time_mgr: TimeManager::new().into(),
+            timer_mgr: TimerManager::new().into(),
             input_mgr: InputManager::new().into(),
+            turn_mgr: TurnManager::new().into(),
             screen_mgr: ScreenManager::new(screen_width, screen_height).into(),
             audio_mgr: AudioManager::new(),
-            asset_mgr: AssetManager::new(asset_mgr_base).into(),
+            asset_mgr: AssetManager::new(asset_mgr_base.clone()).into(),
             transform_mgr: TransformManager::new().into(),
+            entity_mgr: EntityManager::new().into(),
             event_mgr: EventManager::new(),
             entity_event_mgr: EntityEventManager::new(),
-            script_mgr: ScriptManager::new()
-                .with_context(|| "failed to initialize script manager")?
-                .into(),
+            script_mgr: ScriptManager::new(
+                DirModuleSource::new(asset_mgr_base.clone()),
+                debug_server.map(|server| Debugger::new(server, asset_mgr_base)),
+            )
+            .with_context(|| "failed to initialize script manager")?,
+            diagnostic_mgr: DiagnosticManager::new().into(),
+            task_mgr: TaskManager::new().into(),
             glyph_mgr: GlyphManager::new(128f32, 8usize, 48usize, 0.5f32).into(),
             render_mgr: RenderManager::new(gfx_context).into(),
             ui_mgr: UIManager::new().into(),
             ui_event_mgr: UIEventManager::new().into(),
+            system_mgr: SystemManager::new(),
+            user_dir,
         })
     }
 
@@ -78,6 +103,14 @@ impl EngineContext {
         self.time_mgr.borrow_mut()
     }
 
+    pub fn timer_mgr(&self) -> Ref<TimerManager> {
+        self.timer_mgr.borrow()
+    }
+
+    pub fn timer_mgr_mut(&self) -> RefMut<TimerManager> {
+        self.timer_mgr.borrow_mut()
+    }
+
     pub fn input_mgr(&self) -> Ref<InputManager> {
         self.input_mgr.borrow()
     }
@@ -86,6 +119,14 @@ impl EngineContext {
         self.input_mgr.borrow_mut()
     }
 
+    pub fn turn_mgr(&self) -> Ref<TurnManager> {
+        self.turn_mgr.borrow()
+    }
+
+    pub fn turn_mgr_mut(&self) -> RefMut<TurnManager> {
+        self.turn_mgr.borrow_mut()
+    }
+
     pub fn screen_mgr(&self) -> Ref<ScreenManager> {
         self.screen_mgr.borrow()
     }
@@ -114,6 +155,14 @@ impl EngineContext {
         self.transform_mgr.borrow_mut()
     }
 
+    pub fn entity_mgr(&self) -> Ref<EntityManager> {
+        self.entity_mgr.borrow()
+    }
+
+    pub fn entity_mgr_mut(&self) -> RefMut<EntityManager> {
+        self.entity_mgr.borrow_mut()
+    }
+
     pub fn event_mgr(&self) -> &EventManager {
         &self.event_mgr
     }
@@ -126,6 +175,46 @@ impl EngineContext {
         &self.script_mgr
     }
 
+    /// Reads Lua modules from the packed `res` archive in `base_path` instead of the asset
+    /// directory. Call it from `once_engine_initialized`, before the entry module is required.
+    pub fn use_packed_modules(
+        &self,
+        key: impl AsRef<[u8]>,
+        salt: impl AsRef<[u8]>,
+        base_path: impl AsRef<Path>,
+        meta: ResourcesMeta,
+    ) -> Result<()> {
+        let base_path = base_path.as_ref();
+        let loader = ResourceLoader::new(key, salt, base_path).map_err(|err| {
+            anyhow!(
+                "failed to open packed assets in {}: {:?}",
+                base_path.display(),
+                err
+            )
+        })?;
+
+        self.script_mgr
+            .module_loader_mut()
+            .set_source(ResModuleSource::new(loader, meta));
+        Ok(())
+    }
+
+    pub fn diagnostic_mgr(&self) -> Ref<DiagnosticManager> {
+        self.diagnostic_mgr.borrow()
+    }
+
+    pub fn diagnostic_mgr_mut(&self) -> RefMut<DiagnosticManager> {
+        self.diagnostic_mgr.borrow_mut()
+    }
+
+    pub fn task_mgr(&self) -> Ref<TaskManager> {
+        self.task_mgr.borrow()
+    }
+
+    pub fn task_mgr_mut(&self) -> RefMut<TaskManager> {
+        self.task_mgr.borrow_mut()
+    }
+
     pub fn glyph_mgr(&self) -> Ref<GlyphManager> {
         self.glyph_mgr.borrow()
     }
@@ -157,4 +246,13 @@ impl EngineContext {
     pub fn ui_event_mgr_mut(&self) -> RefMut<UIEventManager> {
         self.ui_event_mgr.borrow_mut()
     }
+
+    pub fn system_mgr(&self) -> &SystemManager {
+        &self.system_mgr
+    }
+
+    /// Per-user writable directory for settings and other persisted data.
+    pub fn user_dir(&self) -> &Path {
+        &self.user_dir
+    }
 }
@@ -6,14 +6,28 @@ pub(crate) fn log_diagnostic_event(event: &Diagnostic) {
     let indent = prefix.len();
     let lines = event.message.split('\n').collect::<Vec<_>>();
     let (&first_line, context_lines) = lines.split_first().unwrap();
-    let message = format!(
-        "{}{} [{}:{}:{}]",
-        set_color(event.level, prefix),
-        first_line,
-        event.file,
-        event.line,
-        event.column
-    );
+    let message = if event.file.is_empty() {
+        format!("{}{}", set_color(event.level, prefix), first_line)
+    } else {
+        format!(
+            "{}{} [{}:{}:{}]",
+            set_color(event.level, prefix),
+            first_line,
+            event.file,
+            event.line,
+            event.column
+        )
+    };
+    let context_lines = context_lines
+        .iter()
+        .copied()
+        .chain(
+            event
+                .traceback
+                .iter()
+                .flat_map(|traceback| traceback.lines()),
+        )
+        .collect::<Vec<_>>();
     let message = if context_lines.is_empty() {
         message
     } else {
@@ -0,0 +1,155 @@
+use crate::{
+    component::{GlyphRenderer, Transform, UIElement},
+    script::{entity::Entity as ScriptEntity, event::OnDestroy},
+    EngineContext,
+};
+use mlua::prelude::*;
+use specs::prelude::*;
+use std::collections::HashSet;
+
+/// Destroys the entity and all of its children. `OnDestroy` is emitted on each of them right away, from the deepest,
+/// and their event handlers and turns are removed; they are removed from the world by [`remove_destroyed_entities`].
+/// Returns `false` if the entity is not alive or has already been destroyed.
+pub fn destroy_entity(context: &EngineContext, entity: Entity, lua: &Lua) -> bool {
+    if !context.world().is_alive(entity) || !context.entity_mgr_mut().mark_destroyed(entity) {
+        return false;
+    }
+
+    let entities = transform_and_children(context, entity);
+
+    for &entity in &entities {
+        context.entity_mgr_mut().mark_destroyed(entity);
+    }
+
+    // Children are notified before their parents, while the whole tree is still alive.
+    for &entity in entities.iter().rev() {
+        context
+            .entity_event_mgr()
+            .emit(ScriptEntity::new(entity), &OnDestroy, lua);
+    }
+
+    for &entity in &entities {
+        remove_handlers(context, entity);
+    }
+
+    true
+}
+
+/// Removes the destroyed entities from the world, releasing their transforms, UI elements and glyphs.
+/// Children added to them after they have been destroyed are removed along with them.
+pub fn remove_destroyed_entities(context: &EngineContext) {
+    let destroyed = context.entity_mgr_mut().take_destroyed();
+
+    if destroyed.is_empty() {
+        return;
+    }
+
+    let mut entities = Vec::with_capacity(destroyed.len());
+    let mut transforms = HashSet::with_capacity(destroyed.len());
+
+    {
+        let world = context.world();
+        let transform_storage = world.read_storage::<Transform>();
+        let transform_mgr = context.transform_mgr();
+
+        for entity in destroyed {
+            if !world.is_alive(entity) {
+                continue;
+            }
+
+            match transform_storage.get(entity) {
+                Some(transform) => {
+                    for &transform in transform_mgr
+                        .hierarchy()
+                        .transform_and_children(transform.index())
+                    {
+                        let entity = transform_mgr.allocator().entity(transform);
+
+                        if world.is_alive(entity) && transforms.insert(transform) {
+                            entities.push(entity);
+                        }
+                    }
+                }
+                None => entities.push(entity),
+            }
+        }
+    }
+
+    {
+        let world = context.world();
+        let mut glyph_renderer_storage = world.write_storage::<GlyphRenderer>();
+        let ui_element_storage = world.read_storage::<UIElement>();
+        let mut render_mgr = context.render_mgr_mut();
+        let mut ui_mgr = context.ui_mgr_mut();
+        let mut ui_event_mgr = context.ui_event_mgr_mut();
+
+        for &entity in &entities {
+            if let Some(glyph_renderer) = glyph_renderer_storage.get_mut(entity) {
+                glyph_renderer.deallocate_glyphs(&mut render_mgr);
+            }
+
+            if let Some(ui_element) = ui_element_storage.get(entity) {
+                ui_mgr.dealloc(ui_element.index());
+            }
+
+            ui_event_mgr.forget_entity(entity);
+        }
+    }
+
+    for &entity in &entities {
+        // Handlers may have been added to the children after their parents were destroyed.
+        remove_handlers(context, entity);
+    }
+
+    {
+        let mut transform_mgr = context.transform_mgr_mut();
+        // Deallocating a transform deallocates its children too, so only the topmost ones are deallocated.
+        let roots = transforms
+            .iter()
+            .copied()
+            .filter(|&transform| {
+                !transform_mgr
+                    .hierarchy()
+                    .parents(transform)
+                    .iter()
+                    .any(|parent| transforms.contains(parent))
+            })
+            .collect::<Vec<_>>();
+
+        for transform in roots {
+            transform_mgr.dealloc(transform);
+        }
+    }
+
+    context.world_mut().delete_entities(&entities).ok();
+}
+
+fn transform_and_children(context: &EngineContext, entity: Entity) -> Vec<Entity> {
+    let world = context.world();
+    let transform_mgr = context.transform_mgr();
+
+    let transform = world
+        .read_storage::<Transform>()
+        .get(entity)
+        .map(|transform| transform.index());
+
+    match transform {
+        Some(transform) => transform_mgr
+            .hierarchy()
+            .transform_and_children(transform)
+            .iter()
+            .map(|&transform| transform_mgr.allocator().entity(transform))
+            .collect(),
+        None => vec![entity],
+    }
+}
+
+fn remove_handlers(context: &EngineContext, entity: Entity) {
+    context
+        .entity_event_mgr()
+        .remove_entity(ScriptEntity::new(entity));
+    context
+        .event_mgr()
+        .remove_owned_handlers(ScriptEntity::new(entity));
+    context.turn_mgr_mut().remove_actor(entity);
+}
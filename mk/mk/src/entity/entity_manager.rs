@@ -0,0 +1,28 @@
+use specs::Entity;
+use std::collections::HashSet;
+
+/// Keeps the entities destroyed during the frame, until they are removed from the world at a point where it is not
+/// borrowed by anything else.
+#[derive(Default, Debug)]
+pub struct EntityManager {
+    destroyed: HashSet<Entity>,
+}
+
+impl EntityManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_destroyed(&self, entity: Entity) -> bool {
+        self.destroyed.contains(&entity)
+    }
+
+    /// Marks the entity as destroyed. Returns `false` if it has already been.
+    pub fn mark_destroyed(&mut self, entity: Entity) -> bool {
+        self.destroyed.insert(entity)
+    }
+
+    pub fn take_destroyed(&mut self) -> Vec<Entity> {
+        self.destroyed.drain().collect()
+    }
+}
@@ -0,0 +1,7 @@
+mod entity_lifecycle;
+mod entity_manager;
+mod prefab;
+
+pub use entity_lifecycle::*;
+pub use entity_manager::*;
+pub use prefab::*;
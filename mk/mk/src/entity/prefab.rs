@@ -0,0 +1,24 @@
+/// A template of an entity tree, written as a Lua chunk that returns a table describing the root entity.
+/// The chunk is evaluated when the prefab is instantiated for the first time.
+#[derive(Debug)]
+pub struct Prefab {
+    name: String,
+    source: String,
+}
+
+impl Prefab {
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            source: source.into(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
@@ -22,16 +22,23 @@ impl EntityEventHandler {
         Self::NativeEventHandler(Arc::new(f))
     }
 
+    /// Calls the handler of the entity. Lua handlers also receive the entity the event was emitted on,
+    /// which differs from the entity while the event is propagated.
     pub fn handle<'lua>(
         &self,
         entity: Entity,
+        target: Entity,
         event: &dyn Event,
         lua: &'lua Lua,
     ) -> EventHandlingResult<()> {
         match self {
             Self::LuaEventHandler(f) => {
-                f.as_function(lua)?
-                    .call((entity, event.name(), event.params_to_lua_table(lua)))?;
+                f.as_function(lua)?.call::<_, ()>((
+                    entity,
+                    event.name(),
+                    event.params_to_lua_table(lua)?,
+                    target,
+                ))?;
                 Ok(())
             }
             Self::NativeEventHandler(f) => {
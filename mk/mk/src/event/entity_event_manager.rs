@@ -1,18 +1,76 @@
-use super::{EntityEventHandler, Event};
-use crate::script::entity::Entity;
+use super::{entity_event_handler::EventHandlingError, EntityEventHandler, Event};
+use crate::{
+    diagnostic::{report_script_error, ScriptError},
+    script::entity::Entity,
+};
 use mlua::prelude::*;
 use smartstring::alias::String;
 use std::{
-    cell::RefCell,
-    collections::{hash_map::Entry, HashMap},
+    cell::{Cell, RefCell},
+    collections::HashMap,
     hash::Hash,
+    rc::Rc,
 };
 
+/// The phase of an entity event being propagated through the transform hierarchy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityEventPhase {
+    /// From the root down to the parent of the target, calling capturing handlers.
+    Capture,
+    /// On the target, calling capturing handlers and then the others.
+    Target,
+    /// From the parent of the target up to the root, calling non-capturing handlers.
+    Bubble,
+}
+
+impl EntityEventPhase {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Self::Capture => "capture",
+            Self::Target => "target",
+            Self::Bubble => "bubble",
+        }
+    }
+}
+
+/// Controls how an entity event handler is called, passed to [`EntityEventManager::add_handler_with_options`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EntityEventHandlerOptions {
+    /// Calls the handler in the capture phase, before the handlers of the descendants.
+    pub capture: bool,
+}
+
+impl<'lua> FromLua<'lua> for EntityEventHandlerOptions {
+    fn from_lua(value: LuaValue<'lua>, _lua: &'lua Lua) -> LuaResult<Self> {
+        match value {
+            LuaValue::Nil => Ok(Self::default()),
+            LuaValue::Table(table) => Ok(Self {
+                capture: table.get::<_, Option<bool>>("capture")?.unwrap_or_default(),
+            }),
+            _ => Err(LuaError::external(format!(
+                "expected a table of handler options, got {}",
+                value.type_name()
+            ))),
+        }
+    }
+}
+
+struct EntityEventHandlerEntry {
+    handler: EntityEventHandler,
+    options: EntityEventHandlerOptions,
+    is_removed: Cell<bool>,
+}
+
+struct EntityEventDispatch {
+    target: Entity,
+    phase: EntityEventPhase,
+    is_stopped: bool,
+}
+
 #[derive(Default)]
 pub struct EntityEventManager {
-    per_event_key: RefCell<HashMap<EntityEventKey, Vec<EntityEventHandler>>>,
-    added_handlers: RefCell<Vec<(EntityEventKey, EntityEventHandler)>>,
-    removed_handlers: RefCell<Vec<(EntityEventKey, EntityEventHandler)>>,
+    per_event_key: RefCell<HashMap<EntityEventKey, Vec<Rc<EntityEventHandlerEntry>>>>,
+    dispatches: RefCell<Vec<EntityEventDispatch>>,
 }
 
 impl EntityEventManager {
@@ -25,24 +83,39 @@ impl EntityEventManager {
         entity: Entity,
         event_name: impl Into<String>,
         handler: EntityEventHandler,
+    ) -> EntityEventHandler {
+        self.add_handler_with_options(
+            entity,
+            event_name,
+            handler,
+            EntityEventHandlerOptions::default(),
+        )
+    }
+
+    /// Adds a handler. A handler added while the event is being emitted is called from the next emission.
+    pub fn add_handler_with_options(
+        &self,
+        entity: Entity,
+        event_name: impl Into<String>,
+        handler: EntityEventHandler,
+        options: EntityEventHandlerOptions,
     ) -> EntityEventHandler {
         let key = EntityEventKey::new(entity, event_name);
 
-        self.removed_handlers
+        self.per_event_key
             .borrow_mut()
-            .retain(|(k, h)| k != &key || h != &handler);
-
-        if let Ok(mut per_entity) = self.per_event_key.try_borrow_mut() {
-            per_entity.entry(key).or_default().push(handler.clone());
-        } else {
-            self.added_handlers
-                .borrow_mut()
-                .push((key, handler.clone()));
-        }
+            .entry(key)
+            .or_default()
+            .push(Rc::new(EntityEventHandlerEntry {
+                handler: handler.clone(),
+                options,
+                is_removed: Cell::new(false),
+            }));
 
         handler
     }
 
+    /// Removes a handler. A handler removed while the event is being emitted is not called if it has not been yet.
     pub fn remove_handler(
         &self,
         entity: Entity,
@@ -51,54 +124,153 @@ impl EntityEventManager {
     ) {
         let key = EntityEventKey::new(entity, event_name);
 
-        self.added_handlers
-            .borrow_mut()
-            .retain(|(k, h)| k != &key || h != &handler);
+        if let Some(handlers) = self.per_event_key.borrow_mut().get_mut(&key) {
+            handlers.retain(|e| {
+                if e.handler == handler {
+                    e.is_removed.set(true);
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+    }
 
-        if let Ok(mut per_event_key) = self.per_event_key.try_borrow_mut() {
-            if let Some(handlers) = per_event_key.get_mut(&key) {
-                handlers.retain(|h| h != &handler);
+    /// Removes all handlers of the entity. Called when the entity is destroyed.
+    pub fn remove_entity(&self, entity: Entity) {
+        self.per_event_key.borrow_mut().retain(|key, handlers| {
+            if key.0 != entity {
+                return true;
             }
-        } else {
-            self.removed_handlers.borrow_mut().push((key, handler));
+
+            for entry in handlers {
+                entry.is_removed.set(true);
+            }
+
+            false
+        });
+    }
+
+    /// Stops the entity event being emitted from reaching the next entities. The remaining handlers of the current
+    /// entity are still called. Does nothing if no entity event is being emitted.
+    pub fn stop_propagation(&self) {
+        if let Some(dispatch) = self.dispatches.borrow_mut().last_mut() {
+            dispatch.is_stopped = true;
         }
     }
 
+    /// Returns the entity the event being emitted was emitted on.
+    pub fn target(&self) -> Option<Entity> {
+        self.dispatches
+            .borrow()
+            .last()
+            .map(|dispatch| dispatch.target)
+    }
+
+    /// Returns the phase of the event being emitted.
+    pub fn phase(&self) -> Option<EntityEventPhase> {
+        self.dispatches
+            .borrow()
+            .last()
+            .map(|dispatch| dispatch.phase)
+    }
+
+    /// Calls the handlers of the entity, without propagating the event.
     pub fn emit(&self, entity: Entity, event: &dyn Event, lua: &Lua) {
-        let mut per_event_key = self.per_event_key.borrow_mut();
-
-        {
-            let mut added_handlers = self.added_handlers.borrow_mut();
-            for (key, handler) in added_handlers.drain(..) {
-                match per_event_key.entry(key) {
-                    Entry::Occupied(mut entry) => entry.get_mut().push(handler),
-                    Entry::Vacant(entry) => {
-                        entry.insert(vec![handler]);
-                    }
-                }
+        self.emit_propagating(&[entity], event, lua);
+    }
+
+    /// Emits the event on the entity, propagating it through its parents in the transform hierarchy.
+    /// Returns `true` if a handler stopped the propagation.
+    pub fn emit_bubbling(&self, entity: Entity, event: &dyn Event, lua: &Lua) -> bool {
+        self.emit_propagating(&entity.event_path(), event, lua)
+    }
+
+    /// Emits the event along the path, which starts with the target followed by its ancestors from the nearest.
+    /// Capturing handlers are called from the root down to the target, and the others from the target up to the root.
+    /// Returns `true` if a handler stopped the propagation.
+    pub fn emit_propagating(&self, path: &[Entity], event: &dyn Event, lua: &Lua) -> bool {
+        let (&target, ancestors) = if let Some(path) = path.split_first() {
+            path
+        } else {
+            return false;
+        };
+
+        self.dispatches.borrow_mut().push(EntityEventDispatch {
+            target,
+            phase: EntityEventPhase::Capture,
+            is_stopped: false,
+        });
+
+        let steps = ancestors
+            .iter()
+            .rev()
+            .map(|&entity| (entity, EntityEventPhase::Capture))
+            .chain(std::iter::once((target, EntityEventPhase::Target)))
+            .chain(
+                ancestors
+                    .iter()
+                    .map(|&entity| (entity, EntityEventPhase::Bubble)),
+            );
+
+        for (entity, phase) in steps {
+            if let Some(dispatch) = self.dispatches.borrow_mut().last_mut() {
+                dispatch.phase = phase;
             }
-        }
 
-        {
-            let mut removed_handlers = self.removed_handlers.borrow_mut();
-            for (key, handler) in removed_handlers.drain(..) {
-                if let Some(handlers) = per_event_key.get_mut(&key) {
-                    handlers.retain(|h| h != &handler);
-                }
+            self.call_handlers(entity, target, phase, event, lua);
+
+            if self.is_stopped() {
+                break;
             }
         }
 
+        self.dispatches
+            .borrow_mut()
+            .pop()
+            .map(|dispatch| dispatch.is_stopped)
+            .unwrap_or_default()
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.dispatches
+            .borrow()
+            .last()
+            .map(|dispatch| dispatch.is_stopped)
+            .unwrap_or_default()
+    }
+
+    fn call_handlers(
+        &self,
+        entity: Entity,
+        target: Entity,
+        phase: EntityEventPhase,
+        event: &dyn Event,
+        lua: &Lua,
+    ) {
         let key = EntityEventKey::new(entity, event.name());
-        if let Some(handlers) = per_event_key.get(&key) {
-            for handler in handlers {
-                match handler.handle(entity, event, lua) {
-                    Ok(..) => {}
-                    Err(err) => {
-                        // TODO: Report this error.
-                        println!("Error handling event: {}", err);
-                    }
-                }
+        let mut handlers = match self.per_event_key.borrow().get(&key) {
+            Some(handlers) if !handlers.is_empty() => handlers.clone(),
+            _ => return,
+        };
+
+        match phase {
+            EntityEventPhase::Capture => handlers.retain(|e| e.options.capture),
+            EntityEventPhase::Target => handlers.sort_by_key(|e| !e.options.capture),
+            EntityEventPhase::Bubble => handlers.retain(|e| !e.options.capture),
+        }
+
+        for entry in handlers {
+            if entry.is_removed.get() {
+                continue;
             }
+
+            let err = match entry.handler.handle(entity, target, event, lua) {
+                Ok(..) => continue,
+                Err(EventHandlingError::LuaError(err)) => ScriptError::from_lua_error(&err),
+                Err(EventHandlingError::NativeError(err)) => ScriptError::from_error(&err),
+            };
+            report_script_error(format!("handler of entity event {}", event.name()), &err);
         }
     }
 }
@@ -111,3 +283,150 @@ impl EntityEventKey {
         return Self(entity, event_name.into());
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use codegen::Event;
+    use specs::{Builder, World, WorldExt};
+
+    #[derive(Event, Debug, Clone)]
+    #[event_name("test")]
+    struct TestEvent;
+
+    #[test]
+    fn handlers_of_removed_entities_are_dropped() {
+        let lua = Lua::new();
+        let mut world = World::new();
+        let entity = Entity::new(world.create_entity().build());
+        let other = Entity::new(world.create_entity().build());
+        let entity_event_mgr = Rc::new(EntityEventManager::new());
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        for (owner, tag) in [(entity, "a"), (entity, "b"), (other, "c")] {
+            let log = log.clone();
+            let weak = Rc::downgrade(&entity_event_mgr);
+            entity_event_mgr.add_handler(
+                owner,
+                "test",
+                EntityEventHandler::native(move |entity, _event| {
+                    log.borrow_mut().push(tag);
+                    // The first handler removes the entity while the event is being emitted.
+                    if tag == "a" {
+                        weak.upgrade().unwrap().remove_entity(entity);
+                    }
+                    Ok(())
+                }),
+            );
+        }
+
+        entity_event_mgr.emit(entity, &TestEvent, &lua);
+        entity_event_mgr.emit(entity, &TestEvent, &lua);
+        entity_event_mgr.emit(other, &TestEvent, &lua);
+
+        assert_eq!(*log.borrow(), vec!["a", "c"]);
+        assert_eq!(entity_event_mgr.per_event_key.borrow().len(), 1);
+    }
+
+    type PropagationLog =
+        Rc<RefCell<Vec<(&'static str, Option<EntityEventPhase>, Option<Entity>)>>>;
+
+    /// Listens on the entity, logging the tag with the phase and the target seen by the handler.
+    fn listen(
+        entity_event_mgr: &Rc<EntityEventManager>,
+        log: &PropagationLog,
+        entity: Entity,
+        tag: &'static str,
+        capture: bool,
+    ) {
+        let log = log.clone();
+        let weak = Rc::downgrade(entity_event_mgr);
+        entity_event_mgr.add_handler_with_options(
+            entity,
+            "test",
+            EntityEventHandler::native(move |_entity, _event| {
+                let entity_event_mgr = weak.upgrade().unwrap();
+                log.borrow_mut()
+                    .push((tag, entity_event_mgr.phase(), entity_event_mgr.target()));
+                Ok(())
+            }),
+            EntityEventHandlerOptions { capture },
+        );
+    }
+
+    #[test]
+    fn events_propagate_in_capture_target_bubble_order() {
+        let lua = Lua::new();
+        let mut world = World::new();
+        let [target, parent, root] = [(); 3].map(|_| Entity::new(world.create_entity().build()));
+        let entity_event_mgr = Rc::new(EntityEventManager::new());
+        let log = PropagationLog::default();
+
+        for (entity, name) in [(target, "target"), (parent, "parent"), (root, "root")] {
+            listen(&entity_event_mgr, &log, entity, name, false);
+            listen(&entity_event_mgr, &log, entity, name, true);
+        }
+
+        let is_stopped =
+            entity_event_mgr.emit_propagating(&[target, parent, root], &TestEvent, &lua);
+
+        use EntityEventPhase::*;
+        let target = Some(target);
+        assert!(!is_stopped);
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                ("root", Some(Capture), target),
+                ("parent", Some(Capture), target),
+                ("target", Some(Target), target),
+                ("target", Some(Target), target),
+                ("parent", Some(Bubble), target),
+                ("root", Some(Bubble), target),
+            ]
+        );
+        assert_eq!(entity_event_mgr.phase(), None);
+        assert_eq!(entity_event_mgr.target(), None);
+    }
+
+    #[test]
+    fn stopping_propagation_calls_remaining_handlers_of_the_entity_only() {
+        let lua = Lua::new();
+        let mut world = World::new();
+        let [target, parent, root] = [(); 3].map(|_| Entity::new(world.create_entity().build()));
+        let entity_event_mgr = Rc::new(EntityEventManager::new());
+        let log = PropagationLog::default();
+
+        listen(&entity_event_mgr, &log, target, "target", false);
+        let weak = Rc::downgrade(&entity_event_mgr);
+        entity_event_mgr.add_handler(
+            parent,
+            "test",
+            EntityEventHandler::native(move |_entity, _event| {
+                weak.upgrade().unwrap().stop_propagation();
+                Ok(())
+            }),
+        );
+        listen(&entity_event_mgr, &log, parent, "parent", false);
+        listen(&entity_event_mgr, &log, root, "root", false);
+
+        let path = [target, parent, root];
+        assert!(entity_event_mgr.emit_propagating(&path, &TestEvent, &lua));
+
+        let tags = log
+            .borrow()
+            .iter()
+            .map(|&(tag, ..)| tag)
+            .collect::<Vec<_>>();
+        assert_eq!(tags, vec!["target", "parent"]);
+
+        // The stop concerns that emission alone.
+        log.borrow_mut().clear();
+        assert!(!entity_event_mgr.emit_propagating(&[target, root], &TestEvent, &lua));
+        let tags = log
+            .borrow()
+            .iter()
+            .map(|&(tag, ..)| tag)
+            .collect::<Vec<_>>();
+        assert_eq!(tags, vec!["target", "root"]);
+    }
+}
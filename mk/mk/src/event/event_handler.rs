@@ -26,7 +26,7 @@ impl EventHandler {
         match self {
             Self::LuaEventHandler(f) => {
                 f.as_function(lua)?
-                    .call((event.name(), event.params_to_lua_table(lua)))?;
+                    .call::<_, ()>((event.name(), event.params_to_lua_table(lua)))?;
                 Ok(())
             }
             Self::NativeEventHandler(f) => {
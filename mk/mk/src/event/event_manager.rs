@@ -1,20 +1,111 @@
-use super::{Event, EventHandler};
+use super::{
+    event_handler::EventHandlingError, Event, EventHandler, LuaEventDefinition,
+    LuaEventDefinitionError,
+};
+use crate::{
+    diagnostic::{report_script_error, ScriptError},
+    script::entity::Entity,
+};
+use crossbeam::channel::{unbounded, Receiver, Sender};
 use mlua::prelude::*;
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::{hash_map::Entry, HashMap},
+    rc::Rc,
 };
 
-#[derive(Default)]
+/// The number of posted events delivered per frame by default.
+pub const DEFAULT_POSTED_EVENT_BUDGET: usize = 1024;
+
+/// Controls how a handler is called, passed to [`EventManager::add_handler_with_options`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EventHandlerOptions {
+    /// Handlers with higher priorities are called first. Handlers with the same priority are called in the order they were added.
+    pub priority: i32,
+    /// Removes the handler after it has been called once.
+    pub once: bool,
+    /// Groups the handler with the entity, so that it is removed when the entity is destroyed.
+    pub owner: Option<Entity>,
+}
+
+impl EventHandlerOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_once(mut self, once: bool) -> Self {
+        self.once = once;
+        self
+    }
+
+    pub fn with_owner(mut self, owner: Entity) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+}
+
+impl<'lua> FromLua<'lua> for EventHandlerOptions {
+    fn from_lua(value: LuaValue<'lua>, _lua: &'lua Lua) -> LuaResult<Self> {
+        match value {
+            LuaValue::Nil => Ok(Self::default()),
+            LuaValue::Table(table) => Ok(Self {
+                priority: table.get::<_, Option<i32>>("priority")?.unwrap_or_default(),
+                once: table.get::<_, Option<bool>>("once")?.unwrap_or_default(),
+                owner: table.get("owner")?,
+            }),
+            _ => Err(LuaError::external(format!(
+                "expected a table of handler options, got {}",
+                value.type_name()
+            ))),
+        }
+    }
+}
+
+struct EventHandlerEntry {
+    handler: EventHandler,
+    options: EventHandlerOptions,
+    is_removed: Cell<bool>,
+}
+
+/// Posts events to an [`EventManager`] from any thread. Obtained with [`EventManager::sender`].
+#[derive(Clone)]
+pub struct EventSender(Sender<Box<dyn Event + Send>>);
+
+impl EventSender {
+    /// Queues the event, which is delivered on the main thread by [`EventManager::dispatch_posted`].
+    /// Events posted from the same thread are delivered in the order they were posted.
+    pub fn post(&self, event: impl Event + Send) {
+        // The receiver only goes away with the engine, after which there is nobody to deliver to.
+        let _ = self.0.send(Box::new(event));
+    }
+}
+
 pub struct EventManager {
-    per_event: RefCell<HashMap<String, Vec<EventHandler>>>,
-    added_handlers: RefCell<Vec<(String, EventHandler)>>,
-    removed_handlers: RefCell<Vec<(String, EventHandler)>>,
+    per_event: RefCell<HashMap<String, Vec<Rc<EventHandlerEntry>>>>,
+    consumed: RefCell<Vec<bool>>,
+    lua_event_definitions: RefCell<HashMap<String, Rc<LuaEventDefinition>>>,
+    posted_sender: EventSender,
+    posted_receiver: Receiver<Box<dyn Event + Send>>,
+    posted_event_budget: Cell<usize>,
 }
 
 impl EventManager {
     pub fn new() -> Self {
-        Default::default()
+        let (sender, receiver) = unbounded();
+
+        Self {
+            per_event: Default::default(),
+            consumed: Default::default(),
+            lua_event_definitions: Default::default(),
+            posted_sender: EventSender(sender),
+            posted_receiver: receiver,
+            posted_event_budget: Cell::new(DEFAULT_POSTED_EVENT_BUDGET),
+        }
     }
 
     pub fn add_handler(
@@ -22,79 +113,531 @@ impl EventManager {
         event_name: impl Into<String>,
         handler: EventHandler,
     ) -> EventHandler {
-        let event_name = event_name.into();
-
-        self.removed_handlers
-            .borrow_mut()
-            .retain(|(k, h)| k != &event_name || h != &handler);
+        self.add_handler_with_options(event_name, handler, EventHandlerOptions::default())
+    }
 
-        if let Ok(mut per_entity) = self.per_event.try_borrow_mut() {
-            per_entity
-                .entry(event_name)
-                .or_default()
-                .push(handler.clone());
-        } else {
-            self.added_handlers
-                .borrow_mut()
-                .push((event_name, handler.clone()));
-        }
+    /// Adds a handler. A handler added while the event is being emitted is called from the next emission.
+    pub fn add_handler_with_options(
+        &self,
+        event_name: impl Into<String>,
+        handler: EventHandler,
+        options: EventHandlerOptions,
+    ) -> EventHandler {
+        let entry = Rc::new(EventHandlerEntry {
+            handler: handler.clone(),
+            options,
+            is_removed: Cell::new(false),
+        });
+        let mut per_event = self.per_event.borrow_mut();
+        let handlers = per_event.entry(event_name.into()).or_default();
+        let index = handlers.partition_point(|e| e.options.priority >= options.priority);
+        handlers.insert(index, entry);
 
         handler
     }
 
+    /// Removes a handler. A handler removed while the event is being emitted is not called if it has not been yet.
     pub fn remove_handler(&self, event_name: impl Into<String>, handler: EventHandler) {
-        let event_name = event_name.into();
+        if let Some(handlers) = self.per_event.borrow_mut().get_mut(&event_name.into()) {
+            remove_entries(handlers, |e| e.handler == handler);
+        }
+    }
 
-        self.added_handlers
-            .borrow_mut()
-            .retain(|(k, h)| k != &event_name || h != &handler);
+    /// Removes all handlers grouped with the entity. Called by `destroy_entity` on the entity and its children.
+    pub fn remove_owned_handlers(&self, owner: Entity) {
+        for handlers in self.per_event.borrow_mut().values_mut() {
+            remove_entries(handlers, |e| e.options.owner == Some(owner));
+        }
+    }
+
+    /// Registers an event defined by scripts. Defining it again with the same fields returns the existing definition,
+    /// so that modules reloaded or required from several places agree on it.
+    pub fn define_lua_event(
+        &self,
+        definition: LuaEventDefinition,
+    ) -> Result<Rc<LuaEventDefinition>, LuaEventDefinitionError> {
+        let mut definitions = self.lua_event_definitions.borrow_mut();
 
-        if let Ok(mut per_event) = self.per_event.try_borrow_mut() {
-            if let Some(handlers) = per_event.get_mut(&event_name) {
-                handlers.retain(|h| h != &handler);
+        match definitions.entry(definition.name().to_owned()) {
+            Entry::Occupied(entry) => {
+                if entry.get().as_ref() == &definition {
+                    Ok(entry.get().clone())
+                } else {
+                    Err(LuaEventDefinitionError::Redefined(definition.name().into()))
+                }
             }
-        } else {
-            self.removed_handlers
-                .borrow_mut()
-                .push((event_name, handler));
+            Entry::Vacant(entry) => Ok(entry.insert(Rc::new(definition)).clone()),
         }
     }
 
-    pub fn emit(&self, event: &dyn Event, lua: &Lua) {
-        let mut per_event = self.per_event.borrow_mut();
+    pub fn lua_event_definition(&self, event_name: &str) -> Option<Rc<LuaEventDefinition>> {
+        self.lua_event_definitions.borrow().get(event_name).cloned()
+    }
 
-        {
-            let mut added_handlers = self.added_handlers.borrow_mut();
-            for (event_name, handler) in added_handlers.drain(..) {
-                match per_event.entry(event_name) {
-                    Entry::Occupied(mut entry) => entry.get_mut().push(handler),
-                    Entry::Vacant(entry) => {
-                        entry.insert(vec![handler]);
-                    }
+    /// Stops the event being emitted from reaching the remaining handlers, which have lower priorities.
+    /// Does nothing if no event is being emitted. Consuming an event emitted by a handler does not consume the outer one.
+    pub fn consume(&self) {
+        if let Some(consumed) = self.consumed.borrow_mut().last_mut() {
+            *consumed = true;
+        }
+    }
+
+    /// Returns `true` if the event being emitted has been consumed by a handler.
+    pub fn is_consumed(&self) -> bool {
+        self.consumed.borrow().last().copied().unwrap_or_default()
+    }
+
+    /// Calls the handlers of the event in order of priority. Returns `true` if a handler consumed it.
+    ///
+    /// Handlers may add or remove handlers and emit events, including this one, while being called.
+    pub fn emit(&self, event: &dyn Event, lua: &Lua) -> bool {
+        let event_name = event.name();
+        let handlers = match self.per_event.borrow().get(event_name) {
+            Some(handlers) if !handlers.is_empty() => handlers.clone(),
+            _ => return false,
+        };
+
+        self.consumed.borrow_mut().push(false);
+
+        for entry in handlers {
+            if entry.is_removed.get() {
+                continue;
+            }
+
+            if entry.options.once {
+                if let Some(handlers) = self.per_event.borrow_mut().get_mut(event_name) {
+                    remove_entries(handlers, |e| Rc::ptr_eq(e, &entry));
                 }
             }
+
+            if let Err(err) = entry.handler.handle(event, lua) {
+                let err = match err {
+                    EventHandlingError::LuaError(err) => ScriptError::from_lua_error(&err),
+                    EventHandlingError::NativeError(err) => ScriptError::from_error(&err),
+                };
+                report_script_error(format!("handler of event {}", event_name), &err);
+            }
+
+            if self.is_consumed() {
+                break;
+            }
         }
 
-        {
-            let mut removed_handlers = self.removed_handlers.borrow_mut();
-            for (event_name, handler) in removed_handlers.drain(..) {
-                if let Some(handlers) = per_event.get_mut(&event_name) {
-                    handlers.retain(|h| h != &handler);
+        self.consumed.borrow_mut().pop().unwrap_or_default()
+    }
+
+    /// Returns a sender to post events from other threads, e.g. asset loaders or `rayon` tasks.
+    pub fn sender(&self) -> EventSender {
+        self.posted_sender.clone()
+    }
+
+    /// Queues the event to be delivered by [`EventManager::dispatch_posted`], instead of emitting it now.
+    pub fn post(&self, event: impl Event + Send) {
+        self.posted_sender.post(event);
+    }
+
+    pub fn posted_event_budget(&self) -> usize {
+        self.posted_event_budget.get()
+    }
+
+    /// Sets the maximum number of posted events delivered per frame. The others are kept for the next frames.
+    pub fn set_posted_event_budget(&self, budget: usize) {
+        self.posted_event_budget.set(budget);
+    }
+
+    /// Emits the posted events in the order they were received, up to the budget. Events posted while
+    /// dispatching are delivered by the next call. Returns the number of delivered events.
+    ///
+    /// The engine calls it once per frame, after the input events and before [`crate::script::event::PreUpdate`].
+    pub fn dispatch_posted(&self, lua: &Lua) -> usize {
+        let count = self
+            .posted_receiver
+            .len()
+            .min(self.posted_event_budget.get());
+
+        for _ in 0..count {
+            match self.posted_receiver.try_recv() {
+                Ok(event) => {
+                    self.emit(event.as_ref(), lua);
                 }
+                Err(..) => return count,
             }
         }
 
-        let event_name = event.name();
-        if let Some(handlers) = per_event.get(event_name) {
-            for handler in handlers {
-                match handler.handle(event, lua) {
-                    Ok(..) => {}
-                    Err(err) => {
-                        // TODO: Report this error.
-                        println!("Error handling event: {}", err);
+        count
+    }
+}
+
+impl Default for EventManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn remove_entries(
+    handlers: &mut Vec<Rc<EventHandlerEntry>>,
+    mut predicate: impl FnMut(&Rc<EventHandlerEntry>) -> bool,
+) {
+    handlers.retain(|e| {
+        if predicate(e) {
+            e.is_removed.set(true);
+            false
+        } else {
+            true
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use codegen::Event;
+    use specs::{Builder, World, WorldExt};
+    use std::thread;
+
+    #[derive(Event, Debug, Clone)]
+    #[event_name("test")]
+    struct TestEvent {
+        value: i64,
+    }
+
+    #[derive(Event, Debug, Clone)]
+    #[event_name("other")]
+    struct OtherEvent {
+        value: i64,
+    }
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    fn value_of(event: &dyn Event) -> i64 {
+        *event.param("value").unwrap().downcast_ref::<i64>().unwrap()
+    }
+
+    /// Adds a handler that logs the tag and the value of the event before calling `f`.
+    fn log_handler(
+        event_mgr: &Rc<EventManager>,
+        event_name: &str,
+        log: &Log,
+        tag: &'static str,
+        options: EventHandlerOptions,
+        f: impl Fn(&Rc<EventManager>, i64) + 'static,
+    ) -> EventHandler {
+        let log = log.clone();
+        let weak = Rc::downgrade(event_mgr);
+        event_mgr.add_handler_with_options(
+            event_name,
+            EventHandler::native(move |event| {
+                let value = value_of(event);
+                log.borrow_mut().push(format!("{}{}", tag, value));
+                f(&weak.upgrade().unwrap(), value);
+                Ok(())
+            }),
+            options,
+        )
+    }
+
+    #[test]
+    fn handlers_are_called_in_order_of_priority() {
+        let lua = Lua::new();
+        let event_mgr = Rc::new(EventManager::new());
+        let log = Log::default();
+        let options = EventHandlerOptions::new();
+
+        log_handler(&event_mgr, "test", &log, "a", options, |_, _| {});
+        log_handler(
+            &event_mgr,
+            "test",
+            &log,
+            "b",
+            options.with_priority(10),
+            |_, _| {},
+        );
+        log_handler(&event_mgr, "test", &log, "c", options, |_, _| {});
+        log_handler(
+            &event_mgr,
+            "test",
+            &log,
+            "d",
+            options.with_priority(5),
+            |_, _| {},
+        );
+
+        event_mgr.emit(&TestEvent { value: 0 }, &lua);
+        assert_eq!(*log.borrow(), vec!["b0", "d0", "a0", "c0"]);
+    }
+
+    #[test]
+    fn once_handlers_are_called_once() {
+        let lua = Lua::new();
+        let event_mgr = Rc::new(EventManager::new());
+        let log = Log::default();
+        let options = EventHandlerOptions::new();
+
+        log_handler(
+            &event_mgr,
+            "test",
+            &log,
+            "a",
+            options.with_once(true),
+            |_, _| {},
+        );
+        log_handler(&event_mgr, "test", &log, "b", options, |_, _| {});
+
+        event_mgr.emit(&TestEvent { value: 0 }, &lua);
+        event_mgr.emit(&TestEvent { value: 1 }, &lua);
+        assert_eq!(*log.borrow(), vec!["a0", "b0", "b1"]);
+    }
+
+    #[test]
+    fn consumed_events_do_not_reach_lower_priorities() {
+        let lua = Lua::new();
+        let event_mgr = Rc::new(EventManager::new());
+        let log = Log::default();
+        let options = EventHandlerOptions::new();
+
+        log_handler(
+            &event_mgr,
+            "test",
+            &log,
+            "a",
+            options.with_priority(1),
+            |event_mgr, value| {
+                if value == 0 {
+                    event_mgr.consume();
+                    assert!(event_mgr.is_consumed());
+                }
+            },
+        );
+        log_handler(&event_mgr, "test", &log, "b", options, |_, _| {});
+
+        assert!(event_mgr.emit(&TestEvent { value: 0 }, &lua));
+        assert!(!event_mgr.emit(&TestEvent { value: 1 }, &lua));
+        assert!(!event_mgr.is_consumed());
+        assert_eq!(*log.borrow(), vec!["a0", "a1", "b1"]);
+    }
+
+    #[test]
+    fn removing_an_owner_drops_its_handlers() {
+        let lua = Lua::new();
+        let event_mgr = Rc::new(EventManager::new());
+        let log = Log::default();
+        let options = EventHandlerOptions::new();
+        let mut world = World::new();
+        let owner = Entity::new(world.create_entity().build());
+        let other = Entity::new(world.create_entity().build());
+
+        log_handler(
+            &event_mgr,
+            "test",
+            &log,
+            "a",
+            options.with_owner(owner),
+            |_, _| {},
+        );
+        log_handler(
+            &event_mgr,
+            "other",
+            &log,
+            "b",
+            options.with_owner(owner),
+            |_, _| {},
+        );
+        log_handler(
+            &event_mgr,
+            "test",
+            &log,
+            "c",
+            options.with_owner(other),
+            |_, _| {},
+        );
+        log_handler(&event_mgr, "test", &log, "d", options, |_, _| {});
+
+        event_mgr.remove_owned_handlers(owner);
+        event_mgr.emit(&TestEvent { value: 0 }, &lua);
+        event_mgr.emit(&OtherEvent { value: 1 }, &lua);
+        assert_eq!(*log.borrow(), vec!["c0", "d0"]);
+    }
+
+    #[test]
+    fn handlers_can_emit_the_same_event() {
+        let lua = Lua::new();
+        let event_mgr = Rc::new(EventManager::new());
+        let log = Log::default();
+        let options = EventHandlerOptions::new();
+
+        log_handler(
+            &event_mgr,
+            "test",
+            &log,
+            "a",
+            options,
+            |event_mgr, value| {
+                if value < 2 {
+                    event_mgr.emit(&TestEvent { value: value + 1 }, &Lua::new());
+                }
+            },
+        );
+        log_handler(&event_mgr, "test", &log, "b", options, |_, _| {});
+
+        event_mgr.emit(&TestEvent { value: 0 }, &lua);
+        assert_eq!(*log.borrow(), vec!["a0", "a1", "a2", "b2", "b1", "b0"]);
+    }
+
+    #[test]
+    fn handlers_added_while_emitting_are_called_from_the_next_emission() {
+        let lua = Lua::new();
+        let event_mgr = Rc::new(EventManager::new());
+        let log = Log::default();
+        let options = EventHandlerOptions::new();
+
+        let inner_log = log.clone();
+        log_handler(
+            &event_mgr,
+            "test",
+            &log,
+            "a",
+            options.with_once(true),
+            move |event_mgr, _| {
+                log_handler(event_mgr, "test", &inner_log, "b", options, |_, _| {});
+            },
+        );
+
+        event_mgr.emit(&TestEvent { value: 0 }, &lua);
+        event_mgr.emit(&TestEvent { value: 1 }, &lua);
+        assert_eq!(*log.borrow(), vec!["a0", "b1"]);
+    }
+
+    #[test]
+    fn handlers_removed_while_emitting_are_not_called() {
+        let lua = Lua::new();
+        let event_mgr = Rc::new(EventManager::new());
+        let log = Log::default();
+        let options = EventHandlerOptions::new();
+        let removed = Rc::new(RefCell::new(None::<EventHandler>));
+
+        {
+            let removed = removed.clone();
+            log_handler(
+                &event_mgr,
+                "test",
+                &log,
+                "a",
+                options,
+                move |event_mgr, _| {
+                    if let Some(handler) = removed.borrow_mut().take() {
+                        event_mgr.remove_handler("test", handler);
                     }
+                },
+            );
+        }
+        *removed.borrow_mut() = Some(log_handler(
+            &event_mgr,
+            "test",
+            &log,
+            "b",
+            options,
+            |_, _| {},
+        ));
+
+        event_mgr.emit(&TestEvent { value: 0 }, &lua);
+        event_mgr.emit(&TestEvent { value: 1 }, &lua);
+        assert_eq!(*log.borrow(), vec!["a0", "a1"]);
+    }
+
+    #[test]
+    fn once_handlers_are_not_called_again_by_nested_emissions() {
+        let lua = Lua::new();
+        let event_mgr = Rc::new(EventManager::new());
+        let log = Log::default();
+        let options = EventHandlerOptions::new();
+
+        log_handler(
+            &event_mgr,
+            "test",
+            &log,
+            "a",
+            options.with_once(true),
+            |event_mgr, value| {
+                event_mgr.emit(&TestEvent { value: value + 1 }, &Lua::new());
+            },
+        );
+
+        event_mgr.emit(&TestEvent { value: 0 }, &lua);
+        event_mgr.emit(&TestEvent { value: 2 }, &lua);
+        assert_eq!(*log.borrow(), vec!["a0"]);
+    }
+
+    #[test]
+    fn consuming_a_nested_event_does_not_consume_the_outer_one() {
+        let lua = Lua::new();
+        let event_mgr = Rc::new(EventManager::new());
+        let log = Log::default();
+        let options = EventHandlerOptions::new();
+
+        log_handler(
+            &event_mgr,
+            "test",
+            &log,
+            "a",
+            options.with_priority(1),
+            |event_mgr, value| {
+                assert!(event_mgr.emit(&OtherEvent { value }, &Lua::new()));
+                assert!(!event_mgr.is_consumed());
+            },
+        );
+        log_handler(&event_mgr, "test", &log, "b", options, |_, _| {});
+        log_handler(&event_mgr, "other", &log, "c", options, |event_mgr, _| {
+            event_mgr.consume();
+        });
+        log_handler(
+            &event_mgr,
+            "other",
+            &log,
+            "d",
+            options.with_priority(-1),
+            |_, _| {},
+        );
+
+        assert!(!event_mgr.emit(&TestEvent { value: 0 }, &lua));
+        assert_eq!(*log.borrow(), vec!["a0", "c0", "b0"]);
+    }
+
+    #[test]
+    fn posted_events_are_delivered_in_order_within_the_budget() {
+        let lua = Lua::new();
+        let event_mgr = Rc::new(EventManager::new());
+        let log = Log::default();
+        let options = EventHandlerOptions::new();
+
+        log_handler(
+            &event_mgr,
+            "test",
+            &log,
+            "a",
+            options,
+            |event_mgr, value| {
+                if value == 0 {
+                    event_mgr.post(TestEvent { value: 100 });
                 }
+            },
+        );
+
+        let sender = event_mgr.sender();
+        thread::spawn(move || {
+            for value in 0..5 {
+                sender.post(TestEvent { value });
             }
-        }
+        })
+        .join()
+        .unwrap();
+
+        event_mgr.set_posted_event_budget(4);
+        assert_eq!(event_mgr.dispatch_posted(&lua), 4);
+        assert_eq!(*log.borrow(), vec!["a0", "a1", "a2", "a3"]);
+
+        // The event posted while dispatching comes after the ones left over the budget.
+        assert_eq!(event_mgr.dispatch_posted(&lua), 2);
+        assert_eq!(event_mgr.dispatch_posted(&lua), 0);
+        assert_eq!(*log.borrow(), vec!["a0", "a1", "a2", "a3", "a4", "a100"]);
     }
 }
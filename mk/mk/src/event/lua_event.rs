@@ -1,7 +1,9 @@
-use super::{Event, ParamsToLuaTable};
-use crate::script::FFITable;
+use super::{Event, EventParamProvider, ParamsToLuaTable};
+use crate::script::{entity::Entity, FFITable};
 use mlua::prelude::*;
 use smartstring::alias::String;
+use std::fmt::Display;
+use thiserror::Error;
 
 pub struct LuaEvent {
     pub name: String,
@@ -48,8 +50,323 @@ impl Event for LuaEvent {
     }
 }
 
+impl EventParamProvider for LuaEvent {}
+
 impl ParamsToLuaTable for LuaEvent {
     fn params_to_lua_table<'lua>(&self, lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
         self.params.as_lua_table(lua)
     }
 }
+
+/// An event defined by scripts with `mk.event.define`. Its payload is validated against the
+/// declared fields before being emitted as a [`LuaEvent`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LuaEventDefinition {
+    name: String,
+    fields: Vec<LuaEventField>,
+}
+
+impl LuaEventDefinition {
+    pub fn new(
+        name: impl Into<String>,
+        fields: impl IntoIterator<Item = LuaEventField>,
+    ) -> Result<Self, LuaEventDefinitionError> {
+        let name = name.into();
+
+        if name.is_empty() {
+            return Err(LuaEventDefinitionError::EmptyName);
+        }
+
+        let mut fields = fields.into_iter().collect::<Vec<_>>();
+        fields.sort_unstable_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+
+        if let Some(window) = fields
+            .windows(2)
+            .find(|window| window[0].name == window[1].name)
+        {
+            return Err(LuaEventDefinitionError::DuplicatedField {
+                event: name,
+                field: window[0].name.clone(),
+            });
+        }
+
+        Ok(Self { name, fields })
+    }
+
+    /// Reads fields declared as `{ field_name = "type" }`, e.g. `{ killer = "Entity", damage = "number?" }`.
+    pub fn from_lua_table<'lua>(name: impl Into<String>, table: LuaTable<'lua>) -> LuaResult<Self> {
+        let name = name.into();
+        let mut fields = Vec::new();
+
+        for pair in table.pairs::<LuaString, LuaString>() {
+            let (field_name, ty) = pair?;
+            let field_name = field_name.to_str()?;
+            let ty = ty.to_str()?;
+            let (ty, is_optional) = match ty.strip_suffix('?') {
+                Some(ty) => (ty, true),
+                None => (ty, false),
+            };
+            let ty = LuaEventFieldType::parse(ty).ok_or_else(|| {
+                LuaError::external(LuaEventDefinitionError::InvalidFieldType {
+                    event: name.clone(),
+                    field: field_name.into(),
+                    ty: ty.into(),
+                })
+            })?;
+
+            fields.push(LuaEventField {
+                name: field_name.into(),
+                ty,
+                is_optional,
+            });
+        }
+
+        Self::new(name, fields).map_err(LuaError::external)
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn fields(&self) -> &[LuaEventField] {
+        &self.fields
+    }
+
+    /// Validates the given payload and converts it into an event. A missing payload is treated as an empty table.
+    pub fn create_event<'lua>(
+        &self,
+        lua: &'lua Lua,
+        params: Option<LuaTable<'lua>>,
+    ) -> LuaResult<LuaEvent> {
+        let params = match params {
+            Some(params) => params,
+            None => lua.create_table()?,
+        };
+
+        for pair in params.clone().pairs::<LuaValue, LuaValue>() {
+            let (key, _) = pair?;
+            let key = match &key {
+                LuaValue::String(key) => key.to_str()?.to_owned(),
+                _ => format!("<{}>", key.type_name()),
+            };
+
+            if !self.fields.iter().any(|field| field.name == key.as_str()) {
+                return Err(LuaError::external(format!(
+                    "event {} has no field named {}",
+                    self.name, key
+                )));
+            }
+        }
+
+        for field in &self.fields {
+            let value = params.raw_get::<_, LuaValue>(field.name.as_str())?;
+
+            if let LuaValue::Nil = value {
+                if field.is_optional {
+                    continue;
+                }
+
+                return Err(LuaError::external(format!(
+                    "field {} of event {} is missing",
+                    field.name, self.name
+                )));
+            }
+
+            if !field.ty.matches(&value) {
+                return Err(LuaError::external(format!(
+                    "field {} of event {} must be {}, got {}",
+                    field.name,
+                    self.name,
+                    field.ty,
+                    value.type_name()
+                )));
+            }
+        }
+
+        Ok(LuaEvent::new(
+            self.name.clone(),
+            FFITable::from_lua_table(lua, params)?,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LuaEventField {
+    pub name: String,
+    pub ty: LuaEventFieldType,
+    pub is_optional: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LuaEventFieldType {
+    Any,
+    Boolean,
+    Number,
+    Integer,
+    String,
+    Table,
+    Function,
+    UserData,
+    Entity,
+}
+
+impl LuaEventFieldType {
+    pub fn parse(ty: &str) -> Option<Self> {
+        Some(match ty {
+            "any" => Self::Any,
+            "boolean" => Self::Boolean,
+            "number" => Self::Number,
+            "integer" => Self::Integer,
+            "string" => Self::String,
+            "table" => Self::Table,
+            "function" => Self::Function,
+            "userdata" => Self::UserData,
+            "Entity" => Self::Entity,
+            _ => return None,
+        })
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Self::Any => "any",
+            Self::Boolean => "boolean",
+            Self::Number => "number",
+            Self::Integer => "integer",
+            Self::String => "string",
+            Self::Table => "table",
+            Self::Function => "function",
+            Self::UserData => "userdata",
+            Self::Entity => "Entity",
+        }
+    }
+
+    pub fn matches(self, value: &LuaValue) -> bool {
+        match (self, value) {
+            (Self::Any, _) => true,
+            (Self::Boolean, LuaValue::Boolean(..)) => true,
+            (Self::Number, LuaValue::Number(..) | LuaValue::Integer(..)) => true,
+            (Self::Integer, LuaValue::Integer(..)) => true,
+            (Self::Integer, LuaValue::Number(value)) => value.fract() == 0f64,
+            (Self::String, LuaValue::String(..)) => true,
+            (Self::Table, LuaValue::Table(..)) => true,
+            (Self::Function, LuaValue::Function(..)) => true,
+            (Self::UserData, LuaValue::UserData(..) | LuaValue::LightUserData(..)) => true,
+            (Self::Entity, LuaValue::UserData(value)) => value.is::<Entity>(),
+            _ => false,
+        }
+    }
+}
+
+impl Display for LuaEventFieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.to_str())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum LuaEventDefinitionError {
+    #[error("event name must not be empty")]
+    EmptyName,
+    #[error("field {field} of event {event} is declared more than once")]
+    DuplicatedField { event: String, field: String },
+    #[error("field {field} of event {event} has an unknown type {ty}; expected one of any, boolean, number, integer, string, table, function, userdata or Entity, optionally suffixed with ?")]
+    InvalidFieldType {
+        event: String,
+        field: String,
+        ty: String,
+    },
+    #[error("event {0} is already defined with different fields")]
+    Redefined(String),
+    #[error("event {0} is emitted by the engine; choose another name")]
+    BuiltIn(String),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn definition(lua: &Lua, fields: &str) -> LuaResult<LuaEventDefinition> {
+        LuaEventDefinition::from_lua_table("PlayerDied", lua.load(fields).eval()?)
+    }
+
+    fn create_event(lua: &Lua, params: &str) -> LuaResult<LuaEvent> {
+        let definition = definition(lua, "{ damage = 'number', killer = 'string?' }")?;
+        definition.create_event(lua, lua.load(params).eval()?)
+    }
+
+    #[test]
+    fn from_lua_table_reads_types_and_optional_fields() {
+        let lua = Lua::new();
+        let definition = definition(&lua, "{ killer = 'Entity?', damage = 'integer' }").unwrap();
+
+        assert_eq!(definition.name(), "PlayerDied");
+        assert_eq!(
+            definition.fields(),
+            &[
+                LuaEventField {
+                    name: "damage".into(),
+                    ty: LuaEventFieldType::Integer,
+                    is_optional: false,
+                },
+                LuaEventField {
+                    name: "killer".into(),
+                    ty: LuaEventFieldType::Entity,
+                    is_optional: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn from_lua_table_rejects_unknown_types() {
+        let lua = Lua::new();
+        let err = definition(&lua, "{ damage = 'float' }").unwrap_err();
+
+        assert!(err.to_string().contains("unknown type float"), "{}", err);
+        assert!(definition(&lua, "{ damage = 'number??' }").is_err());
+        assert!(definition(&lua, "{ damage = 1 }").is_err());
+    }
+
+    #[test]
+    fn empty_names_are_rejected() {
+        let lua = Lua::new();
+
+        assert!(LuaEventDefinition::from_lua_table("", lua.create_table().unwrap()).is_err());
+    }
+
+    #[test]
+    fn create_event_accepts_valid_payloads() {
+        let lua = Lua::new();
+
+        let event = create_event(&lua, "{ damage = 3.5, killer = 'trap' }").unwrap();
+        assert_eq!(event.name(), "PlayerDied");
+        assert!(create_event(&lua, "{ damage = 3 }").is_ok());
+
+        let definition = definition(&lua, "{ killer = 'string?' }").unwrap();
+        assert!(definition.create_event(&lua, None).is_ok());
+    }
+
+    #[test]
+    fn create_event_validates_fields() {
+        let lua = Lua::new();
+        let message = |params| match create_event(&lua, params) {
+            Ok(_) => panic!("{} should be rejected", params),
+            Err(err) => err.to_string(),
+        };
+
+        assert!(message("{}").contains("field damage of event PlayerDied is missing"));
+        assert!(message("{ damage = '3' }").contains("must be number, got string"));
+        assert!(message("{ damage = 3, killer = 1 }").contains("must be string, got integer"));
+        assert!(message("{ damage = 3, cause = 'trap' }").contains("has no field named cause"));
+        assert!(message("{ damage = 3, [1] = true }").contains("has no field named <integer>"));
+
+        let definition = definition(&lua, "{ count = 'integer' }").unwrap();
+        let params = |count: f64| {
+            let params = lua.create_table().unwrap();
+            params.set("count", count).unwrap();
+            Some(params)
+        };
+        assert!(definition.create_event(&lua, params(2.0)).is_ok());
+        assert!(definition.create_event(&lua, params(2.5)).is_err());
+    }
+}
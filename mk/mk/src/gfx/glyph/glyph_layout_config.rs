@@ -1,9 +1,13 @@
 use fontdue::layout::{HorizontalAlign, VerticalAlign, WrapStyle};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct GlyphLayoutConfig {
+    #[serde(with = "HorizontalAlignDef")]
     pub horizontal_align: HorizontalAlign,
+    #[serde(with = "VerticalAlignDef")]
     pub vertical_align: VerticalAlign,
+    #[serde(with = "WrapStyleDef")]
     pub wrap_style: WrapStyle,
     pub wrap_hard_breaks: bool,
 }
@@ -34,3 +38,26 @@ impl Default for GlyphLayoutConfig {
         }
     }
 }
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "HorizontalAlign")]
+enum HorizontalAlignDef {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "VerticalAlign")]
+enum VerticalAlignDef {
+    Top,
+    Middle,
+    Bottom,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "WrapStyle")]
+enum WrapStyleDef {
+    Word,
+    Letter,
+}
@@ -1,14 +1,14 @@
-use std::{cell::RefCell, mem::size_of, ops::Range, sync::Arc};
+use std::{cell::RefCell, mem::size_of, ops::Range, rc::Rc};
 
 pub struct SingleHostMemoryAllocator {
-    buffer: Arc<RefCell<Vec<u8>>>,
+    buffer: Rc<RefCell<Vec<u8>>>,
     used: usize,
 }
 
 impl SingleHostMemoryAllocator {
     pub fn new(buffer: Vec<u8>) -> Self {
         Self {
-            buffer: Arc::new(RefCell::new(buffer)),
+            buffer: Rc::new(RefCell::new(buffer)),
             used: 0,
         }
     }
@@ -41,13 +41,13 @@ impl SingleHostMemoryAllocator {
 
 #[derive(Clone)]
 pub struct HostAllocation {
-    buffer: Arc<RefCell<Vec<u8>>>,
+    buffer: Rc<RefCell<Vec<u8>>>,
     offset: u32,
     size: u32,
 }
 
 impl HostAllocation {
-    pub fn new(buffer: Arc<RefCell<Vec<u8>>>, offset: usize, size: usize) -> Self {
+    pub fn new(buffer: Rc<RefCell<Vec<u8>>>, offset: usize, size: usize) -> Self {
         debug_assert!(offset <= u32::MAX as usize);
         debug_assert!(size <= u32::MAX as usize);
         Self {
@@ -57,7 +57,7 @@ impl HostAllocation {
         }
     }
 
-    pub fn buffer(&self) -> &Arc<RefCell<Vec<u8>>> {
+    pub fn buffer(&self) -> &Rc<RefCell<Vec<u8>>> {
         &self.buffer
     }
 
@@ -86,7 +86,7 @@ impl HostAllocation {
     }
 
     pub fn copy_from_allocation(&self, src: &Self, dst_offset: usize) {
-        if Arc::ptr_eq(&self.buffer, &src.buffer) {
+        if Rc::ptr_eq(&self.buffer, &src.buffer) {
             self.buffer
                 .borrow_mut()
                 .copy_within(src.range(), self.offset() + dst_offset);
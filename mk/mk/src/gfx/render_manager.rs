@@ -21,9 +21,9 @@ use wgpu::{
     BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
     BindGroupLayoutEntry, BindingType, BufferAddress, BufferBindingType, BufferDescriptor,
     BufferUsages, CommandEncoder, CommandEncoderDescriptor, Extent3d, FilterMode, ImageCopyTexture,
-    ImageDataLayout, ImageSubresourceRange, Origin3d, Queue, Sampler, SamplerDescriptor,
-    ShaderModuleDescriptor, ShaderSource, ShaderStages, SurfaceTexture, TextureAspect,
-    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
+    ImageDataLayout, ImageSubresourceRange, Origin3d, PresentMode, Queue, Sampler,
+    SamplerDescriptor, ShaderModuleDescriptor, ShaderSource, ShaderStages, SurfaceTexture,
+    TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
     TextureViewDescriptor,
 };
 use winit::dpi::PhysicalSize;
@@ -441,6 +441,22 @@ impl RenderManager {
         );
     }
 
+    pub fn vsync(&self) -> bool {
+        self.gfx_context.surface_config.present_mode != PresentMode::AutoNoVsync
+    }
+
+    /// Turns vertical sync on or off. Without it, frames are presented as soon as they are ready.
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.gfx_context.surface_config.present_mode = if vsync {
+            PresentMode::Fifo
+        } else {
+            PresentMode::AutoNoVsync
+        };
+        self.gfx_context
+            .surface
+            .configure(&self.gfx_context.device, &self.gfx_context.surface_config);
+    }
+
     pub fn resize_gfx(&mut self, size: PhysicalSize<u32>) {
         self.gfx_context.surface_config.width = size.width;
         self.gfx_context.surface_config.height = size.height;
@@ -27,6 +27,12 @@ macro_rules! define_handle {
             }
         }
 
+        impl crate::asset::AssetIdentity for $name {
+            fn identity(&self) -> Option<std::sync::Weak<dyn std::any::Any + Send + Sync>> {
+                Some(std::sync::Arc::downgrade(&self.0) as _)
+            }
+        }
+
         impl std::ops::Deref for $name {
             type Target = std::sync::Arc<$type>;
 
@@ -55,6 +61,8 @@ define_handle!(AudioClipHandle(crate::audio::AudioClip));
 
 define_handle!(FontHandle(fontdue::Font));
 
+define_handle!(PrefabHandle(crate::entity::Prefab));
+
 define_handle!(BindGroupHandle(wgpu::BindGroup));
 define_handle!(BufferHandle(wgpu::Buffer));
 define_handle!(PipelineHandle(wgpu::RenderPipeline));
@@ -1 +1,220 @@
-pub struct Action {}
+use super::{InputChannelValue, InputSources, Trigger};
+use crate::structure::Vec2;
+
+/// A named, device-independent input such as `move_left` or `confirm`.
+#[derive(Debug, Clone)]
+pub struct Action {
+    name: String,
+    triggers: Vec<Trigger>,
+    state: ActionState,
+}
+
+/// The state of an action for the current frame.
+///
+/// `value` is the 1D reading of the strongest trigger: `0` or `1` for keys, `-1..=1` for 1D axes
+/// and the length of the vector for 2D axes. `vector` is its 2D reading, which is zero for keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActionState {
+    pub held: bool,
+    pub pressed: bool,
+    pub released: bool,
+    pub value: f32,
+    pub vector: Vec2,
+}
+
+impl Default for ActionState {
+    fn default() -> Self {
+        Self {
+            held: false,
+            pressed: false,
+            released: false,
+            value: 0f32,
+            vector: Vec2::zero(),
+        }
+    }
+}
+
+impl Action {
+    pub fn new(name: impl Into<String>, triggers: Vec<Trigger>) -> Self {
+        Self {
+            name: name.into(),
+            triggers,
+            state: ActionState::default(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn triggers(&self) -> &[Trigger] {
+        &self.triggers
+    }
+
+    pub fn set_triggers(&mut self, triggers: Vec<Trigger>) {
+        self.triggers = triggers;
+    }
+
+    pub fn state(&self) -> ActionState {
+        self.state
+    }
+
+    pub fn update(&mut self, sources: &InputSources) {
+        let mut value = 0f32;
+        let mut vector = Vec2::zero();
+
+        for trigger in &self.triggers {
+            let (trigger_value, trigger_vector) = match trigger.value(sources) {
+                InputChannelValue::Button(pressed) => {
+                    (if pressed { 1f32 } else { 0f32 }, Vec2::zero())
+                }
+                InputChannelValue::Axis1D(x) => (x, Vec2::new(x, 0f32)),
+                InputChannelValue::Axis2D(x, y) => {
+                    let vector = Vec2::new(x, y);
+                    (vector.len(), vector)
+                }
+            };
+
+            if value.abs() < trigger_value.abs() {
+                value = trigger_value;
+                vector = trigger_vector;
+            }
+        }
+
+        let held = value != 0f32;
+
+        self.state = ActionState {
+            held,
+            pressed: held && !self.state.held,
+            released: !held && self.state.held,
+            value,
+            vector,
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::input::{
+        GamepadAxis, GamepadChannel, GamepadDriver, KeyCode, Keyboard, KeyboardInput, Mouse,
+    };
+
+    fn press(keyboard: &mut Keyboard, key: KeyCode, is_pressed: bool) {
+        keyboard.push_input(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key),
+            is_pressed,
+        });
+        keyboard.update();
+    }
+
+    fn wasd() -> Trigger {
+        Trigger::Axis2D {
+            left: KeyCode::AlphaA,
+            right: KeyCode::AlphaD,
+            down: KeyCode::AlphaS,
+            up: KeyCode::AlphaW,
+        }
+    }
+
+    #[test]
+    fn key_action_reports_edges() {
+        let mut keyboard = Keyboard::new();
+        let mouse = Mouse::new();
+        let gamepads = GamepadDriver::without_backend();
+        let mut action = Action::new(
+            "jump",
+            vec![Trigger::Key {
+                key: KeyCode::Space,
+            }],
+        );
+        let update = |keyboard: &Keyboard, action: &mut Action| {
+            action.update(&InputSources {
+                keyboard: Some(keyboard),
+                mouse: &mouse,
+                gamepads: &gamepads,
+            });
+            action.state()
+        };
+
+        press(&mut keyboard, KeyCode::Space, true);
+        let state = update(&keyboard, &mut action);
+        assert!(state.held && state.pressed && !state.released);
+        assert_eq!(state.value, 1f32);
+
+        keyboard.update();
+        let state = update(&keyboard, &mut action);
+        assert!(state.held && !state.pressed);
+
+        press(&mut keyboard, KeyCode::Space, false);
+        let state = update(&keyboard, &mut action);
+        assert!(!state.held && state.released);
+        assert_eq!(state.value, 0f32);
+    }
+
+    fn wasd_value(keyboard: Option<&Keyboard>) -> InputChannelValue {
+        wasd().value(&InputSources {
+            keyboard,
+            mouse: &Mouse::new(),
+            gamepads: &GamepadDriver::without_backend(),
+        })
+    }
+
+    #[test]
+    fn axis_2d_is_normalized_and_ignores_captured_keyboard() {
+        let mut keyboard = Keyboard::new();
+        let is_zero =
+            |value| matches!(value, InputChannelValue::Axis2D(x, y) if x == 0f32 && y == 0f32);
+
+        assert!(is_zero(wasd_value(Some(&keyboard))));
+
+        for key in [KeyCode::AlphaD, KeyCode::AlphaW] {
+            keyboard.push_input(KeyboardInput {
+                scan_code: 0,
+                key_code: Some(key),
+                is_pressed: true,
+            });
+        }
+        keyboard.update();
+
+        match wasd_value(Some(&keyboard)) {
+            InputChannelValue::Axis2D(x, y) => {
+                assert!((x - y).abs() < 1e-6);
+                assert!(((x * x + y * y).sqrt() - 1f32).abs() < 1e-6);
+            }
+            value => panic!("expected a 2D axis, got {:?}", value),
+        }
+
+        assert!(is_zero(wasd_value(None)));
+    }
+
+    #[test]
+    fn strongest_trigger_wins() {
+        let keyboard = Keyboard::new();
+        let mouse = Mouse::new();
+        let mut gamepads = GamepadDriver::without_backend();
+        let gamepad = gamepads.connect_virtual("virtual");
+        let mut action = Action::new(
+            "move",
+            vec![
+                wasd(),
+                Trigger::Gamepad {
+                    channel: GamepadChannel::LeftStick,
+                },
+            ],
+        );
+
+        gamepad.set_axis(GamepadAxis::LeftStickX, 0.5f32);
+        action.update(&InputSources {
+            keyboard: Some(&keyboard),
+            mouse: &mouse,
+            gamepads: &gamepads,
+        });
+
+        let state = action.state();
+        assert!(state.held && state.pressed);
+        assert!(0f32 < state.vector.x && state.vector.y == 0f32);
+        assert_eq!(state.value, state.vector.len());
+    }
+}
@@ -2,7 +2,7 @@ use super::Device;
 use codegen::Event;
 use std::sync::Arc;
 
-pub trait InputChannel {
+pub trait InputChannel: Send + Sync {
     fn name(&self) -> &str;
     fn device(&self) -> Arc<dyn Device>;
     fn state(&self) -> InputChannelValue;
@@ -15,6 +15,23 @@ pub enum InputChannelValue {
     Axis2D(f32, f32),
 }
 
+impl InputChannelValue {
+    /// Returns how far the channel is pushed: 1 for a pressed button, and the length of an axis.
+    pub fn magnitude(&self) -> f32 {
+        match *self {
+            InputChannelValue::Button(pressed) => {
+                if pressed {
+                    1f32
+                } else {
+                    0f32
+                }
+            }
+            InputChannelValue::Axis1D(x) => x.abs(),
+            InputChannelValue::Axis2D(x, y) => (x * x + y * y).sqrt(),
+        }
+    }
+}
+
 // #[derive(Event, Clone)]
 // pub struct InputChannelStateChangedEvent {
 //     pub channel: Arc<dyn InputChannel>,
@@ -22,7 +39,7 @@ pub enum InputChannelValue {
 //     pub new_state: InputChannelValue,
 // }
 
-pub trait OutputChannel {
+pub trait OutputChannel: Send + Sync {
     fn name(&self) -> &str;
     fn device(&self) -> Arc<dyn Device>;
     fn state(&self) -> OutputChannelValue;
@@ -1,8 +1,25 @@
 use super::{InputChannel, OutputChannel};
 use std::sync::Arc;
 
-pub trait Device {
+pub trait Device: Send + Sync {
     fn name(&self) -> &str;
     fn input_channels(&self) -> &[Arc<dyn InputChannel>];
     fn output_channels(&self) -> &[Arc<dyn OutputChannel>];
 }
+
+/// Stands in for the device of a channel that outlived it. It has no channels.
+pub struct DetachedDevice;
+
+impl Device for DetachedDevice {
+    fn name(&self) -> &str {
+        "detached"
+    }
+
+    fn input_channels(&self) -> &[Arc<dyn InputChannel>] {
+        &[]
+    }
+
+    fn output_channels(&self) -> &[Arc<dyn OutputChannel>] {
+        &[]
+    }
+}
@@ -0,0 +1,951 @@
+use crate::input::{
+    DetachedDevice, Device, Driver, InputChannel, InputChannelValue, OutputChannel,
+    OutputChannelValue,
+};
+use codegen::LuaEnum;
+use gilrs::{
+    ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Repeat},
+    Axis, Button, EventType, Gilrs,
+};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    sync::{Arc, Weak},
+};
+
+/// A button, trigger or stick of a gamepad, named after the Xbox layout.
+#[derive(
+    LuaEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum GamepadChannel {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    LeftStick,
+    RightStick,
+}
+
+impl GamepadChannel {
+    pub const ALL: [GamepadChannel; 19] = [
+        GamepadChannel::South,
+        GamepadChannel::East,
+        GamepadChannel::North,
+        GamepadChannel::West,
+        GamepadChannel::LeftBumper,
+        GamepadChannel::RightBumper,
+        GamepadChannel::LeftTrigger,
+        GamepadChannel::RightTrigger,
+        GamepadChannel::Select,
+        GamepadChannel::Start,
+        GamepadChannel::Mode,
+        GamepadChannel::LeftThumb,
+        GamepadChannel::RightThumb,
+        GamepadChannel::DPadUp,
+        GamepadChannel::DPadDown,
+        GamepadChannel::DPadLeft,
+        GamepadChannel::DPadRight,
+        GamepadChannel::LeftStick,
+        GamepadChannel::RightStick,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GamepadChannel::South => "south",
+            GamepadChannel::East => "east",
+            GamepadChannel::North => "north",
+            GamepadChannel::West => "west",
+            GamepadChannel::LeftBumper => "left_bumper",
+            GamepadChannel::RightBumper => "right_bumper",
+            GamepadChannel::LeftTrigger => "left_trigger",
+            GamepadChannel::RightTrigger => "right_trigger",
+            GamepadChannel::Select => "select",
+            GamepadChannel::Start => "start",
+            GamepadChannel::Mode => "mode",
+            GamepadChannel::LeftThumb => "left_thumb",
+            GamepadChannel::RightThumb => "right_thumb",
+            GamepadChannel::DPadUp => "dpad_up",
+            GamepadChannel::DPadDown => "dpad_down",
+            GamepadChannel::DPadLeft => "dpad_left",
+            GamepadChannel::DPadRight => "dpad_right",
+            GamepadChannel::LeftStick => "left_stick",
+            GamepadChannel::RightStick => "right_stick",
+        }
+    }
+
+    fn from_gilrs_button(button: Button) -> Option<Self> {
+        Some(match button {
+            Button::South => GamepadChannel::South,
+            Button::East => GamepadChannel::East,
+            Button::North => GamepadChannel::North,
+            Button::West => GamepadChannel::West,
+            Button::LeftTrigger => GamepadChannel::LeftBumper,
+            Button::RightTrigger => GamepadChannel::RightBumper,
+            Button::Select => GamepadChannel::Select,
+            Button::Start => GamepadChannel::Start,
+            Button::Mode => GamepadChannel::Mode,
+            Button::LeftThumb => GamepadChannel::LeftThumb,
+            Button::RightThumb => GamepadChannel::RightThumb,
+            Button::DPadUp => GamepadChannel::DPadUp,
+            Button::DPadDown => GamepadChannel::DPadDown,
+            Button::DPadLeft => GamepadChannel::DPadLeft,
+            Button::DPadRight => GamepadChannel::DPadRight,
+            _ => return None,
+        })
+    }
+}
+
+/// A raw analog axis of a gamepad, before dead zones are applied.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    fn from_gilrs_axis(axis: Axis) -> Option<Self> {
+        Some(match axis {
+            Axis::LeftStickX => GamepadAxis::LeftStickX,
+            Axis::LeftStickY => GamepadAxis::LeftStickY,
+            Axis::RightStickX => GamepadAxis::RightStickX,
+            Axis::RightStickY => GamepadAxis::RightStickY,
+            _ => return None,
+        })
+    }
+
+    fn from_gilrs_button(button: Button) -> Option<Self> {
+        Some(match button {
+            Button::LeftTrigger2 => GamepadAxis::LeftTrigger,
+            Button::RightTrigger2 => GamepadAxis::RightTrigger,
+            _ => return None,
+        })
+    }
+}
+
+/// Readings below the dead zone are treated as zero; the rest is rescaled to the full range.
+/// Sticks use a radial dead zone so that diagonals behave like the cardinal directions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamepadDeadZones {
+    pub stick: f32,
+    pub trigger: f32,
+}
+
+impl Default for GamepadDeadZones {
+    fn default() -> Self {
+        Self {
+            stick: 0.15f32,
+            trigger: 0.05f32,
+        }
+    }
+}
+
+impl GamepadDeadZones {
+    pub fn apply_trigger(&self, value: f32) -> f32 {
+        let value = value.clamp(0f32, 1f32);
+
+        if value <= self.trigger {
+            0f32
+        } else {
+            (value - self.trigger) / (1f32 - self.trigger)
+        }
+    }
+
+    pub fn apply_stick(&self, x: f32, y: f32) -> (f32, f32) {
+        let len = (x * x + y * y).sqrt();
+
+        if len <= self.stick {
+            return (0f32, 0f32);
+        }
+
+        let scaled = ((len - self.stick) / (1f32 - self.stick)).min(1f32);
+        (x / len * scaled, y / len * scaled)
+    }
+}
+
+#[derive(Default)]
+struct GamepadRawState {
+    pressed: HashSet<GamepadChannel>,
+    left_stick: (f32, f32),
+    right_stick: (f32, f32),
+    left_trigger: f32,
+    right_trigger: f32,
+}
+
+/// A connected gamepad. Its state is fed by the `GamepadDriver`, or directly for virtual devices.
+pub struct Gamepad {
+    id: usize,
+    name: String,
+    dead_zones: Mutex<GamepadDeadZones>,
+    raw: Mutex<GamepadRawState>,
+    rumble: Mutex<f64>,
+    input_channels: Vec<Arc<dyn InputChannel>>,
+    output_channels: Vec<Arc<dyn OutputChannel>>,
+}
+
+impl Gamepad {
+    pub fn new(id: usize, name: impl Into<String>, dead_zones: GamepadDeadZones) -> Arc<Self> {
+        Arc::new_cyclic(|gamepad: &Weak<Gamepad>| Self {
+            id,
+            name: name.into(),
+            dead_zones: Mutex::new(dead_zones),
+            raw: Mutex::new(GamepadRawState::default()),
+            rumble: Mutex::new(0f64),
+            input_channels: GamepadChannel::ALL
+                .iter()
+                .map(|&channel| {
+                    Arc::new(GamepadInputChannel {
+                        gamepad: gamepad.clone(),
+                        channel,
+                    }) as Arc<dyn InputChannel>
+                })
+                .collect(),
+            output_channels: vec![Arc::new(GamepadRumbleChannel {
+                gamepad: gamepad.clone(),
+            })],
+        })
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn dead_zones(&self) -> GamepadDeadZones {
+        *self.dead_zones.lock()
+    }
+
+    pub fn set_dead_zones(&self, dead_zones: GamepadDeadZones) {
+        *self.dead_zones.lock() = dead_zones;
+    }
+
+    pub fn set_button(&self, channel: GamepadChannel, pressed: bool) {
+        let mut raw = self.raw.lock();
+
+        if pressed {
+            raw.pressed.insert(channel);
+        } else {
+            raw.pressed.remove(&channel);
+        }
+    }
+
+    pub fn set_axis(&self, axis: GamepadAxis, value: f32) {
+        let mut raw = self.raw.lock();
+
+        match axis {
+            GamepadAxis::LeftStickX => raw.left_stick.0 = value,
+            GamepadAxis::LeftStickY => raw.left_stick.1 = value,
+            GamepadAxis::RightStickX => raw.right_stick.0 = value,
+            GamepadAxis::RightStickY => raw.right_stick.1 = value,
+            GamepadAxis::LeftTrigger => raw.left_trigger = value,
+            GamepadAxis::RightTrigger => raw.right_trigger = value,
+        }
+    }
+
+    /// Returns the state of the channel with dead zones applied.
+    pub fn channel_state(&self, channel: GamepadChannel) -> InputChannelValue {
+        let dead_zones = self.dead_zones();
+        let raw = self.raw.lock();
+
+        match channel {
+            GamepadChannel::LeftTrigger => {
+                InputChannelValue::Axis1D(dead_zones.apply_trigger(raw.left_trigger))
+            }
+            GamepadChannel::RightTrigger => {
+                InputChannelValue::Axis1D(dead_zones.apply_trigger(raw.right_trigger))
+            }
+            GamepadChannel::LeftStick => {
+                let (x, y) = dead_zones.apply_stick(raw.left_stick.0, raw.left_stick.1);
+                InputChannelValue::Axis2D(x, y)
+            }
+            GamepadChannel::RightStick => {
+                let (x, y) = dead_zones.apply_stick(raw.right_stick.0, raw.right_stick.1);
+                InputChannelValue::Axis2D(x, y)
+            }
+            channel => InputChannelValue::Button(raw.pressed.contains(&channel)),
+        }
+    }
+
+    /// The requested rumble strength, from `0` to `1`.
+    pub fn rumble(&self) -> f64 {
+        *self.rumble.lock()
+    }
+
+    pub fn set_rumble(&self, strength: f64) {
+        *self.rumble.lock() = strength.clamp(0f64, 1f64);
+    }
+}
+
+impl Device for Gamepad {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn input_channels(&self) -> &[Arc<dyn InputChannel>] {
+        &self.input_channels
+    }
+
+    fn output_channels(&self) -> &[Arc<dyn OutputChannel>] {
+        &self.output_channels
+    }
+}
+
+fn device(gamepad: &Weak<Gamepad>) -> Arc<dyn Device> {
+    match gamepad.upgrade() {
+        Some(gamepad) => gamepad,
+        None => Arc::new(DetachedDevice),
+    }
+}
+
+pub struct GamepadInputChannel {
+    gamepad: Weak<Gamepad>,
+    channel: GamepadChannel,
+}
+
+impl GamepadInputChannel {
+    pub fn channel(&self) -> GamepadChannel {
+        self.channel
+    }
+}
+
+impl InputChannel for GamepadInputChannel {
+    fn name(&self) -> &str {
+        self.channel.name()
+    }
+
+    fn device(&self) -> Arc<dyn Device> {
+        device(&self.gamepad)
+    }
+
+    /// Released, or centered, once the gamepad is dropped.
+    fn state(&self) -> InputChannelValue {
+        match self.gamepad.upgrade() {
+            Some(gamepad) => gamepad.channel_state(self.channel),
+            None => match self.channel {
+                GamepadChannel::LeftTrigger | GamepadChannel::RightTrigger => {
+                    InputChannelValue::Axis1D(0f32)
+                }
+                GamepadChannel::LeftStick | GamepadChannel::RightStick => {
+                    InputChannelValue::Axis2D(0f32, 0f32)
+                }
+                _ => InputChannelValue::Button(false),
+            },
+        }
+    }
+}
+
+/// Rumble strength from `0` to `1`. `Bool` and `Int` states are accepted as well.
+pub struct GamepadRumbleChannel {
+    gamepad: Weak<Gamepad>,
+}
+
+impl OutputChannel for GamepadRumbleChannel {
+    fn name(&self) -> &str {
+        "rumble"
+    }
+
+    fn device(&self) -> Arc<dyn Device> {
+        device(&self.gamepad)
+    }
+
+    fn state(&self) -> OutputChannelValue {
+        OutputChannelValue::Float(
+            self.gamepad
+                .upgrade()
+                .map_or(0f64, |gamepad| gamepad.rumble()),
+        )
+    }
+
+    fn set_state(&self, state: OutputChannelValue) {
+        let strength = match state {
+            OutputChannelValue::Bool(on) => {
+                if on {
+                    1f64
+                } else {
+                    0f64
+                }
+            }
+            OutputChannelValue::Int(value) => value as f64,
+            OutputChannelValue::Float(value) => value,
+        };
+
+        if let Some(gamepad) = self.gamepad.upgrade() {
+            gamepad.set_rumble(strength);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GamepadEvent {
+    Connected { id: usize, name: String },
+    Disconnected { id: usize, name: String },
+}
+
+/// A change of the gamepads, as read from the backend. Gamepads are referred to by their id.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum GamepadInput {
+    Connected {
+        id: usize,
+        name: String,
+    },
+    Disconnected {
+        id: usize,
+    },
+    Button {
+        id: usize,
+        channel: GamepadChannel,
+        pressed: bool,
+    },
+    Axis {
+        id: usize,
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+struct GamepadSlot {
+    gamepad: Arc<Gamepad>,
+    gilrs_id: Option<gilrs::GamepadId>,
+    rumble: f64,
+    effect: Option<Effect>,
+}
+
+/// Polls gamepads through gilrs and exposes each of them as a `Gamepad` device.
+pub struct GamepadDriver {
+    gilrs: Option<Gilrs>,
+    pending_connections: Vec<(gilrs::GamepadId, String)>,
+    slots: BTreeMap<usize, GamepadSlot>,
+    devices: Vec<Arc<dyn Device>>,
+    events: Vec<GamepadEvent>,
+    warnings: Vec<String>,
+    dead_zones: GamepadDeadZones,
+    channels_suppressed: HashSet<GamepadChannel>,
+    next_id: usize,
+}
+
+impl GamepadDriver {
+    /// Creates a driver backed by gilrs. Gamepads connected before the call are reported as
+    /// connected on the first update. Falls back to `without_backend` if gilrs is unavailable.
+    pub fn new() -> Self {
+        let mut driver = Self::without_backend();
+
+        match Gilrs::new() {
+            Ok(gilrs) => {
+                driver.pending_connections = gilrs
+                    .gamepads()
+                    .map(|(id, gamepad)| (id, gamepad.name().to_owned()))
+                    .collect();
+                driver.gilrs = Some(gilrs);
+            }
+            Err(err) => {
+                driver
+                    .warnings
+                    .push(format!("gamepads are not available: {}", err));
+            }
+        }
+
+        driver
+    }
+
+    /// Creates a driver that only tracks virtual gamepads.
+    pub fn without_backend() -> Self {
+        Self {
+            gilrs: None,
+            pending_connections: Vec::new(),
+            slots: BTreeMap::new(),
+            devices: Vec::new(),
+            events: Vec::new(),
+            warnings: Vec::new(),
+            dead_zones: GamepadDeadZones::default(),
+            channels_suppressed: HashSet::new(),
+            next_id: 1,
+        }
+    }
+
+    pub fn gamepad(&self, id: usize) -> Option<&Arc<Gamepad>> {
+        self.slots.get(&id).map(|slot| &slot.gamepad)
+    }
+
+    /// Returns the connected gamepads, sorted by the order of connection.
+    pub fn gamepads(&self) -> impl Iterator<Item = &Arc<Gamepad>> {
+        self.slots.values().map(|slot| &slot.gamepad)
+    }
+
+    pub fn dead_zones(&self) -> GamepadDeadZones {
+        self.dead_zones
+    }
+
+    /// Sets the dead zones of every connected and future gamepad.
+    pub fn set_dead_zones(&mut self, dead_zones: GamepadDeadZones) {
+        self.dead_zones = dead_zones;
+
+        for slot in self.slots.values() {
+            slot.gamepad.set_dead_zones(dead_zones);
+        }
+    }
+
+    /// Returns the channels pushed at least `threshold` far on any gamepad, suppressed or not.
+    pub fn active_channels(&self, threshold: f32) -> BTreeSet<GamepadChannel> {
+        GamepadChannel::ALL
+            .into_iter()
+            .filter(|&channel| {
+                self.gamepads()
+                    .any(|gamepad| threshold <= gamepad.channel_state(channel).magnitude())
+            })
+            .collect()
+    }
+
+    /// Hides the channel from triggers until it is released on every gamepad, so that a press
+    /// consumed elsewhere (e.g. by rebinding) does not also trigger actions.
+    pub fn suppress_until_released(&mut self, channel: GamepadChannel) {
+        self.channels_suppressed.insert(channel);
+    }
+
+    pub fn is_suppressed(&self, channel: GamepadChannel) -> bool {
+        self.channels_suppressed.contains(&channel)
+    }
+
+    /// Stops suppressing the channels released on every gamepad. Should be called once per frame.
+    pub fn update_suppressed(&mut self) {
+        let mut channels = std::mem::take(&mut self.channels_suppressed);
+        channels.retain(|&channel| {
+            self.gamepads()
+                .any(|gamepad| 0f32 < gamepad.channel_state(channel).magnitude())
+        });
+        self.channels_suppressed = channels;
+    }
+
+    /// Adds a gamepad that is not backed by any hardware. Its state is set directly.
+    pub fn connect_virtual(&mut self, name: impl Into<String>) -> Arc<Gamepad> {
+        self.connect(None, name.into())
+    }
+
+    pub fn disconnect(&mut self, id: usize) -> bool {
+        let slot = match self.slots.remove(&id) {
+            Some(slot) => slot,
+            None => return false,
+        };
+
+        self.devices = self.collect_devices();
+        self.events.push(GamepadEvent::Disconnected {
+            id,
+            name: slot.gamepad.name().to_owned(),
+        });
+        true
+    }
+
+    /// Takes the connections and disconnections since the last call.
+    pub fn take_events(&mut self) -> Vec<GamepadEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Takes the problems met with the backend since the last call, to be reported as diagnostics.
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Applies a change of the gamepads, e.g. one read back from a recording.
+    /// A connected gamepad keeps the given id; later connections are numbered after it.
+    pub fn apply(&mut self, input: GamepadInput) {
+        match input {
+            GamepadInput::Connected { id, name } => {
+                self.insert(id, None, name);
+            }
+            GamepadInput::Disconnected { id } => {
+                self.disconnect(id);
+            }
+            GamepadInput::Button {
+                id,
+                channel,
+                pressed,
+            } => {
+                if let Some(gamepad) = self.gamepad(id) {
+                    gamepad.set_button(channel, pressed);
+                }
+            }
+            GamepadInput::Axis { id, axis, value } => {
+                if let Some(gamepad) = self.gamepad(id) {
+                    gamepad.set_axis(axis, value);
+                }
+            }
+        }
+    }
+
+    /// Applies the pending gilrs events and sends the requested rumble to the hardware.
+    /// Returns the applied changes, so that they can be recorded.
+    pub fn update(&mut self) -> Vec<GamepadInput> {
+        let mut gilrs = match self.gilrs.take() {
+            Some(gilrs) => gilrs,
+            None => return Vec::new(),
+        };
+        let mut inputs = Vec::new();
+
+        for (gilrs_id, name) in std::mem::take(&mut self.pending_connections) {
+            let id = self.connect(Some(gilrs_id), name.clone()).id();
+            inputs.push(GamepadInput::Connected { id, name });
+        }
+
+        while let Some(event) = gilrs.next_event() {
+            let id = self.find_gilrs(event.id);
+            let input = match (event.event, id) {
+                (EventType::Connected, None) => {
+                    let name = gilrs.gamepad(event.id).name().to_owned();
+                    let id = self.connect(Some(event.id), name.clone()).id();
+                    inputs.push(GamepadInput::Connected { id, name });
+                    continue;
+                }
+                (EventType::Disconnected, Some(id)) => GamepadInput::Disconnected { id },
+                (EventType::ButtonPressed(button, _), Some(id))
+                | (EventType::ButtonReleased(button, _), Some(id)) => {
+                    match GamepadChannel::from_gilrs_button(button) {
+                        Some(channel) => GamepadInput::Button {
+                            id,
+                            channel,
+                            pressed: matches!(event.event, EventType::ButtonPressed(..)),
+                        },
+                        None => continue,
+                    }
+                }
+                (EventType::ButtonChanged(button, value, _), Some(id)) => {
+                    match GamepadAxis::from_gilrs_button(button) {
+                        Some(axis) => GamepadInput::Axis { id, axis, value },
+                        None => continue,
+                    }
+                }
+                (EventType::AxisChanged(axis, value, _), Some(id)) => {
+                    match GamepadAxis::from_gilrs_axis(axis) {
+                        Some(axis) => GamepadInput::Axis { id, axis, value },
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+
+            self.apply(input.clone());
+            inputs.push(input);
+        }
+
+        for slot in self.slots.values_mut() {
+            let gilrs_id = match slot.gilrs_id {
+                Some(gilrs_id) => gilrs_id,
+                None => continue,
+            };
+            let rumble = slot.gamepad.rumble();
+
+            if rumble == slot.rumble {
+                continue;
+            }
+
+            slot.rumble = rumble;
+            // Dropping the effect stops it.
+            slot.effect = None;
+
+            if rumble == 0f64 || !gilrs.gamepad(gilrs_id).is_ff_supported() {
+                continue;
+            }
+
+            let magnitude = (rumble * u16::MAX as f64) as u16;
+            let effect = EffectBuilder::new()
+                .add_effect(BaseEffect {
+                    kind: BaseEffectType::Strong { magnitude },
+                    ..Default::default()
+                })
+                .add_effect(BaseEffect {
+                    kind: BaseEffectType::Weak { magnitude },
+                    ..Default::default()
+                })
+                .gamepads(&[gilrs_id])
+                .repeat(Repeat::Infinitely)
+                .finish(&mut gilrs);
+
+            match effect.and_then(|effect| effect.play().map(|_| effect)) {
+                Ok(effect) => slot.effect = Some(effect),
+                Err(err) => self.warnings.push(format!(
+                    "failed to rumble gamepad {}: {}",
+                    slot.gamepad.id(),
+                    err
+                )),
+            }
+        }
+
+        self.gilrs = Some(gilrs);
+        inputs
+    }
+
+    fn connect(&mut self, gilrs_id: Option<gilrs::GamepadId>, name: String) -> Arc<Gamepad> {
+        self.insert(self.next_id, gilrs_id, name)
+    }
+
+    fn insert(
+        &mut self,
+        id: usize,
+        gilrs_id: Option<gilrs::GamepadId>,
+        name: String,
+    ) -> Arc<Gamepad> {
+        self.next_id = self.next_id.max(id + 1);
+
+        let gamepad = Gamepad::new(id, name.clone(), self.dead_zones);
+        self.slots.insert(
+            id,
+            GamepadSlot {
+                gamepad: gamepad.clone(),
+                gilrs_id,
+                rumble: 0f64,
+                effect: None,
+            },
+        );
+        self.devices = self.collect_devices();
+        self.events.push(GamepadEvent::Connected { id, name });
+        gamepad
+    }
+
+    fn find_gilrs(&self, gilrs_id: gilrs::GamepadId) -> Option<usize> {
+        self.slots
+            .iter()
+            .find(|(_, slot)| slot.gilrs_id == Some(gilrs_id))
+            .map(|(&id, _)| id)
+    }
+
+    fn collect_devices(&self) -> Vec<Arc<dyn Device>> {
+        self.slots
+            .values()
+            .map(|slot| slot.gamepad.clone() as Arc<dyn Device>)
+            .collect()
+    }
+}
+
+impl Default for GamepadDriver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Driver for GamepadDriver {
+    fn name(&self) -> &str {
+        "gamepad"
+    }
+
+    fn devices(&self) -> &[Arc<dyn Device>] {
+        &self.devices
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn button(value: InputChannelValue) -> bool {
+        match value {
+            InputChannelValue::Button(pressed) => pressed,
+            _ => panic!("expected a button, got {:?}", value),
+        }
+    }
+
+    fn axis_1d(value: InputChannelValue) -> f32 {
+        match value {
+            InputChannelValue::Axis1D(x) => x,
+            _ => panic!("expected a 1D axis, got {:?}", value),
+        }
+    }
+
+    fn axis_2d(value: InputChannelValue) -> (f32, f32) {
+        match value {
+            InputChannelValue::Axis2D(x, y) => (x, y),
+            _ => panic!("expected a 2D axis, got {:?}", value),
+        }
+    }
+
+    fn channel(gamepad: &Gamepad, name: &str) -> Arc<dyn InputChannel> {
+        gamepad
+            .input_channels()
+            .iter()
+            .find(|channel| channel.name() == name)
+            .cloned()
+            .unwrap()
+    }
+
+    #[test]
+    fn virtual_gamepad_exposes_channels() {
+        let mut driver = GamepadDriver::without_backend();
+        let gamepad = driver.connect_virtual("virtual");
+
+        assert_eq!(driver.devices().len(), 1);
+        assert_eq!(driver.devices()[0].name(), "virtual");
+        assert_eq!(gamepad.input_channels().len(), GamepadChannel::ALL.len());
+
+        let south = channel(&gamepad, "south");
+        assert!(!button(south.state()));
+        gamepad.set_button(GamepadChannel::South, true);
+        assert!(button(south.state()));
+        assert_eq!(south.device().name(), "virtual");
+    }
+
+    #[test]
+    fn channels_outliving_their_gamepad_are_neutral() {
+        let mut driver = GamepadDriver::without_backend();
+        let gamepad = driver.connect_virtual("virtual");
+        let south = channel(&gamepad, "south");
+        let trigger = channel(&gamepad, "left_trigger");
+        let stick = channel(&gamepad, "left_stick");
+        let rumble = gamepad.output_channels()[0].clone();
+
+        gamepad.set_button(GamepadChannel::South, true);
+        gamepad.set_rumble(1f64);
+        driver.disconnect(gamepad.id());
+        drop(gamepad);
+
+        assert!(!button(south.state()));
+        assert_eq!(axis_1d(trigger.state()), 0f32);
+        assert_eq!(axis_2d(stick.state()), (0f32, 0f32));
+        assert_eq!(south.device().name(), "detached");
+        assert!(matches!(rumble.state(), OutputChannelValue::Float(value) if value == 0f64));
+        rumble.set_state(OutputChannelValue::Bool(true));
+    }
+
+    #[test]
+    fn trigger_dead_zone() {
+        let mut driver = GamepadDriver::without_backend();
+        driver.set_dead_zones(GamepadDeadZones {
+            stick: 0.2f32,
+            trigger: 0.1f32,
+        });
+        let gamepad = driver.connect_virtual("virtual");
+        let trigger = channel(&gamepad, "left_trigger");
+
+        gamepad.set_axis(GamepadAxis::LeftTrigger, 0.05f32);
+        assert_eq!(axis_1d(trigger.state()), 0f32);
+
+        gamepad.set_axis(GamepadAxis::LeftTrigger, 0.55f32);
+        assert!((axis_1d(trigger.state()) - 0.5f32).abs() < 1e-5);
+
+        gamepad.set_axis(GamepadAxis::LeftTrigger, 1f32);
+        assert_eq!(axis_1d(trigger.state()), 1f32);
+    }
+
+    #[test]
+    fn stick_dead_zone_is_radial() {
+        let gamepad = Gamepad::new(
+            1,
+            "virtual",
+            GamepadDeadZones {
+                stick: 0.2f32,
+                trigger: 0f32,
+            },
+        );
+        let stick = channel(&gamepad, "right_stick");
+
+        gamepad.set_axis(GamepadAxis::RightStickX, 0.1f32);
+        gamepad.set_axis(GamepadAxis::RightStickY, 0.1f32);
+        assert_eq!(axis_2d(stick.state()), (0f32, 0f32));
+
+        // Each component alone is below the dead zone, but the stick is not.
+        gamepad.set_axis(GamepadAxis::RightStickX, 0.6f32);
+        gamepad.set_axis(GamepadAxis::RightStickY, -0.8f32);
+        let (x, y) = axis_2d(stick.state());
+        assert!(((x * x + y * y).sqrt() - 1f32).abs() < 1e-5);
+        assert!((x / y + 0.75f32).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rumble_through_output_channel() {
+        let mut driver = GamepadDriver::without_backend();
+        let gamepad = driver.connect_virtual("virtual");
+        let rumble = gamepad.output_channels()[0].clone();
+
+        assert_eq!(rumble.name(), "rumble");
+        rumble.set_state(OutputChannelValue::Float(2f64));
+        assert_eq!(gamepad.rumble(), 1f64);
+        rumble.set_state(OutputChannelValue::Bool(false));
+        assert_eq!(gamepad.rumble(), 0f64);
+        rumble.set_state(OutputChannelValue::Int(1));
+
+        // The strength is queued on the gamepad until the driver sends it to the hardware.
+        assert!(matches!(rumble.state(), OutputChannelValue::Float(strength) if strength == 1f64));
+        assert_eq!(driver.slots[&gamepad.id()].rumble, 0f64);
+    }
+
+    #[test]
+    fn hot_plug_events() {
+        let mut driver = GamepadDriver::without_backend();
+        let first = driver.connect_virtual("first");
+        let second = driver.connect_virtual("second");
+
+        assert!(driver.disconnect(first.id()));
+        assert!(!driver.disconnect(first.id()));
+        assert_eq!(driver.devices().len(), 1);
+        assert_eq!(driver.gamepads().next().unwrap().id(), second.id());
+        assert_eq!(
+            driver.take_events(),
+            vec![
+                GamepadEvent::Connected {
+                    id: first.id(),
+                    name: "first".to_owned()
+                },
+                GamepadEvent::Connected {
+                    id: second.id(),
+                    name: "second".to_owned()
+                },
+                GamepadEvent::Disconnected {
+                    id: first.id(),
+                    name: "first".to_owned()
+                },
+            ]
+        );
+        assert!(driver.take_events().is_empty());
+    }
+
+    #[test]
+    fn applied_inputs_rebuild_the_gamepads() {
+        let mut driver = GamepadDriver::without_backend();
+
+        driver.apply(GamepadInput::Connected {
+            id: 3,
+            name: "recorded".to_owned(),
+        });
+        driver.apply(GamepadInput::Button {
+            id: 3,
+            channel: GamepadChannel::South,
+            pressed: true,
+        });
+        driver.apply(GamepadInput::Axis {
+            id: 3,
+            axis: GamepadAxis::RightTrigger,
+            value: 1f32,
+        });
+        // Changes of unknown gamepads are ignored.
+        driver.apply(GamepadInput::Button {
+            id: 4,
+            channel: GamepadChannel::South,
+            pressed: true,
+        });
+
+        let gamepad = driver.gamepad(3).unwrap().clone();
+        assert_eq!(gamepad.name(), "recorded");
+        assert!(button(gamepad.channel_state(GamepadChannel::South)));
+        assert_eq!(
+            axis_1d(gamepad.channel_state(GamepadChannel::RightTrigger)),
+            1f32
+        );
+        assert_eq!(driver.connect_virtual("live").id(), 4);
+
+        driver.apply(GamepadInput::Disconnected { id: 3 });
+        assert!(driver.gamepad(3).is_none());
+        assert_eq!(driver.take_events().len(), 3);
+    }
+}
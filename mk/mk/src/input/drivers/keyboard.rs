@@ -1,10 +1,15 @@
-use crate::input::Driver;
 use codegen::{Event, LuaEnum};
-use winit::event::KeyboardInput as Test;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use winit::event::{ElementState, VirtualKeyCode};
 
 #[derive(Default)]
 pub struct Keyboard {
     inputs: Vec<KeyboardInput>,
+    keys_down: HashSet<KeyCode>,
+    keys_pressed: HashSet<KeyCode>,
+    keys_released: HashSet<KeyCode>,
+    keys_suppressed: HashSet<KeyCode>,
 }
 
 impl Keyboard {
@@ -15,6 +20,56 @@ impl Keyboard {
     pub fn push_input(&mut self, input: KeyboardInput) {
         self.inputs.push(input);
     }
+
+    /// Returns `true` if the key is held, or has been pressed during the last frame.
+    /// Keys tapped between two frames are therefore still observed for a single frame.
+    pub fn is_key_down(&self, key: KeyCode) -> bool {
+        !self.keys_suppressed.contains(&key)
+            && (self.keys_down.contains(&key) || self.keys_pressed.contains(&key))
+    }
+
+    pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+        !self.keys_suppressed.contains(&key) && self.keys_pressed.contains(&key)
+    }
+
+    pub fn is_key_released(&self, key: KeyCode) -> bool {
+        self.keys_released.contains(&key)
+    }
+
+    pub fn pressed_keys(&self) -> impl Iterator<Item = KeyCode> + '_ {
+        self.keys_pressed.iter().copied()
+    }
+
+    /// Hides the key from queries until it is released, so that a press consumed elsewhere
+    /// (e.g. by rebinding) does not also trigger actions.
+    pub fn suppress_until_released(&mut self, key: KeyCode) {
+        self.keys_suppressed.insert(key);
+    }
+
+    /// Applies every input pushed since the last call. Should be called once per frame.
+    pub fn update(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+
+        for input in self.inputs.drain(..) {
+            let key = match input.key_code {
+                Some(key) => key,
+                None => continue,
+            };
+
+            if input.is_pressed {
+                if self.keys_down.insert(key) {
+                    self.keys_pressed.insert(key);
+                }
+            } else if self.keys_down.remove(&key) && !self.keys_suppressed.remove(&key) {
+                self.keys_released.insert(key);
+            }
+        }
+
+        // A suppressed key tapped within a single frame has no release to wait for.
+        let keys_down = &self.keys_down;
+        self.keys_suppressed.retain(|key| keys_down.contains(key));
+    }
 }
 
 #[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -24,7 +79,21 @@ pub struct KeyboardInput {
     pub is_pressed: bool,
 }
 
-#[derive(LuaEnum, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+impl KeyboardInput {
+    pub fn from_winit(input: &winit::event::KeyboardInput) -> Self {
+        Self {
+            scan_code: input.scancode,
+            key_code: input
+                .virtual_keycode
+                .and_then(KeyCode::from_virtual_keycode),
+            is_pressed: input.state == ElementState::Pressed,
+        }
+    }
+}
+
+#[derive(
+    LuaEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
 pub enum KeyCode {
     Escape,
     Space,
@@ -143,6 +212,129 @@ pub enum KeyCode {
     Slash,
 }
 
+impl KeyCode {
+    pub fn from_virtual_keycode(keycode: VirtualKeyCode) -> Option<Self> {
+        Some(match keycode {
+            VirtualKeyCode::Escape => Self::Escape,
+            VirtualKeyCode::Space => Self::Space,
+            VirtualKeyCode::LShift => Self::LeftShift,
+            VirtualKeyCode::LControl => Self::LeftControl,
+            VirtualKeyCode::LAlt => Self::LeftAlt,
+            VirtualKeyCode::LWin => Self::LeftSuper,
+            VirtualKeyCode::RShift => Self::RightShift,
+            VirtualKeyCode::RControl => Self::RightControl,
+            VirtualKeyCode::RAlt => Self::RightAlt,
+            VirtualKeyCode::RWin => Self::RightSuper,
+            VirtualKeyCode::Key0 => Self::Num0,
+            VirtualKeyCode::Key1 => Self::Num1,
+            VirtualKeyCode::Key2 => Self::Num2,
+            VirtualKeyCode::Key3 => Self::Num3,
+            VirtualKeyCode::Key4 => Self::Num4,
+            VirtualKeyCode::Key5 => Self::Num5,
+            VirtualKeyCode::Key6 => Self::Num6,
+            VirtualKeyCode::Key7 => Self::Num7,
+            VirtualKeyCode::Key8 => Self::Num8,
+            VirtualKeyCode::Key9 => Self::Num9,
+            VirtualKeyCode::F1 => Self::F1,
+            VirtualKeyCode::F2 => Self::F2,
+            VirtualKeyCode::F3 => Self::F3,
+            VirtualKeyCode::F4 => Self::F4,
+            VirtualKeyCode::F5 => Self::F5,
+            VirtualKeyCode::F6 => Self::F6,
+            VirtualKeyCode::F7 => Self::F7,
+            VirtualKeyCode::F8 => Self::F8,
+            VirtualKeyCode::F9 => Self::F9,
+            VirtualKeyCode::F10 => Self::F10,
+            VirtualKeyCode::F11 => Self::F11,
+            VirtualKeyCode::F12 => Self::F12,
+            VirtualKeyCode::F13 => Self::F13,
+            VirtualKeyCode::F14 => Self::F14,
+            VirtualKeyCode::F15 => Self::F15,
+            VirtualKeyCode::F16 => Self::F16,
+            VirtualKeyCode::F17 => Self::F17,
+            VirtualKeyCode::F18 => Self::F18,
+            VirtualKeyCode::F19 => Self::F19,
+            VirtualKeyCode::F20 => Self::F20,
+            VirtualKeyCode::F21 => Self::F21,
+            VirtualKeyCode::F22 => Self::F22,
+            VirtualKeyCode::F23 => Self::F23,
+            VirtualKeyCode::F24 => Self::F24,
+            VirtualKeyCode::A => Self::AlphaA,
+            VirtualKeyCode::B => Self::AlphaB,
+            VirtualKeyCode::C => Self::AlphaC,
+            VirtualKeyCode::D => Self::AlphaD,
+            VirtualKeyCode::E => Self::AlphaE,
+            VirtualKeyCode::F => Self::AlphaF,
+            VirtualKeyCode::G => Self::AlphaG,
+            VirtualKeyCode::H => Self::AlphaH,
+            VirtualKeyCode::I => Self::AlphaI,
+            VirtualKeyCode::J => Self::AlphaJ,
+            VirtualKeyCode::K => Self::AlphaK,
+            VirtualKeyCode::L => Self::AlphaL,
+            VirtualKeyCode::M => Self::AlphaM,
+            VirtualKeyCode::N => Self::AlphaN,
+            VirtualKeyCode::O => Self::AlphaO,
+            VirtualKeyCode::P => Self::AlphaP,
+            VirtualKeyCode::Q => Self::AlphaQ,
+            VirtualKeyCode::R => Self::AlphaR,
+            VirtualKeyCode::S => Self::AlphaS,
+            VirtualKeyCode::T => Self::AlphaT,
+            VirtualKeyCode::U => Self::AlphaU,
+            VirtualKeyCode::V => Self::AlphaV,
+            VirtualKeyCode::W => Self::AlphaW,
+            VirtualKeyCode::X => Self::AlphaX,
+            VirtualKeyCode::Y => Self::AlphaY,
+            VirtualKeyCode::Z => Self::AlphaZ,
+            VirtualKeyCode::Snapshot => Self::PrintScreen,
+            VirtualKeyCode::Scroll => Self::ScrollLock,
+            VirtualKeyCode::Pause => Self::Pause,
+            VirtualKeyCode::Insert => Self::Insert,
+            VirtualKeyCode::Home => Self::Home,
+            VirtualKeyCode::PageUp => Self::PageUp,
+            VirtualKeyCode::Delete => Self::Delete,
+            VirtualKeyCode::End => Self::End,
+            VirtualKeyCode::PageDown => Self::PageDown,
+            VirtualKeyCode::Right => Self::Right,
+            VirtualKeyCode::Left => Self::Left,
+            VirtualKeyCode::Down => Self::Down,
+            VirtualKeyCode::Up => Self::Up,
+            VirtualKeyCode::Numlock => Self::NumLock,
+            VirtualKeyCode::NumpadDivide => Self::NumpadDivide,
+            VirtualKeyCode::NumpadMultiply => Self::NumpadMultiply,
+            VirtualKeyCode::NumpadSubtract => Self::NumpadSubtract,
+            VirtualKeyCode::NumpadAdd => Self::NumpadAdd,
+            VirtualKeyCode::NumpadEnter => Self::NumpadEnter,
+            VirtualKeyCode::NumpadDecimal => Self::NumpadDecimal,
+            VirtualKeyCode::Numpad0 => Self::Numpad0,
+            VirtualKeyCode::Numpad1 => Self::Numpad1,
+            VirtualKeyCode::Numpad2 => Self::Numpad2,
+            VirtualKeyCode::Numpad3 => Self::Numpad3,
+            VirtualKeyCode::Numpad4 => Self::Numpad4,
+            VirtualKeyCode::Numpad5 => Self::Numpad5,
+            VirtualKeyCode::Numpad6 => Self::Numpad6,
+            VirtualKeyCode::Numpad7 => Self::Numpad7,
+            VirtualKeyCode::Numpad8 => Self::Numpad8,
+            VirtualKeyCode::Numpad9 => Self::Numpad9,
+            VirtualKeyCode::Grave => Self::Grave,
+            VirtualKeyCode::Minus => Self::Minus,
+            VirtualKeyCode::Equals => Self::Equals,
+            VirtualKeyCode::Back => Self::Backspace,
+            VirtualKeyCode::Tab => Self::Tab,
+            VirtualKeyCode::LBracket => Self::LeftBracket,
+            VirtualKeyCode::RBracket => Self::RightBracket,
+            VirtualKeyCode::Backslash => Self::Backslash,
+            VirtualKeyCode::Capital => Self::CapsLock,
+            VirtualKeyCode::Semicolon => Self::Semicolon,
+            VirtualKeyCode::Apostrophe => Self::Apostrophe,
+            VirtualKeyCode::Return => Self::Enter,
+            VirtualKeyCode::Comma => Self::Comma,
+            VirtualKeyCode::Period => Self::Period,
+            VirtualKeyCode::Slash => Self::Slash,
+            _ => return None,
+        })
+    }
+}
+
 // impl Driver for Keyboard {
 //     type Input = ();
 
@@ -1,5 +1,9 @@
+mod gamepad;
 mod keyboard;
+mod mouse;
 mod platform;
 
+pub use gamepad::*;
 pub use keyboard::*;
+pub use mouse::*;
 pub use platform::*;
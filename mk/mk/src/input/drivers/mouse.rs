@@ -0,0 +1,554 @@
+use crate::{
+    input::{
+        DetachedDevice, Device, Driver, InputChannel, InputChannelValue, OutputChannel,
+        OutputChannelValue,
+    },
+    structure::Vec2,
+};
+use codegen::LuaEnum;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Weak},
+};
+use winit::event::{ElementState, MouseScrollDelta};
+
+/// Wheel deltas reported in pixels (e.g. by touchpads) are converted to lines with this ratio.
+pub const MOUSE_WHEEL_PIXELS_PER_LINE: f32 = 20f32;
+
+#[derive(
+    LuaEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+impl MouseButton {
+    pub fn from_winit(button: winit::event::MouseButton) -> Option<Self> {
+        match button {
+            winit::event::MouseButton::Left => Some(MouseButton::Left),
+            winit::event::MouseButton::Right => Some(MouseButton::Right),
+            winit::event::MouseButton::Middle => Some(MouseButton::Middle),
+            winit::event::MouseButton::Other(_) => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MouseButton::Left => "left",
+            MouseButton::Right => "right",
+            MouseButton::Middle => "middle",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MouseInput {
+    /// The cursor moved to the given position, in logical pixels from the top-left of the window.
+    Moved(Vec2),
+    /// The cursor left the window.
+    Left,
+    /// Raw motion of the device, reported even while the cursor is locked.
+    Motion(Vec2),
+    /// Wheel scroll, in lines.
+    Wheel(Vec2),
+    Button {
+        button: MouseButton,
+        is_pressed: bool,
+    },
+}
+
+impl MouseInput {
+    pub fn from_winit_button(
+        button: winit::event::MouseButton,
+        state: ElementState,
+    ) -> Option<Self> {
+        MouseButton::from_winit(button).map(|button| MouseInput::Button {
+            button,
+            is_pressed: state == ElementState::Pressed,
+        })
+    }
+
+    /// Scrolling up or right is positive.
+    pub fn from_winit_wheel(delta: MouseScrollDelta, scale_factor: f64) -> Self {
+        MouseInput::Wheel(match delta {
+            MouseScrollDelta::LineDelta(x, y) => Vec2::new(x, y),
+            MouseScrollDelta::PixelDelta(position) => {
+                let position = position.to_logical::<f32>(scale_factor);
+                Vec2::new(position.x, position.y) * (1f32 / MOUSE_WHEEL_PIXELS_PER_LINE)
+            }
+        })
+    }
+}
+
+/// Requested cursor state, applied to the window by the engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CursorState {
+    pub visible: bool,
+    /// Hides the cursor movement from the OS, so that only `delta` changes.
+    pub locked: bool,
+}
+
+impl Default for CursorState {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            locked: false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct MouseState {
+    inputs: Vec<MouseInput>,
+    focused: bool,
+    position: Option<Vec2>,
+    delta: (f32, f32),
+    wheel: (f32, f32),
+    buttons_down: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    buttons_suppressed: HashSet<MouseButton>,
+    cursor: CursorState,
+    cursor_dirty: bool,
+}
+
+/// The system mouse. Inputs are pushed as they arrive and applied once per frame by `update`.
+pub struct Mouse {
+    state: Mutex<MouseState>,
+    input_channels: Vec<Arc<dyn InputChannel>>,
+    output_channels: Vec<Arc<dyn OutputChannel>>,
+}
+
+impl Mouse {
+    pub fn new() -> Arc<Self> {
+        Arc::new_cyclic(|mouse: &Weak<Mouse>| {
+            let input = |kind| {
+                Arc::new(MouseInputChannel {
+                    mouse: mouse.clone(),
+                    kind,
+                }) as Arc<dyn InputChannel>
+            };
+            let output = |kind| {
+                Arc::new(MouseOutputChannel {
+                    mouse: mouse.clone(),
+                    kind,
+                }) as Arc<dyn OutputChannel>
+            };
+
+            Self {
+                state: Mutex::new(MouseState {
+                    focused: true,
+                    ..Default::default()
+                }),
+                input_channels: vec![
+                    input(MouseInputKind::Position),
+                    input(MouseInputKind::Delta),
+                    input(MouseInputKind::Wheel),
+                    input(MouseInputKind::Button(MouseButton::Left)),
+                    input(MouseInputKind::Button(MouseButton::Right)),
+                    input(MouseInputKind::Button(MouseButton::Middle)),
+                ],
+                output_channels: vec![
+                    output(MouseOutputKind::CursorVisible),
+                    output(MouseOutputKind::CursorLocked),
+                ],
+            }
+        })
+    }
+
+    pub fn push_input(&self, input: MouseInput) {
+        self.state.lock().inputs.push(input);
+    }
+
+    /// Raw motion is ignored while the window is not focused.
+    pub fn set_focused(&self, focused: bool) {
+        self.state.lock().focused = focused;
+    }
+
+    /// Applies every input pushed since the last call. Should be called once per frame.
+    pub fn update(&self) {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+
+        state.delta = (0f32, 0f32);
+        state.wheel = (0f32, 0f32);
+        state.buttons_pressed.clear();
+        state.buttons_released.clear();
+
+        for input in state.inputs.drain(..) {
+            match input {
+                MouseInput::Moved(position) => state.position = Some(position),
+                MouseInput::Left => state.position = None,
+                MouseInput::Motion(delta) => {
+                    if state.focused {
+                        state.delta.0 += delta.x;
+                        state.delta.1 += delta.y;
+                    }
+                }
+                MouseInput::Wheel(delta) => {
+                    state.wheel.0 += delta.x;
+                    state.wheel.1 += delta.y;
+                }
+                MouseInput::Button { button, is_pressed } => {
+                    if is_pressed {
+                        if state.buttons_down.insert(button) {
+                            state.buttons_pressed.insert(button);
+                        }
+                    } else if state.buttons_down.remove(&button)
+                        && !state.buttons_suppressed.remove(&button)
+                    {
+                        state.buttons_released.insert(button);
+                    }
+                }
+            }
+        }
+
+        // A suppressed button clicked within a single frame has no release to wait for.
+        let buttons_down = &state.buttons_down;
+        state
+            .buttons_suppressed
+            .retain(|button| buttons_down.contains(button));
+    }
+
+    /// Returns the cursor position in logical pixels, or `None` if it is outside the window.
+    pub fn position(&self) -> Option<Vec2> {
+        self.state.lock().position
+    }
+
+    /// Returns the raw motion during the last frame. Positive `y` points down.
+    pub fn delta(&self) -> Vec2 {
+        let (x, y) = self.state.lock().delta;
+        Vec2::new(x, y)
+    }
+
+    /// Returns the wheel scroll during the last frame, in lines.
+    pub fn wheel(&self) -> Vec2 {
+        let (x, y) = self.state.lock().wheel;
+        Vec2::new(x, y)
+    }
+
+    /// Returns `true` if the button is held, or has been pressed during the last frame.
+    pub fn is_button_down(&self, button: MouseButton) -> bool {
+        let state = self.state.lock();
+        !state.buttons_suppressed.contains(&button)
+            && (state.buttons_down.contains(&button) || state.buttons_pressed.contains(&button))
+    }
+
+    pub fn is_button_pressed(&self, button: MouseButton) -> bool {
+        let state = self.state.lock();
+        !state.buttons_suppressed.contains(&button) && state.buttons_pressed.contains(&button)
+    }
+
+    pub fn is_button_released(&self, button: MouseButton) -> bool {
+        self.state.lock().buttons_released.contains(&button)
+    }
+
+    /// Hides the button from queries until it is released, so that a click consumed elsewhere
+    /// (e.g. by rebinding) does not also trigger actions.
+    pub fn suppress_until_released(&self, button: MouseButton) {
+        self.state.lock().buttons_suppressed.insert(button);
+    }
+
+    pub fn cursor(&self) -> CursorState {
+        self.state.lock().cursor
+    }
+
+    pub fn set_cursor(&self, cursor: CursorState) {
+        let mut state = self.state.lock();
+
+        if state.cursor != cursor {
+            state.cursor = cursor;
+            state.cursor_dirty = true;
+        }
+    }
+
+    /// Returns the cursor state if it has changed since the last call.
+    pub fn take_cursor_change(&self) -> Option<CursorState> {
+        let mut state = self.state.lock();
+
+        if state.cursor_dirty {
+            state.cursor_dirty = false;
+            Some(state.cursor)
+        } else {
+            None
+        }
+    }
+}
+
+impl Device for Mouse {
+    fn name(&self) -> &str {
+        "mouse"
+    }
+
+    fn input_channels(&self) -> &[Arc<dyn InputChannel>] {
+        &self.input_channels
+    }
+
+    fn output_channels(&self) -> &[Arc<dyn OutputChannel>] {
+        &self.output_channels
+    }
+}
+
+fn device(mouse: &Weak<Mouse>) -> Arc<dyn Device> {
+    match mouse.upgrade() {
+        Some(mouse) => mouse,
+        None => Arc::new(DetachedDevice),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MouseInputKind {
+    Position,
+    Delta,
+    Wheel,
+    Button(MouseButton),
+}
+
+pub struct MouseInputChannel {
+    mouse: Weak<Mouse>,
+    kind: MouseInputKind,
+}
+
+impl InputChannel for MouseInputChannel {
+    fn name(&self) -> &str {
+        match self.kind {
+            MouseInputKind::Position => "position",
+            MouseInputKind::Delta => "delta",
+            MouseInputKind::Wheel => "wheel",
+            MouseInputKind::Button(button) => button.name(),
+        }
+    }
+
+    fn device(&self) -> Arc<dyn Device> {
+        device(&self.mouse)
+    }
+
+    /// Released, or zero, once the mouse is dropped.
+    fn state(&self) -> InputChannelValue {
+        let axis = |value: Vec2| InputChannelValue::Axis2D(value.x, value.y);
+        let mouse = match self.mouse.upgrade() {
+            Some(mouse) => mouse,
+            None => {
+                return match self.kind {
+                    MouseInputKind::Button(_) => InputChannelValue::Button(false),
+                    _ => axis(Vec2::zero()),
+                };
+            }
+        };
+
+        match self.kind {
+            MouseInputKind::Position => axis(mouse.position().unwrap_or_else(Vec2::zero)),
+            MouseInputKind::Delta => axis(mouse.delta()),
+            MouseInputKind::Wheel => axis(mouse.wheel()),
+            MouseInputKind::Button(button) => {
+                InputChannelValue::Button(mouse.is_button_down(button))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MouseOutputKind {
+    CursorVisible,
+    CursorLocked,
+}
+
+pub struct MouseOutputChannel {
+    mouse: Weak<Mouse>,
+    kind: MouseOutputKind,
+}
+
+impl OutputChannel for MouseOutputChannel {
+    fn name(&self) -> &str {
+        match self.kind {
+            MouseOutputKind::CursorVisible => "cursor_visible",
+            MouseOutputKind::CursorLocked => "cursor_locked",
+        }
+    }
+
+    fn device(&self) -> Arc<dyn Device> {
+        device(&self.mouse)
+    }
+
+    fn state(&self) -> OutputChannelValue {
+        let cursor = self
+            .mouse
+            .upgrade()
+            .map_or_else(CursorState::default, |mouse| mouse.cursor());
+
+        OutputChannelValue::Bool(match self.kind {
+            MouseOutputKind::CursorVisible => cursor.visible,
+            MouseOutputKind::CursorLocked => cursor.locked,
+        })
+    }
+
+    fn set_state(&self, state: OutputChannelValue) {
+        let on = match state {
+            OutputChannelValue::Bool(on) => on,
+            OutputChannelValue::Int(value) => value != 0,
+            OutputChannelValue::Float(value) => value != 0f64,
+        };
+        let mouse = match self.mouse.upgrade() {
+            Some(mouse) => mouse,
+            None => return,
+        };
+        let mut cursor = mouse.cursor();
+
+        match self.kind {
+            MouseOutputKind::CursorVisible => cursor.visible = on,
+            MouseOutputKind::CursorLocked => cursor.locked = on,
+        }
+
+        mouse.set_cursor(cursor);
+    }
+}
+
+pub struct MouseDriver {
+    mouse: Arc<Mouse>,
+    devices: Vec<Arc<dyn Device>>,
+}
+
+impl MouseDriver {
+    pub fn new() -> Self {
+        let mouse = Mouse::new();
+
+        Self {
+            devices: vec![mouse.clone()],
+            mouse,
+        }
+    }
+
+    pub fn mouse(&self) -> &Arc<Mouse> {
+        &self.mouse
+    }
+}
+
+impl Default for MouseDriver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Driver for MouseDriver {
+    fn name(&self) -> &str {
+        "mouse"
+    }
+
+    fn devices(&self) -> &[Arc<dyn Device>] {
+        &self.devices
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn press(mouse: &Mouse, button: MouseButton, is_pressed: bool) {
+        mouse.push_input(MouseInput::Button { button, is_pressed });
+    }
+
+    #[test]
+    fn button_edges_last_one_frame() {
+        let mouse = Mouse::new();
+
+        press(&mouse, MouseButton::Left, true);
+        mouse.update();
+        assert!(mouse.is_button_pressed(MouseButton::Left));
+        assert!(mouse.is_button_down(MouseButton::Left));
+
+        mouse.update();
+        assert!(!mouse.is_button_pressed(MouseButton::Left));
+        assert!(mouse.is_button_down(MouseButton::Left));
+
+        press(&mouse, MouseButton::Left, false);
+        mouse.update();
+        assert!(mouse.is_button_released(MouseButton::Left));
+        assert!(!mouse.is_button_down(MouseButton::Left));
+
+        // A click within a single frame is still seen as down during that frame.
+        press(&mouse, MouseButton::Right, true);
+        press(&mouse, MouseButton::Right, false);
+        mouse.update();
+        assert!(mouse.is_button_pressed(MouseButton::Right));
+        assert!(mouse.is_button_released(MouseButton::Right));
+        assert!(mouse.is_button_down(MouseButton::Right));
+    }
+
+    #[test]
+    fn motion_and_wheel_accumulate_per_frame() {
+        let mouse = Mouse::new();
+
+        mouse.push_input(MouseInput::Motion(Vec2::new(1f32, 2f32)));
+        mouse.push_input(MouseInput::Motion(Vec2::new(3f32, 4f32)));
+        mouse.push_input(MouseInput::Wheel(Vec2::new(0f32, 1f32)));
+        mouse.push_input(MouseInput::Wheel(Vec2::new(0f32, 2f32)));
+        mouse.update();
+        assert_eq!(mouse.delta(), Vec2::new(4f32, 6f32));
+        assert_eq!(mouse.wheel(), Vec2::new(0f32, 3f32));
+
+        mouse.update();
+        assert_eq!(mouse.delta(), Vec2::zero());
+        assert_eq!(mouse.wheel(), Vec2::zero());
+
+        mouse.set_focused(false);
+        mouse.push_input(MouseInput::Motion(Vec2::new(1f32, 1f32)));
+        mouse.update();
+        assert_eq!(mouse.delta(), Vec2::zero());
+    }
+
+    #[test]
+    fn position_is_cleared_when_the_cursor_leaves() {
+        let mouse = Mouse::new();
+
+        mouse.push_input(MouseInput::Moved(Vec2::new(10f32, 20f32)));
+        mouse.update();
+        assert_eq!(mouse.position(), Some(Vec2::new(10f32, 20f32)));
+
+        mouse.push_input(MouseInput::Left);
+        mouse.update();
+        assert_eq!(mouse.position(), None);
+    }
+
+    #[test]
+    fn cursor_changes_are_taken_once() {
+        let mouse = Mouse::new();
+
+        mouse.set_cursor(CursorState::default());
+        assert_eq!(mouse.take_cursor_change(), None);
+
+        mouse.output_channels()[1].set_state(OutputChannelValue::Bool(true));
+        assert_eq!(
+            mouse.take_cursor_change(),
+            Some(CursorState {
+                visible: true,
+                locked: true,
+            })
+        );
+        assert_eq!(mouse.take_cursor_change(), None);
+    }
+
+    #[test]
+    fn channels_outliving_their_mouse_are_neutral() {
+        let mouse = Mouse::new();
+        let position = mouse.input_channels()[0].clone();
+        let left = mouse.input_channels()[3].clone();
+        let visible = mouse.output_channels()[0].clone();
+
+        mouse.push_input(MouseInput::Moved(Vec2::new(10f32, 20f32)));
+        press(&mouse, MouseButton::Left, true);
+        mouse.update();
+        drop(mouse);
+
+        assert!(
+            matches!(position.state(), InputChannelValue::Axis2D(x, y) if x == 0f32 && y == 0f32)
+        );
+        assert!(matches!(left.state(), InputChannelValue::Button(false)));
+        assert!(matches!(visible.state(), OutputChannelValue::Bool(true)));
+        assert_eq!(left.device().name(), "detached");
+        visible.set_state(OutputChannelValue::Bool(false));
+    }
+}
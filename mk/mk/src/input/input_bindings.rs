@@ -0,0 +1,123 @@
+use super::Trigger;
+use crate::asset::AssetIdentity;
+use crate::util::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::read_to_string,
+    io::{Error as IOError, ErrorKind as IOErrorKind, Result as IOResult},
+    path::Path,
+};
+
+/// Serializable set of triggers per action, e.g.
+///
+/// ```json
+/// {
+///   "confirm": [{ "type": "key", "key": "Enter" }],
+///   "move": [{ "type": "axis_2d", "left": "Left", "right": "Right", "down": "Down", "up": "Up" }]
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct InputBindings {
+    pub actions: BTreeMap<String, Vec<Trigger>>,
+}
+
+impl InputBindings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> IOResult<Self> {
+        Self::from_json(&read_to_string(path)?)
+            .map_err(|err| IOError::new(IOErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> IOResult<()> {
+        let json = self
+            .to_json()
+            .map_err(|err| IOError::new(IOErrorKind::InvalidData, err))?;
+        write_atomic(path, json)
+    }
+}
+
+// Bindings are cloned out of the loaded ones, so there is nothing shared to identify them by.
+impl AssetIdentity for InputBindings {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::input::{KeyCode, MouseButton};
+
+    fn bindings() -> InputBindings {
+        let mut bindings = InputBindings::new();
+        bindings.actions.insert(
+            "confirm".to_owned(),
+            vec![
+                Trigger::Key {
+                    key: KeyCode::Enter,
+                },
+                Trigger::Mouse {
+                    button: MouseButton::Left,
+                },
+            ],
+        );
+        bindings.actions.insert(
+            "move".to_owned(),
+            vec![Trigger::Axis2D {
+                left: KeyCode::Left,
+                right: KeyCode::Right,
+                down: KeyCode::Down,
+                up: KeyCode::Up,
+            }],
+        );
+        bindings
+    }
+
+    #[test]
+    fn parses_the_documented_format() {
+        let parsed = InputBindings::from_json(
+            r#"{
+                "confirm": [{ "type": "key", "key": "Enter" }, { "type": "mouse", "button": "Left" }],
+                "move": [{ "type": "axis_2d", "left": "Left", "right": "Right", "down": "Down", "up": "Up" }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(parsed, bindings());
+    }
+
+    #[test]
+    fn save_then_load_gives_the_same_bindings() {
+        let path =
+            std::env::temp_dir().join(format!("mk-input-bindings-{}.json", std::process::id()));
+
+        bindings().save(&path).unwrap();
+        let loaded = InputBindings::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), bindings());
+    }
+
+    #[test]
+    fn loading_invalid_bindings_fails_with_invalid_data() {
+        let path = std::env::temp_dir().join(format!(
+            "mk-input-bindings-invalid-{}.json",
+            std::process::id()
+        ));
+
+        std::fs::write(&path, r#"{ "confirm": [{ "type": "key" }] }"#).unwrap();
+        let loaded = InputBindings::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap_err().kind(), IOErrorKind::InvalidData);
+    }
+}
@@ -1,19 +1,45 @@
-use crate::input::InputType;
+use crate::emit_diagnostic_warn;
+use crate::input::{
+    GamepadChannel, GamepadDriver, GamepadEvent, GamepadInput, InputMap, InputRecorder,
+    InputReplay, InputSources, InputType, Keyboard, KeyboardInput, Mouse, MouseButton, MouseDriver,
+    MouseInput, RebindOutcome, RebindResult, Rebinding, RecordedFrame, RecordedInput, TriggerInput,
+    REBIND_AXIS_THRESHOLD, REBIND_CANCEL_KEY,
+};
 use parking_lot::Mutex;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
-use winit::event::KeyboardInput;
 
 pub type Input = Arc<Mutex<dyn InputType>>;
 
 pub struct InputManager {
     inputs: HashMap<String, Input>,
+    keyboard: Keyboard,
+    keyboard_captured: bool,
+    mouse: MouseDriver,
+    gamepads: GamepadDriver,
+    /// Gamepad channels pushed past `REBIND_AXIS_THRESHOLD` in the last update.
+    gamepad_channels_active: BTreeSet<GamepadChannel>,
+    input_map: InputMap,
+    rebinding: Option<Rebinding>,
+    rebind_results: Vec<RebindResult>,
+    recorder: Option<InputRecorder>,
+    replay: Option<InputReplay>,
 }
 
 impl InputManager {
     pub fn new() -> InputManager {
         InputManager {
             inputs: HashMap::new(),
+            keyboard: Keyboard::new(),
+            keyboard_captured: false,
+            mouse: MouseDriver::new(),
+            gamepads: GamepadDriver::new(),
+            gamepad_channels_active: BTreeSet::new(),
+            input_map: InputMap::new(),
+            rebinding: None,
+            rebind_results: Vec::new(),
+            recorder: None,
+            replay: None,
         }
     }
 
@@ -31,9 +57,352 @@ impl InputManager {
         self.inputs.remove(name.as_ref());
     }
 
-    pub fn handle_event(&mut self, input: &KeyboardInput) {
+    pub fn keyboard(&self) -> &Keyboard {
+        &self.keyboard
+    }
+
+    /// While captured, keys do not trigger actions. Keyboard queries are not affected.
+    pub fn set_keyboard_captured(&mut self, captured: bool) {
+        self.keyboard_captured = captured;
+    }
+
+    pub fn mouse(&self) -> &Mouse {
+        self.mouse.mouse()
+    }
+
+    pub fn mouse_driver(&self) -> &MouseDriver {
+        &self.mouse
+    }
+
+    pub fn gamepads(&self) -> &GamepadDriver {
+        &self.gamepads
+    }
+
+    pub fn gamepads_mut(&mut self) -> &mut GamepadDriver {
+        &mut self.gamepads
+    }
+
+    /// Takes the gamepad connections and disconnections since the last call.
+    pub fn take_gamepad_events(&mut self) -> Vec<GamepadEvent> {
+        self.gamepads.take_events()
+    }
+
+    /// Takes the gamepad backend warnings since the last call.
+    pub fn take_gamepad_warnings(&mut self) -> Vec<String> {
+        self.gamepads.take_warnings()
+    }
+
+    pub fn input_map(&self) -> &InputMap {
+        &self.input_map
+    }
+
+    pub fn input_map_mut(&mut self) -> &mut InputMap {
+        &mut self.input_map
+    }
+
+    pub fn handle_event(&mut self, input: &winit::event::KeyboardInput) {
         for input_type in self.inputs.values() {
             input_type.lock().handle_event(input);
         }
+
+        self.keyboard.push_input(KeyboardInput::from_winit(input));
+    }
+
+    pub fn handle_mouse_input(&mut self, input: MouseInput) {
+        self.mouse.mouse().push_input(input);
+    }
+
+    pub fn rebinding(&self) -> Option<&Rebinding> {
+        self.rebinding.as_ref()
+    }
+
+    /// Starts listening for the next pressed key, mouse button or gamepad channel to bind it as requested.
+    /// Any rebinding in progress is cancelled. Returns `false` if there is no such action.
+    pub fn start_rebinding(&mut self, rebinding: Rebinding) -> bool {
+        if self.input_map.action(&rebinding.action).is_none() {
+            return false;
+        }
+
+        self.cancel_rebinding();
+        self.rebinding = Some(rebinding);
+        true
+    }
+
+    pub fn cancel_rebinding(&mut self) {
+        if let Some(rebinding) = self.rebinding.take() {
+            self.rebind_results.push(RebindResult {
+                rebinding,
+                outcome: RebindOutcome::Cancelled,
+            });
+        }
+    }
+
+    /// Takes the rebindings finished or cancelled since the last call.
+    pub fn take_rebind_results(&mut self) -> Vec<RebindResult> {
+        std::mem::take(&mut self.rebind_results)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Starts recording every input passed to `record`, replacing any recording in progress.
+    pub fn start_recording(&mut self, recorder: InputRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    pub fn stop_recording(&mut self) {
+        self.recorder = None;
+    }
+
+    pub fn record(&mut self, input: RecordedInput) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(input);
+        }
+    }
+
+    /// Ends the recorded frame. The recording is stopped if it can no longer be written.
+    pub fn end_recorded_frame(&mut self, dt: f64) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(err) = recorder.end_frame(dt) {
+                emit_diagnostic_warn!(format!("input recording stopped: {:#}", err));
+                self.recorder = None;
+            }
+        }
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Starts replaying the recording. Live inputs should be ignored until it ends.
+    pub fn start_replay(&mut self, replay: InputReplay) {
+        self.replay = Some(replay);
+    }
+
+    pub fn stop_replay(&mut self) {
+        self.replay = None;
+    }
+
+    /// Takes the next frame of the replay. The replay ends once every frame has been taken.
+    pub fn next_replay_frame(&mut self) -> Option<RecordedFrame> {
+        let replay = self.replay.as_mut()?;
+        let frame = replay.next_frame();
+
+        if replay.remaining_frames() == 0 {
+            self.replay = None;
+        }
+
+        frame
+    }
+
+    /// Reads the gamepads and records their changes. The gamepads are left untouched while a
+    /// recording is replayed, as their changes are replayed through `handle_gamepad_input`.
+    pub fn poll_gamepads(&mut self) {
+        if self.is_replaying() {
+            return;
+        }
+
+        for input in self.gamepads.update() {
+            self.record(RecordedInput::Gamepad(input));
+        }
+    }
+
+    pub fn handle_gamepad_input(&mut self, input: GamepadInput) {
+        self.gamepads.apply(input);
+    }
+
+    /// Applies the inputs received since the last frame and refreshes the state of every action.
+    pub fn update(&mut self) {
+        self.keyboard.update();
+        self.mouse.mouse().update();
+        self.gamepads.update_suppressed();
+
+        let gamepad_channels = self.gamepads.active_channels(REBIND_AXIS_THRESHOLD);
+        let pressed_channel = gamepad_channels
+            .difference(&self.gamepad_channels_active)
+            .next()
+            .copied();
+        self.gamepad_channels_active = gamepad_channels;
+
+        if self.rebinding.is_some() {
+            if let Some(input) = self.pressed_input(pressed_channel) {
+                self.finish_rebinding(input);
+            }
+        }
+
+        self.input_map.update(&InputSources {
+            keyboard: (!self.keyboard_captured).then_some(&self.keyboard),
+            mouse: self.mouse.mouse(),
+            gamepads: &self.gamepads,
+        });
+    }
+
+    /// Returns the input pressed during the last frame, preferring keys, then mouse buttons.
+    fn pressed_input(&self, pressed_channel: Option<GamepadChannel>) -> Option<TriggerInput> {
+        let mouse = self.mouse.mouse();
+
+        self.keyboard
+            .pressed_keys()
+            .min()
+            .map(TriggerInput::Key)
+            .or_else(|| {
+                [MouseButton::Left, MouseButton::Right, MouseButton::Middle]
+                    .into_iter()
+                    .find(|&button| mouse.is_button_pressed(button))
+                    .map(TriggerInput::Mouse)
+            })
+            .or(pressed_channel.map(TriggerInput::Gamepad))
+    }
+
+    fn finish_rebinding(&mut self, input: TriggerInput) {
+        let rebinding = self.rebinding.take().unwrap();
+
+        // The input has been consumed by the rebinding; don't let it trigger actions.
+        match input {
+            TriggerInput::Key(key) => self.keyboard.suppress_until_released(key),
+            TriggerInput::Mouse(button) => self.mouse.mouse().suppress_until_released(button),
+            TriggerInput::Gamepad(channel) => self.gamepads.suppress_until_released(channel),
+        }
+
+        let outcome = if input == TriggerInput::Key(REBIND_CANCEL_KEY) {
+            RebindOutcome::Cancelled
+        } else if self.input_map.set_trigger_input(
+            &rebinding.action,
+            rebinding.trigger,
+            rebinding.part.as_deref(),
+            input,
+        ) {
+            RebindOutcome::Bound(input)
+        } else {
+            RebindOutcome::Rejected(input)
+        };
+
+        self.rebind_results
+            .push(RebindResult { rebinding, outcome });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::input::{KeyCode, Trigger};
+
+    fn input_mgr() -> InputManager {
+        let mut input_mgr = InputManager::new();
+        input_mgr.gamepads = GamepadDriver::without_backend();
+        input_mgr.input_map_mut().add_action(
+            "jump",
+            vec![Trigger::Key {
+                key: KeyCode::Space,
+            }],
+        );
+        input_mgr.input_map_mut().add_action(
+            "move",
+            vec![Trigger::Axis1D {
+                negative: KeyCode::Left,
+                positive: KeyCode::Right,
+            }],
+        );
+        input_mgr
+    }
+
+    fn rebind(input_mgr: &mut InputManager, action: &str, part: Option<&str>) {
+        assert!(input_mgr.start_rebinding(Rebinding {
+            action: action.to_owned(),
+            trigger: 0,
+            part: part.map(str::to_owned),
+        }));
+        input_mgr.update();
+        assert!(input_mgr.take_rebind_results().is_empty());
+    }
+
+    fn outcome(input_mgr: &mut InputManager) -> RebindOutcome {
+        input_mgr.update();
+        let results = input_mgr.take_rebind_results();
+        assert_eq!(results.len(), 1);
+        results[0].outcome
+    }
+
+    #[test]
+    fn gamepad_channels_are_captured_and_suppressed() {
+        let mut input_mgr = input_mgr();
+        let gamepad = input_mgr.gamepads_mut().connect_virtual("pad");
+
+        // A button held before the rebinding starts, e.g. to confirm it in a menu, is not captured.
+        gamepad.set_button(GamepadChannel::East, true);
+        input_mgr.update();
+        rebind(&mut input_mgr, "jump", None);
+
+        gamepad.set_button(GamepadChannel::South, true);
+        let input = TriggerInput::Gamepad(GamepadChannel::South);
+        assert_eq!(outcome(&mut input_mgr), RebindOutcome::Bound(input));
+        assert_eq!(
+            input_mgr.input_map().action("jump").unwrap().triggers(),
+            &[Trigger::from(input)]
+        );
+        // The press that has been bound does not also jump.
+        assert!(!input_mgr.input_map().action("jump").unwrap().state().held);
+
+        gamepad.set_button(GamepadChannel::South, false);
+        input_mgr.update();
+        gamepad.set_button(GamepadChannel::South, true);
+        input_mgr.update();
+        assert!(
+            input_mgr
+                .input_map()
+                .action("jump")
+                .unwrap()
+                .state()
+                .pressed
+        );
+    }
+
+    #[test]
+    fn mouse_buttons_are_captured() {
+        let mut input_mgr = input_mgr();
+        rebind(&mut input_mgr, "jump", Some("button"));
+
+        input_mgr.handle_mouse_input(MouseInput::Button {
+            button: MouseButton::Right,
+            is_pressed: true,
+        });
+        // A key trigger has no `button` slot.
+        let input = TriggerInput::Mouse(MouseButton::Right);
+        assert_eq!(outcome(&mut input_mgr), RebindOutcome::Rejected(input));
+
+        rebind(&mut input_mgr, "jump", None);
+        input_mgr.handle_mouse_input(MouseInput::Button {
+            button: MouseButton::Middle,
+            is_pressed: true,
+        });
+        let input = TriggerInput::Mouse(MouseButton::Middle);
+        assert_eq!(outcome(&mut input_mgr), RebindOutcome::Bound(input));
+    }
+
+    #[test]
+    fn rejections_are_told_apart_from_cancellations() {
+        let mut input_mgr = input_mgr();
+        let gamepad = input_mgr.gamepads_mut().connect_virtual("pad");
+
+        rebind(&mut input_mgr, "move", Some("negative"));
+        gamepad.set_button(GamepadChannel::DPadLeft, true);
+        let input = TriggerInput::Gamepad(GamepadChannel::DPadLeft);
+        assert_eq!(outcome(&mut input_mgr), RebindOutcome::Rejected(input));
+        assert_eq!(
+            input_mgr.input_map().action("move").unwrap().triggers(),
+            &[Trigger::Axis1D {
+                negative: KeyCode::Left,
+                positive: KeyCode::Right,
+            }]
+        );
+
+        rebind(&mut input_mgr, "move", Some("negative"));
+        input_mgr.keyboard.push_input(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(REBIND_CANCEL_KEY),
+            is_pressed: true,
+        });
+        assert_eq!(outcome(&mut input_mgr), RebindOutcome::Cancelled);
     }
 }
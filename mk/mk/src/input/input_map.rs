@@ -1 +1,291 @@
-pub struct InputMap {}
+use super::{Action, InputBindings, InputSources, Trigger, TriggerInput};
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Default)]
+pub struct InputMap {
+    actions: HashMap<String, Action>,
+    defaults: InputBindings,
+}
+
+/// An input bound to more than one action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputConflict {
+    pub input: TriggerInput,
+    pub actions: Vec<String>,
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn action(&self, name: &str) -> Option<&Action> {
+        self.actions.get(name)
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = &Action> {
+        self.actions.values()
+    }
+
+    /// Adds an action, replacing the triggers of an existing one with the same name.
+    pub fn add_action(&mut self, name: impl Into<String>, triggers: Vec<Trigger>) {
+        let name = name.into();
+
+        match self.actions.get_mut(&name) {
+            Some(action) => action.set_triggers(triggers),
+            None => {
+                self.actions
+                    .insert(name.clone(), Action::new(name, triggers));
+            }
+        }
+    }
+
+    pub fn remove_action(&mut self, name: &str) -> Option<Action> {
+        self.actions.remove(name)
+    }
+
+    /// Adds or rebinds every action listed in the bindings.
+    /// Actions not present in the bindings are left untouched.
+    pub fn apply_bindings(&mut self, bindings: &InputBindings) {
+        for (name, triggers) in &bindings.actions {
+            self.add_action(name.as_str(), triggers.clone());
+        }
+    }
+
+    pub fn bindings(&self) -> InputBindings {
+        InputBindings {
+            actions: self
+                .actions
+                .values()
+                .map(|action| (action.name().to_owned(), action.triggers().to_vec()))
+                .collect(),
+        }
+    }
+
+    pub fn defaults(&self) -> &InputBindings {
+        &self.defaults
+    }
+
+    pub fn set_defaults(&mut self, bindings: InputBindings) {
+        self.defaults = bindings;
+    }
+
+    /// Restores the default triggers of every action that has defaults.
+    pub fn reset_to_defaults(&mut self) {
+        let defaults = self.defaults.clone();
+        self.apply_bindings(&defaults);
+    }
+
+    /// Restores the default triggers of the given action. Returns `false` if it has no defaults.
+    pub fn reset_action_to_defaults(&mut self, name: &str) -> bool {
+        match self.defaults.actions.get(name) {
+            Some(triggers) => {
+                let triggers = triggers.clone();
+                self.add_action(name, triggers);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Binds the input to a slot of the trigger at the given index, as described by [`Trigger::with_input`].
+    /// An index equal to the number of triggers appends a new trigger of the input.
+    /// Returns `false` if there is no such action, trigger or slot, or if the slot cannot take the input.
+    pub fn set_trigger_input(
+        &mut self,
+        name: &str,
+        index: usize,
+        part: Option<&str>,
+        input: TriggerInput,
+    ) -> bool {
+        let action = match self.actions.get_mut(name) {
+            Some(action) => action,
+            None => return false,
+        };
+        let mut triggers = action.triggers().to_vec();
+
+        if index == triggers.len() {
+            if part.is_some_and(|part| part != input.part()) {
+                return false;
+            }
+            triggers.push(input.into());
+        } else {
+            let trigger = match triggers.get(index).and_then(|t| t.with_input(part, input)) {
+                Some(trigger) => trigger,
+                None => return false,
+            };
+            triggers[index] = trigger;
+        }
+
+        action.set_triggers(triggers);
+        true
+    }
+
+    /// Returns the names of the actions bound to the input, sorted by name.
+    pub fn actions_bound_to(&self, input: TriggerInput) -> Vec<&str> {
+        let mut names = self
+            .actions
+            .values()
+            .filter(|action| {
+                action
+                    .triggers()
+                    .iter()
+                    .any(|trigger| trigger.inputs().contains(&input))
+            })
+            .map(|action| action.name())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    /// Returns every input bound to more than one action.
+    pub fn conflicts(&self) -> Vec<InputConflict> {
+        let mut per_input = HashMap::<TriggerInput, BTreeSet<&str>>::new();
+
+        for action in self.actions.values() {
+            for trigger in action.triggers() {
+                for input in trigger.inputs() {
+                    per_input.entry(input).or_default().insert(action.name());
+                }
+            }
+        }
+
+        let mut conflicts = per_input
+            .into_iter()
+            .filter(|(_, actions)| 1 < actions.len())
+            .map(|(input, actions)| InputConflict {
+                input,
+                actions: actions.into_iter().map(str::to_owned).collect(),
+            })
+            .collect::<Vec<_>>();
+        // Keep the result stable between calls.
+        conflicts
+            .sort_unstable_by(|lhs, rhs| (&lhs.actions, lhs.input).cmp(&(&rhs.actions, rhs.input)));
+        conflicts
+    }
+
+    pub fn update(&mut self, sources: &InputSources) {
+        for action in self.actions.values_mut() {
+            action.update(sources);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::input::{GamepadChannel, KeyCode, MouseButton};
+
+    fn conflict(input: TriggerInput, actions: &[&str]) -> InputConflict {
+        InputConflict {
+            input,
+            actions: actions.iter().map(|&action| action.to_owned()).collect(),
+        }
+    }
+
+    #[test]
+    fn keys_of_axes_conflict_with_keys() {
+        let mut input_map = InputMap::new();
+        input_map.add_action(
+            "move",
+            vec![Trigger::Axis1D {
+                negative: KeyCode::Left,
+                positive: KeyCode::Right,
+            }],
+        );
+        input_map.add_action("back", vec![Trigger::Key { key: KeyCode::Left }]);
+
+        assert_eq!(
+            input_map.conflicts(),
+            vec![conflict(
+                TriggerInput::Key(KeyCode::Left),
+                &["back", "move"]
+            )]
+        );
+        assert_eq!(
+            input_map.actions_bound_to(TriggerInput::Key(KeyCode::Right)),
+            vec!["move"]
+        );
+    }
+
+    #[test]
+    fn mouse_buttons_bound_to_several_actions_conflict() {
+        let mut input_map = InputMap::new();
+        input_map.add_action(
+            "fire",
+            vec![Trigger::Mouse {
+                button: MouseButton::Left,
+            }],
+        );
+        input_map.add_action(
+            "select",
+            vec![
+                Trigger::Key {
+                    key: KeyCode::Enter,
+                },
+                Trigger::Mouse {
+                    button: MouseButton::Left,
+                },
+            ],
+        );
+        input_map.add_action(
+            "aim",
+            vec![Trigger::Mouse {
+                button: MouseButton::Right,
+            }],
+        );
+
+        assert_eq!(
+            input_map.conflicts(),
+            vec![conflict(
+                TriggerInput::Mouse(MouseButton::Left),
+                &["fire", "select"]
+            )]
+        );
+        assert_eq!(
+            input_map.actions_bound_to(TriggerInput::Mouse(MouseButton::Left)),
+            vec!["fire", "select"]
+        );
+    }
+
+    #[test]
+    fn gamepad_channels_bound_to_several_actions_conflict() {
+        let mut input_map = InputMap::new();
+        input_map.add_action(
+            "jump",
+            vec![Trigger::Gamepad {
+                channel: GamepadChannel::South,
+            }],
+        );
+        input_map.add_action(
+            "confirm",
+            vec![Trigger::Gamepad {
+                channel: GamepadChannel::South,
+            }],
+        );
+        // A channel does not conflict with a key or mouse button.
+        input_map.add_action(
+            "crouch",
+            vec![
+                Trigger::Gamepad {
+                    channel: GamepadChannel::East,
+                },
+                Trigger::Mouse {
+                    button: MouseButton::Middle,
+                },
+            ],
+        );
+
+        assert_eq!(
+            input_map.conflicts(),
+            vec![conflict(
+                TriggerInput::Gamepad(GamepadChannel::South),
+                &["confirm", "jump"]
+            )]
+        );
+        assert_eq!(
+            input_map.actions_bound_to(TriggerInput::Gamepad(GamepadChannel::East)),
+            vec!["crouch"]
+        );
+    }
+}
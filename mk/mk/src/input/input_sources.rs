@@ -0,0 +1,52 @@
+use super::{
+    GamepadChannel, GamepadDriver, InputChannelValue, KeyCode, Keyboard, Mouse, MouseButton,
+};
+
+/// The devices triggers are evaluated against.
+#[derive(Clone, Copy)]
+pub struct InputSources<'a> {
+    /// `None` while the keyboard is captured, e.g. by a focused text field.
+    pub keyboard: Option<&'a Keyboard>,
+    pub mouse: &'a Mouse,
+    pub gamepads: &'a GamepadDriver,
+}
+
+impl<'a> InputSources<'a> {
+    pub fn is_key_down(&self, key: KeyCode) -> bool {
+        self.keyboard
+            .is_some_and(|keyboard| keyboard.is_key_down(key))
+    }
+
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.mouse.is_button_down(button)
+    }
+
+    /// Returns the strongest reading of the channel among every connected gamepad.
+    /// A suppressed channel reads as released.
+    pub fn gamepad_channel(&self, channel: GamepadChannel) -> InputChannelValue {
+        let empty = match channel {
+            GamepadChannel::LeftTrigger | GamepadChannel::RightTrigger => {
+                InputChannelValue::Axis1D(0f32)
+            }
+            GamepadChannel::LeftStick | GamepadChannel::RightStick => {
+                InputChannelValue::Axis2D(0f32, 0f32)
+            }
+            _ => InputChannelValue::Button(false),
+        };
+
+        if self.gamepads.is_suppressed(channel) {
+            return empty;
+        }
+
+        self.gamepads
+            .gamepads()
+            .map(|gamepad| gamepad.channel_state(channel))
+            .fold(empty, |strongest, value| {
+                if strongest.magnitude() < value.magnitude() {
+                    value
+                } else {
+                    strongest
+                }
+            })
+    }
+}
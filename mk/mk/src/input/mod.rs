@@ -4,9 +4,13 @@ mod channel;
 mod device;
 mod driver;
 mod drivers;
+mod input_bindings;
 mod input_manager;
 mod input_map;
+mod input_sources;
 mod input_type;
+mod rebinding;
+mod recording;
 mod trigger;
 
 pub use action::*;
@@ -15,7 +19,11 @@ pub use channel::*;
 pub use device::*;
 pub use driver::*;
 pub use drivers::*;
+pub use input_bindings::*;
 pub use input_manager::*;
 pub use input_map::*;
+pub use input_sources::*;
 pub use input_type::*;
+pub use rebinding::*;
+pub use recording::*;
 pub use trigger::*;
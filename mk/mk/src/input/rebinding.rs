@@ -0,0 +1,35 @@
+use super::{KeyCode, TriggerInput};
+
+/// Pressing this key while listening for a new binding cancels the rebinding.
+pub const REBIND_CANCEL_KEY: KeyCode = KeyCode::Escape;
+
+/// An analog gamepad channel, such as a trigger or a stick, is captured once it is pushed this far.
+pub const REBIND_AXIS_THRESHOLD: f32 = 0.5;
+
+/// A pending request to bind the next pressed key, mouse button or gamepad channel to a slot of an action's trigger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rebinding {
+    pub action: String,
+    /// Index of the trigger to rebind. An index equal to the number of triggers appends a new trigger.
+    pub trigger: usize,
+    /// Input slot of the trigger, e.g. `left` of a 2D axis. Without it, a trigger of a single input is replaced
+    /// by a trigger of the captured input.
+    pub part: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebindOutcome {
+    /// The input has been bound.
+    Bound(TriggerInput),
+    /// The input cannot be bound to the requested slot, e.g. a mouse button to a slot of a key axis.
+    /// The bindings are left untouched.
+    Rejected(TriggerInput),
+    /// The rebinding has been cancelled, by the cancel key or by starting another one.
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebindResult {
+    pub rebinding: Rebinding,
+    pub outcome: RebindOutcome,
+}
@@ -0,0 +1,288 @@
+use crate::input::GamepadInput;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    path::Path,
+};
+use winit::event::{ElementState, KeyboardInput, ModifiersState, MouseButton};
+
+const RECORDING_MAGIC: [u8; 4] = *b"MKIR";
+const RECORDING_VERSION: u32 = 2;
+
+/// An input as fed into the engine, before it reaches the input and UI event managers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum RecordedInput {
+    Focused(bool),
+    /// Raw motion of the mouse.
+    MouseMotion {
+        x: f32,
+        y: f32,
+    },
+    Keyboard(KeyboardInput),
+    Modifiers(ModifiersState),
+    Character(char),
+    /// Text being composed by an input method. The caret is counted in characters.
+    Composition {
+        text: String,
+        caret: Option<usize>,
+    },
+    /// Text committed by an input method.
+    Commit(String),
+    CompositionEnd,
+    PointerEnter,
+    PointerExit,
+    /// The cursor moved, in logical pixels from the top-left of the window.
+    PointerMove {
+        x: f64,
+        y: f64,
+    },
+    PointerButton {
+        button: MouseButton,
+        state: ElementState,
+    },
+    /// Wheel scroll, in lines.
+    PointerWheel {
+        x: f32,
+        y: f32,
+    },
+    /// A gamepad connection, disconnection, button or axis change.
+    Gamepad(GamepadInput),
+    /// Text read from the clipboard to be pasted into the focused text field.
+    Paste(String),
+}
+
+/// Every input received during a frame, followed by the delta time of that frame.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedFrame {
+    pub dt: f64,
+    pub inputs: Vec<RecordedInput>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+struct RecordingHeader {
+    magic: [u8; 4],
+    version: u32,
+    seed: u64,
+}
+
+/// Streams frames into a file as they end, so that a recording survives a crash.
+///
+/// The file starts with a header holding the seed of the script RNG, then one entry per frame.
+pub struct InputRecorder {
+    writer: BufWriter<File>,
+    inputs: Vec<RecordedInput>,
+}
+
+impl InputRecorder {
+    pub fn create(path: impl AsRef<Path>, seed: u64) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("failed to create input recording {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+
+        bincode::serialize_into(
+            &mut writer,
+            &RecordingHeader {
+                magic: RECORDING_MAGIC,
+                version: RECORDING_VERSION,
+                seed,
+            },
+        )
+        .with_context(|| "failed to write input recording header")?;
+
+        Ok(Self {
+            writer,
+            inputs: Vec::new(),
+        })
+    }
+
+    pub fn record(&mut self, input: RecordedInput) {
+        self.inputs.push(input);
+    }
+
+    /// Writes the inputs recorded since the last call as a frame lasting `dt` seconds.
+    pub fn end_frame(&mut self, dt: f64) -> Result<()> {
+        let frame = RecordedFrame {
+            dt,
+            inputs: std::mem::take(&mut self.inputs),
+        };
+
+        bincode::serialize_into(&mut self.writer, &frame)
+            .with_context(|| "failed to write input recording frame")?;
+        self.writer
+            .flush()
+            .with_context(|| "failed to flush input recording")?;
+
+        Ok(())
+    }
+}
+
+/// Frames of a recording, handed back one per engine frame.
+pub struct InputReplay {
+    seed: u64,
+    frames: VecDeque<RecordedFrame>,
+}
+
+impl InputReplay {
+    /// Reads a recording. A frame cut short at the end of the file is dropped.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("failed to open input recording {}", path.display()))?;
+        Self::from_reader(BufReader::new(file))
+    }
+
+    pub fn from_reader(mut reader: impl Read) -> Result<Self> {
+        let header: RecordingHeader = bincode::deserialize_from(&mut reader)
+            .with_context(|| "failed to read input recording header")?;
+
+        if header.magic != RECORDING_MAGIC {
+            bail!("not an input recording");
+        }
+
+        if header.version != RECORDING_VERSION {
+            bail!(
+                "unsupported input recording version {} (expected {})",
+                header.version,
+                RECORDING_VERSION
+            );
+        }
+
+        let mut frames = VecDeque::new();
+
+        loop {
+            match bincode::deserialize_from::<_, RecordedFrame>(&mut reader) {
+                Ok(frame) => frames.push_back(frame),
+                Err(err) => match *err {
+                    bincode::ErrorKind::Io(err) if err.kind() == ErrorKind::UnexpectedEof => break,
+                    err => return Err(err).with_context(|| "failed to read input recording frame"),
+                },
+            }
+        }
+
+        Ok(Self {
+            seed: header.seed,
+            frames,
+        })
+    }
+
+    /// Returns the seed of the script RNG when the recording started.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn remaining_frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn next_frame(&mut self) -> Option<RecordedFrame> {
+        self.frames.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::input::GamepadAxis;
+    use winit::event::VirtualKeyCode;
+
+    fn frames() -> Vec<RecordedFrame> {
+        #[allow(deprecated)]
+        let key = KeyboardInput {
+            scancode: 30,
+            state: ElementState::Pressed,
+            virtual_keycode: Some(VirtualKeyCode::A),
+            modifiers: ModifiersState::empty(),
+        };
+
+        vec![
+            RecordedFrame {
+                dt: 1f64 / 60f64,
+                inputs: vec![
+                    RecordedInput::Focused(true),
+                    RecordedInput::Keyboard(key),
+                    RecordedInput::Character('a'),
+                    RecordedInput::PointerMove { x: 12.5, y: 40.0 },
+                ],
+            },
+            RecordedFrame {
+                dt: 1f64 / 30f64,
+                inputs: vec![],
+            },
+            RecordedFrame {
+                dt: 1f64 / 60f64,
+                inputs: vec![
+                    RecordedInput::Composition {
+                        text: "にほ".to_owned(),
+                        caret: Some(1),
+                    },
+                    RecordedInput::Commit("日本".to_owned()),
+                    RecordedInput::PointerButton {
+                        button: MouseButton::Left,
+                        state: ElementState::Released,
+                    },
+                    RecordedInput::PointerWheel { x: 0.0, y: -1.0 },
+                    RecordedInput::Paste("pasted".to_owned()),
+                    RecordedInput::Gamepad(GamepadInput::Connected {
+                        id: 1,
+                        name: "gamepad".to_owned(),
+                    }),
+                    RecordedInput::Gamepad(GamepadInput::Axis {
+                        id: 1,
+                        axis: GamepadAxis::LeftTrigger,
+                        value: 0.5,
+                    }),
+                ],
+            },
+        ]
+    }
+
+    fn record(path: &Path) {
+        let mut recorder = InputRecorder::create(path, 42).unwrap();
+
+        for frame in frames() {
+            for input in frame.inputs {
+                recorder.record(input);
+            }
+
+            recorder.end_frame(frame.dt).unwrap();
+        }
+    }
+
+    #[test]
+    fn replay_gives_back_the_recorded_frames() {
+        let path = std::env::temp_dir().join(format!("mk-input-recording-{}", std::process::id()));
+
+        record(&path);
+        let replay = InputReplay::open(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let mut replay = replay.unwrap();
+        assert_eq!(replay.seed(), 42);
+        assert_eq!(replay.remaining_frames(), frames().len());
+        assert_eq!(
+            std::iter::from_fn(|| replay.next_frame()).collect::<Vec<_>>(),
+            frames()
+        );
+    }
+
+    #[test]
+    fn truncated_frame_is_dropped() {
+        let path = std::env::temp_dir().join(format!(
+            "mk-input-recording-truncated-{}",
+            std::process::id()
+        ));
+
+        record(&path);
+        let mut bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        bytes.truncate(bytes.len() - 1);
+
+        let replay = InputReplay::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(replay.remaining_frames(), frames().len() - 1);
+        assert!(InputReplay::from_reader(&b"not a recording"[..]).is_err());
+    }
+}
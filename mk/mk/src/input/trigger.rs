@@ -1,3 +1,167 @@
-pub trait Trigger {
-    fn update(&self);
+use super::{GamepadChannel, InputChannelValue, InputSources, KeyCode, MouseButton};
+use serde::{Deserialize, Serialize};
+
+/// A single binding of an action to a physical input.
+/// Composite axes combine several keys into a 1D or 2D axis.
+/// Gamepad triggers read a channel of whichever connected gamepad is the most active.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(tag = "type")]
+pub enum Trigger {
+    #[serde(rename = "key")]
+    Key { key: KeyCode },
+    #[serde(rename = "axis_1d")]
+    Axis1D {
+        negative: KeyCode,
+        positive: KeyCode,
+    },
+    #[serde(rename = "axis_2d")]
+    Axis2D {
+        left: KeyCode,
+        right: KeyCode,
+        down: KeyCode,
+        up: KeyCode,
+    },
+    #[serde(rename = "mouse")]
+    Mouse { button: MouseButton },
+    #[serde(rename = "gamepad")]
+    Gamepad { channel: GamepadChannel },
+}
+
+/// A single physical input, which a trigger can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TriggerInput {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadChannel),
+}
+
+impl TriggerInput {
+    /// Name of the slot that holds the input in a trigger of its own.
+    pub fn part(self) -> &'static str {
+        match self {
+            TriggerInput::Key(_) => "key",
+            TriggerInput::Mouse(_) => "button",
+            TriggerInput::Gamepad(_) => "channel",
+        }
+    }
+}
+
+impl From<TriggerInput> for Trigger {
+    fn from(input: TriggerInput) -> Self {
+        match input {
+            TriggerInput::Key(key) => Trigger::Key { key },
+            TriggerInput::Mouse(button) => Trigger::Mouse { button },
+            TriggerInput::Gamepad(channel) => Trigger::Gamepad { channel },
+        }
+    }
+}
+
+impl Trigger {
+    /// Names of the input slots of this trigger, matching the field names of its serialized form.
+    pub fn parts(&self) -> &'static [&'static str] {
+        match self {
+            Trigger::Key { .. } => &["key"],
+            Trigger::Axis1D { .. } => &["negative", "positive"],
+            Trigger::Axis2D { .. } => &["left", "right", "down", "up"],
+            Trigger::Mouse { .. } => &["button"],
+            Trigger::Gamepad { .. } => &["channel"],
+        }
+    }
+
+    pub fn keys(&self) -> Vec<KeyCode> {
+        match *self {
+            Trigger::Key { key } => vec![key],
+            Trigger::Axis1D { negative, positive } => vec![negative, positive],
+            Trigger::Axis2D {
+                left,
+                right,
+                down,
+                up,
+            } => vec![left, right, down, up],
+            Trigger::Mouse { .. } | Trigger::Gamepad { .. } => vec![],
+        }
+    }
+
+    /// Returns every input the trigger reads, in the order of its slots.
+    pub fn inputs(&self) -> Vec<TriggerInput> {
+        match *self {
+            Trigger::Mouse { button } => vec![TriggerInput::Mouse(button)],
+            Trigger::Gamepad { channel } => vec![TriggerInput::Gamepad(channel)],
+            _ => self.keys().into_iter().map(TriggerInput::Key).collect(),
+        }
+    }
+
+    /// Returns a copy of this trigger with the key of the given slot replaced.
+    /// Returns `None` if the trigger has no such slot.
+    pub fn with_key(mut self, part: &str, key: KeyCode) -> Option<Self> {
+        let slot = match (&mut self, part) {
+            (Trigger::Key { key: slot }, "key") => slot,
+            (Trigger::Axis1D { negative, .. }, "negative") => negative,
+            (Trigger::Axis1D { positive, .. }, "positive") => positive,
+            (Trigger::Axis2D { left, .. }, "left") => left,
+            (Trigger::Axis2D { right, .. }, "right") => right,
+            (Trigger::Axis2D { down, .. }, "down") => down,
+            (Trigger::Axis2D { up, .. }, "up") => up,
+            _ => return None,
+        };
+        *slot = key;
+        Some(self)
+    }
+
+    /// Returns the trigger with the input bound to the given slot.
+    /// A trigger of a single input is replaced by a trigger of the new input, whatever its device, unless `part`
+    /// names a slot of another device. Slots of composite axes only take keys.
+    /// Returns `None` if the input cannot be bound to the slot.
+    pub fn with_input(self, part: Option<&str>, input: TriggerInput) -> Option<Self> {
+        match (self, input) {
+            (Trigger::Axis1D { .. } | Trigger::Axis2D { .. }, TriggerInput::Key(key)) => {
+                self.with_key(part?, key)
+            }
+            (Trigger::Axis1D { .. } | Trigger::Axis2D { .. }, _) => None,
+            _ => match part {
+                Some(part) if part != input.part() || self.parts() != [part] => None,
+                _ => Some(input.into()),
+            },
+        }
+    }
+
+    pub fn value(&self, sources: &InputSources) -> InputChannelValue {
+        let axis = |negative: KeyCode, positive: KeyCode| {
+            let mut value = 0f32;
+            if sources.is_key_down(negative) {
+                value -= 1f32;
+            }
+            if sources.is_key_down(positive) {
+                value += 1f32;
+            }
+            value
+        };
+
+        match *self {
+            Trigger::Key { key } => InputChannelValue::Button(sources.is_key_down(key)),
+            Trigger::Axis1D { negative, positive } => {
+                InputChannelValue::Axis1D(axis(negative, positive))
+            }
+            Trigger::Axis2D {
+                left,
+                right,
+                down,
+                up,
+            } => {
+                let (x, y) = (axis(left, right), axis(down, up));
+                let len = (x * x + y * y).sqrt();
+
+                // Diagonals must not be faster than the cardinal directions.
+                if 1f32 < len {
+                    InputChannelValue::Axis2D(x / len, y / len)
+                } else {
+                    InputChannelValue::Axis2D(x, y)
+                }
+            }
+            Trigger::Mouse { button } => {
+                InputChannelValue::Button(sources.is_mouse_button_down(button))
+            }
+            Trigger::Gamepad { channel } => sources.gamepad_channel(channel),
+        }
+    }
 }
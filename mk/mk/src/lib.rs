@@ -14,16 +14,20 @@ mod engine;
 mod engine_context;
 mod engine_diagnostic;
 mod engine_gfx;
+pub mod entity;
 pub mod event;
 pub mod gfx;
 pub mod handles;
 pub mod input;
 pub mod res;
+pub mod save;
+pub mod scene;
 pub mod script;
 pub mod structure;
 pub mod system;
 pub mod time;
 pub mod transform;
+pub mod turn;
 pub mod ui;
 pub mod util;
 
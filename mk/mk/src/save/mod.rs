@@ -0,0 +1,7 @@
+mod save_error;
+mod save_file;
+mod saved_value;
+
+pub use save_error::*;
+pub use save_file::*;
+pub use saved_value::*;
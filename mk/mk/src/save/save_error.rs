@@ -0,0 +1,29 @@
+use crate::scene::SceneError;
+use res::blob::BlobError;
+use std::io::Error as IOError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SaveError {
+    #[error("io error: {0}")]
+    IOError(#[from] IOError),
+    #[error("malformed save header: {0}")]
+    HeaderError(#[from] bincode::Error),
+    #[error("malformed save data: {0}")]
+    JSONError(#[from] serde_json::Error),
+    #[error("not a save file")]
+    InvalidMagic,
+    #[error(
+        "save format version {0} is not supported; expected version {}",
+        super::SAVE_FORMAT_VERSION
+    )]
+    UnsupportedFormat(u32),
+    #[error("the save is encrypted, but no key is given")]
+    KeyRequired,
+    #[error("cannot decompress the save: {0}")]
+    DecompressError(IOError),
+    #[error("cipher error: {0:?}")]
+    CipherError(BlobError),
+    #[error("scene error: {0}")]
+    SceneError(#[from] SceneError),
+}
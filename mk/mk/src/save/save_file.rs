@@ -0,0 +1,171 @@
+use super::{SaveError, SavedTables, SavedValue};
+use crate::{
+    scene::{Scene, SceneError, SCENE_VERSION},
+    util::write_atomic,
+};
+use res::blob::{compress_blob, decompress_blob, decrypt_blob, encrypt_blob};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs::read, io::Cursor, path::Path};
+
+const SAVE_MAGIC: [u8; 4] = *b"MKSV";
+/// The version of the save file layout written by this engine, regardless of what the game stores in it.
+pub const SAVE_FORMAT_VERSION: u32 = 1;
+/// Salt of the hash the cipher key of encrypted saves is masked with.
+const SAVE_SALT: &[u8] = b"mk.save.cipher";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+struct SaveHeader {
+    magic: [u8; 4],
+    format_version: u32,
+    schema_version: u32,
+    is_compressed: bool,
+    is_encrypted: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavePayload {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scene: Option<Scene>,
+    values: BTreeMap<String, SavedValue>,
+    tables: SavedTables,
+}
+
+/// How a save file is written.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SaveOptions {
+    /// Compresses the data with brotli.
+    pub compress: bool,
+    /// Encrypts the data with AES under the key, to keep players from editing their saves.
+    pub key: Option<String>,
+}
+
+/// A saved game: an optional scene, and named values saved by scripts.
+///
+/// The file starts with a header holding the format version and the schema version, which the game sets and bumps
+/// whenever what it saves changes, so that older saves can be migrated. The rest is the data as JSON,
+/// compressed and then encrypted if requested.
+#[derive(Clone)]
+pub struct SaveFile {
+    pub schema_version: u32,
+    pub scene: Option<Scene>,
+    pub values: BTreeMap<String, SavedValue>,
+    pub tables: SavedTables,
+}
+
+impl SaveFile {
+    pub fn to_bytes(&self, options: &SaveOptions) -> Result<Vec<u8>, SaveError> {
+        let payload = SavePayload {
+            scene: self.scene.clone(),
+            values: self.values.clone(),
+            tables: self.tables.clone(),
+        };
+        let mut data = serde_json::to_vec(&payload)?;
+
+        if options.compress {
+            data = compress_blob(&data, 11, 22);
+        }
+
+        if let Some(key) = &options.key {
+            data = encrypt_blob(key, SAVE_SALT, &data).map_err(SaveError::CipherError)?;
+        }
+
+        let mut bytes = bincode::serialize(&SaveHeader {
+            magic: SAVE_MAGIC,
+            format_version: SAVE_FORMAT_VERSION,
+            schema_version: self.schema_version,
+            is_compressed: options.compress,
+            is_encrypted: options.key.is_some(),
+        })?;
+        bytes.extend_from_slice(&data);
+
+        Ok(bytes)
+    }
+
+    /// Reads a save. `key` is needed only if the save is encrypted.
+    pub fn from_bytes(bytes: &[u8], key: Option<&str>) -> Result<Self, SaveError> {
+        let mut cursor = Cursor::new(bytes);
+        let header = bincode::deserialize_from::<_, SaveHeader>(&mut cursor)?;
+
+        if header.magic != SAVE_MAGIC {
+            return Err(SaveError::InvalidMagic);
+        }
+
+        if header.format_version != SAVE_FORMAT_VERSION {
+            return Err(SaveError::UnsupportedFormat(header.format_version));
+        }
+
+        let mut data = bytes[cursor.position() as usize..].to_vec();
+
+        if header.is_encrypted {
+            let key = key.ok_or(SaveError::KeyRequired)?;
+            data = decrypt_blob(key, SAVE_SALT, &data).map_err(SaveError::CipherError)?;
+        }
+
+        if header.is_compressed {
+            data = decompress_blob(&data).map_err(SaveError::DecompressError)?;
+        }
+
+        let payload = serde_json::from_slice::<SavePayload>(&data)?;
+
+        if let Some(scene) = &payload.scene {
+            if scene.version != SCENE_VERSION {
+                return Err(SceneError::UnsupportedVersion(scene.version).into());
+            }
+        }
+
+        Ok(Self {
+            schema_version: header.schema_version,
+            scene: payload.scene,
+            values: payload.values,
+            tables: payload.tables,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>, key: Option<&str>) -> Result<Self, SaveError> {
+        Self::from_bytes(&read(path)?, key)
+    }
+
+    /// Writes the save, replacing the file atomically so that a crash never leaves a broken save behind.
+    pub fn save(&self, path: impl AsRef<Path>, options: &SaveOptions) -> Result<(), SaveError> {
+        Ok(write_atomic(path, self.to_bytes(options)?)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::structure::Vec2;
+
+    #[test]
+    fn compressed_and_encrypted_round_trip() {
+        let save = SaveFile {
+            schema_version: 3,
+            scene: None,
+            values: BTreeMap::from([
+                ("floor".to_owned(), SavedValue::Integer(4)),
+                ("seen".to_owned(), SavedValue::Table(0)),
+            ]),
+            tables: vec![vec![
+                (
+                    SavedValue::String("spawn".to_owned()),
+                    SavedValue::Vec2(Vec2::new(1f32, 2f32)),
+                ),
+                (SavedValue::Integer(1), SavedValue::Table(0)),
+            ]],
+        };
+        let options = SaveOptions {
+            compress: true,
+            key: Some("secret".to_owned()),
+        };
+        let bytes = save.to_bytes(&options).unwrap();
+        let loaded = SaveFile::from_bytes(&bytes, Some("secret")).unwrap();
+
+        assert_eq!(loaded.schema_version, save.schema_version);
+        assert_eq!(loaded.values, save.values);
+        assert_eq!(loaded.tables, save.tables);
+        assert!(matches!(
+            SaveFile::from_bytes(&bytes, None),
+            Err(SaveError::KeyRequired)
+        ));
+    }
+}
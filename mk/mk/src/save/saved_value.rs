@@ -0,0 +1,385 @@
+use crate::{
+    gfx::Color,
+    script::{entity::Entity as ScriptEntity, ConversionByValueReadOnly, UserDataIntoSelf},
+    structure::{Size, Vec2},
+};
+use mlua::prelude::*;
+use serde::{Deserialize, Serialize};
+use specs::Entity;
+use std::{collections::HashMap, ffi::c_void, fmt::Debug};
+
+/// A Lua value converted to be saved.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SavedValue {
+    Nil,
+    Boolean(bool),
+    Integer(i64),
+    Number(#[serde(with = "number")] f64),
+    String(String),
+    /// A table, by its index in [`SavedTables`].
+    Table(u32),
+    Vec2(Vec2),
+    Size(Size),
+    Color(Color),
+    /// An entity, by its `id` in the saved scene.
+    Entity(u32),
+}
+
+/// The tables the saved values refer to, each as its key-value pairs.
+///
+/// Tables are kept apart from the values referring to them, so that a table referred to more than once, even by
+/// itself, is saved once and restored as a single table.
+pub type SavedTables = Vec<Vec<(SavedValue, SavedValue)>>;
+
+/// Converts Lua values into saved values, collecting the tables they refer to.
+pub struct SavedValueEncoder<'a> {
+    tables: SavedTables,
+    table_indices: HashMap<*const c_void, u32>,
+    entity_ids: &'a HashMap<Entity, u32>,
+    warnings: Vec<String>,
+}
+
+impl<'a> SavedValueEncoder<'a> {
+    /// `entity_ids` maps the entities of the saved scene to their `id`; other entities are saved as `nil`.
+    pub fn new(entity_ids: &'a HashMap<Entity, u32>) -> Self {
+        Self {
+            tables: Vec::new(),
+            table_indices: HashMap::new(),
+            entity_ids,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Converts the value. Metatables are not saved, and functions, threads and other userdata cannot be.
+    pub fn encode(&mut self, value: LuaValue) -> LuaResult<SavedValue> {
+        Ok(match value {
+            LuaValue::Nil => SavedValue::Nil,
+            LuaValue::Boolean(boolean) => SavedValue::Boolean(boolean),
+            LuaValue::Integer(integer) => SavedValue::Integer(integer),
+            LuaValue::Number(number) => SavedValue::Number(number),
+            LuaValue::String(string) => SavedValue::String(string.to_str()?.to_owned()),
+            LuaValue::Table(table) => SavedValue::Table(self.encode_table(table)?),
+            LuaValue::UserData(user_data) => self.encode_user_data(user_data)?,
+            value => {
+                return Err(LuaError::external(format!(
+                    "cannot save a value of type {}",
+                    value.type_name()
+                )))
+            }
+        })
+    }
+
+    /// Returns the warnings about the values that could not be saved as they are, such as entities outside of
+    /// the saved scene.
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub fn finish(self) -> SavedTables {
+        self.tables
+    }
+
+    fn encode_table(&mut self, table: LuaTable) -> LuaResult<u32> {
+        if let Some(&index) = self.table_indices.get(&table.to_pointer()) {
+            return Ok(index);
+        }
+
+        // The index is taken before the pairs are encoded, so that they can refer back to the table.
+        let index = self.tables.len() as u32;
+        self.table_indices.insert(table.to_pointer(), index);
+        self.tables.push(Vec::new());
+
+        let mut pairs = Vec::new();
+
+        for pair in table.pairs::<LuaValue, LuaValue>() {
+            let (key, value) = pair?;
+
+            // An entity outside of the scene would be restored as a nil key, which tables cannot hold.
+            if let Some(entity) = self.entity_outside_scene(&key)? {
+                self.warnings.push(format!(
+                    "a table entry is not saved, as its key entity {:?} is not in the saved scene",
+                    entity
+                ));
+                continue;
+            }
+
+            pairs.push((self.encode(key)?, self.encode(value)?));
+        }
+
+        self.tables[index as usize] = pairs;
+        Ok(index)
+    }
+
+    fn encode_user_data(&mut self, user_data: LuaAnyUserData) -> LuaResult<SavedValue> {
+        if user_data.is::<Vec2>() {
+            let vec2 = Vec2::into_self(user_data)?;
+            Ok(SavedValue::Vec2(finite(vec2, &[vec2.x, vec2.y])?))
+        } else if user_data.is::<Size>() {
+            let size = Size::into_self(user_data)?;
+            Ok(SavedValue::Size(finite(size, &[size.width, size.height])?))
+        } else if user_data.is::<Color>() {
+            let color = Color::into_self(user_data)?;
+            Ok(SavedValue::Color(finite(
+                color,
+                &[color.r, color.g, color.b, color.a],
+            )?))
+        } else if user_data.is::<ScriptEntity>() {
+            let entity = user_data.borrow::<ScriptEntity>()?.0;
+
+            Ok(match self.entity_ids.get(&entity) {
+                Some(&id) => SavedValue::Entity(id),
+                None => {
+                    self.warnings.push(format!(
+                        "entity {:?} is saved as nil, as it is not in the saved scene",
+                        entity
+                    ));
+                    SavedValue::Nil
+                }
+            })
+        } else {
+            Err(LuaError::external(
+                "cannot save a userdata other than Vec2, Size, Color and Entity",
+            ))
+        }
+    }
+
+    fn entity_outside_scene(&self, value: &LuaValue) -> LuaResult<Option<Entity>> {
+        match value {
+            LuaValue::UserData(user_data) if user_data.is::<ScriptEntity>() => {
+                let entity = user_data.borrow::<ScriptEntity>()?.0;
+                Ok(Some(entity).filter(|entity| !self.entity_ids.contains_key(entity)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Rejects the value if any of its components is not finite, as they cannot be read back once written as JSON.
+fn finite<T>(value: T, components: &[f32]) -> LuaResult<T>
+where
+    T: Debug,
+{
+    if components.iter().all(|component| component.is_finite()) {
+        Ok(value)
+    } else {
+        Err(LuaError::external(format!(
+            "cannot save {:?}, as it has a non-finite component",
+            value
+        )))
+    }
+}
+
+/// Writes non-finite numbers as the strings `inf`, `-inf` and `nan`, as JSON has no numbers for them.
+mod number {
+    use serde::{de::Error, de::Unexpected, Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number {
+        Finite(f64),
+        NonFinite(String),
+    }
+
+    pub fn serialize<S>(number: &f64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if number.is_finite() {
+            serializer.serialize_f64(*number)
+        } else if number.is_nan() {
+            serializer.serialize_str("nan")
+        } else if number.is_sign_positive() {
+            serializer.serialize_str("inf")
+        } else {
+            serializer.serialize_str("-inf")
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<f64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Number::deserialize(deserializer)? {
+            Number::Finite(number) => Ok(number),
+            Number::NonFinite(number) => match number.as_str() {
+                "inf" => Ok(f64::INFINITY),
+                "-inf" => Ok(f64::NEG_INFINITY),
+                "nan" => Ok(f64::NAN),
+                _ => Err(D::Error::invalid_value(
+                    Unexpected::Str(&number),
+                    &"a number, \"inf\", \"-inf\" or \"nan\"",
+                )),
+            },
+        }
+    }
+}
+
+/// Converts saved values back into Lua values, creating the tables they refer to.
+pub struct SavedValueDecoder<'lua, 'a> {
+    lua: &'lua Lua,
+    tables: Vec<LuaTable<'lua>>,
+    entities: &'a HashMap<u32, Entity>,
+}
+
+impl<'lua, 'a> SavedValueDecoder<'lua, 'a> {
+    /// `entities` maps the `id`s of the saved scene to the entities created for them.
+    pub fn new(
+        lua: &'lua Lua,
+        tables: &SavedTables,
+        entities: &'a HashMap<u32, Entity>,
+    ) -> LuaResult<Self> {
+        let mut decoder = Self {
+            lua,
+            tables: Vec::with_capacity(tables.len()),
+            entities,
+        };
+
+        // Every table is created before any is filled, as they may refer to each other.
+        for _ in tables {
+            decoder.tables.push(lua.create_table()?);
+        }
+
+        for (table, pairs) in decoder.tables.iter().zip(tables) {
+            for (key, value) in pairs {
+                table.raw_set(decoder.decode(key)?, decoder.decode(value)?)?;
+            }
+        }
+
+        Ok(decoder)
+    }
+
+    pub fn decode(&self, value: &SavedValue) -> LuaResult<LuaValue<'lua>> {
+        Ok(match value {
+            SavedValue::Nil => LuaValue::Nil,
+            SavedValue::Boolean(boolean) => LuaValue::Boolean(*boolean),
+            SavedValue::Integer(integer) => LuaValue::Integer(*integer),
+            SavedValue::Number(number) => LuaValue::Number(*number),
+            SavedValue::String(string) => LuaValue::String(self.lua.create_string(string)?),
+            SavedValue::Table(index) => match self.tables.get(*index as usize) {
+                Some(table) => LuaValue::Table(table.clone()),
+                None => {
+                    return Err(LuaError::external(format!(
+                        "saved table {} does not exist",
+                        index
+                    )))
+                }
+            },
+            SavedValue::Vec2(vec2) => vec2.perform_convertion_to_lua(self.lua)?,
+            SavedValue::Size(size) => size.perform_convertion_to_lua(self.lua)?,
+            SavedValue::Color(color) => color.perform_convertion_to_lua(self.lua)?,
+            SavedValue::Entity(id) => match self.entities.get(id) {
+                Some(&entity) => ScriptEntity::new(entity).to_lua(self.lua)?,
+                None => LuaValue::Nil,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::save::{SaveFile, SaveOptions};
+    use specs::{Builder, World, WorldExt};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn entries_keyed_by_entities_outside_the_scene_are_skipped() {
+        let lua = Lua::new();
+        let mut world = World::new();
+        let inside = world.create_entity().build();
+        let outside = world.create_entity().build();
+        let restored = world.create_entity().build();
+
+        let table = lua
+            .load(
+                r#"
+                local inside, outside = ...
+                return { [inside] = "in", [outside] = "out", other = outside, n = 1 }
+                "#,
+            )
+            .into_function()
+            .unwrap()
+            .call::<_, LuaTable>((ScriptEntity::new(inside), ScriptEntity::new(outside)))
+            .unwrap();
+
+        let entity_ids = HashMap::from([(inside, 7)]);
+        let mut encoder = SavedValueEncoder::new(&entity_ids);
+        let value = encoder.encode(LuaValue::Table(table)).unwrap();
+        assert_eq!(encoder.take_warnings().len(), 2);
+        let tables = encoder.finish();
+        assert_eq!(tables[0].len(), 3);
+
+        let entities = HashMap::from([(7, restored)]);
+        let decoder = SavedValueDecoder::new(&lua, &tables, &entities).unwrap();
+        let table = match decoder.decode(&value).unwrap() {
+            LuaValue::Table(table) => table,
+            value => panic!("expected a table, got {:?}", value),
+        };
+
+        let mut pairs = 0;
+        for pair in table.pairs::<LuaValue, LuaValue>() {
+            pairs += 1;
+            match pair.unwrap() {
+                (LuaValue::UserData(key), value) => {
+                    assert_eq!(key.borrow::<ScriptEntity>().unwrap().0, restored);
+                    assert_eq!(String::from_lua(value, &lua).unwrap(), "in");
+                }
+                (key, value) => {
+                    let key = String::from_lua(key, &lua).unwrap();
+                    assert!(key == "n" && matches!(value, LuaValue::Integer(1)));
+                }
+            }
+        }
+        assert_eq!(pairs, 2);
+    }
+
+    #[test]
+    fn non_finite_numbers_round_trip() {
+        let lua = Lua::new();
+        let table = lua
+            .load("return { math.huge, -math.huge, 0 / 0, 0.5 }")
+            .eval::<LuaTable>()
+            .unwrap();
+
+        let entity_ids = HashMap::new();
+        let mut encoder = SavedValueEncoder::new(&entity_ids);
+        let value = encoder.encode(LuaValue::Table(table)).unwrap();
+        let save = SaveFile {
+            schema_version: 1,
+            scene: None,
+            values: BTreeMap::from([("numbers".to_owned(), value)]),
+            tables: encoder.finish(),
+        };
+        let bytes = save.to_bytes(&SaveOptions::default()).unwrap();
+        let loaded = SaveFile::from_bytes(&bytes, None).unwrap();
+
+        let entities = HashMap::new();
+        let decoder = SavedValueDecoder::new(&lua, &loaded.tables, &entities).unwrap();
+        let numbers = match decoder.decode(&loaded.values["numbers"]).unwrap() {
+            LuaValue::Table(table) => table
+                .sequence_values::<f64>()
+                .collect::<LuaResult<Vec<_>>>(),
+            value => panic!("expected a table, got {:?}", value),
+        }
+        .unwrap();
+
+        assert_eq!(numbers.len(), 4);
+        assert_eq!(numbers[0], f64::INFINITY);
+        assert_eq!(numbers[1], f64::NEG_INFINITY);
+        assert!(numbers[2].is_nan());
+        assert_eq!(numbers[3], 0.5);
+    }
+
+    #[test]
+    fn non_finite_components_are_rejected() {
+        let lua = Lua::new();
+        let vec2 = Vec2::new(f32::INFINITY, 0f32).to_lua(&lua).unwrap();
+
+        let entity_ids = HashMap::new();
+        let mut encoder = SavedValueEncoder::new(&entity_ids);
+        let error = encoder.encode(vec2).unwrap_err().to_string();
+
+        assert!(error.contains("non-finite"), "{}", error);
+    }
+}
@@ -0,0 +1,9 @@
+mod scene;
+mod scene_capture;
+mod scene_error;
+mod scene_instantiate;
+
+pub use scene::*;
+pub use scene_capture::*;
+pub use scene_error::*;
+pub use scene_instantiate::*;
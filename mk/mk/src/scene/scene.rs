@@ -0,0 +1,233 @@
+use super::SceneError;
+use crate::{
+    gfx::{ClearMode, Color, GlyphLayoutConfig, Layer},
+    structure::{Size, Vec2},
+    ui::{UIAnchor, UIMargin, UIScaleMode},
+    util::write_atomic,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs::read_to_string, path::Path, path::PathBuf};
+
+/// The version of the scene format written by this engine.
+pub const SCENE_VERSION: u32 = 1;
+
+/// Entities, their transform hierarchy and their components, as written to scene files.
+///
+/// Assets are referred to by the paths they have been loaded from, and entities by their `id` within the scene,
+/// so that a scene can be instantiated any number of times.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Scene {
+    pub version: u32,
+    /// The entities, with parents preceding their children.
+    pub entities: Vec<SceneEntity>,
+}
+
+impl Scene {
+    pub fn new(entities: Vec<SceneEntity>) -> Self {
+        Self {
+            version: SCENE_VERSION,
+            entities,
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SceneError> {
+        #[derive(Deserialize)]
+        struct SceneHeader {
+            version: u32,
+        }
+
+        // Checks the version first, as other versions may not parse at all.
+        let header = serde_json::from_str::<SceneHeader>(json)?;
+
+        if header.version != SCENE_VERSION {
+            return Err(SceneError::UnsupportedVersion(header.version));
+        }
+
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, SceneError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SceneError> {
+        Self::from_json(&read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SceneError> {
+        Ok(write_atomic(path, self.to_json()?)?)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct SceneEntity {
+    /// Identifies the entity within the scene.
+    pub id: u32,
+    /// The `id` of the parent. The entities without one are the roots of the scene.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub position: Vec2,
+    pub scale: Vec2,
+    pub angle: f32,
+    pub size: Size,
+    #[serde(default)]
+    pub components: SceneComponents,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct SceneComponents {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_source: Option<SceneAudioSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub camera: Option<SceneCamera>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub diagnostic: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glyph_renderer: Option<SceneGlyphRenderer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sprite_renderer: Option<SceneSpriteRenderer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_field: Option<SceneTextField>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ui_element: Option<SceneUIElement>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ui_mask: Option<SceneUIMask>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ui_scaler: Option<SceneUIScaler>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SceneAudioSource {
+    pub volume: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clip: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SceneCamera {
+    pub layer: Layer,
+    pub order: isize,
+    pub clear_mode: ClearMode,
+    pub clear_color: Color,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct SceneGlyphRenderer {
+    pub layer: Layer,
+    pub order: i32,
+    pub color: Color,
+    pub shader: PathBuf,
+    pub thickness: f32,
+    pub smoothness: f32,
+    pub font: PathBuf,
+    pub font_size: f32,
+    pub text: String,
+    pub config: GlyphLayoutConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SceneSpriteRenderer {
+    pub layer: Layer,
+    pub order: i32,
+    pub color: Color,
+    pub shader: PathBuf,
+    pub sprite: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SceneTextField {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    /// The `id` of the caret entity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caret: Option<u32>,
+    /// The `id` of the selection entity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection: Option<u32>,
+    pub caret_width: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SceneUIElement {
+    pub anchor: UIAnchor,
+    pub margin: UIMargin,
+    pub is_interactible: bool,
+    pub order_index: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SceneUIMask {
+    pub render_itself: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SceneUIScaler {
+    pub mode: UIScaleMode,
+    pub reference_size: Size,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let scene = Scene::new(vec![
+            SceneEntity {
+                id: 0,
+                parent: None,
+                name: Some("root".to_owned()),
+                position: Vec2::new(1f32, 2f32),
+                scale: Vec2::new(1f32, 1f32),
+                angle: 0.5f32,
+                size: Size::new(16f32, 8f32),
+                components: SceneComponents {
+                    camera: Some(SceneCamera {
+                        layer: Layer::new(1),
+                        order: 0,
+                        clear_mode: ClearMode::Color,
+                        clear_color: Color::from_rgb(0f32, 0f32, 0f32),
+                    }),
+                    diagnostic: true,
+                    ..Default::default()
+                },
+            },
+            SceneEntity {
+                id: 1,
+                parent: Some(0),
+                name: None,
+                position: Vec2::new(0f32, 0f32),
+                scale: Vec2::new(2f32, 2f32),
+                angle: 0f32,
+                size: Size::new(0f32, 0f32),
+                components: SceneComponents {
+                    sprite_renderer: Some(SceneSpriteRenderer {
+                        layer: Layer::new(1),
+                        order: 2,
+                        color: Color::from_rgb(1f32, 1f32, 1f32),
+                        shader: PathBuf::from("sprite"),
+                        sprite: PathBuf::from("player/idle"),
+                    }),
+                    ..Default::default()
+                },
+            },
+        ]);
+
+        let json = scene.to_json().unwrap();
+        assert!(Scene::from_json(&json).unwrap() == scene);
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let json = r#"{ "version": 2, "entities": [{ "unknown": true }] }"#;
+
+        match Scene::from_json(json) {
+            Err(SceneError::UnsupportedVersion(2)) => {}
+            Err(err) => panic!("expected an unsupported version, got {}", err),
+            Ok(..) => panic!("expected an unsupported version"),
+        }
+    }
+}
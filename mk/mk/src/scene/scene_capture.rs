@@ -0,0 +1,230 @@
+use super::*;
+use crate::{
+    asset::AssetIdentity,
+    component::{
+        AudioSource, Camera, Diagnostic, GlyphRenderer, Size, SpriteRenderer, TextField, Transform,
+        UIElement, UIMask, UIScaler,
+    },
+    emit_diagnostic_warn,
+    entity::EntityManager,
+    transform::TransformManager,
+    EngineContext,
+};
+use specs::prelude::*;
+use std::collections::HashMap;
+
+/// Captures the entities of the world, with their transform hierarchy and components, into a scene.
+/// If `root` is given, only the entity and its children are captured, and the entity becomes the root of the scene.
+///
+/// Destroyed entities are left out. Components referring to assets that have not been loaded through the asset
+/// manager cannot be saved by path; they are left out with a warning.
+pub fn capture_scene(context: &EngineContext, root: Option<Entity>) -> Scene {
+    capture_scene_with_ids(context, root).0
+}
+
+/// Captures the scene like [`capture_scene`], along with the `id` each captured entity is given in the scene.
+pub fn capture_scene_with_ids(
+    context: &EngineContext,
+    root: Option<Entity>,
+) -> (Scene, HashMap<Entity, u32>) {
+    let mut warnings = Vec::new();
+    let (scene, ids) = {
+        let world = context.world();
+        let transform_mgr = context.transform_mgr();
+        let entity_mgr = context.entity_mgr();
+        let asset_mgr = context.asset_mgr();
+        let ui_mgr = context.ui_mgr();
+        let (captured, ids) = captured_entities(&world, &transform_mgr, &entity_mgr, root);
+
+        let size_storage = world.read_storage::<Size>();
+        let audio_source_storage = world.read_storage::<AudioSource>();
+        let camera_storage = world.read_storage::<Camera>();
+        let diagnostic_storage = world.read_storage::<Diagnostic>();
+        let glyph_renderer_storage = world.read_storage::<GlyphRenderer>();
+        let sprite_renderer_storage = world.read_storage::<SpriteRenderer>();
+        let text_field_storage = world.read_storage::<TextField>();
+        let ui_element_storage = world.read_storage::<UIElement>();
+        let ui_mask_storage = world.read_storage::<UIMask>();
+        let ui_scaler_storage = world.read_storage::<UIScaler>();
+
+        let entities = captured
+            .into_iter()
+            .map(|(transform, entity)| {
+                let size = size_storage
+                    .get(entity)
+                    .map(|size| size.size)
+                    .unwrap_or_default();
+                let mut scene_entity = placed_entity(&transform_mgr, &ids, transform, size);
+                let name = scene_entity.name.as_deref().unwrap_or("(unnamed)");
+                let mut asset_path = |asset: &dyn AssetIdentity, component: &str| {
+                    let path = asset_mgr.path_of(asset);
+
+                    if path.is_none() {
+                        warnings.push(format!(
+                            "the {} of entity {} is not saved, as it refers to an asset that has not been loaded from a path",
+                            component, name
+                        ));
+                    }
+
+                    path
+                };
+
+                let components = SceneComponents {
+                    audio_source: audio_source_storage.get(entity).and_then(|audio_source| {
+                        let clip = match audio_source.clip() {
+                            Some(clip) => Some(asset_path(clip, "audio_source")?),
+                            None => None,
+                        };
+
+                        Some(SceneAudioSource {
+                            volume: audio_source.volume(),
+                            clip,
+                        })
+                    }),
+                    camera: camera_storage.get(entity).map(|camera| SceneCamera {
+                        layer: camera.layer,
+                        order: camera.order,
+                        clear_mode: camera.clear_mode,
+                        clear_color: camera.clear_color,
+                    }),
+                    diagnostic: diagnostic_storage.contains(entity),
+                    glyph_renderer: glyph_renderer_storage.get(entity).and_then(
+                        |glyph_renderer| {
+                            Some(SceneGlyphRenderer {
+                                layer: glyph_renderer.layer,
+                                order: glyph_renderer.order,
+                                color: glyph_renderer.color,
+                                shader: asset_path(&glyph_renderer.shader, "glyph_renderer")?,
+                                thickness: glyph_renderer.thickness,
+                                smoothness: glyph_renderer.smoothness,
+                                font: asset_path(glyph_renderer.font(), "glyph_renderer")?,
+                                font_size: glyph_renderer.font_size(),
+                                text: glyph_renderer.text().to_owned(),
+                                config: glyph_renderer.config().clone(),
+                            })
+                        },
+                    ),
+                    sprite_renderer: sprite_renderer_storage.get(entity).and_then(
+                        |sprite_renderer| {
+                            Some(SceneSpriteRenderer {
+                                layer: sprite_renderer.layer,
+                                order: sprite_renderer.order,
+                                color: sprite_renderer.color,
+                                shader: asset_path(&sprite_renderer.shader, "sprite_renderer")?,
+                                sprite: asset_path(sprite_renderer.sprite(), "sprite_renderer")?,
+                            })
+                        },
+                    ),
+                    text_field: text_field_storage
+                        .get(entity)
+                        .map(|text_field| scene_text_field(text_field, &ids)),
+                    ui_element: ui_element_storage.get(entity).map(|ui_element| {
+                        let element = ui_mgr.element(ui_element.index());
+
+                        SceneUIElement {
+                            anchor: element.anchor.clone(),
+                            margin: element.margin.clone(),
+                            is_interactible: element.is_interactible(),
+                            order_index: element.order_index(),
+                        }
+                    }),
+                    ui_mask: ui_mask_storage.get(entity).map(|ui_mask| SceneUIMask {
+                        render_itself: ui_mask.render_itself,
+                    }),
+                    ui_scaler: ui_scaler_storage.get(entity).map(|ui_scaler| SceneUIScaler {
+                        mode: ui_scaler.mode,
+                        reference_size: ui_scaler.reference_size,
+                    }),
+                };
+
+                scene_entity.components = components;
+                scene_entity
+            })
+            .collect();
+
+        (Scene::new(entities), ids)
+    };
+
+    // Diagnostics run script handlers, so they are emitted once nothing is borrowed.
+    for warning in warnings {
+        emit_diagnostic_warn!(warning);
+    }
+
+    (scene, ids)
+}
+
+/// Returns the transforms and entities to capture, parents first, along with the `id` given to each entity.
+pub(super) fn captured_entities(
+    world: &World,
+    transform_mgr: &TransformManager,
+    entity_mgr: &EntityManager,
+    root: Option<Entity>,
+) -> (Vec<(u32, Entity)>, HashMap<Entity, u32>) {
+    let hierarchy = transform_mgr.hierarchy();
+    let transforms = match root {
+        Some(root) => match world.read_storage::<Transform>().get(root) {
+            Some(transform) => hierarchy.transform_and_children(transform.index()),
+            None => &[],
+        },
+        None => hierarchy.ordered_transforms(),
+    };
+
+    // Ids are assigned up front, as text fields may refer to entities that come after them.
+    let mut ids = HashMap::with_capacity(transforms.len());
+    let mut captured = Vec::with_capacity(transforms.len());
+
+    for &transform in transforms {
+        let entity = transform_mgr.allocator().entity(transform);
+
+        if world.is_alive(entity) && !entity_mgr.is_destroyed(entity) {
+            ids.insert(entity, ids.len() as u32);
+            captured.push((transform, entity));
+        }
+    }
+
+    (captured, ids)
+}
+
+/// Returns the entity of the transform with its parent, name and local transform, without components.
+pub(super) fn placed_entity(
+    transform_mgr: &TransformManager,
+    ids: &HashMap<Entity, u32>,
+    transform: u32,
+    size: crate::structure::Size,
+) -> SceneEntity {
+    let local = transform_mgr.allocator().transform(transform);
+
+    SceneEntity {
+        id: ids[&transform_mgr.allocator().entity(transform)],
+        parent: transform_mgr
+            .hierarchy()
+            .parent(transform)
+            .and_then(|parent| ids.get(&transform_mgr.allocator().entity(parent)))
+            .copied(),
+        name: transform_mgr
+            .name_manager()
+            .name(transform)
+            .map(|name| name.to_string()),
+        position: local.position,
+        scale: local.scale,
+        angle: local.angle,
+        size,
+        components: SceneComponents::default(),
+    }
+}
+
+/// Refers to the caret and selection entities by their `id`s; ones outside the scene are left out.
+pub(super) fn scene_text_field(
+    text_field: &TextField,
+    ids: &HashMap<Entity, u32>,
+) -> SceneTextField {
+    SceneTextField {
+        text: text_field.editor().text().to_owned(),
+        max_length: text_field.editor().max_length(),
+        caret: text_field.caret.and_then(|caret| ids.get(&caret).copied()),
+        selection: text_field
+            .selection
+            .and_then(|selection| ids.get(&selection).copied()),
+        caret_width: text_field.caret_width,
+    }
+}
@@ -0,0 +1,29 @@
+use crate::asset::AssetLoadError;
+use mlua::prelude::*;
+use std::{io::Error as IOError, path::PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SceneError {
+    #[error("io error: {0}")]
+    IOError(#[from] IOError),
+    #[error("malformed scene: {0}")]
+    JSONError(#[from] serde_json::Error),
+    #[error(
+        "scene version {0} is not supported; expected version {}",
+        super::SCENE_VERSION
+    )]
+    UnsupportedVersion(u32),
+    #[error("entity {0} appears more than once in the scene")]
+    DuplicatedEntity(u32),
+    #[error("entity {entity} refers to parent {parent}, which does not precede it in the scene")]
+    MissingParent { entity: u32, parent: u32 },
+    #[error("failed to load asset {} due to: {error}", path.display())]
+    AssetLoadError {
+        path: PathBuf,
+        #[source]
+        error: AssetLoadError,
+    },
+    #[error("failed to create an entity due to: {0}")]
+    LuaError(#[from] LuaError),
+}
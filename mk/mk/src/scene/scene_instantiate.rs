@@ -0,0 +1,439 @@
+use super::*;
+use crate::{
+    asset::AssetIdentity,
+    component::{TextField, Transform},
+    entity::destroy_entity,
+    script::entity::{
+        AudioSourceParams, CameraParams, EntityBuilderImpl, GlyphRendererParams,
+        SpriteRendererParams, TextFieldParams, UIElementParams, UIMaskParams, UIScalerParams,
+    },
+    EngineContext,
+};
+use mlua::prelude::*;
+use specs::prelude::*;
+use std::{any::Any, collections::HashMap, path::Path};
+
+/// Creates the entities of the scene, emitting `OnCreate` on each of them as it is created.
+/// The roots of the scene are attached to `parent`, if given. Returns the roots.
+///
+/// References between the entities of the scene are remapped to the created entities. If creating any of them fails,
+/// the ones already created are destroyed.
+pub fn instantiate_scene(
+    context: &EngineContext,
+    scene: &Scene,
+    parent: Option<Entity>,
+    lua: &Lua,
+) -> Result<Vec<Entity>, SceneError> {
+    instantiate_scene_with_ids(context, scene, parent, lua).map(|(roots, _)| roots)
+}
+
+/// Instantiates the scene like [`instantiate_scene`], also returning the entity created for each `id` of the scene.
+pub fn instantiate_scene_with_ids(
+    context: &EngineContext,
+    scene: &Scene,
+    parent: Option<Entity>,
+    lua: &Lua,
+) -> Result<(Vec<Entity>, HashMap<u32, Entity>), SceneError> {
+    let parent = parent.and_then(|parent| {
+        context
+            .world()
+            .read_storage::<Transform>()
+            .get(parent)
+            .copied()
+    });
+    let mut roots = Vec::new();
+    let mut entities = HashMap::with_capacity(scene.entities.len());
+
+    let result = instantiate_entities(
+        scene,
+        parent,
+        &mut roots,
+        &mut entities,
+        |scene_entity, mut builder| {
+            set_components(context, &scene_entity.components, &mut builder)?;
+
+            let entity = builder.build(lua)?.0;
+            let transform = *context
+                .world()
+                .read_storage::<Transform>()
+                .get(entity)
+                .unwrap();
+            Ok((entity, transform))
+        },
+    )
+    .map(|_| remap_references(&context.world(), scene, &entities));
+
+    match result {
+        Ok(()) => Ok((
+            roots,
+            entities
+                .into_iter()
+                .map(|(id, (entity, _))| (id, entity))
+                .collect(),
+        )),
+        Err(err) => {
+            for root in roots {
+                destroy_entity(context, root, lua);
+            }
+
+            Err(err)
+        }
+    }
+}
+
+/// Creates the entities of the scene in order with `create`, given a builder holding the name, parent and transform
+/// of each.
+fn instantiate_entities(
+    scene: &Scene,
+    parent: Option<Transform>,
+    roots: &mut Vec<Entity>,
+    entities: &mut HashMap<u32, (Entity, Transform)>,
+    mut create: impl FnMut(&SceneEntity, EntityBuilderImpl) -> Result<(Entity, Transform), SceneError>,
+) -> Result<(), SceneError> {
+    for scene_entity in &scene.entities {
+        if entities.contains_key(&scene_entity.id) {
+            return Err(SceneError::DuplicatedEntity(scene_entity.id));
+        }
+
+        let transform_parent = match scene_entity.parent {
+            Some(id) => match entities.get(&id) {
+                Some(&(_, transform)) => Some(transform),
+                None => {
+                    return Err(SceneError::MissingParent {
+                        entity: scene_entity.id,
+                        parent: id,
+                    })
+                }
+            },
+            None => parent,
+        };
+
+        let builder = EntityBuilderImpl {
+            name: scene_entity.name.clone(),
+            transform_parent,
+            transform_position: Some(scene_entity.position),
+            transform_scale: Some(scene_entity.scale),
+            transform_angle: Some(scene_entity.angle),
+            size: Some(scene_entity.size),
+            ..Default::default()
+        };
+        let (entity, transform) = create(scene_entity, builder)?;
+
+        if scene_entity.parent.is_none() {
+            roots.push(entity);
+        }
+
+        entities.insert(scene_entity.id, (entity, transform));
+    }
+
+    Ok(())
+}
+
+fn set_components(
+    context: &EngineContext,
+    components: &SceneComponents,
+    builder: &mut EntityBuilderImpl,
+) -> Result<(), SceneError> {
+    if let Some(audio_source) = &components.audio_source {
+        builder.audio_source_params = Some(AudioSourceParams {
+            volume: Some(audio_source.volume),
+            clip: match &audio_source.clip {
+                Some(clip) => Some(load(context, clip)?),
+                None => None,
+            },
+        });
+    }
+
+    if let Some(camera) = &components.camera {
+        builder.camera_params = Some(CameraParams {
+            layer: camera.layer,
+            order: camera.order,
+            clear_mode: camera.clear_mode,
+            clear_color: camera.clear_color,
+        });
+    }
+
+    builder.is_diagnostic = components.diagnostic;
+
+    if let Some(glyph_renderer) = &components.glyph_renderer {
+        builder.glyph_renderer_params = Some(GlyphRendererParams {
+            layer: glyph_renderer.layer,
+            order: glyph_renderer.order,
+            color: glyph_renderer.color,
+            shader: load(context, &glyph_renderer.shader)?,
+            thickness: glyph_renderer.thickness,
+            smoothness: glyph_renderer.smoothness,
+            font: load(context, &glyph_renderer.font)?,
+            font_size: glyph_renderer.font_size,
+            text: Some(glyph_renderer.text.clone()),
+            config: Some(glyph_renderer.config.clone()),
+        });
+    }
+
+    if let Some(sprite_renderer) = &components.sprite_renderer {
+        builder.sprite_renderer_params = Some(SpriteRendererParams {
+            layer: sprite_renderer.layer,
+            order: sprite_renderer.order,
+            color: sprite_renderer.color,
+            shader: load(context, &sprite_renderer.shader)?,
+            sprite: load(context, &sprite_renderer.sprite)?,
+        });
+    }
+
+    if let Some(text_field) = &components.text_field {
+        // The caret and selection are set once the entities they refer to are created.
+        builder.text_field_params = Some(TextFieldParams {
+            text: Some(text_field.text.clone()),
+            max_length: text_field.max_length,
+            caret: None,
+            selection: None,
+            caret_width: Some(text_field.caret_width),
+        });
+    }
+
+    if let Some(ui_element) = &components.ui_element {
+        builder.ui_element_params = Some(UIElementParams {
+            anchor: ui_element.anchor.clone(),
+            margin: ui_element.margin.clone(),
+            is_interactible: Some(ui_element.is_interactible),
+            order_index: ui_element.order_index,
+        });
+    }
+
+    if let Some(ui_mask) = &components.ui_mask {
+        builder.ui_mask_params = Some(UIMaskParams {
+            render_itself: ui_mask.render_itself,
+        });
+    }
+
+    if let Some(ui_scaler) = &components.ui_scaler {
+        builder.ui_scaler_params = Some(UIScalerParams {
+            mode: ui_scaler.mode,
+            reference_size: ui_scaler.reference_size,
+        });
+    }
+
+    Ok(())
+}
+
+fn remap_references(world: &World, scene: &Scene, entities: &HashMap<u32, (Entity, Transform)>) {
+    let mut text_field_storage = world.write_storage::<TextField>();
+
+    for scene_entity in &scene.entities {
+        let scene_text_field = match &scene_entity.components.text_field {
+            Some(text_field) => text_field,
+            None => continue,
+        };
+        let text_field = match text_field_storage.get_mut(entities[&scene_entity.id].0) {
+            Some(text_field) => text_field,
+            None => continue,
+        };
+
+        text_field.caret = scene_text_field
+            .caret
+            .and_then(|id| entities.get(&id))
+            .map(|&(entity, _)| entity);
+        text_field.selection = scene_text_field
+            .selection
+            .and_then(|id| entities.get(&id))
+            .map(|&(entity, _)| entity);
+    }
+}
+
+fn load<T>(context: &EngineContext, path: &Path) -> Result<T, SceneError>
+where
+    T: 'static + Clone + Any + Send + Sync + AssetIdentity,
+{
+    context
+        .asset_mgr()
+        .load::<T>(path)
+        .map_err(|error| SceneError::AssetLoadError {
+            path: path.to_path_buf(),
+            error,
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::scene_capture::{captured_entities, placed_entity, scene_text_field};
+    use super::*;
+    use crate::{
+        component::Size, entity::EntityManager, structure::Vec2, transform::TransformManager,
+    };
+
+    fn new_world() -> World {
+        let mut world = World::new();
+        world.register::<Transform>();
+        world.register::<Size>();
+        world.register::<TextField>();
+        world
+    }
+
+    fn spawn(
+        world: &mut World,
+        transform_mgr: &mut TransformManager,
+        name: &str,
+        parent: Option<Entity>,
+        position: Vec2,
+        angle: f32,
+    ) -> Entity {
+        let transform_parent =
+            parent.map(|parent| *world.read_storage::<Transform>().get(parent).unwrap());
+
+        EntityBuilderImpl {
+            name: Some(name.to_owned()),
+            transform_parent,
+            transform_position: Some(position),
+            transform_scale: Some(Vec2::new(2f32, 3f32)),
+            transform_angle: Some(angle),
+            ..Default::default()
+        }
+        .place(world, transform_mgr)
+        .0
+    }
+
+    fn capture(world: &World, transform_mgr: &TransformManager) -> Scene {
+        let (captured, ids) = captured_entities(world, transform_mgr, &EntityManager::new(), None);
+        let size_storage = world.read_storage::<Size>();
+        let text_field_storage = world.read_storage::<TextField>();
+
+        Scene::new(
+            captured
+                .into_iter()
+                .map(|(transform, entity)| {
+                    let size = size_storage.get(entity).unwrap().size;
+                    let mut scene_entity = placed_entity(transform_mgr, &ids, transform, size);
+                    scene_entity.components.text_field = text_field_storage
+                        .get(entity)
+                        .map(|text_field| scene_text_field(text_field, &ids));
+                    scene_entity
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn hierarchies_round_trip_into_a_fresh_world() {
+        let mut world = new_world();
+        let mut transform_mgr = TransformManager::new();
+        let root = spawn(
+            &mut world,
+            &mut transform_mgr,
+            "root",
+            None,
+            Vec2::new(1f32, 2f32),
+            0.5f32,
+        );
+        let field = spawn(
+            &mut world,
+            &mut transform_mgr,
+            "field",
+            Some(root),
+            Vec2::new(3f32, 4f32),
+            0f32,
+        );
+        // The caret comes after the text field referring to it.
+        let caret = spawn(
+            &mut world,
+            &mut transform_mgr,
+            "caret",
+            Some(field),
+            Vec2::new(5f32, 6f32),
+            1f32,
+        );
+        let mut text_field = TextField::new("name");
+        text_field.caret = Some(caret);
+        world
+            .write_storage::<TextField>()
+            .insert(field, text_field)
+            .unwrap();
+
+        let scene = Scene::from_json(&capture(&world, &transform_mgr).to_json().unwrap()).unwrap();
+
+        let mut loaded = new_world();
+        let mut loaded_transform_mgr = TransformManager::new();
+        // Taken up front, so that the loaded entities end up with other ids.
+        for name in ["other", "another"] {
+            spawn(
+                &mut loaded,
+                &mut loaded_transform_mgr,
+                name,
+                None,
+                Vec2::new(0f32, 0f32),
+                0f32,
+            );
+        }
+
+        let mut roots = Vec::new();
+        let mut entities = HashMap::new();
+        instantiate_entities(
+            &scene,
+            None,
+            &mut roots,
+            &mut entities,
+            |scene_entity, mut builder| {
+                let entity = builder.place(&mut loaded, &mut loaded_transform_mgr).0;
+
+                if let Some(text_field) = &scene_entity.components.text_field {
+                    loaded
+                        .write_storage::<TextField>()
+                        .insert(entity, TextField::new(text_field.text.clone()))
+                        .unwrap();
+                }
+
+                let transform = *loaded.read_storage::<Transform>().get(entity).unwrap();
+                Ok((entity, transform))
+            },
+        )
+        .unwrap();
+        remap_references(&loaded, &scene, &entities);
+
+        let named = |name: &str| {
+            entities
+                .values()
+                .copied()
+                .find(|(_, transform)| {
+                    loaded_transform_mgr
+                        .name_manager()
+                        .name(transform.index())
+                        .is_some_and(|transform_name| transform_name.as_str() == name)
+                })
+                .unwrap()
+        };
+        let (loaded_root, root_transform) = named("root");
+        let (loaded_field, field_transform) = named("field");
+        let (loaded_caret, caret_transform) = named("caret");
+        let hierarchy = loaded_transform_mgr.hierarchy();
+        let local = |transform: Transform| {
+            loaded_transform_mgr
+                .allocator()
+                .transform(transform.index())
+        };
+
+        assert_eq!(entities.len(), 3);
+        assert_eq!(roots, vec![loaded_root]);
+        assert_ne!(loaded_caret, caret);
+        assert_eq!(hierarchy.parent(root_transform.index()), None);
+        assert_eq!(
+            hierarchy.parent(field_transform.index()),
+            Some(root_transform.index())
+        );
+        assert_eq!(
+            hierarchy.parent(caret_transform.index()),
+            Some(field_transform.index())
+        );
+
+        assert_eq!(local(root_transform).position, Vec2::new(1f32, 2f32));
+        assert_eq!(local(root_transform).angle, 0.5f32);
+        assert_eq!(local(field_transform).position, Vec2::new(3f32, 4f32));
+        assert_eq!(local(caret_transform).position, Vec2::new(5f32, 6f32));
+        assert_eq!(local(caret_transform).scale, Vec2::new(2f32, 3f32));
+        assert_eq!(local(caret_transform).angle, 1f32);
+
+        let text_field_storage = loaded.read_storage::<TextField>();
+        let text_field = text_field_storage.get(loaded_field).unwrap();
+        assert_eq!(text_field.editor().text(), "name");
+        assert_eq!(text_field.caret, Some(loaded_caret));
+        assert_eq!(text_field.selection, None);
+    }
+}
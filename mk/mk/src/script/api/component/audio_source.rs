@@ -1,4 +1,7 @@
-use crate::handles::*;
+use crate::{
+    handles::*,
+    script::{LuaClassMeta, LuaFieldMeta, LuaMethodMeta, LuaUserDataMetaProvider},
+};
 use mlua::prelude::*;
 
 pub type ComponentAudioSource = super::Component<crate::component::AudioSource>;
@@ -52,3 +55,49 @@ impl LuaUserData for ComponentAudioSource {
         });
     }
 }
+
+impl LuaUserDataMetaProvider for ComponentAudioSource {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "ComponentAudioSource",
+            doc: "Plays an audio clip.",
+            fields: &[
+                LuaFieldMeta {
+                    name: "is_playing",
+                    ty: "boolean?",
+                    doc: "",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "volume",
+                    ty: "number?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "clip",
+                    ty: "AudioClip?",
+                    doc: "",
+                    is_readonly: false,
+                },
+            ],
+            methods: &[
+                super::IS_EXISTS_META,
+                LuaMethodMeta {
+                    name: "play",
+                    doc: "",
+                    is_static: false,
+                    params: &[],
+                    returns: None,
+                },
+                LuaMethodMeta {
+                    name: "stop",
+                    doc: "",
+                    is_static: false,
+                    params: &[],
+                    returns: None,
+                },
+            ],
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::script::{LuaClassMeta, LuaFieldMeta, LuaUserDataMetaProvider};
 use mlua::prelude::*;
 
 pub type ComponentCamera = super::Component<crate::component::Camera>;
@@ -51,3 +52,39 @@ impl LuaUserData for ComponentCamera {
         });
     }
 }
+
+impl LuaUserDataMetaProvider for ComponentCamera {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "ComponentCamera",
+            doc: "Renders the layers of its mask, in ascending `order`.",
+            fields: &[
+                LuaFieldMeta {
+                    name: "layer",
+                    ty: "Layer?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "order",
+                    ty: "integer?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "clear_mode",
+                    ty: "ClearMode?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "clear_color",
+                    ty: "Color?",
+                    doc: "",
+                    is_readonly: false,
+                },
+            ],
+            methods: &[super::IS_EXISTS_META],
+        }
+    }
+}
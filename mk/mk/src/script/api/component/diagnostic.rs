@@ -1,3 +1,4 @@
+use crate::script::{LuaClassMeta, LuaUserDataMetaProvider};
 use mlua::prelude::*;
 
 pub type ComponentDiagnostic = super::Component<crate::component::Diagnostic>;
@@ -17,3 +18,14 @@ impl LuaUserData for ComponentDiagnostic {
         });
     }
 }
+
+impl LuaUserDataMetaProvider for ComponentDiagnostic {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "ComponentDiagnostic",
+            doc: "Marks an entity created by the engine for diagnostics, e.g. the console.",
+            fields: &[],
+            methods: &[super::IS_EXISTS_META],
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{engine::use_context, handles::*};
+use crate::{
+    engine::use_context,
+    handles::*,
+    script::{LuaClassMeta, LuaFieldMeta, LuaMethodMeta, LuaUserDataMetaProvider},
+};
 use mlua::prelude::*;
 
 pub type ComponentGlyphRenderer = super::Component<crate::component::GlyphRenderer>;
@@ -104,3 +108,84 @@ impl LuaUserData for ComponentGlyphRenderer {
         });
     }
 }
+
+impl LuaUserDataMetaProvider for ComponentGlyphRenderer {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "ComponentGlyphRenderer",
+            doc: "Renders a text.",
+            fields: &[
+                LuaFieldMeta {
+                    name: "layer",
+                    ty: "Layer?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "order",
+                    ty: "integer?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "color",
+                    ty: "Color?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "shader",
+                    ty: "Shader?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "thickness",
+                    ty: "number?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "smoothness",
+                    ty: "number?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "font",
+                    ty: "Font?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "font_size",
+                    ty: "number?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "text",
+                    ty: "string?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "config",
+                    ty: "GlyphLayoutConfig?",
+                    doc: "",
+                    is_readonly: false,
+                },
+            ],
+            methods: &[
+                super::IS_EXISTS_META,
+                LuaMethodMeta {
+                    name: "compute_size",
+                    doc: "Returns the size of the laid out text.",
+                    is_static: false,
+                    params: &[],
+                    returns: Some("Size?"),
+                },
+            ],
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{engine::use_context, script::api::LuaApiTable};
+use crate::{
+    engine::use_context,
+    script::{api::LuaApiTable, register_lua_class, LuaMethodMeta},
+};
 use mlua::prelude::*;
 use specs::prelude::*;
 use std::{
@@ -92,6 +95,7 @@ mod diagnostic;
 mod glyph_renderer;
 mod size;
 mod sprite_renderer;
+mod text_field;
 // mod tilemap_renderer;
 mod transform;
 mod ui_element;
@@ -105,16 +109,38 @@ pub use diagnostic::*;
 pub use glyph_renderer::*;
 pub use size::*;
 pub use sprite_renderer::*;
+pub use text_field::*;
 // pub use tilemap_renderer::*;
 pub use transform::*;
 pub use ui_element::*;
 pub use ui_mask::*;
 pub use ui_scaler::*;
 
+/// The `is_exists` method every component has.
+const IS_EXISTS_META: LuaMethodMeta = LuaMethodMeta {
+    name: "is_exists",
+    doc: "Returns whether the entity still has the component.",
+    is_static: false,
+    params: &[],
+    returns: Some("boolean"),
+};
+
 pub struct ComponentModule;
 
 impl LuaApiTable for ComponentModule {
     fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        // Components have no table of their own; they are reached through the fields of `Entity`.
+        register_lua_class::<ComponentAudioSource>(lua);
+        register_lua_class::<ComponentCamera>(lua);
+        register_lua_class::<ComponentDiagnostic>(lua);
+        register_lua_class::<ComponentGlyphRenderer>(lua);
+        register_lua_class::<ComponentSize>(lua);
+        register_lua_class::<ComponentSpriteRenderer>(lua);
+        register_lua_class::<ComponentTextField>(lua);
+        register_lua_class::<ComponentTransform>(lua);
+        register_lua_class::<ComponentUIElement>(lua);
+        register_lua_class::<ComponentUIMask>(lua);
+        register_lua_class::<ComponentUIScaler>(lua);
         let table = lua.create_table()?;
 
         Ok(table)
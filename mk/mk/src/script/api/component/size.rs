@@ -1,3 +1,4 @@
+use crate::script::{LuaClassMeta, LuaFieldMeta, LuaUserDataMetaProvider};
 use mlua::prelude::*;
 
 pub type ComponentSize = super::Component<crate::component::Size>;
@@ -26,3 +27,19 @@ impl LuaUserData for ComponentSize {
         });
     }
 }
+
+impl LuaUserDataMetaProvider for ComponentSize {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "ComponentSize",
+            doc: "The size of an entity, used by the renderers and UI elements.",
+            fields: &[LuaFieldMeta {
+                name: "size",
+                ty: "Size?",
+                doc: "",
+                is_readonly: false,
+            }],
+            methods: &[super::IS_EXISTS_META],
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{engine::use_context, handles::*};
+use crate::{
+    engine::use_context,
+    handles::*,
+    script::{LuaClassMeta, LuaFieldMeta, LuaUserDataMetaProvider},
+};
 use mlua::prelude::*;
 
 pub type ComponentSpriteRenderer = super::Component<crate::component::SpriteRenderer>;
@@ -59,3 +63,45 @@ impl LuaUserData for ComponentSpriteRenderer {
         });
     }
 }
+
+impl LuaUserDataMetaProvider for ComponentSpriteRenderer {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "ComponentSpriteRenderer",
+            doc: "Renders a sprite.",
+            fields: &[
+                LuaFieldMeta {
+                    name: "layer",
+                    ty: "Layer?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "order",
+                    ty: "integer?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "color",
+                    ty: "Color?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "shader",
+                    ty: "Shader?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "sprite",
+                    ty: "Sprite?",
+                    doc: "",
+                    is_readonly: false,
+                },
+            ],
+            methods: &[super::IS_EXISTS_META],
+        }
+    }
+}
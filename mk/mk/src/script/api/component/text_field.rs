@@ -0,0 +1,186 @@
+use crate::{
+    engine::use_context,
+    script::{entity::Entity, LuaClassMeta, LuaFieldMeta, LuaMethodMeta, LuaUserDataMetaProvider},
+};
+use mlua::prelude::*;
+
+pub type ComponentTextField = super::Component<crate::component::TextField>;
+
+impl LuaUserData for ComponentTextField {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("text", |_lua, this| {
+            Ok(this.with_ref(|this| this.editor().text().to_owned()))
+        });
+        fields.add_field_method_get("max_length", |_lua, this| {
+            Ok(this.with_ref(|this| this.editor().max_length()))
+        });
+        fields.add_field_method_get("caret_index", |_lua, this| {
+            Ok(this.with_ref(|this| this.editor().caret()))
+        });
+        fields.add_field_method_get("selected_text", |_lua, this| {
+            Ok(this.with_ref(|this| this.editor().selected_text()))
+        });
+        fields.add_field_method_get("caret", |_lua, this| {
+            Ok(this.with_ref(|this| this.caret.map(Entity::new)))
+        });
+        fields.add_field_method_get("selection", |_lua, this| {
+            Ok(this.with_ref(|this| this.selection.map(Entity::new)))
+        });
+        fields.add_field_method_get("caret_width", |_lua, this| {
+            Ok(this.with_ref(|this| this.caret_width))
+        });
+        fields.add_field_method_get("is_focused", |_lua, this| {
+            Ok(use_context().ui_event_mgr().focus == Some(this.entity))
+        });
+
+        fields.add_field_method_set("text", |_lua, this, text: LuaString| {
+            let text = text.to_str()?.to_owned();
+            this.with_mut(|this| this.editor_mut().set_text(text));
+            Ok(())
+        });
+        fields.add_field_method_set("max_length", |_lua, this, max_length| {
+            this.with_mut(|this| this.editor_mut().set_max_length(max_length));
+            Ok(())
+        });
+        fields.add_field_method_set("caret_index", |_lua, this, caret| {
+            this.with_mut(|this| this.editor_mut().move_to(caret, false));
+            Ok(())
+        });
+        fields.add_field_method_set("caret", |_lua, this, caret: Option<Entity>| {
+            this.with_mut(|this| {
+                this.caret = caret.map(|entity| entity.0);
+                this.mark_as_dirty();
+            });
+            Ok(())
+        });
+        fields.add_field_method_set("selection", |_lua, this, selection: Option<Entity>| {
+            this.with_mut(|this| {
+                this.selection = selection.map(|entity| entity.0);
+                this.mark_as_dirty();
+            });
+            Ok(())
+        });
+        fields.add_field_method_set("caret_width", |_lua, this, caret_width| {
+            this.with_mut(|this| {
+                this.caret_width = caret_width;
+                this.mark_as_dirty();
+            });
+            Ok(())
+        });
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("is_exists", |_lua, this, ()| Ok(this.is_exists()));
+
+        methods.add_meta_method(LuaMetaMethod::ToString, |_lua, this, ()| {
+            Ok(format!(
+                "ComponentTextField(entity={:?}, is_exists={})",
+                this.entity,
+                this.is_exists()
+            ))
+        });
+
+        methods.add_method("select_all", |_lua, this, ()| {
+            this.with_mut(|this| this.editor_mut().select_all());
+            Ok(())
+        });
+        methods.add_method("focus", |_lua, this, ()| {
+            use_context()
+                .ui_event_mgr_mut()
+                .set_focus(Some(this.entity));
+            Ok(())
+        });
+        methods.add_method("blur", |_lua, this, ()| {
+            let mut ui_event_mgr = use_context().ui_event_mgr_mut();
+
+            if ui_event_mgr.focus == Some(this.entity) {
+                ui_event_mgr.set_focus(None);
+            }
+
+            Ok(())
+        });
+    }
+}
+
+impl LuaUserDataMetaProvider for ComponentTextField {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "ComponentTextField",
+            doc: "An editable text, rendered by the glyph renderer of the entity.",
+            fields: &[
+                LuaFieldMeta {
+                    name: "text",
+                    ty: "string?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "max_length",
+                    ty: "integer?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "caret_index",
+                    ty: "integer?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "selected_text",
+                    ty: "string?",
+                    doc: "",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "caret",
+                    ty: "Entity?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "selection",
+                    ty: "Entity?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "caret_width",
+                    ty: "number?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "is_focused",
+                    ty: "boolean",
+                    doc: "",
+                    is_readonly: true,
+                },
+            ],
+            methods: &[
+                super::IS_EXISTS_META,
+                LuaMethodMeta {
+                    name: "select_all",
+                    doc: "",
+                    is_static: false,
+                    params: &[],
+                    returns: None,
+                },
+                LuaMethodMeta {
+                    name: "focus",
+                    doc: "",
+                    is_static: false,
+                    params: &[],
+                    returns: None,
+                },
+                LuaMethodMeta {
+                    name: "blur",
+                    doc: "",
+                    is_static: false,
+                    params: &[],
+                    returns: None,
+                },
+            ],
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use crate::engine::use_context;
+use crate::{
+    engine::use_context,
+    script::{LuaClassMeta, LuaFieldMeta, LuaUserDataMetaProvider},
+};
 use mlua::prelude::*;
 
 pub type ComponentTransform = crate::component::Transform;
@@ -109,3 +112,57 @@ impl LuaUserData for ComponentTransform {
         });
     }
 }
+
+impl LuaUserDataMetaProvider for ComponentTransform {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "ComponentTransform",
+            doc: "Places the entity in the hierarchy; unprefixed fields are in world space.",
+            fields: &[
+                LuaFieldMeta {
+                    name: "parent",
+                    ty: "ComponentTransform?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "position",
+                    ty: "Vec2",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "scale",
+                    ty: "Vec2",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "angle",
+                    ty: "number",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "local_position",
+                    ty: "Vec2?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "local_scale",
+                    ty: "Vec2?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "local_angle",
+                    ty: "number?",
+                    doc: "",
+                    is_readonly: false,
+                },
+            ],
+            methods: &[],
+        }
+    }
+}
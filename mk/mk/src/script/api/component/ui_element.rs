@@ -1,3 +1,4 @@
+use crate::script::{LuaClassMeta, LuaFieldMeta, LuaUserDataMetaProvider};
 use mlua::prelude::*;
 
 pub type ComponentUIElement = super::Component<crate::component::UIElement>;
@@ -67,3 +68,39 @@ impl LuaUserData for ComponentUIElement {
         });
     }
 }
+
+impl LuaUserDataMetaProvider for ComponentUIElement {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "ComponentUIElement",
+            doc: "Lays out the entity relative to its parent UI element.",
+            fields: &[
+                LuaFieldMeta {
+                    name: "anchor",
+                    ty: "UIAnchor?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "margin",
+                    ty: "UIMargin?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "is_interactible",
+                    ty: "boolean?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "order_index",
+                    ty: "integer?",
+                    doc: "",
+                    is_readonly: false,
+                },
+            ],
+            methods: &[super::IS_EXISTS_META],
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::script::{LuaClassMeta, LuaFieldMeta, LuaUserDataMetaProvider};
 use mlua::prelude::*;
 
 pub type ComponentUIMask = super::Component<crate::component::UIMask>;
@@ -28,3 +29,19 @@ impl LuaUserData for ComponentUIMask {
         });
     }
 }
+
+impl LuaUserDataMetaProvider for ComponentUIMask {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "ComponentUIMask",
+            doc: "Clips the rendering of its children to the entity.",
+            fields: &[LuaFieldMeta {
+                name: "render_itself",
+                ty: "boolean?",
+                doc: "",
+                is_readonly: false,
+            }],
+            methods: &[super::IS_EXISTS_META],
+        }
+    }
+}
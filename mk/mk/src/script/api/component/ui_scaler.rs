@@ -1,3 +1,4 @@
+use crate::script::{LuaClassMeta, LuaFieldMeta, LuaUserDataMetaProvider};
 use mlua::prelude::*;
 
 pub type ComponentUIScaler = super::Component<crate::component::UIScaler>;
@@ -35,3 +36,27 @@ impl LuaUserData for ComponentUIScaler {
         });
     }
 }
+
+impl LuaUserDataMetaProvider for ComponentUIScaler {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "ComponentUIScaler",
+            doc: "Scales its children from a reference size to the screen.",
+            fields: &[
+                LuaFieldMeta {
+                    name: "mode",
+                    ty: "UIScaleMode?",
+                    doc: "",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "reference_size",
+                    ty: "Size?",
+                    doc: "",
+                    is_readonly: false,
+                },
+            ],
+            methods: &[super::IS_EXISTS_META],
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{
+    diagnostic::{complete_console_line, execute_console_line, ConsoleCommand},
+    engine::use_context,
+    script::{api::LuaApiTable, FFIFunction, LuaMethodMeta, LuaParamMeta},
+};
+use mlua::prelude::*;
+
+pub struct ConsoleModule;
+
+impl LuaApiTable for ConsoleModule {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        table.set(
+            "is_open",
+            lua.create_function(|_lua, ()| Ok(use_context().diagnostic_mgr().console_enabled))?,
+        )?;
+        table.set(
+            "set_open",
+            lua.create_function(|_lua, is_open: bool| {
+                use_context().diagnostic_mgr_mut().console_enabled = is_open;
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "toggle",
+            lua.create_function(|_lua, ()| {
+                let mut diagnostic_mgr = use_context().diagnostic_mgr_mut();
+                diagnostic_mgr.console_enabled = !diagnostic_mgr.console_enabled;
+                Ok(diagnostic_mgr.console_enabled)
+            })?,
+        )?;
+        table.set(
+            "execute",
+            lua.create_function(|_lua, line: String| {
+                execute_console_line(use_context(), &line);
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "complete",
+            lua.create_function(|lua, line: String| {
+                let candidates = complete_console_line(lua, &line)?;
+                let completed = match candidates.split_first() {
+                    Some((first, rest)) => rest.iter().fold(first.as_str(), |prefix, candidate| {
+                        common_prefix(prefix, candidate)
+                    }),
+                    None => line.as_str(),
+                };
+                Ok((completed.to_owned(), candidates))
+            })?,
+        )?;
+        table.set(
+            "previous_history",
+            lua.create_function(|_lua, ()| {
+                Ok(use_context()
+                    .diagnostic_mgr_mut()
+                    .console_mut()
+                    .previous_history()
+                    .map(str::to_owned))
+            })?,
+        )?;
+        table.set(
+            "next_history",
+            lua.create_function(|_lua, ()| {
+                Ok(use_context()
+                    .diagnostic_mgr_mut()
+                    .console_mut()
+                    .next_history()
+                    .map(str::to_owned))
+            })?,
+        )?;
+        table.set(
+            "output",
+            lua.create_function(|_lua, max_lines: Option<usize>| {
+                let diagnostic_mgr = use_context().diagnostic_mgr();
+                let output = diagnostic_mgr.console().output().collect::<Vec<_>>();
+                let skip = max_lines.map_or(0, |max_lines| output.len().saturating_sub(max_lines));
+                Ok(output[skip..].join("\n"))
+            })?,
+        )?;
+        table.set(
+            "print",
+            lua.create_function(|_lua, text: String| {
+                use_context().diagnostic_mgr_mut().console_mut().print(text);
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "clear",
+            lua.create_function(|_lua, ()| {
+                use_context().diagnostic_mgr_mut().console_mut().clear();
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "register_command",
+            lua.create_function(
+                |lua, (name, function, help): (String, LuaFunction, Option<String>)| {
+                    use_context()
+                        .diagnostic_mgr_mut()
+                        .console_mut()
+                        .register_command(
+                            name,
+                            ConsoleCommand {
+                                help: help.unwrap_or_default(),
+                                function: FFIFunction::new(lua, function)?,
+                            },
+                        );
+                    Ok(())
+                },
+            )?,
+        )?;
+        table.set(
+            "unregister_command",
+            lua.create_function(|_lua, name: String| {
+                Ok(use_context()
+                    .diagnostic_mgr_mut()
+                    .console_mut()
+                    .unregister_command(&name))
+            })?,
+        )?;
+
+        Ok(table)
+    }
+
+    fn lua_functions() -> &'static [LuaMethodMeta] {
+        &[
+            LuaMethodMeta {
+                name: "is_open",
+                doc: "Returns whether the console is shown.",
+                is_static: true,
+                params: &[],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "set_open",
+                doc: "Shows or hides the console.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "is_open",
+                    ty: "boolean",
+                }],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "toggle",
+                doc: "Shows the console if hidden and hides it otherwise. Returns whether it is now shown.",
+                is_static: true,
+                params: &[],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "execute",
+                doc: "Runs a line as if it were typed into the console.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "line",
+                    ty: "string",
+                }],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "complete",
+                doc: "Completes the last name of a line. Returns the completed line and every candidate.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "line",
+                    ty: "string",
+                }],
+                returns: Some("string, string[]"),
+            },
+            LuaMethodMeta {
+                name: "previous_history",
+                doc: "Steps back through the executed lines. Returns `nil` past the oldest one.",
+                is_static: true,
+                params: &[],
+                returns: Some("string?"),
+            },
+            LuaMethodMeta {
+                name: "next_history",
+                doc: "Steps forward through the executed lines. Returns `nil` past the newest one.",
+                is_static: true,
+                params: &[],
+                returns: Some("string?"),
+            },
+            LuaMethodMeta {
+                name: "output",
+                doc: "Returns the output of the console, limited to the last `max_lines` lines if given.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "max_lines",
+                    ty: "integer?",
+                }],
+                returns: Some("string"),
+            },
+            LuaMethodMeta {
+                name: "print",
+                doc: "Appends a line to the output of the console.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "text",
+                    ty: "string",
+                }],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "clear",
+                doc: "Clears the output of the console.",
+                is_static: true,
+                params: &[],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "register_command",
+                doc: "Registers a command, called with the arguments typed after its name.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "name",
+                        ty: "string",
+                    },
+                    LuaParamMeta {
+                        name: "callback",
+                        ty: "function",
+                    },
+                    LuaParamMeta {
+                        name: "help",
+                        ty: "string?",
+                    },
+                ],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "unregister_command",
+                doc: "Unregisters a command. Returns whether it was registered.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "name",
+                    ty: "string",
+                }],
+                returns: Some("boolean"),
+            },
+        ]
+    }
+}
+
+fn common_prefix<'a>(lhs: &'a str, rhs: &str) -> &'a str {
+    let length = lhs
+        .char_indices()
+        .zip(rhs.chars())
+        .find(|((_, l), r)| l != r)
+        .map_or(lhs.len().min(rhs.len()), |((index, _), _)| index);
+    &lhs[..length]
+}
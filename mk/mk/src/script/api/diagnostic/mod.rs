@@ -0,0 +1,81 @@
+use crate::{
+    diagnostic::resume_from_script_error,
+    engine::use_context,
+    script::{api::LuaApiTable, LuaMethodMeta, LuaParamMeta},
+};
+use mlua::prelude::*;
+
+pub struct DiagnosticModule;
+
+impl LuaApiTable for DiagnosticModule {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        table.set(
+            "pause_on_script_error",
+            lua.create_function(|_lua, ()| {
+                Ok(use_context().diagnostic_mgr().pause_on_script_error())
+            })?,
+        )?;
+        table.set(
+            "set_pause_on_script_error",
+            lua.create_function(|_lua, pause_on_script_error: bool| {
+                use_context()
+                    .diagnostic_mgr_mut()
+                    .set_pause_on_script_error(pause_on_script_error);
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "script_error",
+            lua.create_function(|_lua, ()| {
+                Ok(use_context()
+                    .diagnostic_mgr()
+                    .script_error_pause()
+                    .map(|pause| pause.diagnostic.clone()))
+            })?,
+        )?;
+        table.set(
+            "resume",
+            lua.create_function(|_lua, ()| Ok(resume_from_script_error(use_context())))?,
+        )?;
+
+        Ok(table)
+    }
+
+    fn lua_functions() -> &'static [LuaMethodMeta] {
+        &[
+            LuaMethodMeta {
+                name: "pause_on_script_error",
+                doc: "Returns whether the game pauses when a script error is reported.",
+                is_static: true,
+                params: &[],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "set_pause_on_script_error",
+                doc: "Sets whether the game pauses when a script error is reported.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "pause_on_script_error",
+                    ty: "boolean",
+                }],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "script_error",
+                doc: "Returns the script error the game is paused on, if any.",
+                is_static: true,
+                params: &[],
+                returns: Some("Diagnostic?"),
+            },
+            LuaMethodMeta {
+                name: "resume",
+                doc: "Resumes the game paused on a script error. Returns whether it was paused.",
+                is_static: true,
+                params: &[],
+                returns: Some("boolean"),
+            },
+        ]
+    }
+}
@@ -1,11 +1,15 @@
+use super::{has_component, remove_component, ComponentParams};
 use crate::{
     component::*,
     engine::use_context,
-    event::{EntityEventHandler, LuaEvent},
+    entity::destroy_entity,
+    event::{EntityEventHandler, EntityEventHandlerOptions, LuaEvent},
     script::{
         api::{component::*, LuaApiTable},
-        FFIFunction,
+        register_lua_class, FFIFunction, LuaClassMeta, LuaFieldMeta, LuaMethodMeta, LuaParamMeta,
+        LuaUserDataMetaProvider,
     },
+    transform::TransformManager,
 };
 use mlua::prelude::*;
 use smartstring::SmartString;
@@ -40,10 +44,29 @@ impl Entity {
             .get_mut(self.0)
             .map(|component| f(component))
     }
+
+    /// Returns the entity followed by its parents from the nearest, along which entity events propagate.
+    /// An entity without a transform has no parents.
+    pub fn event_path(self) -> Vec<Entity> {
+        match self.with_ref(|component: &Transform| component.index()) {
+            Some(index) => Self::transform_event_path(&use_context().transform_mgr(), index),
+            None => vec![self],
+        }
+    }
+
+    /// Returns the entity of the transform followed by the entities of its parents from the nearest.
+    pub fn transform_event_path(transform_mgr: &TransformManager, index: u32) -> Vec<Entity> {
+        let allocator = transform_mgr.allocator();
+        std::iter::once(index)
+            .chain(transform_mgr.hierarchy().parents(index).iter().copied())
+            .map(|transform| Self::new(allocator.entity(transform)))
+            .collect()
+    }
 }
 
 impl LuaApiTable for Entity {
     fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        register_lua_class::<Self>(lua);
         let table = lua.create_table()?;
 
         table.set(
@@ -78,6 +101,217 @@ impl LuaApiTable for Entity {
     }
 }
 
+impl LuaUserDataMetaProvider for Entity {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "Entity",
+            doc: "An entity of the world; its components are reached through its fields.",
+            fields: &[
+                LuaFieldMeta {
+                    name: "name",
+                    ty: "string?",
+                    doc: "The name of its transform; `nil` without a transform.",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "audio_source",
+                    ty: "ComponentAudioSource",
+                    doc: "",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "camera",
+                    ty: "ComponentCamera",
+                    doc: "",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "diagnostic",
+                    ty: "ComponentDiagnostic",
+                    doc: "",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "glyph_renderer",
+                    ty: "ComponentGlyphRenderer",
+                    doc: "",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "size",
+                    ty: "ComponentSize",
+                    doc: "",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "sprite_renderer",
+                    ty: "ComponentSpriteRenderer",
+                    doc: "",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "text_field",
+                    ty: "ComponentTextField",
+                    doc: "",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "transform",
+                    ty: "ComponentTransform?",
+                    doc: "`nil` without a transform.",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "ui_element",
+                    ty: "ComponentUIElement",
+                    doc: "",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "ui_mask",
+                    ty: "ComponentUIMask",
+                    doc: "",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "ui_scaler",
+                    ty: "ComponentUIScaler",
+                    doc: "",
+                    is_readonly: true,
+                },
+            ],
+            methods: &[
+                LuaMethodMeta {
+                    name: "find_by_name",
+                    doc: "Returns the first entity with the given name.",
+                    is_static: true,
+                    params: &[LuaParamMeta {
+                        name: "name",
+                        ty: "string",
+                    }],
+                    returns: Some("Entity?"),
+                },
+                LuaMethodMeta {
+                    name: "find_all_by_name",
+                    doc: "Returns every entity with the given name.",
+                    is_static: true,
+                    params: &[LuaParamMeta {
+                        name: "name",
+                        ty: "string",
+                    }],
+                    returns: Some("Entity[]"),
+                },
+                LuaMethodMeta {
+                    name: "listen",
+                    doc: "See the entity events for the arguments of the handler.",
+                    is_static: false,
+                    params: &[
+                        LuaParamMeta {
+                            name: "event_name",
+                            ty: "EntityEventName|string",
+                        },
+                        LuaParamMeta {
+                            name: "handler",
+                            ty: "function|EntityEventHandler",
+                        },
+                        LuaParamMeta {
+                            name: "options",
+                            ty: "table?",
+                        },
+                    ],
+                    returns: Some("EntityEventHandler"),
+                },
+                LuaMethodMeta {
+                    name: "unlisten",
+                    doc: "",
+                    is_static: false,
+                    params: &[
+                        LuaParamMeta {
+                            name: "event_name",
+                            ty: "string",
+                        },
+                        LuaParamMeta {
+                            name: "handler",
+                            ty: "EntityEventHandler",
+                        },
+                    ],
+                    returns: None,
+                },
+                LuaMethodMeta {
+                    name: "is_alive",
+                    doc: "Returns `false` once the entity is destroyed.",
+                    is_static: false,
+                    params: &[],
+                    returns: Some("boolean"),
+                },
+                LuaMethodMeta {
+                    name: "destroy",
+                    doc: "Destroys it with its children; `false` if already destroyed.",
+                    is_static: false,
+                    params: &[],
+                    returns: Some("boolean"),
+                },
+                LuaMethodMeta {
+                    name: "add_component",
+                    doc: "Adds the component with the given name, replacing the existing one.",
+                    is_static: false,
+                    params: &[
+                        LuaParamMeta {
+                            name: "name",
+                            ty: "string",
+                        },
+                        LuaParamMeta {
+                            name: "params",
+                            ty: "table?",
+                        },
+                    ],
+                    returns: None,
+                },
+                LuaMethodMeta {
+                    name: "remove_component",
+                    doc: "Returns `false` if the entity does not have the component.",
+                    is_static: false,
+                    params: &[LuaParamMeta {
+                        name: "name",
+                        ty: "string",
+                    }],
+                    returns: Some("boolean"),
+                },
+                LuaMethodMeta {
+                    name: "has_component",
+                    doc: "",
+                    is_static: false,
+                    params: &[LuaParamMeta {
+                        name: "name",
+                        ty: "string",
+                    }],
+                    returns: Some("boolean"),
+                },
+                LuaMethodMeta {
+                    name: "emit",
+                    doc: "Bubbles through the parents if asked; `true` if a handler stopped it.",
+                    is_static: false,
+                    params: &[
+                        LuaParamMeta {
+                            name: "event_name",
+                            ty: "string",
+                        },
+                        LuaParamMeta {
+                            name: "event",
+                            ty: "table?",
+                        },
+                        LuaParamMeta {
+                            name: "bubbles",
+                            ty: "boolean?",
+                        },
+                    ],
+                    returns: Some("boolean"),
+                },
+            ],
+        }
+    }
+}
+
 impl LuaUserData for Entity {
     fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
         fields.add_field_method_get("name", |_lua, this| {
@@ -119,6 +353,9 @@ impl LuaUserData for Entity {
         fields.add_field_method_get("sprite_renderer", |_lua, this| {
             Ok(ComponentSpriteRenderer::new(this.0))
         });
+        fields.add_field_method_get("text_field", |_lua, this| {
+            Ok(ComponentTextField::new(this.0))
+        });
         // fields.add_field_method_get("tilemap_renderer", |_lua, this| {
         //     Ok(ComponentTilemapRenderer::new(this.0))
         // });
@@ -135,8 +372,14 @@ impl LuaUserData for Entity {
     fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
         methods.add_method(
             "listen",
-            |lua, this, (event_name, handler): (LuaString, LuaValue)| {
-                Ok(use_context().entity_event_mgr().add_handler(
+            |lua,
+             this,
+             (event_name, handler, options): (
+                LuaString,
+                LuaValue,
+                EntityEventHandlerOptions,
+            )| {
+                Ok(use_context().entity_event_mgr().add_handler_with_options(
                     *this,
                     event_name.to_str()?,
                     match handler {
@@ -149,6 +392,7 @@ impl LuaUserData for Entity {
                         }
                         _ => return Err(LuaError::external("invalid event handler type")),
                     },
+                    options,
                 ))
             },
         );
@@ -163,13 +407,46 @@ impl LuaUserData for Entity {
                 Ok(())
             },
         );
+        methods.add_meta_method(LuaMetaMethod::Eq, |_lua, this, other: LuaAnyUserData| {
+            Ok(other.borrow::<Entity>().is_ok_and(|other| *this == *other))
+        });
+        methods.add_method("is_alive", |_lua, this, ()| {
+            let context = use_context();
+            Ok(context.world().is_alive(this.0) && !context.entity_mgr().is_destroyed(this.0))
+        });
+        methods.add_method("destroy", |lua, this, ()| {
+            Ok(destroy_entity(use_context(), this.0, lua))
+        });
+        methods.add_method(
+            "add_component",
+            |lua, this, (name, params): (LuaString, LuaValue)| {
+                let params = ComponentParams::from_lua(name.to_str()?, params, lua)?;
+                params.insert(&use_context().world(), this.0)
+            },
+        );
+        methods.add_method("remove_component", |_lua, this, name: LuaString| {
+            remove_component(&use_context().world(), this.0, name.to_str()?)
+        });
+        methods.add_method("has_component", |_lua, this, name: LuaString| {
+            has_component(&use_context().world(), this.0, name.to_str()?)
+        });
         methods.add_method("emit", |lua, this, event: LuaMultiValue| {
-            use_context().entity_event_mgr().emit(
-                *this,
-                &LuaEvent::from_lua_multi(event, lua)?,
-                lua,
-            );
-            Ok(())
+            // An optional third argument makes the event propagate through the parents.
+            let mut values = event.into_vec();
+            let bubbles = match values.len() {
+                3 => bool::from_lua(values.pop().unwrap(), lua)?,
+                _ => false,
+            };
+            let event = LuaEvent::from_lua_multi(LuaMultiValue::from_vec(values), lua)?;
+
+            if bubbles {
+                Ok(use_context()
+                    .entity_event_mgr()
+                    .emit_bubbling(*this, &event, lua))
+            } else {
+                use_context().entity_event_mgr().emit(*this, &event, lua);
+                Ok(false)
+            }
         });
     }
 }
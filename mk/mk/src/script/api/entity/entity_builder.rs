@@ -1,5 +1,11 @@
-use super::entity_builder_params::*;
-use crate::{component::*, engine::use_context, script::api::LuaApiTable, structure::Vec2};
+use super::{entity_builder_params::*, entity_components::ComponentParams};
+use crate::{
+    component::*,
+    engine::use_context,
+    script::{api::LuaApiTable, event::OnCreate},
+    structure::Vec2,
+    transform::TransformManager,
+};
 use mlua::prelude::*;
 use parking_lot::Mutex;
 use specs::prelude::*;
@@ -7,22 +13,136 @@ use std::sync::Arc;
 
 #[derive(Default)]
 pub struct EntityBuilderImpl {
-    name: Option<String>,
-    transform_parent: Option<Transform>,
-    transform_position: Option<Vec2>,
-    transform_scale: Option<Vec2>,
-    transform_angle: Option<f32>,
-    size: Option<crate::structure::Size>,
-    // alpha_tilemap_renderer_params: Option<AlphaTilemapRendererParams>,
-    audio_source_params: Option<AudioSourceParams>,
-    camera_params: Option<CameraParams>,
-    is_diagnostic: bool,
-    glyph_renderer_params: Option<GlyphRendererParams>,
-    sprite_renderer_params: Option<SpriteRendererParams>,
-    // tilemap_renderer_params: Option<TilemapRendererParams>,
-    ui_element_params: Option<UIElementParams>,
-    ui_mask_params: Option<UIMaskParams>,
-    ui_scaler_params: Option<UIScalerParams>,
+    pub name: Option<String>,
+    pub transform_parent: Option<Transform>,
+    pub transform_position: Option<Vec2>,
+    pub transform_scale: Option<Vec2>,
+    pub transform_angle: Option<f32>,
+    pub size: Option<crate::structure::Size>,
+    // pub alpha_tilemap_renderer_params: Option<AlphaTilemapRendererParams>,
+    pub audio_source_params: Option<AudioSourceParams>,
+    pub camera_params: Option<CameraParams>,
+    pub is_diagnostic: bool,
+    pub glyph_renderer_params: Option<GlyphRendererParams>,
+    pub sprite_renderer_params: Option<SpriteRendererParams>,
+    pub text_field_params: Option<TextFieldParams>,
+    // pub tilemap_renderer_params: Option<TilemapRendererParams>,
+    pub ui_element_params: Option<UIElementParams>,
+    pub ui_mask_params: Option<UIMaskParams>,
+    pub ui_scaler_params: Option<UIScalerParams>,
+}
+
+impl EntityBuilderImpl {
+    /// Sets the parameters of the component, replacing the ones set before.
+    pub fn set_component(&mut self, params: ComponentParams) {
+        match params {
+            ComponentParams::AudioSource(params) => self.audio_source_params = Some(params),
+            ComponentParams::Camera(params) => self.camera_params = Some(params),
+            ComponentParams::Diagnostic => self.is_diagnostic = true,
+            ComponentParams::GlyphRenderer(params) => self.glyph_renderer_params = Some(params),
+            ComponentParams::Size(size) => self.size = Some(size),
+            ComponentParams::SpriteRenderer(params) => self.sprite_renderer_params = Some(params),
+            ComponentParams::TextField(params) => self.text_field_params = Some(params),
+            ComponentParams::UIElement(params) => self.ui_element_params = Some(params),
+            ComponentParams::UIMask(params) => self.ui_mask_params = Some(params),
+            ComponentParams::UIScaler(params) => self.ui_scaler_params = Some(params),
+        }
+    }
+
+    /// Builds the entity, taking the parameters set, and emits `OnCreate` on it, propagating through its parents.
+    pub fn build(&mut self, lua: &Lua) -> LuaResult<super::entity::Entity> {
+        let context = use_context();
+        let (entity, path) = self.place(&mut context.world_mut(), &mut context.transform_mgr_mut());
+
+        {
+            let world = context.world();
+            let components = [
+                self.audio_source_params
+                    .take()
+                    .map(ComponentParams::AudioSource),
+                self.camera_params.take().map(ComponentParams::Camera),
+                self.is_diagnostic.then_some(ComponentParams::Diagnostic),
+                self.glyph_renderer_params
+                    .take()
+                    .map(ComponentParams::GlyphRenderer),
+                self.sprite_renderer_params
+                    .take()
+                    .map(ComponentParams::SpriteRenderer),
+                self.text_field_params
+                    .take()
+                    .map(ComponentParams::TextField),
+                self.ui_element_params
+                    .take()
+                    .map(ComponentParams::UIElement),
+                self.ui_mask_params.take().map(ComponentParams::UIMask),
+                self.ui_scaler_params.take().map(ComponentParams::UIScaler),
+            ];
+
+            for params in components.into_iter().flatten() {
+                params.insert(&world, entity)?;
+            }
+        }
+
+        // The parents see their children being created, as no handler can be on the entity yet.
+        context
+            .entity_event_mgr()
+            .emit_propagating(&path, &OnCreate, lua);
+
+        Ok(super::entity::Entity::new(entity))
+    }
+
+    /// Creates the entity with its name, parent, transform and size, taking them from the parameters set.
+    /// Returns it along with the path `OnCreate` propagates through.
+    pub fn place(
+        &mut self,
+        world: &mut World,
+        transform_mgr: &mut TransformManager,
+    ) -> (Entity, Vec<super::entity::Entity>) {
+        let transform = transform_mgr.alloc();
+
+        transform_mgr
+            .name_manager_mut()
+            .set_name(transform, self.name.take().map(|name| name.as_str().into()));
+
+        if let Some(parent) = self.transform_parent {
+            transform_mgr
+                .hierarchy_mut()
+                .set_parent(transform, Some(parent.index()));
+        }
+
+        {
+            let transform = transform_mgr.allocator_mut().transform_mut(transform);
+
+            if let Some(position) = self.transform_position {
+                transform.position = position;
+            }
+
+            if let Some(scale) = self.transform_scale {
+                transform.scale = scale;
+            }
+
+            if let Some(angle) = self.transform_angle {
+                transform.angle = angle;
+            }
+        }
+
+        let mut size = Size::new(transform);
+
+        if let Some(param) = self.size.take() {
+            size.size = param;
+        }
+
+        let entity = world
+            .create_entity()
+            .with(Transform::new(transform))
+            .with(size)
+            .build();
+        transform_mgr.alloc_entity(transform, entity);
+        (
+            entity,
+            super::entity::Entity::transform_event_path(transform_mgr, transform),
+        )
+    }
 }
 
 #[derive(Default, Clone)]
@@ -143,6 +263,14 @@ impl LuaUserData for EntityBuilder {
             })?;
             Ok(this.clone())
         });
+        methods.add_method("text_field", |_lua, this, params: Option<LuaTable>| {
+            this.with_mut(|this| -> LuaResult<_> {
+                this.text_field_params =
+                    params.map(|params| <_>::from_table(params)).transpose()?;
+                Ok(())
+            })?;
+            Ok(this.clone())
+        });
         methods.add_method("ui_mask", |_lua, this, params: Option<LuaTable>| {
             this.with_mut(|this| -> LuaResult<_> {
                 this.ui_mask_params = params.map(|params| <_>::from_table(params)).transpose()?;
@@ -158,192 +286,63 @@ impl LuaUserData for EntityBuilder {
             Ok(this.clone())
         });
 
-        methods.add_method("build", |_lua, this, ()| {
-            let mut this = this.0.lock();
-
-            let context = use_context();
-            let mut world = context.world_mut();
-            let mut builder = world.create_entity();
-
-            let mut transform_mgr = context.transform_mgr_mut();
-            let transform = transform_mgr.alloc();
-            builder = builder.with(Transform::new(transform));
-
-            let mut render_mgr = context.render_mgr_mut();
-            let mut glyph_mgr = context.glyph_mgr_mut();
-
-            transform_mgr
-                .name_manager_mut()
-                .set_name(transform, this.name.take().map(|name| name.as_str().into()));
-
-            if let Some(parent) = this.transform_parent {
-                transform_mgr
-                    .hierarchy_mut()
-                    .set_parent(transform, Some(parent.index()));
-            }
-
-            {
-                let transform = transform_mgr.allocator_mut().transform_mut(transform);
-
-                if let Some(position) = this.transform_position {
-                    transform.position = position;
-                }
-
-                if let Some(scale) = this.transform_scale {
-                    transform.scale = scale;
-                }
-
-                if let Some(angle) = this.transform_angle {
-                    transform.angle = angle;
-                }
-            }
-
-            let mut size = Size::new(transform);
-
-            if let Some(param) = this.size.take() {
-                size.size = param;
-            }
-
-            builder = builder.with(size);
-
-            // if let Some(param) = this.alpha_tilemap_renderer_params.take() {
-            //     let alpha_tilemap_renderer = AlphaTilemapRenderer::new(
-            //         param.layer,
-            //         param.order,
-            //         param.color,
-            //         param.fore_shader,
-            //         param.back_shader,
-            //         param.font,
-            //         param.font_size,
-            //         param.thickness,
-            //         param.smoothness,
-            //         param.tilemap,
-            //     );
-            //     builder = builder.with(alpha_tilemap_renderer);
-            // }
-
-            if let Some(param) = this.audio_source_params.take() {
-                let mut audio_source = AudioSource::new();
-
-                if let Some(volume) = param.volume {
-                    audio_source.set_volume(volume);
-                }
-
-                audio_source.set_clip(param.clip);
-
-                builder = builder.with(audio_source);
-            }
-
-            if let Some(param) = this.camera_params.take() {
-                let camera = Camera::new(
-                    &render_mgr,
-                    param.layer,
-                    param.order,
-                    param.clear_mode,
-                    param.clear_color,
-                );
-                builder = builder.with(camera);
-            }
-
-            if this.is_diagnostic {
-                builder = builder.with(Diagnostic);
-            }
-
-            if let Some(param) = this.glyph_renderer_params.take() {
-                let mut glyph_renderer = GlyphRenderer::new(
-                    param.layer,
-                    param.order,
-                    param.color,
-                    param.shader,
-                    param.thickness,
-                    param.smoothness,
-                    param.font,
-                    param.font_size,
-                );
-
-                if let Some(text) = param.text {
-                    glyph_renderer.set_text(
-                        &mut glyph_mgr,
-                        &mut render_mgr,
-                        text.as_str().to_owned(),
-                    );
-                }
-
-                if let Some(config) = param.config {
-                    glyph_renderer.set_config(config);
-                }
-
-                builder = builder.with(glyph_renderer);
-            }
-
-            if let Some(param) = this.sprite_renderer_params.take() {
-                let sprite_renderer = SpriteRenderer::new(
-                    &mut render_mgr,
-                    param.layer,
-                    param.order,
-                    param.color,
-                    param.shader,
-                    param.sprite,
-                );
-
-                builder = builder.with(sprite_renderer);
-            }
-
-            // if let Some(param) = this.tilemap_renderer_params.take() {
-            //     let tilemap_renderer = TilemapRenderer::new(
-            //         param.layer,
-            //         param.order,
-            //         param.color,
-            //         param.shader,
-            //         param.tilemap,
-            //     );
-
-            //     builder = builder.with(tilemap_renderer);
-            // }
-
-            let mut ui_element_index = None;
-
-            if let Some(param) = this.ui_element_params.take() {
-                let mut ui_mgr = context.ui_mgr_mut();
-                let index = ui_mgr.begin_alloc();
-                let ui_element = ui_mgr.element_mut(index);
-                ui_element.mark_as_dirty();
-
-                ui_element.anchor = param.anchor;
-                ui_element.margin = param.margin;
-                ui_element.set_interactible(param.is_interactible.unwrap_or(true));
-                ui_element.set_order_index(param.order_index);
-
-                builder = builder.with(UIElement::new(index));
-
-                ui_element_index = Some(index);
-            }
-
-            if let Some(param) = this.ui_mask_params.take() {
-                let ui_mask = UIMask {
-                    render_itself: param.render_itself,
-                };
-
-                builder = builder.with(ui_mask);
-            }
-
-            if let Some(param) = this.ui_scaler_params.take() {
-                let ui_scaler = UIScaler {
-                    mode: param.mode,
-                    reference_size: param.reference_size,
-                };
-
-                builder = builder.with(ui_scaler);
-            }
+        methods.add_method("build", |lua, this, ()| {
+            this.with_mut(|this| this.build(lua))
+        });
+    }
+}
 
-            let entity = builder.build();
+#[cfg(test)]
+mod test {
+    use super::super::entity::Entity;
+    use crate::{
+        event::{EntityEventHandler, EntityEventManager},
+        script::event::OnCreate,
+        transform::TransformManager,
+    };
+    use mlua::prelude::*;
+    use specs::{Builder, World, WorldExt};
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn parents_see_their_children_being_created() {
+        let lua = Lua::new();
+        let mut world = World::new();
+        let mut transform_mgr = TransformManager::new();
+        let entity_event_mgr = Rc::new(EntityEventManager::new());
+        let created = Rc::new(RefCell::new(Vec::new()));
+
+        let root = transform_mgr.alloc();
+        let parent = transform_mgr.alloc();
+        let child = transform_mgr.alloc();
+        transform_mgr.hierarchy_mut().set_parent(parent, Some(root));
+        transform_mgr
+            .hierarchy_mut()
+            .set_parent(child, Some(parent));
+
+        for transform in [root, parent, child] {
+            let entity = world.create_entity().build();
             transform_mgr.alloc_entity(transform, entity);
+        }
+
+        let root_entity = Entity::new(transform_mgr.allocator().entity(root));
+        let weak = Rc::downgrade(&entity_event_mgr);
+        let log = created.clone();
+        entity_event_mgr.add_handler(
+            root_entity,
+            "entity-create",
+            EntityEventHandler::native(move |_entity, _event| {
+                log.borrow_mut()
+                    .push(weak.upgrade().unwrap().target().unwrap());
+                Ok(())
+            }),
+        );
 
-            if let Some(index) = ui_element_index {
-                context.ui_mgr_mut().fin_alloc(index, entity);
-            }
+        let path = Entity::transform_event_path(&transform_mgr, child);
+        entity_event_mgr.emit_propagating(&path, &OnCreate, &lua);
 
-            Ok(super::entity::Entity::new(entity))
-        });
+        let child_entity = Entity::new(transform_mgr.allocator().entity(child));
+        assert_eq!(path.len(), 3);
+        assert_eq!(*created.borrow(), vec![child_entity]);
     }
 }
@@ -12,6 +12,7 @@ mod audio_source;
 mod camera_params;
 mod glyph_renderer;
 mod sprite_renderer;
+mod text_field;
 // mod tilemap_renderer;
 mod ui_element;
 mod ui_mask;
@@ -22,6 +23,7 @@ pub use audio_source::*;
 pub use camera_params::*;
 pub use glyph_renderer::*;
 pub use sprite_renderer::*;
+pub use text_field::*;
 // pub use tilemap_renderer::*;
 pub use ui_element::*;
 pub use ui_mask::*;
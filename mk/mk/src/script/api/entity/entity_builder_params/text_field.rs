@@ -0,0 +1,39 @@
+use super::EntityBuilderParam;
+use crate::script::entity::Entity;
+use anyhow::Context;
+use mlua::prelude::*;
+
+pub struct TextFieldParams {
+    pub text: Option<String>,
+    pub max_length: Option<usize>,
+    pub caret: Option<Entity>,
+    pub selection: Option<Entity>,
+    pub caret_width: Option<f32>,
+}
+
+impl EntityBuilderParam for TextFieldParams {
+    fn from_table<'lua>(table: LuaTable<'lua>) -> LuaResult<Self> {
+        Ok(Self {
+            text: table
+                .get("text")
+                .with_context(|| "invalid value for 'text' of TextFieldParams")
+                .to_lua_err()?,
+            max_length: table
+                .get("max_length")
+                .with_context(|| "invalid value for 'max_length' of TextFieldParams")
+                .to_lua_err()?,
+            caret: table
+                .get("caret")
+                .with_context(|| "invalid value for 'caret' of TextFieldParams")
+                .to_lua_err()?,
+            selection: table
+                .get("selection")
+                .with_context(|| "invalid value for 'selection' of TextFieldParams")
+                .to_lua_err()?,
+            caret_width: table
+                .get("caret_width")
+                .with_context(|| "invalid value for 'caret_width' of TextFieldParams")
+                .to_lua_err()?,
+        })
+    }
+}
@@ -0,0 +1,270 @@
+use super::entity_builder_params::*;
+use crate::{component::*, engine::use_context};
+use mlua::prelude::*;
+use specs::prelude::*;
+
+/// The names of the components that can be added to and removed from an entity at runtime.
+pub const COMPONENT_NAMES: [&str; 10] = [
+    "audio_source",
+    "camera",
+    "diagnostic",
+    "glyph_renderer",
+    "size",
+    "sprite_renderer",
+    "text_field",
+    "ui_element",
+    "ui_mask",
+    "ui_scaler",
+];
+
+/// A component to be added to an entity, along with the parameters to create it.
+pub enum ComponentParams {
+    AudioSource(AudioSourceParams),
+    Camera(CameraParams),
+    Diagnostic,
+    GlyphRenderer(GlyphRendererParams),
+    Size(crate::structure::Size),
+    SpriteRenderer(SpriteRendererParams),
+    TextField(TextFieldParams),
+    UIElement(UIElementParams),
+    UIMask(UIMaskParams),
+    UIScaler(UIScalerParams),
+}
+
+impl ComponentParams {
+    /// Parses the parameters of the component with the given name, as passed to `entity:add_component`.
+    pub fn from_lua<'lua>(name: &str, params: LuaValue<'lua>, lua: &'lua Lua) -> LuaResult<Self> {
+        let table = || -> LuaResult<LuaTable<'lua>> {
+            match &params {
+                LuaValue::Nil => lua.create_table(),
+                _ => LuaTable::from_lua(params.clone(), lua),
+            }
+        };
+
+        Ok(match name {
+            "audio_source" => Self::AudioSource(<_>::from_table(table()?)?),
+            "camera" => Self::Camera(<_>::from_table(table()?)?),
+            "diagnostic" => Self::Diagnostic,
+            "glyph_renderer" => Self::GlyphRenderer(<_>::from_table(table()?)?),
+            "size" => Self::Size(<_>::from_lua(params, lua)?),
+            "sprite_renderer" => Self::SpriteRenderer(<_>::from_table(table()?)?),
+            "text_field" => Self::TextField(<_>::from_table(table()?)?),
+            "ui_element" => Self::UIElement(<_>::from_table(table()?)?),
+            "ui_mask" => Self::UIMask(<_>::from_table(table()?)?),
+            "ui_scaler" => Self::UIScaler(<_>::from_table(table()?)?),
+            _ => return Err(unknown_component(name)),
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AudioSource(..) => "audio_source",
+            Self::Camera(..) => "camera",
+            Self::Diagnostic => "diagnostic",
+            Self::GlyphRenderer(..) => "glyph_renderer",
+            Self::Size(..) => "size",
+            Self::SpriteRenderer(..) => "sprite_renderer",
+            Self::TextField(..) => "text_field",
+            Self::UIElement(..) => "ui_element",
+            Self::UIMask(..) => "ui_mask",
+            Self::UIScaler(..) => "ui_scaler",
+        }
+    }
+
+    /// Creates the component and adds it to the entity, replacing the existing one.
+    pub fn insert(self, world: &World, entity: Entity) -> LuaResult<()> {
+        remove_component(world, entity, self.name())?;
+
+        let context = use_context();
+
+        match self {
+            Self::AudioSource(param) => {
+                let mut audio_source = AudioSource::new();
+
+                if let Some(volume) = param.volume {
+                    audio_source.set_volume(volume);
+                }
+
+                audio_source.set_clip(param.clip);
+
+                insert(world, entity, audio_source)
+            }
+            Self::Camera(param) => {
+                let camera = Camera::new(
+                    &context.render_mgr(),
+                    param.layer,
+                    param.order,
+                    param.clear_mode,
+                    param.clear_color,
+                );
+
+                insert(world, entity, camera)
+            }
+            Self::Diagnostic => insert(world, entity, Diagnostic),
+            Self::GlyphRenderer(param) => {
+                let mut glyph_renderer = GlyphRenderer::new(
+                    param.layer,
+                    param.order,
+                    param.color,
+                    param.shader,
+                    param.thickness,
+                    param.smoothness,
+                    param.font,
+                    param.font_size,
+                );
+
+                if let Some(text) = param.text {
+                    glyph_renderer.set_text(
+                        &mut context.glyph_mgr_mut(),
+                        &mut context.render_mgr_mut(),
+                        text.as_str().to_owned(),
+                    );
+                }
+
+                if let Some(config) = param.config {
+                    glyph_renderer.set_config(config);
+                }
+
+                insert(world, entity, glyph_renderer)
+            }
+            Self::Size(param) => {
+                let transform = match world.read_storage::<Transform>().get(entity) {
+                    Some(transform) => transform.index(),
+                    None => return Err(LuaError::external("the entity has no transform")),
+                };
+                let mut size = Size::new(transform);
+                size.size = param;
+
+                insert(world, entity, size)
+            }
+            Self::SpriteRenderer(param) => {
+                let sprite_renderer = SpriteRenderer::new(
+                    &mut context.render_mgr_mut(),
+                    param.layer,
+                    param.order,
+                    param.color,
+                    param.shader,
+                    param.sprite,
+                );
+
+                insert(world, entity, sprite_renderer)
+            }
+            Self::TextField(param) => {
+                let mut text_field = TextField::new(String::new());
+                text_field.editor_mut().set_max_length(param.max_length);
+                text_field
+                    .editor_mut()
+                    .set_text(param.text.unwrap_or_default());
+                text_field.caret = param.caret.map(|entity| entity.0);
+                text_field.selection = param.selection.map(|entity| entity.0);
+
+                if let Some(caret_width) = param.caret_width {
+                    text_field.caret_width = caret_width;
+                }
+
+                insert(world, entity, text_field)
+            }
+            Self::UIElement(param) => {
+                let mut ui_mgr = context.ui_mgr_mut();
+                let index = ui_mgr.begin_alloc();
+                let ui_element = ui_mgr.element_mut(index);
+                ui_element.mark_as_dirty();
+
+                ui_element.anchor = param.anchor;
+                ui_element.margin = param.margin;
+                ui_element.set_interactible(param.is_interactible.unwrap_or(true));
+                ui_element.set_order_index(param.order_index);
+
+                ui_mgr.fin_alloc(index, entity);
+                insert(world, entity, UIElement::new(index))
+            }
+            Self::UIMask(param) => {
+                let ui_mask = UIMask {
+                    render_itself: param.render_itself,
+                };
+
+                insert(world, entity, ui_mask)
+            }
+            Self::UIScaler(param) => {
+                let ui_scaler = UIScaler {
+                    mode: param.mode,
+                    reference_size: param.reference_size,
+                };
+
+                insert(world, entity, ui_scaler)
+            }
+        }
+    }
+}
+
+/// Removes the component with the given name from the entity, releasing what it has allocated.
+/// Returns `false` if the entity does not have it.
+pub fn remove_component(world: &World, entity: Entity, name: &str) -> LuaResult<bool> {
+    let context = use_context();
+
+    Ok(match name {
+        "audio_source" => world
+            .write_storage::<AudioSource>()
+            .remove(entity)
+            .is_some(),
+        "camera" => world.write_storage::<Camera>().remove(entity).is_some(),
+        "diagnostic" => world.write_storage::<Diagnostic>().remove(entity).is_some(),
+        "glyph_renderer" => match world.write_storage::<GlyphRenderer>().remove(entity) {
+            Some(mut glyph_renderer) => {
+                glyph_renderer.deallocate_glyphs(&mut context.render_mgr_mut());
+                true
+            }
+            None => false,
+        },
+        "size" => world.write_storage::<Size>().remove(entity).is_some(),
+        "sprite_renderer" => world
+            .write_storage::<SpriteRenderer>()
+            .remove(entity)
+            .is_some(),
+        "text_field" => world.write_storage::<TextField>().remove(entity).is_some(),
+        "ui_element" => match world.write_storage::<UIElement>().remove(entity) {
+            Some(ui_element) => {
+                context.ui_mgr_mut().dealloc(ui_element.index());
+                true
+            }
+            None => false,
+        },
+        "ui_mask" => world.write_storage::<UIMask>().remove(entity).is_some(),
+        "ui_scaler" => world.write_storage::<UIScaler>().remove(entity).is_some(),
+        _ => return Err(unknown_component(name)),
+    })
+}
+
+/// Returns `true` if the entity has the component with the given name.
+pub fn has_component(world: &World, entity: Entity, name: &str) -> LuaResult<bool> {
+    Ok(match name {
+        "audio_source" => world.read_storage::<AudioSource>().contains(entity),
+        "camera" => world.read_storage::<Camera>().contains(entity),
+        "diagnostic" => world.read_storage::<Diagnostic>().contains(entity),
+        "glyph_renderer" => world.read_storage::<GlyphRenderer>().contains(entity),
+        "size" => world.read_storage::<Size>().contains(entity),
+        "sprite_renderer" => world.read_storage::<SpriteRenderer>().contains(entity),
+        "text_field" => world.read_storage::<TextField>().contains(entity),
+        "transform" => world.read_storage::<Transform>().contains(entity),
+        "ui_element" => world.read_storage::<UIElement>().contains(entity),
+        "ui_mask" => world.read_storage::<UIMask>().contains(entity),
+        "ui_scaler" => world.read_storage::<UIScaler>().contains(entity),
+        _ => return Err(unknown_component(name)),
+    })
+}
+
+fn insert<T: Component>(world: &World, entity: Entity, component: T) -> LuaResult<()> {
+    world
+        .write_storage::<T>()
+        .insert(entity, component)
+        .map(|_| ())
+        .map_err(|err| LuaError::external(format!("cannot add a component: {}", err)))
+}
+
+fn unknown_component(name: &str) -> LuaError {
+    LuaError::external(format!(
+        "'{}' is not a component that can be added or removed; expected one of {}",
+        name,
+        COMPONENT_NAMES.join(", ")
+    ))
+}
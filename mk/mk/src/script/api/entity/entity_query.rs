@@ -0,0 +1,130 @@
+use super::COMPONENT_NAMES;
+use crate::{component::*, entity::EntityManager, EngineContext};
+use mlua::prelude::*;
+use specs::prelude::*;
+
+/// Matches the entities having every component of `with` and none of `without`, with components named as in
+/// `entity:has_component`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityQuery {
+    with: Vec<String>,
+    without: Vec<String>,
+}
+
+impl EntityQuery {
+    /// Fails if `with` is empty or any of the names is not a component.
+    pub fn new(with: Vec<String>, without: Vec<String>) -> LuaResult<Self> {
+        if with.is_empty() {
+            return Err(LuaError::external(
+                "a query needs at least one component to match",
+            ));
+        }
+
+        for name in with.iter().chain(&without) {
+            if name != "transform" && !COMPONENT_NAMES.contains(&name.as_str()) {
+                return Err(LuaError::external(format!(
+                    "'{}' is not a component that can be queried; expected transform or one of {}",
+                    name,
+                    COMPONENT_NAMES.join(", ")
+                )));
+            }
+        }
+
+        Ok(Self { with, without })
+    }
+
+    /// Returns the matching entities, in the order of their ids. Destroyed entities are left out.
+    pub fn entities(&self, context: &EngineContext) -> Vec<Entity> {
+        self.entities_in(&context.world(), &context.entity_mgr())
+    }
+
+    /// Like [`EntityQuery::entities`], over the given world. The storages of the queried components must be
+    /// registered in it.
+    pub fn entities_in(&self, world: &World, entity_mgr: &EntityManager) -> Vec<Entity> {
+        let mut with = component_mask(world, &self.with[0]);
+        let mut without = BitSet::new();
+
+        for name in &self.with[1..] {
+            with &= &component_mask(world, name);
+        }
+
+        for name in &self.without {
+            without |= &component_mask(world, name);
+        }
+
+        let entities = world.entities();
+        (&entities, &with, !&without)
+            .join()
+            .map(|(entity, _, _)| entity)
+            .filter(|&entity| !entity_mgr.is_destroyed(entity))
+            .collect()
+    }
+}
+
+fn component_mask(world: &World, name: &str) -> BitSet {
+    match name {
+        "audio_source" => world.read_storage::<AudioSource>().mask().clone(),
+        "camera" => world.read_storage::<Camera>().mask().clone(),
+        "diagnostic" => world.read_storage::<Diagnostic>().mask().clone(),
+        "glyph_renderer" => world.read_storage::<GlyphRenderer>().mask().clone(),
+        "size" => world.read_storage::<Size>().mask().clone(),
+        "sprite_renderer" => world.read_storage::<SpriteRenderer>().mask().clone(),
+        "text_field" => world.read_storage::<TextField>().mask().clone(),
+        "transform" => world.read_storage::<Transform>().mask().clone(),
+        "ui_element" => world.read_storage::<UIElement>().mask().clone(),
+        "ui_mask" => world.read_storage::<UIMask>().mask().clone(),
+        "ui_scaler" => world.read_storage::<UIScaler>().mask().clone(),
+        // Names are checked when the query is made.
+        _ => BitSet::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn query(with: &[&str], without: &[&str]) -> LuaResult<EntityQuery> {
+        EntityQuery::new(
+            with.iter().map(|&name| name.to_owned()).collect(),
+            without.iter().map(|&name| name.to_owned()).collect(),
+        )
+    }
+
+    #[test]
+    fn queries_need_known_components() {
+        assert!(query(&[], &["ui_mask"]).is_err());
+        assert!(query(&["transform", "enemy"], &[]).is_err());
+        assert!(query(&["transform"], &["health"]).is_err());
+        assert!(query(&["transform", "diagnostic"], &["ui_mask"]).is_ok());
+    }
+
+    #[test]
+    fn entities_match_with_and_without() {
+        let mut world = World::new();
+        world.register::<Diagnostic>();
+        world.register::<UIMask>();
+
+        let mask = || UIMask {
+            render_itself: false,
+        };
+        let plain = world.create_entity().with(Diagnostic).build();
+        let masked = world.create_entity().with(Diagnostic).with(mask()).build();
+        let mask_only = world.create_entity().with(mask()).build();
+        let destroyed = world.create_entity().with(Diagnostic).build();
+        world.create_entity().build();
+
+        let mut entity_mgr = EntityManager::new();
+        entity_mgr.mark_destroyed(destroyed);
+
+        let entities = |with: &[&str], without: &[&str]| {
+            query(with, without)
+                .unwrap()
+                .entities_in(&world, &entity_mgr)
+        };
+
+        assert_eq!(entities(&["diagnostic"], &[]), [plain, masked]);
+        assert_eq!(entities(&["diagnostic", "ui_mask"], &[]), [masked]);
+        assert_eq!(entities(&["diagnostic"], &["ui_mask"]), [plain]);
+        assert_eq!(entities(&["ui_mask"], &["diagnostic"]), [mask_only]);
+    }
+}
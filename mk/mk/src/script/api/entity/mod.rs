@@ -1,13 +1,21 @@
-use crate::script::api::LuaApiTable;
+use crate::{
+    engine::use_context,
+    script::{api::LuaApiTable, LuaMethodMeta, LuaParamMeta},
+};
 use mlua::prelude::*;
+use specs::WorldExt;
 
 mod entity;
 mod entity_builder;
 mod entity_builder_params;
+mod entity_components;
+mod entity_query;
 
 pub use entity::*;
 pub use entity_builder::*;
 pub use entity_builder_params::*;
+pub use entity_components::*;
+pub use entity_query::*;
 
 pub struct EntityModule;
 
@@ -20,7 +28,48 @@ impl LuaApiTable for EntityModule {
             "EntityBuilder",
             entity_builder::EntityBuilder::create_api_table(lua)?,
         )?;
+        table.set(
+            "query",
+            lua.create_function(|lua, (with, without): (Vec<String>, Option<Vec<String>>)| {
+                let query = EntityQuery::new(with, without.unwrap_or_default())?;
+                let mut entities = query.entities(use_context()).into_iter();
+
+                lua.create_function_mut(move |_lua, ()| {
+                    let context = use_context();
+
+                    // Entities destroyed while iterating are skipped.
+                    Ok(entities
+                        .by_ref()
+                        .find(|&entity| {
+                            context.world().is_alive(entity)
+                                && !context.entity_mgr().is_destroyed(entity)
+                        })
+                        .map(Entity::new))
+                })
+            })?,
+        )?;
 
         Ok(table)
     }
+
+    fn lua_functions() -> &'static [LuaMethodMeta] {
+        &[
+            LuaMethodMeta {
+                name: "query",
+                doc: "Returns an iterator over the entities having every component in `with` and none in `without`.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "with",
+                        ty: "string[]",
+                    },
+                    LuaParamMeta {
+                        name: "without",
+                        ty: "string[]?",
+                    },
+                ],
+                returns: Some("fun(): Entity?"),
+            },
+        ]
+    }
 }
@@ -64,6 +64,9 @@ pub struct Diagnostic {
     pub level: DiagnosticLevel,
     pub message: String,
     pub sub_diagnostics: Vec<SubDiagnostic>,
+    /// The Lua stack traceback, for errors raised by scripts.
+    pub traceback: Option<String>,
+    /// The source file, or the script chunk for errors raised by scripts. Empty if unknown.
     pub file: String,
     pub line: u32,
     pub column: u32,
@@ -76,6 +79,7 @@ impl LuaUserData for Diagnostic {
         fields.add_field_method_get("sub_diagnostics", |_lua, this| {
             Ok(this.sub_diagnostics.clone())
         });
+        fields.add_field_method_get("traceback", |_lua, this| Ok(this.traceback.clone()));
         fields.add_field_method_get("file", |_lua, this| Ok(this.file.clone()));
         fields.add_field_method_get("line", |_lua, this| Ok(this.line));
         fields.add_field_method_get("column", |_lua, this| Ok(this.column));
@@ -0,0 +1,11 @@
+use codegen::Event;
+
+/// Emitted on an entity once it has been built, propagating through its parents.
+#[derive(Event, Debug, Clone)]
+#[event_name("entity-create")]
+pub struct OnCreate;
+
+/// Emitted on an entity and each of its children before they are destroyed. It does not propagate to their parents.
+#[derive(Event, Debug, Clone)]
+#[event_name("entity-destroy")]
+pub struct OnDestroy;
@@ -1,4 +1,7 @@
-use crate::script::api::LuaApiTable;
+use crate::{
+    input::{GamepadChannel, KeyCode, MouseButton, RebindOutcome, RebindResult, TriggerInput},
+    script::api::LuaApiTable,
+};
 use codegen::Event;
 use mlua::prelude::*;
 use winit::event::VirtualKeyCode;
@@ -330,3 +333,190 @@ impl LuaUserData for PointerUp {
         fields.add_field_method_get("button", |_lua, this| Ok(this.button));
     }
 }
+
+#[derive(Event, Debug, Clone, Copy)]
+#[event_name("pointer_wheel")]
+pub struct PointerWheel {
+    pub delta_x: f32,
+    pub delta_y: f32,
+}
+
+impl LuaApiTable for PointerWheel {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        impl_event_listeners!(lua, table);
+
+        Ok(table)
+    }
+}
+
+impl LuaUserData for PointerWheel {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("delta_x", |_lua, this| Ok(this.delta_x));
+        fields.add_field_method_get("delta_y", |_lua, this| Ok(this.delta_y));
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+#[event_name("text_input")]
+pub struct TextInput {
+    pub text: String,
+}
+
+impl LuaApiTable for TextInput {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        impl_event_listeners!(lua, table);
+
+        Ok(table)
+    }
+}
+
+impl LuaUserData for TextInput {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("text", |_lua, this| Ok(this.text.clone()));
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+#[event_name("text_composition")]
+pub struct TextComposition {
+    pub text: String,
+    pub caret: Option<usize>,
+}
+
+impl LuaApiTable for TextComposition {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        impl_event_listeners!(lua, table);
+
+        Ok(table)
+    }
+}
+
+impl LuaUserData for TextComposition {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("text", |_lua, this| Ok(this.text.clone()));
+        fields.add_field_method_get("caret", |_lua, this| Ok(this.caret));
+    }
+}
+
+/// Emitted when a rebinding started with `mk.input.rebind` ends. Only one of `key`, `mouse_button` and
+/// `gamepad_channel` is set, to the captured input; none is set if the rebinding has been cancelled.
+#[derive(Event, Debug, Clone)]
+#[event_name("input_rebound")]
+pub struct InputRebound {
+    pub action: String,
+    pub trigger: usize,
+    pub part: Option<String>,
+    pub key: Option<KeyCode>,
+    pub mouse_button: Option<MouseButton>,
+    pub gamepad_channel: Option<GamepadChannel>,
+    pub cancelled: bool,
+    /// Whether the captured input could not be bound to the slot, e.g. a mouse button to a slot of a key axis.
+    pub rejected: bool,
+}
+
+impl From<RebindResult> for InputRebound {
+    fn from(result: RebindResult) -> Self {
+        let input = match result.outcome {
+            RebindOutcome::Bound(input) | RebindOutcome::Rejected(input) => Some(input),
+            RebindOutcome::Cancelled => None,
+        };
+
+        Self {
+            action: result.rebinding.action,
+            // Lua indices are 1-based.
+            trigger: result.rebinding.trigger + 1,
+            part: result.rebinding.part,
+            key: match input {
+                Some(TriggerInput::Key(key)) => Some(key),
+                _ => None,
+            },
+            mouse_button: match input {
+                Some(TriggerInput::Mouse(button)) => Some(button),
+                _ => None,
+            },
+            gamepad_channel: match input {
+                Some(TriggerInput::Gamepad(channel)) => Some(channel),
+                _ => None,
+            },
+            cancelled: result.outcome == RebindOutcome::Cancelled,
+            rejected: matches!(result.outcome, RebindOutcome::Rejected(_)),
+        }
+    }
+}
+
+impl LuaApiTable for InputRebound {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        impl_event_listeners!(lua, table);
+
+        Ok(table)
+    }
+}
+
+impl LuaUserData for InputRebound {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("action", |_lua, this| Ok(this.action.clone()));
+        fields.add_field_method_get("trigger", |_lua, this| Ok(this.trigger));
+        fields.add_field_method_get("part", |_lua, this| Ok(this.part.clone()));
+        fields.add_field_method_get("key", |_lua, this| Ok(this.key));
+        fields.add_field_method_get("mouse_button", |_lua, this| Ok(this.mouse_button));
+        fields.add_field_method_get("gamepad_channel", |_lua, this| Ok(this.gamepad_channel));
+        fields.add_field_method_get("cancelled", |_lua, this| Ok(this.cancelled));
+        fields.add_field_method_get("rejected", |_lua, this| Ok(this.rejected));
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+#[event_name("gamepad_connected")]
+pub struct GamepadConnected {
+    pub gamepad: usize,
+    pub name: String,
+}
+
+impl LuaApiTable for GamepadConnected {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        impl_event_listeners!(lua, table);
+
+        Ok(table)
+    }
+}
+
+impl LuaUserData for GamepadConnected {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("gamepad", |_lua, this| Ok(this.gamepad));
+        fields.add_field_method_get("name", |_lua, this| Ok(this.name.clone()));
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+#[event_name("gamepad_disconnected")]
+pub struct GamepadDisconnected {
+    pub gamepad: usize,
+    pub name: String,
+}
+
+impl LuaApiTable for GamepadDisconnected {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        impl_event_listeners!(lua, table);
+
+        Ok(table)
+    }
+}
+
+impl LuaUserData for GamepadDisconnected {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("gamepad", |_lua, this| Ok(this.gamepad));
+        fields.add_field_method_get("name", |_lua, this| Ok(this.name.clone()));
+    }
+}
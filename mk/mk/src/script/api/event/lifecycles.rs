@@ -24,6 +24,29 @@ impl LuaUserData for PreUpdate {
     }
 }
 
+/// Emitted zero or more times per frame, each time with the same fixed `dt`.
+#[derive(Event, Debug, Clone, Copy)]
+#[event_name("__fixed_update__")]
+pub struct FixedUpdate {
+    pub dt: f64,
+}
+
+impl LuaApiTable for FixedUpdate {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        impl_event_listeners!(lua, table);
+
+        Ok(table)
+    }
+}
+
+impl LuaUserData for FixedUpdate {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("dt", |_lua, this| Ok(this.dt));
+    }
+}
+
 #[derive(Event, Debug, Clone, Copy)]
 #[event_name("__update__")]
 pub struct Update {
@@ -72,6 +95,7 @@ impl LuaUserData for PostUpdate {
 #[event_name("__pre_render__")]
 pub struct PreRender {
     pub dt: f64,
+    pub alpha: f64,
 }
 
 impl LuaApiTable for PreRender {
@@ -87,6 +111,7 @@ impl LuaApiTable for PreRender {
 impl LuaUserData for PreRender {
     fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
         fields.add_field_method_get("dt", |_lua, this| Ok(this.dt));
+        fields.add_field_method_get("alpha", |_lua, this| Ok(this.alpha));
     }
 }
 
@@ -111,3 +136,20 @@ impl LuaUserData for PostRender {
         fields.add_field_method_get("dt", |_lua, this| Ok(this.dt));
     }
 }
+
+/// Emitted once when the window is closed, before the engine exits; the last chance to save the game.
+#[derive(Event, Debug, Clone, Copy)]
+#[event_name("__exit__")]
+pub struct Exit;
+
+impl LuaApiTable for Exit {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        impl_event_listeners!(lua, table);
+
+        Ok(table)
+    }
+}
+
+impl LuaUserData for Exit {}
@@ -0,0 +1,126 @@
+use crate::{
+    engine::use_context,
+    event::{EventHandler, EventHandlerOptions, LuaEventDefinition, LuaEventDefinitionError},
+    script::FFIFunction,
+};
+use mlua::prelude::*;
+use std::{collections::HashSet, rc::Rc};
+
+/// Defines an event from scripts, e.g. `mk.event.define("PlayerDied", { killer = "Entity" })`.
+/// The names of the events emitted by the engine are rejected, since their handlers expect the engine's payload.
+pub fn define_lua_event<'lua>(
+    lua: &'lua Lua,
+    (name, fields): (LuaString<'lua>, Option<LuaTable<'lua>>),
+    builtin_event_names: &HashSet<String>,
+) -> LuaResult<LuaTable<'lua>> {
+    let name = name.to_str()?;
+
+    if builtin_event_names.contains(name) {
+        return Err(LuaError::external(LuaEventDefinitionError::BuiltIn(
+            name.into(),
+        )));
+    }
+
+    let fields = match fields {
+        Some(fields) => fields,
+        None => lua.create_table()?,
+    };
+    let definition = LuaEventDefinition::from_lua_table(name, fields)?;
+    let definition = use_context()
+        .event_mgr()
+        .define_lua_event(definition)
+        .map_err(LuaError::external)?;
+
+    create_lua_event_table(lua, definition)
+}
+
+fn create_lua_event_table<'lua>(
+    lua: &'lua Lua,
+    definition: Rc<LuaEventDefinition>,
+) -> LuaResult<LuaTable<'lua>> {
+    let table = lua.create_table()?;
+    let fields = lua.create_table()?;
+
+    for field in definition.fields() {
+        let ty = if field.is_optional {
+            format!("{}?", field.ty)
+        } else {
+            field.ty.to_string()
+        };
+        fields.set(field.name.as_str(), ty)?;
+    }
+
+    table.set("name", definition.name())?;
+    table.set("fields", fields)?;
+    table.set("listen", {
+        let definition = definition.clone();
+        lua.create_function(
+            move |lua, (handler, options): (LuaFunction, EventHandlerOptions)| {
+                Ok(use_context().event_mgr().add_handler_with_options(
+                    definition.name(),
+                    EventHandler::lua(FFIFunction::new(lua, handler)?),
+                    options,
+                ))
+            },
+        )?
+    })?;
+    table.set("listen_once", {
+        let definition = definition.clone();
+        lua.create_function(
+            move |lua, (handler, options): (LuaFunction, EventHandlerOptions)| {
+                Ok(use_context().event_mgr().add_handler_with_options(
+                    definition.name(),
+                    EventHandler::lua(FFIFunction::new(lua, handler)?),
+                    options.with_once(true),
+                ))
+            },
+        )?
+    })?;
+    table.set("unlisten", {
+        let definition = definition.clone();
+        lua.create_function(move |_lua, handler: EventHandler| {
+            use_context()
+                .event_mgr()
+                .remove_handler(definition.name(), handler);
+            Ok(())
+        })?
+    })?;
+    table.set(
+        "emit",
+        lua.create_function(move |lua, params: Option<LuaTable>| {
+            let event = definition.create_event(lua, params)?;
+            Ok(use_context().event_mgr().emit(&event, lua))
+        })?,
+    )?;
+
+    Ok(table)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::script::{api::LuaApiTable, event::EventModule};
+    use mlua::prelude::*;
+
+    #[test]
+    fn names_of_engine_events_cannot_be_defined() {
+        let lua = Lua::new();
+        let event = EventModule::create_api_table(&lua).unwrap();
+        let define = event.get::<_, LuaFunction>("define").unwrap();
+
+        for name in [
+            "key_down",
+            "__update__",
+            "turn_end",
+            "entity-destroy",
+            "ui-mouse-down",
+        ] {
+            let err = define.call::<_, LuaTable>(name).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains(&format!("event {} is emitted by the engine", name)),
+                "{}",
+                err
+            );
+        }
+    }
+}
@@ -1,17 +1,40 @@
-use crate::script::api::LuaApiTable;
+use crate::script::{api::LuaApiTable, LuaEventMetaProvider, LuaMethodMeta, LuaParamMeta};
 use mlua::prelude::*;
+use std::collections::HashSet;
 
 macro_rules! impl_event_listeners {
     ($lua:ident, $table:ident) => {
+        crate::script::register_lua_event::<Self>($lua);
+        $table.set("name", <Self as crate::event::NativeEvent>::name())?;
         $table.set(
             "listen",
-            $lua.create_function(|lua, handler: mlua::Function| {
-                let event_mgr = crate::engine::use_context().event_mgr();
-                Ok(event_mgr.add_handler(
-                    <Self as crate::event::NativeEvent>::name(),
-                    crate::event::EventHandler::lua(crate::script::FFIFunction::new(lua, handler)?),
-                ))
-            })?,
+            $lua.create_function(
+                |lua, (handler, options): (mlua::Function, crate::event::EventHandlerOptions)| {
+                    let event_mgr = crate::engine::use_context().event_mgr();
+                    Ok(event_mgr.add_handler_with_options(
+                        <Self as crate::event::NativeEvent>::name(),
+                        crate::event::EventHandler::lua(crate::script::FFIFunction::new(
+                            lua, handler,
+                        )?),
+                        options,
+                    ))
+                },
+            )?,
+        )?;
+        $table.set(
+            "listen_once",
+            $lua.create_function(
+                |lua, (handler, options): (mlua::Function, crate::event::EventHandlerOptions)| {
+                    let event_mgr = crate::engine::use_context().event_mgr();
+                    Ok(event_mgr.add_handler_with_options(
+                        <Self as crate::event::NativeEvent>::name(),
+                        crate::event::EventHandler::lua(crate::script::FFIFunction::new(
+                            lua, handler,
+                        )?),
+                        options.with_once(true),
+                    ))
+                },
+            )?,
         )?;
         $table.set(
             "unlisten",
@@ -25,13 +48,19 @@ macro_rules! impl_event_listeners {
 }
 
 mod diagnostic;
+mod entity;
 mod input;
 mod lifecycles;
+mod lua_event;
+mod turn;
 mod ui;
 
 pub use diagnostic::*;
+pub use entity::*;
 pub use input::*;
 pub use lifecycles::*;
+pub use lua_event::*;
+pub use turn::*;
 pub use ui::*;
 
 pub struct EventModule;
@@ -40,23 +69,172 @@ impl LuaApiTable for EventModule {
     fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
         let table = lua.create_table()?;
 
+        table.set(
+            "consume",
+            lua.create_function(|_lua, ()| {
+                crate::engine::use_context().event_mgr().consume();
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "is_consumed",
+            lua.create_function(|_lua, ()| {
+                Ok(crate::engine::use_context().event_mgr().is_consumed())
+            })?,
+        )?;
+        table.set(
+            "stop_propagation",
+            lua.create_function(|_lua, ()| {
+                crate::engine::use_context()
+                    .entity_event_mgr()
+                    .stop_propagation();
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "phase",
+            lua.create_function(|_lua, ()| {
+                Ok(crate::engine::use_context()
+                    .entity_event_mgr()
+                    .phase()
+                    .map(|phase| phase.to_str()))
+            })?,
+        )?;
+        table.set("Diagnostic", diagnostic::Diagnostic::create_api_table(lua)?)?;
         table.set(
             "DiagnosticLevel",
             diagnostic::DiagnosticLevel::create_api_table(lua)?,
         )?;
         table.set("KeyDown", input::KeyDown::create_api_table(lua)?)?;
         table.set("KeyUp", input::KeyUp::create_api_table(lua)?)?;
+        table.set("TextInput", input::TextInput::create_api_table(lua)?)?;
+        table.set(
+            "TextComposition",
+            input::TextComposition::create_api_table(lua)?,
+        )?;
+        table.set("InputRebound", input::InputRebound::create_api_table(lua)?)?;
+        table.set(
+            "GamepadConnected",
+            input::GamepadConnected::create_api_table(lua)?,
+        )?;
+        table.set(
+            "GamepadDisconnected",
+            input::GamepadDisconnected::create_api_table(lua)?,
+        )?;
         table.set("PointerEnter", input::PointerEnter::create_api_table(lua)?)?;
         table.set("PointerExit", input::PointerExit::create_api_table(lua)?)?;
         table.set("PointerMove", input::PointerMove::create_api_table(lua)?)?;
         table.set("PointerDown", input::PointerDown::create_api_table(lua)?)?;
         table.set("PointerUp", input::PointerUp::create_api_table(lua)?)?;
+        table.set("PointerWheel", input::PointerWheel::create_api_table(lua)?)?;
         table.set("PreUpdate", lifecycles::PreUpdate::create_api_table(lua)?)?;
+        table.set(
+            "FixedUpdate",
+            lifecycles::FixedUpdate::create_api_table(lua)?,
+        )?;
         table.set("Update", lifecycles::Update::create_api_table(lua)?)?;
         table.set("PostUpdate", lifecycles::PostUpdate::create_api_table(lua)?)?;
+        table.set("TurnBegin", turn::TurnBegin::create_api_table(lua)?)?;
+        table.set("TurnEnd", turn::TurnEnd::create_api_table(lua)?)?;
         table.set("PreRender", lifecycles::PreRender::create_api_table(lua)?)?;
         table.set("PostRender", lifecycles::PostRender::create_api_table(lua)?)?;
+        table.set("Exit", lifecycles::Exit::create_api_table(lua)?)?;
+
+        // Entity events have no table of their own; they are listened by name with `entity:listen`.
+        let mut builtin_event_names = [
+            register_entity_event::<entity::OnCreate>(lua),
+            register_entity_event::<entity::OnDestroy>(lua),
+            register_entity_event::<ui::UIMouseEnter>(lua),
+            register_entity_event::<ui::UIMouseExit>(lua),
+            register_entity_event::<ui::UIMouseMove>(lua),
+            register_entity_event::<ui::UIMouseDown>(lua),
+            register_entity_event::<ui::UIMouseUp>(lua),
+            register_entity_event::<ui::UIDragBegin>(lua),
+            register_entity_event::<ui::UIDragEnd>(lua),
+            register_entity_event::<ui::UIDragDrop>(lua),
+            register_entity_event::<ui::UIFocusIn>(lua),
+            register_entity_event::<ui::UIFocusOut>(lua),
+            register_entity_event::<ui::UITextChange>(lua),
+            register_entity_event::<ui::UITextSubmit>(lua),
+        ]
+        .into_iter()
+        .map(str::to_owned)
+        .collect::<HashSet<_>>();
+
+        // So are the events set above, found by their `name`.
+        for pair in table.clone().pairs::<LuaValue, LuaValue>() {
+            if let (_, LuaValue::Table(event)) = pair? {
+                if let LuaValue::String(name) = event.raw_get("name")? {
+                    builtin_event_names.insert(name.to_str()?.to_owned());
+                }
+            }
+        }
+
+        table.set(
+            "define",
+            lua.create_function(move |lua, params| {
+                define_lua_event(lua, params, &builtin_event_names)
+            })?,
+        )?;
 
         Ok(table)
     }
+
+    fn lua_functions() -> &'static [LuaMethodMeta] {
+        &[
+            LuaMethodMeta {
+                name: "consume",
+                doc: "Stops the event being dispatched from reaching the remaining handlers.",
+                is_static: true,
+                params: &[],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "is_consumed",
+                doc: "Returns whether the event being dispatched has been consumed.",
+                is_static: true,
+                params: &[],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "stop_propagation",
+                doc: "Stops the entity event being dispatched from reaching the ancestors of the current entity.",
+                is_static: true,
+                params: &[],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "phase",
+                doc: "Returns the phase of the entity event being dispatched, or `nil` outside entity events.",
+                is_static: true,
+                params: &[],
+                returns: Some("string?"),
+            },
+            LuaMethodMeta {
+                name: "define",
+                doc: "Defines an event emitted and listened from scripts, with fields mapping names to types such as `'Entity?'`.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "name",
+                        ty: "string",
+                    },
+                    LuaParamMeta {
+                        name: "fields",
+                        ty: "table<string, string>?",
+                    },
+                ],
+                returns: Some("table"),
+            },
+        ]
+    }
+}
+
+/// Registers the metadata of an entity event and returns its name.
+fn register_entity_event<T>(lua: &Lua) -> &'static str
+where
+    T: LuaEventMetaProvider,
+{
+    crate::script::register_lua_entity_event::<T>(lua);
+    T::lua_event_meta().event_name
 }
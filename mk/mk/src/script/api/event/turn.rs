@@ -0,0 +1,53 @@
+use crate::script::{api::LuaApiTable, entity::Entity};
+use codegen::Event;
+use mlua::prelude::*;
+
+#[derive(Event, Debug, Clone, Copy)]
+#[event_name("turn_begin")]
+pub struct TurnBegin {
+    pub entity: Entity,
+    pub turn: u64,
+}
+
+impl LuaApiTable for TurnBegin {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        impl_event_listeners!(lua, table);
+
+        Ok(table)
+    }
+}
+
+impl LuaUserData for TurnBegin {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("entity", |_lua, this| Ok(this.entity));
+        fields.add_field_method_get("turn", |_lua, this| Ok(this.turn));
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+#[event_name("turn_end")]
+pub struct TurnEnd {
+    pub entity: Entity,
+    pub turn: u64,
+    pub cost: u32,
+}
+
+impl LuaApiTable for TurnEnd {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        impl_event_listeners!(lua, table);
+
+        Ok(table)
+    }
+}
+
+impl LuaUserData for TurnEnd {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("entity", |_lua, this| Ok(this.entity));
+        fields.add_field_method_get("turn", |_lua, this| Ok(this.turn));
+        fields.add_field_method_get("cost", |_lua, this| Ok(this.cost));
+    }
+}
@@ -1,22 +1,26 @@
 use crate::{script::entity::Entity, structure::Vec2};
 use codegen::Event;
 
+/// Emitted on an element when the mouse enters it. Unlike other UI events, it does not propagate.
 #[derive(Event, Debug, Clone)]
 #[event_name("ui-mouse-enter")]
 pub struct UIMouseEnter {
     pub mouse_position: Vec2,
 }
 
+/// Emitted on an element when the mouse exits it. Unlike other UI events, it does not propagate.
 #[derive(Event, Debug, Clone)]
 #[event_name("ui-mouse-exit")]
 pub struct UIMouseExit;
 
+/// Emitted on the element under the mouse when it moves, propagating through its parents.
 #[derive(Event, Debug, Clone)]
 #[event_name("ui-mouse-move")]
 pub struct UIMouseMove {
     pub mouse_position: Vec2,
 }
 
+/// Emitted on the element under the mouse on a button press, propagating through its parents.
 #[derive(Event, Debug, Clone)]
 #[event_name("ui-mouse-down")]
 pub struct UIMouseDown {
@@ -24,6 +28,7 @@ pub struct UIMouseDown {
     pub mouse_button: &'static str,
 }
 
+/// Emitted on the element under the mouse on a button release, propagating through its parents.
 #[derive(Event, Debug, Clone)]
 #[event_name("ui-mouse-up")]
 pub struct UIMouseUp {
@@ -31,6 +36,7 @@ pub struct UIMouseUp {
     pub mouse_button: &'static str,
 }
 
+/// Emitted on an element when it starts being dragged, propagating through its parents.
 #[derive(Event, Debug, Clone)]
 #[event_name("ui-drag-begin")]
 pub struct UIDragBegin {
@@ -38,10 +44,12 @@ pub struct UIDragBegin {
     pub mouse_button: &'static str,
 }
 
+/// Emitted on the dragged element when the drag ends, propagating through its parents.
 #[derive(Event, Debug, Clone)]
 #[event_name("ui-drag-end")]
 pub struct UIDragEnd;
 
+/// Emitted on the element a dragged element is dropped on, propagating through its parents.
 #[derive(Event, Debug, Clone)]
 #[event_name("ui-drag-drop")]
 pub struct UIDragDrop {
@@ -50,10 +58,26 @@ pub struct UIDragDrop {
     pub mouse_button: &'static str,
 }
 
+/// Emitted on an element when it gains the focus, propagating through its parents.
 #[derive(Event, Debug, Clone)]
 #[event_name("ui-focus-in")]
 pub struct UIFocusIn;
 
+/// Emitted on an element when it loses the focus, propagating through its parents.
 #[derive(Event, Debug, Clone)]
 #[event_name("ui-focus-out")]
 pub struct UIFocusOut;
+
+/// Emitted on a text field when its text changes, propagating through its parents.
+#[derive(Event, Debug, Clone)]
+#[event_name("ui-text-change")]
+pub struct UITextChange {
+    pub text: String,
+}
+
+/// Emitted on a text field when its text is submitted, propagating through its parents.
+#[derive(Event, Debug, Clone)]
+#[event_name("ui-text-submit")]
+pub struct UITextSubmit {
+    pub text: String,
+}
@@ -1,10 +1,14 @@
-use crate::script::api::LuaApiTable;
+use crate::script::{
+    api::LuaApiTable, register_lua_class, ConversionByValueReadOnly, LuaClassMeta, LuaFieldMeta,
+    LuaMethodMeta, LuaParamMeta, LuaUserDataMetaProvider, UserDataIntoSelf,
+};
 use mlua::prelude::*;
 
 pub type Color = crate::gfx::Color;
 
 impl LuaApiTable for Color {
     fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        register_lua_class::<Self>(lua);
         let table = lua.create_table()?;
 
         table.set(
@@ -79,3 +83,169 @@ impl LuaUserData for Color {
         });
     }
 }
+
+impl LuaUserDataMetaProvider for Color {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "Color",
+            doc: "An RGBA color.",
+            fields: &[
+                LuaFieldMeta {
+                    name: "r",
+                    ty: "number",
+                    doc: "The red channel, from 0 to 1.",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "g",
+                    ty: "number",
+                    doc: "The green channel, from 0 to 1.",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "b",
+                    ty: "number",
+                    doc: "The blue channel, from 0 to 1.",
+                    is_readonly: false,
+                },
+                LuaFieldMeta {
+                    name: "a",
+                    ty: "number",
+                    doc: "The alpha channel, from 0 to 1.",
+                    is_readonly: false,
+                },
+            ],
+            methods: &[
+                LuaMethodMeta {
+                    name: "from_rgb",
+                    doc: "An opaque color.",
+                    is_static: true,
+                    params: &[
+                        LuaParamMeta {
+                            name: "r",
+                            ty: "number",
+                        },
+                        LuaParamMeta {
+                            name: "g",
+                            ty: "number",
+                        },
+                        LuaParamMeta {
+                            name: "b",
+                            ty: "number",
+                        },
+                    ],
+                    returns: Some("Color"),
+                },
+                LuaMethodMeta {
+                    name: "from_rgba",
+                    doc: "",
+                    is_static: true,
+                    params: &[
+                        LuaParamMeta {
+                            name: "r",
+                            ty: "number",
+                        },
+                        LuaParamMeta {
+                            name: "g",
+                            ty: "number",
+                        },
+                        LuaParamMeta {
+                            name: "b",
+                            ty: "number",
+                        },
+                        LuaParamMeta {
+                            name: "a",
+                            ty: "number",
+                        },
+                    ],
+                    returns: Some("Color"),
+                },
+                LuaMethodMeta {
+                    name: "parse_hex",
+                    doc: "Parses a hex string such as `#RRGGBBAA`; raises if malformed.",
+                    is_static: true,
+                    params: &[LuaParamMeta {
+                        name: "hex",
+                        ty: "string",
+                    }],
+                    returns: Some("Color"),
+                },
+                LuaMethodMeta {
+                    name: "transparent",
+                    doc: "",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("Color"),
+                },
+                LuaMethodMeta {
+                    name: "black",
+                    doc: "",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("Color"),
+                },
+                LuaMethodMeta {
+                    name: "red",
+                    doc: "",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("Color"),
+                },
+                LuaMethodMeta {
+                    name: "green",
+                    doc: "",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("Color"),
+                },
+                LuaMethodMeta {
+                    name: "blue",
+                    doc: "",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("Color"),
+                },
+                LuaMethodMeta {
+                    name: "yellow",
+                    doc: "",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("Color"),
+                },
+                LuaMethodMeta {
+                    name: "magenta",
+                    doc: "",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("Color"),
+                },
+                LuaMethodMeta {
+                    name: "cyan",
+                    doc: "",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("Color"),
+                },
+                LuaMethodMeta {
+                    name: "white",
+                    doc: "",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("Color"),
+                },
+            ],
+        }
+    }
+}
+
+impl UserDataIntoSelf for Color {
+    fn into_self<'lua>(user_data: LuaAnyUserData<'lua>) -> LuaResult<Self> {
+        Ok(*user_data.borrow::<Self>()?)
+    }
+}
+
+impl ConversionByValueReadOnly for Color {
+    fn perform_convertion_to_lua<'lua>(&self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        (*self).to_lua(lua)
+    }
+}
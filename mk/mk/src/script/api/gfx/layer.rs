@@ -1,10 +1,14 @@
-use crate::script::api::LuaApiTable;
+use crate::script::{
+    api::LuaApiTable, register_lua_class, LuaClassMeta, LuaMethodMeta, LuaParamMeta,
+    LuaUserDataMetaProvider,
+};
 use mlua::prelude::*;
 
 pub type Layer = crate::gfx::Layer;
 
 impl LuaApiTable for Layer {
     fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        register_lua_class::<Self>(lua);
         let table = lua.create_table()?;
 
         table.set(
@@ -32,3 +36,62 @@ impl LuaUserData for Layer {
         methods.add_method("get", |_lua, this, ()| Ok(this.get()));
     }
 }
+
+impl LuaUserDataMetaProvider for Layer {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "Layer",
+            doc: "A bit mask of the layers a camera renders and a renderer is drawn on.",
+            fields: &[],
+            methods: &[
+                LuaMethodMeta {
+                    name: "new",
+                    doc: "A mask from its bits.",
+                    is_static: true,
+                    params: &[LuaParamMeta {
+                        name: "layer",
+                        ty: "integer",
+                    }],
+                    returns: Some("Layer"),
+                },
+                LuaMethodMeta {
+                    name: "none",
+                    doc: "A mask of no layers.",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("Layer"),
+                },
+                LuaMethodMeta {
+                    name: "all",
+                    doc: "A mask of every layer.",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("Layer"),
+                },
+                LuaMethodMeta {
+                    name: "has_overlap",
+                    doc: "Returns whether the two masks share a layer.",
+                    is_static: true,
+                    params: &[
+                        LuaParamMeta {
+                            name: "lhs",
+                            ty: "Layer",
+                        },
+                        LuaParamMeta {
+                            name: "rhs",
+                            ty: "Layer",
+                        },
+                    ],
+                    returns: Some("boolean"),
+                },
+                LuaMethodMeta {
+                    name: "get",
+                    doc: "Returns the bits of the mask.",
+                    is_static: false,
+                    params: &[],
+                    returns: Some("integer"),
+                },
+            ],
+        }
+    }
+}
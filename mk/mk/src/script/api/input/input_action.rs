@@ -0,0 +1,111 @@
+use crate::{
+    engine::use_context,
+    input::ActionState,
+    script::{LuaClassMeta, LuaFieldMeta, LuaUserDataMetaProvider},
+};
+use mlua::prelude::*;
+
+/// A reference to an action of the input map; its state is read when accessed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InputAction(String);
+
+impl InputAction {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+
+    pub fn state(&self) -> ActionState {
+        use_context()
+            .input_mgr()
+            .input_map()
+            .action(&self.0)
+            .map(|action| action.state())
+            .unwrap_or_default()
+    }
+}
+
+impl LuaUserData for InputAction {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("name", |_lua, this| Ok(this.0.clone()));
+        fields.add_field_method_get("held", |_lua, this| Ok(this.state().held));
+        fields.add_field_method_get("pressed", |_lua, this| Ok(this.state().pressed));
+        fields.add_field_method_get("released", |_lua, this| Ok(this.state().released));
+        fields.add_field_method_get("value", |_lua, this| Ok(this.state().value));
+        fields.add_field_method_get("vector", |_lua, this| Ok(this.state().vector));
+        fields.add_field_method_get("triggers", |_lua, this| {
+            Ok(use_context()
+                .input_mgr()
+                .input_map()
+                .action(&this.0)
+                .map(|action| action.triggers().to_vec())
+                .unwrap_or_default())
+        });
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_meta_method(LuaMetaMethod::ToString, |_lua, this, ()| {
+            Ok(format!("InputAction({})", this.0))
+        });
+        methods.add_meta_function(LuaMetaMethod::Eq, |_lua, (lhs, rhs): (Self, Self)| {
+            Ok(lhs == rhs)
+        });
+    }
+}
+
+impl LuaUserDataMetaProvider for InputAction {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "InputAction",
+            doc: "A reference to an action of the input map; its state is read when accessed.",
+            fields: &[
+                LuaFieldMeta {
+                    name: "name",
+                    ty: "string",
+                    doc: "",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "held",
+                    ty: "boolean",
+                    doc: "",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "pressed",
+                    ty: "boolean",
+                    doc: "`true` on the frame the action is pressed.",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "released",
+                    ty: "boolean",
+                    doc: "`true` on the frame the action is released.",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "value",
+                    ty: "number",
+                    doc: "The 1D reading of the strongest trigger.",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "vector",
+                    ty: "Vec2",
+                    doc: "The 2D reading of the strongest trigger; zero for keys.",
+                    is_readonly: true,
+                },
+                LuaFieldMeta {
+                    name: "triggers",
+                    ty: "Trigger[]",
+                    doc: "",
+                    is_readonly: true,
+                },
+            ],
+            methods: &[],
+        }
+    }
+}
@@ -0,0 +1,686 @@
+use crate::{
+    component::{Camera, Transform},
+    emit_diagnostic_warn,
+    engine::use_context,
+    input::{
+        CursorState, Device, GamepadChannel, GamepadDeadZones, InputBindings, InputChannelValue,
+        KeyCode, MouseButton, OutputChannelValue, Rebinding, Trigger, TriggerInput,
+    },
+    script::{api::LuaApiTable, entity::Entity, register_lua_class, LuaMethodMeta, LuaParamMeta},
+    structure::{Vec2, Vec3},
+    util::user_file_path,
+};
+use mlua::prelude::*;
+use specs::WorldExt;
+use std::{io::ErrorKind as IOErrorKind, path::PathBuf};
+
+mod input_action;
+mod trigger;
+
+pub use input_action::*;
+pub use trigger::*;
+
+pub struct InputModule;
+
+impl LuaApiTable for InputModule {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        register_lua_class::<InputAction>(lua);
+        let table = lua.create_table()?;
+
+        table.set("KeyCode", KeyCode::create_api_table(lua)?)?;
+        table.set("MouseButton", MouseButton::create_api_table(lua)?)?;
+        table.set("GamepadChannel", GamepadChannel::create_api_table(lua)?)?;
+        table.set(
+            "action",
+            lua.create_function(|_lua, name: LuaString| {
+                let name = name.to_str()?;
+                Ok(use_context()
+                    .input_mgr()
+                    .input_map()
+                    .action(name)
+                    .map(|_| InputAction::new(name)))
+            })?,
+        )?;
+        table.set(
+            "add_action",
+            lua.create_function(|_lua, (name, triggers): (LuaString, Vec<Trigger>)| {
+                let name = name.to_str()?;
+                use_context()
+                    .input_mgr_mut()
+                    .input_map_mut()
+                    .add_action(name, triggers);
+                Ok(InputAction::new(name))
+            })?,
+        )?;
+        table.set(
+            "remove_action",
+            lua.create_function(|_lua, name: LuaString| {
+                Ok(use_context()
+                    .input_mgr_mut()
+                    .input_map_mut()
+                    .remove_action(name.to_str()?)
+                    .is_some())
+            })?,
+        )?;
+        table.set(
+            "load_bindings",
+            lua.create_function(|_lua, path: LuaString| {
+                let path = path.to_str()?;
+                let context = use_context();
+                let bindings = match context.asset_mgr().load::<InputBindings>(path) {
+                    Ok(bindings) => bindings,
+                    Err(err) => {
+                        emit_diagnostic_warn!(format!(
+                            "failed to load input bindings from {} due to: {}",
+                            path, err
+                        ));
+                        return Ok(false);
+                    }
+                };
+                context
+                    .input_mgr_mut()
+                    .input_map_mut()
+                    .apply_bindings(&bindings);
+                Ok(true)
+            })?,
+        )?;
+
+        table.set(
+            "rebind",
+            lua.create_function(
+                |_lua, (name, trigger, part): (LuaString, usize, Option<LuaString>)| {
+                    if trigger == 0 {
+                        return Err(LuaError::external("trigger index must start from 1"));
+                    }
+
+                    Ok(use_context().input_mgr_mut().start_rebinding(Rebinding {
+                        action: name.to_str()?.to_owned(),
+                        trigger: trigger - 1,
+                        part: part
+                            .map(|part| part.to_str().map(str::to_owned))
+                            .transpose()?,
+                    }))
+                },
+            )?,
+        )?;
+        table.set(
+            "cancel_rebind",
+            lua.create_function(|_lua, ()| {
+                use_context().input_mgr_mut().cancel_rebinding();
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "is_rebinding",
+            lua.create_function(|_lua, ()| Ok(use_context().input_mgr().rebinding().is_some()))?,
+        )?;
+        table.set(
+            "actions_bound_to",
+            lua.create_function(|_lua, input: TriggerInput| {
+                Ok(use_context()
+                    .input_mgr()
+                    .input_map()
+                    .actions_bound_to(input)
+                    .into_iter()
+                    .map(str::to_owned)
+                    .collect::<Vec<_>>())
+            })?,
+        )?;
+        table.set(
+            "conflicts",
+            lua.create_function(|lua, ()| {
+                let conflicts = use_context().input_mgr().input_map().conflicts();
+                let table = lua.create_table()?;
+
+                for (index, conflict) in conflicts.into_iter().enumerate() {
+                    let item = lua.create_table()?;
+                    set_trigger_input_field(&item, conflict.input)?;
+                    item.set("actions", conflict.actions)?;
+                    table.set(index + 1, item)?;
+                }
+
+                Ok(table)
+            })?,
+        )?;
+        table.set(
+            "set_default_bindings",
+            lua.create_function(|_lua, ()| {
+                let mut input_mgr = use_context().input_mgr_mut();
+                let bindings = input_mgr.input_map().bindings();
+                input_mgr.input_map_mut().set_defaults(bindings);
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "reset_bindings",
+            lua.create_function(|_lua, name: Option<LuaString>| {
+                let mut input_mgr = use_context().input_mgr_mut();
+                Ok(match name {
+                    Some(name) => input_mgr
+                        .input_map_mut()
+                        .reset_action_to_defaults(name.to_str()?),
+                    None => {
+                        input_mgr.input_map_mut().reset_to_defaults();
+                        true
+                    }
+                })
+            })?,
+        )?;
+        table.set(
+            "save_bindings",
+            lua.create_function(|_lua, name: LuaString| {
+                let path = user_bindings_path(name.to_str()?)?;
+                let bindings = use_context().input_mgr().input_map().bindings();
+
+                Ok(match bindings.save(&path) {
+                    Ok(()) => true,
+                    Err(err) => {
+                        emit_diagnostic_warn!(format!(
+                            "failed to save input bindings to {} due to: {}",
+                            path.display(),
+                            err
+                        ));
+                        false
+                    }
+                })
+            })?,
+        )?;
+        table.set(
+            "load_user_bindings",
+            lua.create_function(|_lua, name: LuaString| {
+                let path = user_bindings_path(name.to_str()?)?;
+                let bindings = match InputBindings::load(&path) {
+                    Ok(bindings) => bindings,
+                    // Nothing has been saved yet.
+                    Err(err) if err.kind() == IOErrorKind::NotFound => return Ok(false),
+                    Err(err) => {
+                        emit_diagnostic_warn!(format!(
+                            "failed to load input bindings from {} due to: {}",
+                            path.display(),
+                            err
+                        ));
+                        return Ok(false);
+                    }
+                };
+
+                use_context()
+                    .input_mgr_mut()
+                    .input_map_mut()
+                    .apply_bindings(&bindings);
+                Ok(true)
+            })?,
+        )?;
+
+        table.set(
+            "mouse_position",
+            lua.create_function(|_lua, ()| Ok(use_context().input_mgr().mouse().position()))?,
+        )?;
+        table.set(
+            "mouse_world_position",
+            lua.create_function(|_lua, camera: Entity| {
+                let position = use_context().input_mgr().mouse().position();
+                Ok(position.and_then(|position| screen_to_world(position, camera)))
+            })?,
+        )?;
+        table.set(
+            "mouse_delta",
+            lua.create_function(|_lua, ()| Ok(use_context().input_mgr().mouse().delta()))?,
+        )?;
+        table.set(
+            "mouse_wheel",
+            lua.create_function(|_lua, ()| Ok(use_context().input_mgr().mouse().wheel()))?,
+        )?;
+        table.set(
+            "is_mouse_down",
+            lua.create_function(|_lua, button: MouseButton| {
+                Ok(use_context().input_mgr().mouse().is_button_down(button))
+            })?,
+        )?;
+        table.set(
+            "is_mouse_pressed",
+            lua.create_function(|_lua, button: MouseButton| {
+                Ok(use_context().input_mgr().mouse().is_button_pressed(button))
+            })?,
+        )?;
+        table.set(
+            "is_mouse_released",
+            lua.create_function(|_lua, button: MouseButton| {
+                Ok(use_context().input_mgr().mouse().is_button_released(button))
+            })?,
+        )?;
+        table.set(
+            "is_cursor_visible",
+            lua.create_function(|_lua, ()| Ok(use_context().input_mgr().mouse().cursor().visible))?,
+        )?;
+        table.set(
+            "set_cursor_visible",
+            lua.create_function(|_lua, visible: bool| {
+                let input_mgr = use_context().input_mgr();
+                let cursor = input_mgr.mouse().cursor();
+                input_mgr
+                    .mouse()
+                    .set_cursor(CursorState { visible, ..cursor });
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "is_cursor_locked",
+            lua.create_function(|_lua, ()| Ok(use_context().input_mgr().mouse().cursor().locked))?,
+        )?;
+        table.set(
+            "set_cursor_locked",
+            lua.create_function(|_lua, locked: bool| {
+                let input_mgr = use_context().input_mgr();
+                let cursor = input_mgr.mouse().cursor();
+                input_mgr
+                    .mouse()
+                    .set_cursor(CursorState { locked, ..cursor });
+                Ok(())
+            })?,
+        )?;
+
+        table.set(
+            "gamepads",
+            lua.create_function(|lua, ()| {
+                let input_mgr = use_context().input_mgr();
+                let table = lua.create_table()?;
+
+                for (index, gamepad) in input_mgr.gamepads().gamepads().enumerate() {
+                    let item = lua.create_table()?;
+                    item.set("id", gamepad.id())?;
+                    item.set("name", gamepad.name())?;
+                    table.set(index + 1, item)?;
+                }
+
+                Ok(table)
+            })?,
+        )?;
+        table.set(
+            "gamepad_channel",
+            lua.create_function(|lua, (id, channel): (usize, GamepadChannel)| {
+                let input_mgr = use_context().input_mgr();
+                let gamepad = match input_mgr.gamepads().gamepad(id) {
+                    Some(gamepad) => gamepad,
+                    None => return Ok(LuaNil),
+                };
+
+                match gamepad.channel_state(channel) {
+                    InputChannelValue::Button(pressed) => pressed.to_lua(lua),
+                    InputChannelValue::Axis1D(x) => x.to_lua(lua),
+                    InputChannelValue::Axis2D(x, y) => Vec2::new(x, y).to_lua(lua),
+                }
+            })?,
+        )?;
+        table.set(
+            "dead_zones",
+            lua.create_function(|_lua, ()| {
+                let dead_zones = use_context().input_mgr().gamepads().dead_zones();
+                Ok((dead_zones.stick, dead_zones.trigger))
+            })?,
+        )?;
+        table.set(
+            "set_dead_zones",
+            lua.create_function(|_lua, (stick, trigger): (f32, f32)| {
+                if !(0f32..1f32).contains(&stick) || !(0f32..1f32).contains(&trigger) {
+                    return Err(LuaError::external("dead zones must be in range [0, 1)"));
+                }
+
+                use_context()
+                    .input_mgr_mut()
+                    .gamepads_mut()
+                    .set_dead_zones(GamepadDeadZones { stick, trigger });
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "rumble",
+            lua.create_function(|_lua, (id, strength): (usize, f64)| {
+                let input_mgr = use_context().input_mgr();
+                let gamepad = match input_mgr.gamepads().gamepad(id) {
+                    Some(gamepad) => gamepad,
+                    None => return Ok(false),
+                };
+
+                for channel in gamepad.output_channels() {
+                    if channel.name() == "rumble" {
+                        channel.set_state(OutputChannelValue::Float(strength));
+                    }
+                }
+
+                Ok(true)
+            })?,
+        )?;
+
+        Ok(table)
+    }
+
+    fn lua_functions() -> &'static [LuaMethodMeta] {
+        &[
+            LuaMethodMeta {
+                name: "action",
+                doc: "Returns the action with the given name, or `nil` if there is none.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "name",
+                    ty: "string",
+                }],
+                returns: Some("InputAction?"),
+            },
+            LuaMethodMeta {
+                name: "add_action",
+                doc: "Adds an action triggered by any of the given triggers, replacing the one of the same name.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "name",
+                        ty: "string",
+                    },
+                    LuaParamMeta {
+                        name: "triggers",
+                        ty: "table[]",
+                    },
+                ],
+                returns: Some("InputAction"),
+            },
+            LuaMethodMeta {
+                name: "remove_action",
+                doc: "Removes an action. Returns whether it existed.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "name",
+                    ty: "string",
+                }],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "load_bindings",
+                doc: "Applies the bindings of an asset. Returns whether it could be loaded.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "path",
+                    ty: "string",
+                }],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "rebind",
+                doc: "Binds the next pressed key, mouse button or gamepad channel to a trigger of an action; `trigger` starts from 1. Returns `false` if there is no such action.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "name",
+                        ty: "string",
+                    },
+                    LuaParamMeta {
+                        name: "trigger",
+                        ty: "integer",
+                    },
+                    LuaParamMeta {
+                        name: "part",
+                        ty: "string?",
+                    },
+                ],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "cancel_rebind",
+                doc: "Cancels the rebinding in progress.",
+                is_static: true,
+                params: &[],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "is_rebinding",
+                doc: "Returns whether a rebinding is in progress.",
+                is_static: true,
+                params: &[],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "actions_bound_to",
+                doc: "Returns the names of the actions bound to an input, given as a `KeyCode` or a table of `key`, `mouse_button` or `gamepad_channel`.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "input",
+                    ty: "KeyCode|table",
+                }],
+                returns: Some("string[]"),
+            },
+            LuaMethodMeta {
+                name: "conflicts",
+                doc: "Returns the inputs bound to several actions, as tables of `actions` and one of `key`, `mouse_button` and `gamepad_channel`.",
+                is_static: true,
+                params: &[],
+                returns: Some("table[]"),
+            },
+            LuaMethodMeta {
+                name: "set_default_bindings",
+                doc: "Makes the current bindings the defaults restored by `reset_bindings`.",
+                is_static: true,
+                params: &[],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "reset_bindings",
+                doc: "Restores the default bindings of an action, or of every action if no name is given. Returns `false` if there is no such action.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "name",
+                    ty: "string?",
+                }],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "save_bindings",
+                doc: "Saves the current bindings to the user directory. Returns whether they could be saved.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "name",
+                    ty: "string",
+                }],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "load_user_bindings",
+                doc: "Applies the bindings saved with `save_bindings`. Returns whether they could be loaded.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "name",
+                    ty: "string",
+                }],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "mouse_position",
+                doc: "Returns the position of the mouse in the window, or `nil` if it is outside.",
+                is_static: true,
+                params: &[],
+                returns: Some("Vec2?"),
+            },
+            LuaMethodMeta {
+                name: "mouse_world_position",
+                doc: "Returns the position of the mouse in the world seen by a camera, or `nil` if it is outside the window.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "camera",
+                    ty: "Entity",
+                }],
+                returns: Some("Vec2?"),
+            },
+            LuaMethodMeta {
+                name: "mouse_delta",
+                doc: "Returns how far the mouse moved in the current frame.",
+                is_static: true,
+                params: &[],
+                returns: Some("Vec2"),
+            },
+            LuaMethodMeta {
+                name: "mouse_wheel",
+                doc: "Returns how far the mouse wheel scrolled in the current frame.",
+                is_static: true,
+                params: &[],
+                returns: Some("Vec2"),
+            },
+            LuaMethodMeta {
+                name: "is_mouse_down",
+                doc: "Returns whether a mouse button is held.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "button",
+                    ty: "MouseButton",
+                }],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "is_mouse_pressed",
+                doc: "Returns whether a mouse button was pressed in the current frame.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "button",
+                    ty: "MouseButton",
+                }],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "is_mouse_released",
+                doc: "Returns whether a mouse button was released in the current frame.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "button",
+                    ty: "MouseButton",
+                }],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "is_cursor_visible",
+                doc: "Returns whether the cursor is shown.",
+                is_static: true,
+                params: &[],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "set_cursor_visible",
+                doc: "Shows or hides the cursor.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "visible",
+                    ty: "boolean",
+                }],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "is_cursor_locked",
+                doc: "Returns whether the cursor is locked to the window.",
+                is_static: true,
+                params: &[],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "set_cursor_locked",
+                doc: "Locks the cursor to the window or releases it.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "locked",
+                    ty: "boolean",
+                }],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "gamepads",
+                doc: "Returns the connected gamepads, as tables of `id` and `name`.",
+                is_static: true,
+                params: &[],
+                returns: Some("table[]"),
+            },
+            LuaMethodMeta {
+                name: "gamepad_channel",
+                doc: "Returns the state of a channel of a gamepad, or `nil` if the gamepad is not connected.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "id",
+                        ty: "integer",
+                    },
+                    LuaParamMeta {
+                        name: "channel",
+                        ty: "GamepadChannel",
+                    },
+                ],
+                returns: Some("boolean|number|Vec2|nil"),
+            },
+            LuaMethodMeta {
+                name: "dead_zones",
+                doc: "Returns the dead zones of the sticks and the triggers.",
+                is_static: true,
+                params: &[],
+                returns: Some("number, number"),
+            },
+            LuaMethodMeta {
+                name: "set_dead_zones",
+                doc: "Sets the dead zones of the sticks and the triggers, each in range `[0, 1)`.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "stick",
+                        ty: "number",
+                    },
+                    LuaParamMeta {
+                        name: "trigger",
+                        ty: "number",
+                    },
+                ],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "rumble",
+                doc: "Rumbles a gamepad with a strength in range `[0, 1]`. Returns `false` if the gamepad is not connected.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "id",
+                        ty: "integer",
+                    },
+                    LuaParamMeta {
+                        name: "strength",
+                        ty: "number",
+                    },
+                ],
+                returns: Some("boolean"),
+            },
+        ]
+    }
+}
+
+/// Returns the path of the user bindings, rejecting names that could escape the input directory.
+fn user_bindings_path(name: &str) -> LuaResult<PathBuf> {
+    user_file_path(
+        use_context().user_dir().join("input"),
+        "bindings",
+        name,
+        "json",
+    )
+    .map_err(LuaError::external)
+}
+
+/// Converts a point in logical screen pixels to the world space seen by the camera.
+/// Returns `None` if the entity is not a camera.
+fn screen_to_world(point_in_screen: Vec2, camera: Entity) -> Option<Vec2> {
+    let context = use_context();
+    let world = context.world();
+
+    world.read_storage::<Camera>().get(camera.0)?;
+
+    let transform_index = world.read_storage::<Transform>().get(camera.0)?.index();
+    let screen_mgr = context.screen_mgr();
+    let point_in_camera = Vec3::new(
+        point_in_screen.x - screen_mgr.width() as f32 * 0.5f32,
+        -point_in_screen.y + screen_mgr.height() as f32 * 0.5f32,
+        1f32,
+    );
+    let point_in_world = point_in_camera
+        * context
+            .transform_mgr()
+            .transform_world_matrix(transform_index);
+
+    Some(Vec2::new(point_in_world.x, point_in_world.y))
+}
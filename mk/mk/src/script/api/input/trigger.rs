@@ -0,0 +1,151 @@
+use crate::input::{KeyCode, Trigger, TriggerInput};
+use anyhow::Context;
+use mlua::prelude::*;
+
+impl<'lua> FromLua<'lua> for Trigger {
+    fn from_lua(value: LuaValue<'lua>, _lua: &'lua Lua) -> LuaResult<Self> {
+        let table = match value {
+            LuaValue::Table(table) => table,
+            _ => {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "Trigger",
+                    message: Some(format!("expected a table, got [{}]", value.type_name())),
+                })
+            }
+        };
+        let ty: LuaString = table
+            .get("type")
+            .with_context(|| "invalid value for 'type' of Trigger")
+            .to_lua_err()?;
+
+        Ok(match ty.to_str()? {
+            "key" => Trigger::Key {
+                key: table
+                    .get("key")
+                    .with_context(|| "invalid value for 'key' of Trigger")
+                    .to_lua_err()?,
+            },
+            "axis_1d" => Trigger::Axis1D {
+                negative: table
+                    .get("negative")
+                    .with_context(|| "invalid value for 'negative' of Trigger")
+                    .to_lua_err()?,
+                positive: table
+                    .get("positive")
+                    .with_context(|| "invalid value for 'positive' of Trigger")
+                    .to_lua_err()?,
+            },
+            "axis_2d" => Trigger::Axis2D {
+                left: table
+                    .get("left")
+                    .with_context(|| "invalid value for 'left' of Trigger")
+                    .to_lua_err()?,
+                right: table
+                    .get("right")
+                    .with_context(|| "invalid value for 'right' of Trigger")
+                    .to_lua_err()?,
+                down: table
+                    .get("down")
+                    .with_context(|| "invalid value for 'down' of Trigger")
+                    .to_lua_err()?,
+                up: table
+                    .get("up")
+                    .with_context(|| "invalid value for 'up' of Trigger")
+                    .to_lua_err()?,
+            },
+            "mouse" => Trigger::Mouse {
+                button: table
+                    .get("button")
+                    .with_context(|| "invalid value for 'button' of Trigger")
+                    .to_lua_err()?,
+            },
+            "gamepad" => Trigger::Gamepad {
+                channel: table
+                    .get("channel")
+                    .with_context(|| "invalid value for 'channel' of Trigger")
+                    .to_lua_err()?,
+            },
+            ty => return Err(LuaError::external(format!("invalid trigger type '{}'", ty))),
+        })
+    }
+}
+
+impl<'lua> ToLua<'lua> for Trigger {
+    fn to_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        let table = lua.create_table()?;
+
+        match self {
+            Trigger::Key { key } => {
+                table.set("type", "key")?;
+                table.set("key", key)?;
+            }
+            Trigger::Axis1D { negative, positive } => {
+                table.set("type", "axis_1d")?;
+                table.set("negative", negative)?;
+                table.set("positive", positive)?;
+            }
+            Trigger::Axis2D {
+                left,
+                right,
+                down,
+                up,
+            } => {
+                table.set("type", "axis_2d")?;
+                table.set("left", left)?;
+                table.set("right", right)?;
+                table.set("down", down)?;
+                table.set("up", up)?;
+            }
+            Trigger::Mouse { button } => {
+                table.set("type", "mouse")?;
+                table.set("button", button)?;
+            }
+            Trigger::Gamepad { channel } => {
+                table.set("type", "gamepad")?;
+                table.set("channel", channel)?;
+            }
+        }
+
+        Ok(LuaValue::Table(table))
+    }
+}
+
+/// Sets the field describing the input, one of `key`, `mouse_button` and `gamepad_channel`, as in `input_rebound`.
+pub fn set_trigger_input_field(table: &LuaTable, input: TriggerInput) -> LuaResult<()> {
+    match input {
+        TriggerInput::Key(key) => table.set("key", key),
+        TriggerInput::Mouse(button) => table.set("mouse_button", button),
+        TriggerInput::Gamepad(channel) => table.set("gamepad_channel", channel),
+    }
+}
+
+impl<'lua> FromLua<'lua> for TriggerInput {
+    fn from_lua(value: LuaValue<'lua>, lua: &'lua Lua) -> LuaResult<Self> {
+        let table = match value {
+            LuaValue::Table(table) => table,
+            // A bare key code is a key.
+            value => return Ok(TriggerInput::Key(KeyCode::from_lua(value, lua)?)),
+        };
+
+        if let Some(key) = table.get("key")? {
+            Ok(TriggerInput::Key(key))
+        } else if let Some(button) = table.get("mouse_button")? {
+            Ok(TriggerInput::Mouse(button))
+        } else if let Some(channel) = table.get("gamepad_channel")? {
+            Ok(TriggerInput::Gamepad(channel))
+        } else {
+            Err(LuaError::external(
+                "expected one of 'key', 'mouse_button' and 'gamepad_channel' in the input",
+            ))
+        }
+    }
+}
+
+impl<'lua> ToLua<'lua> for TriggerInput {
+    fn to_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        let table = lua.create_table()?;
+        set_trigger_input_field(&table, self)?;
+        Ok(LuaValue::Table(table))
+    }
+}
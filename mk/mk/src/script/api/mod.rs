@@ -1,18 +1,33 @@
+use crate::script::LuaMethodMeta;
 use mlua::prelude::*;
 
 pub trait LuaApiTable {
     fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>>;
+
+    /// Describes the functions set on the table, for the namespaces passed to `set_namespace`.
+    fn lua_functions() -> &'static [LuaMethodMeta] {
+        &[]
+    }
 }
 
 pub mod asset;
 pub mod audio;
 pub mod component;
+pub mod console;
+pub mod diagnostic;
 pub mod entity;
 pub mod event;
 pub mod gfx;
+pub mod input;
+pub mod prefab;
+pub mod save;
+pub mod scene;
 pub mod screen;
 pub mod structure;
+pub mod system;
+pub mod task;
 pub mod time;
+pub mod turn;
 pub mod ui;
 
 pub struct Module;
@@ -21,23 +36,40 @@ impl LuaApiTable for Module {
     fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
         let table = lua.create_table()?;
 
-        table.set("asset", asset::AssetModule::create_api_table(lua)?)?;
-        table.set("audio", audio::AudioModule::create_api_table(lua)?)?;
-        table.set(
-            "component",
-            component::ComponentModule::create_api_table(lua)?,
-        )?;
-        table.set("entity", entity::EntityModule::create_api_table(lua)?)?;
-        table.set("event", event::EventModule::create_api_table(lua)?)?;
-        table.set("gfx", gfx::GfxModule::create_api_table(lua)?)?;
-        table.set("screen", screen::ScreenModule::create_api_table(lua)?)?;
-        table.set(
-            "structure",
-            structure::StructureModule::create_api_table(lua)?,
-        )?;
-        table.set("time", time::TimeModule::create_api_table(lua)?)?;
-        table.set("ui", ui::UIModule::create_api_table(lua)?)?;
+        set_namespace::<asset::AssetModule>(lua, &table, "asset")?;
+        set_namespace::<audio::AudioModule>(lua, &table, "audio")?;
+        set_namespace::<component::ComponentModule>(lua, &table, "component")?;
+        set_namespace::<console::ConsoleModule>(lua, &table, "console")?;
+        set_namespace::<diagnostic::DiagnosticModule>(lua, &table, "diagnostic")?;
+        set_namespace::<entity::EntityModule>(lua, &table, "entity")?;
+        set_namespace::<event::EventModule>(lua, &table, "event")?;
+        set_namespace::<gfx::GfxModule>(lua, &table, "gfx")?;
+        set_namespace::<input::InputModule>(lua, &table, "input")?;
+        set_namespace::<prefab::PrefabModule>(lua, &table, "prefab")?;
+        set_namespace::<save::SaveModule>(lua, &table, "save")?;
+        set_namespace::<scene::SceneModule>(lua, &table, "scene")?;
+        set_namespace::<screen::ScreenModule>(lua, &table, "screen")?;
+        set_namespace::<structure::StructureModule>(lua, &table, "structure")?;
+        set_namespace::<system::SystemModule>(lua, &table, "system")?;
+        set_namespace::<task::TaskModule>(lua, &table, "task")?;
+        set_namespace::<time::TimeModule>(lua, &table, "time")?;
+        set_namespace::<turn::TurnModule>(lua, &table, "turn")?;
+        set_namespace::<ui::UIModule>(lua, &table, "ui")?;
 
         Ok(table)
     }
 }
+
+/// Sets the API table of a namespace, registering it for the metadata collected by `lua_api_meta`.
+fn set_namespace<'lua, T>(
+    lua: &'lua Lua,
+    table: &LuaTable<'lua>,
+    name: &'static str,
+) -> LuaResult<()>
+where
+    T: LuaApiTable,
+{
+    crate::script::register_lua_namespace(lua, name);
+    crate::script::register_lua_functions(lua, T::lua_functions());
+    table.set(name, T::create_api_table(lua)?)
+}
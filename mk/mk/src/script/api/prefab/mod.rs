@@ -0,0 +1,392 @@
+use crate::{
+    component::Transform,
+    engine::use_context,
+    entity::destroy_entity,
+    handles::PrefabHandle,
+    script::{
+        api::{
+            entity::{ComponentParams, Entity, EntityBuilderImpl},
+            LuaApiTable,
+        },
+        LuaMethodMeta, LuaParamMeta,
+    },
+};
+use mlua::prelude::*;
+
+/// The registry key of the table caching the templates of the prefabs, by name.
+const TEMPLATES_KEY: &str = "mk.prefab.templates";
+
+pub struct PrefabModule;
+
+impl LuaApiTable for PrefabModule {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        table.set(
+            "instantiate",
+            lua.create_function(|lua, (name, overrides): (LuaString, Option<LuaTable>)| {
+                instantiate(lua, name.to_str()?, overrides)
+            })?,
+        )?;
+        table.set(
+            "template",
+            lua.create_function(|lua, name: LuaString| copy(lua, template(lua, name.to_str()?)?))?,
+        )?;
+        table.set(
+            "reload",
+            lua.create_function(|lua, name: Option<LuaString>| {
+                let templates = templates(lua)?;
+
+                match name {
+                    Some(name) => templates.set(name, LuaNil)?,
+                    None => lua.set_named_registry_value(TEMPLATES_KEY, lua.create_table()?)?,
+                }
+
+                Ok(())
+            })?,
+        )?;
+
+        Ok(table)
+    }
+
+    fn lua_functions() -> &'static [LuaMethodMeta] {
+        &[
+            LuaMethodMeta {
+                name: "instantiate",
+                doc: "Builds the entity tree of a prefab, with the overrides merged into its template. Returns the root.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "name",
+                        ty: "string",
+                    },
+                    LuaParamMeta {
+                        name: "overrides",
+                        ty: "table?",
+                    },
+                ],
+                returns: Some("Entity"),
+            },
+            LuaMethodMeta {
+                name: "template",
+                doc: "Returns a copy of the template of a prefab.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "name",
+                    ty: "string",
+                }],
+                returns: Some("table"),
+            },
+            LuaMethodMeta {
+                name: "reload",
+                doc: "Forgets the cached template of a prefab, or of every prefab if no name is given.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "name",
+                    ty: "string?",
+                }],
+                returns: None,
+            },
+        ]
+    }
+}
+
+/// Builds the entity tree of the prefab, with the overrides merged into its template.
+/// The `parent` field of the overrides attaches the root to the entity.
+pub fn instantiate<'lua>(
+    lua: &'lua Lua,
+    name: &str,
+    overrides: Option<LuaTable<'lua>>,
+) -> LuaResult<Entity> {
+    let node = lua.create_table()?;
+    node.set("prefab", name)?;
+
+    let parent = match overrides {
+        Some(overrides) => {
+            let parent = overrides.get::<_, Option<Entity>>("parent")?;
+
+            for pair in overrides.pairs::<LuaValue, LuaValue>() {
+                let (key, value) = pair?;
+                node.set(key, value)?;
+            }
+
+            node.set("prefab", name)?;
+            node.set("parent", LuaNil)?;
+            parent
+        }
+        None => None,
+    };
+    let parent = match parent {
+        Some(parent) => Some(
+            parent
+                .with_ref(|transform: &Transform| *transform)
+                .ok_or_else(|| LuaError::external("the parent has no transform"))?,
+        ),
+        None => None,
+    };
+
+    instantiate_node(lua, node, parent, &mut Vec::new())
+}
+
+/// Builds the entity described by the node and its children. `prefabs` holds the prefabs being instantiated,
+/// from the outermost, to detect prefabs including themselves.
+fn instantiate_node<'lua>(
+    lua: &'lua Lua,
+    node: LuaTable<'lua>,
+    parent: Option<Transform>,
+    prefabs: &mut Vec<String>,
+) -> LuaResult<Entity> {
+    let depth = prefabs.len();
+    let result =
+        resolve(lua, node, prefabs).and_then(|node| build_node(lua, node, parent, prefabs));
+    prefabs.truncate(depth);
+    result
+}
+
+/// Replaces a node naming a prefab with the template of the prefab, with the other fields of the node merged into it.
+/// Repeats as long as the template names another prefab, so that a prefab can be a variant of another.
+fn resolve<'lua>(
+    lua: &'lua Lua,
+    mut node: LuaTable<'lua>,
+    prefabs: &mut Vec<String>,
+) -> LuaResult<LuaTable<'lua>> {
+    while let Some(name) = node.get::<_, Option<String>>("prefab")? {
+        if prefabs.contains(&name) {
+            return Err(LuaError::external(format!(
+                "prefab {} includes itself through {}",
+                name,
+                prefabs.join(" -> ")
+            )));
+        }
+
+        let overrides = copy(lua, node)?;
+        overrides.set("prefab", LuaNil)?;
+        node = merge(lua, template(lua, &name)?, overrides)?;
+        prefabs.push(name);
+    }
+
+    Ok(node)
+}
+
+fn build_node<'lua>(
+    lua: &'lua Lua,
+    node: LuaTable<'lua>,
+    parent: Option<Transform>,
+    prefabs: &mut Vec<String>,
+) -> LuaResult<Entity> {
+    let mut builder = EntityBuilderImpl {
+        name: node.get("name")?,
+        transform_parent: parent,
+        transform_position: node.get("position")?,
+        transform_scale: node.get("scale")?,
+        transform_angle: node.get("angle")?,
+        size: node.get("size")?,
+        ..Default::default()
+    };
+
+    if let Some(components) = node.get::<_, Option<LuaTable>>("components")? {
+        for pair in components.pairs::<LuaString, LuaValue>() {
+            let (name, params) = pair?;
+
+            // Overrides remove a component of the template by setting it to false.
+            if let LuaValue::Boolean(false) = params {
+                continue;
+            }
+
+            builder.set_component(ComponentParams::from_lua(name.to_str()?, params, lua)?);
+        }
+    }
+
+    let entity = builder.build(lua)?;
+    let transform = entity.with_ref(|transform: &Transform| *transform);
+
+    let result = build_children(lua, &node, transform, prefabs).and_then(|_| {
+        match node.get::<_, Option<LuaFunction>>("on_instantiate")? {
+            Some(on_instantiate) => on_instantiate.call::<_, ()>(entity),
+            None => Ok(()),
+        }
+    });
+
+    // The entity is destroyed along with the children already built if the rest fails.
+    if let Err(err) = result {
+        destroy_entity(use_context(), entity.0, lua);
+        return Err(err);
+    }
+
+    Ok(entity)
+}
+
+fn build_children<'lua>(
+    lua: &'lua Lua,
+    node: &LuaTable<'lua>,
+    parent: Option<Transform>,
+    prefabs: &mut Vec<String>,
+) -> LuaResult<()> {
+    if let Some(children) = node.get::<_, Option<LuaTable>>("children")? {
+        for child in children.sequence_values::<LuaTable>() {
+            instantiate_node(lua, child?, parent, prefabs)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn templates(lua: &Lua) -> LuaResult<LuaTable> {
+    match lua.named_registry_value::<_, Option<LuaTable>>(TEMPLATES_KEY)? {
+        Some(templates) => Ok(templates),
+        None => {
+            let templates = lua.create_table()?;
+            lua.set_named_registry_value(TEMPLATES_KEY, templates.clone())?;
+            Ok(templates)
+        }
+    }
+}
+
+/// Returns the table the prefab evaluates to, evaluating it when it is used for the first time.
+fn template<'lua>(lua: &'lua Lua, name: &str) -> LuaResult<LuaTable<'lua>> {
+    let templates = templates(lua)?;
+
+    if let Some(template) = templates.get::<_, Option<LuaTable>>(name)? {
+        return Ok(template);
+    }
+
+    let prefab = use_context()
+        .asset_mgr()
+        .load::<PrefabHandle>(name)
+        .map_err(|err| {
+            LuaError::external(format!("failed to load prefab {} due to: {}", name, err))
+        })?;
+    let template = lua
+        .load(prefab.source())
+        .set_name(format!("@prefabs/{}.lua", prefab.name()))?
+        .call::<_, LuaTable>(())?;
+    templates.set(name, template.clone())?;
+
+    Ok(template)
+}
+
+/// Returns a copy of the table, copying the tables in it too.
+fn copy<'lua>(lua: &'lua Lua, table: LuaTable<'lua>) -> LuaResult<LuaTable<'lua>> {
+    let copied = lua.create_table()?;
+
+    for pair in table.pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair?;
+        let value = match value {
+            LuaValue::Table(table) => LuaValue::Table(copy(lua, table)?),
+            value => value,
+        };
+        copied.set(key, value)?;
+    }
+
+    Ok(copied)
+}
+
+/// Returns a copy of the table with the overrides merged into it. Tables present in both are merged in turn, so that
+/// `children` are merged by index and `components` by name; the other values of the overrides replace the ones
+/// of the table.
+fn merge<'lua>(
+    lua: &'lua Lua,
+    table: LuaTable<'lua>,
+    overrides: LuaTable<'lua>,
+) -> LuaResult<LuaTable<'lua>> {
+    let merged = copy(lua, table)?;
+
+    for pair in overrides.pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair?;
+        let value = match (merged.get::<_, LuaValue>(key.clone())?, value) {
+            (LuaValue::Table(table), LuaValue::Table(overrides)) => {
+                LuaValue::Table(merge(lua, table, overrides)?)
+            }
+            (_, value) => value,
+        };
+        merged.set(key, value)?;
+    }
+
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn overrides_are_merged_into_copies() {
+        let lua = Lua::new();
+        let template = lua
+            .load(
+                r#"{
+                    name = "enemy",
+                    components = { size = { 1, 1 }, sprite_renderer = { order = 1 } },
+                    children = { { name = "shadow" }, { name = "weapon" } },
+                }"#,
+            )
+            .eval::<LuaTable>()
+            .unwrap();
+        let overrides = lua
+            .load(
+                r#"{
+                    name = "boss",
+                    components = { sprite_renderer = false, text_field = { text = "!" } },
+                    children = { [2] = { position = 2 } },
+                }"#,
+            )
+            .eval::<LuaTable>()
+            .unwrap();
+
+        let merged = merge(&lua, template.clone(), overrides).unwrap();
+        let check = lua
+            .load(
+                r#"
+                local merged, template = ...
+                assert(merged.name == "boss")
+                assert(merged.components.size[2] == 1)
+                assert(merged.components.sprite_renderer == false)
+                assert(merged.components.text_field.text == "!")
+                assert(merged.children[1].name == "shadow")
+                assert(merged.children[2].name == "weapon" and merged.children[2].position == 2)
+                assert(template.name == "enemy" and template.components.sprite_renderer.order == 1)
+                assert(template.children[2].position == nil)
+                assert(merged.components.size ~= template.components.size)
+                "#,
+            )
+            .into_function()
+            .unwrap();
+
+        check.call::<_, ()>((merged, template)).unwrap();
+    }
+
+    #[test]
+    fn prefabs_including_themselves_are_rejected() {
+        let lua = Lua::new();
+        let templates = lua
+            .load(
+                r#"{
+                    a = { prefab = "b", name = "a" },
+                    b = { prefab = "c" },
+                    c = { prefab = "a" },
+                    d = { prefab = "e", name = "d" },
+                    e = { name = "e", position = 1 },
+                }"#,
+            )
+            .eval::<LuaTable>()
+            .unwrap();
+        lua.set_named_registry_value(TEMPLATES_KEY, templates)
+            .unwrap();
+
+        let node = lua.create_table().unwrap();
+        node.set("prefab", "a").unwrap();
+        let err = resolve(&lua, node, &mut Vec::new()).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("prefab a includes itself through a -> b -> c"));
+
+        // A variant is resolved into its base, the fields of the variant winning.
+        let node = lua.create_table().unwrap();
+        node.set("prefab", "d").unwrap();
+        let mut prefabs = Vec::new();
+        let node = resolve(&lua, node, &mut prefabs).unwrap();
+        assert_eq!(node.get::<_, String>("name").unwrap(), "d");
+        assert_eq!(node.get::<_, i64>("position").unwrap(), 1);
+        assert_eq!(prefabs, vec!["d", "e"]);
+    }
+}
@@ -0,0 +1,401 @@
+use crate::{
+    emit_diagnostic_warn,
+    engine::use_context,
+    entity::destroy_entity,
+    save::{SaveFile, SaveOptions, SavedValueDecoder, SavedValueEncoder},
+    scene::{capture_scene_with_ids, instantiate_scene_with_ids},
+    script::{api::LuaApiTable, entity::Entity, LuaMethodMeta, LuaParamMeta},
+    util::user_file_path,
+};
+use mlua::prelude::*;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{read_dir, remove_file},
+    io::ErrorKind as IOErrorKind,
+    path::PathBuf,
+};
+
+/// The registry key of the table holding the configuration and the migrations.
+const CONFIG_KEY: &str = "mk.save.config";
+const SAVE_EXTENSION: &str = "sav";
+
+pub struct SaveModule;
+
+impl LuaApiTable for SaveModule {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        table.set(
+            "configure",
+            lua.create_function(|lua, options: LuaTable| {
+                let config = config(lua)?;
+
+                for key in ["version", "compress", "key"] {
+                    if let Some(value) = options.get::<_, Option<LuaValue>>(key)? {
+                        config.set(key, value)?;
+                    }
+                }
+
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "add_migration",
+            lua.create_function(|lua, (version, migration): (u32, LuaFunction)| {
+                config(lua)?
+                    .get::<_, LuaTable>("migrations")?
+                    .set(version, migration)
+            })?,
+        )?;
+        table.set(
+            "save",
+            lua.create_function(
+                |lua, (slot, values, scene): (LuaString, LuaTable, Option<LuaValue>)| {
+                    let slot = slot.to_str()?;
+                    let path = slot_path(slot)?;
+
+                    Ok(
+                        match save(lua, values, scene).and_then(|(save, options)| {
+                            save.save(&path, &options)
+                                .map_err(|err| LuaError::external(err.to_string()))
+                        }) {
+                            Ok(()) => true,
+                            Err(err) => {
+                                emit_diagnostic_warn!(format!(
+                                    "failed to save to slot {} due to: {}",
+                                    slot, err
+                                ));
+                                false
+                            }
+                        },
+                    )
+                },
+            )?,
+        )?;
+        table.set(
+            "load",
+            lua.create_function(|lua, (slot, parent): (LuaString, Option<Entity>)| {
+                let slot = slot.to_str()?;
+                let path = slot_path(slot)?;
+                let key = config(lua)?.get::<_, Option<String>>("key")?;
+                let save = match SaveFile::load(&path, key.as_deref()) {
+                    Ok(save) => save,
+                    Err(err) => {
+                        emit_diagnostic_warn!(format!(
+                            "failed to load slot {} due to: {}",
+                            slot, err
+                        ));
+                        return Ok(LuaMultiValue::new());
+                    }
+                };
+
+                Ok(match load(lua, save, parent) {
+                    Ok((values, roots)) => (values, roots).to_lua_multi(lua)?,
+                    Err(err) => {
+                        emit_diagnostic_warn!(format!(
+                            "failed to load slot {} due to: {}",
+                            slot, err
+                        ));
+                        LuaMultiValue::new()
+                    }
+                })
+            })?,
+        )?;
+        table.set(
+            "exists",
+            lua.create_function(|_lua, slot: LuaString| Ok(slot_path(slot.to_str()?)?.is_file()))?,
+        )?;
+        table.set(
+            "delete",
+            lua.create_function(|_lua, slot: LuaString| {
+                let slot = slot.to_str()?;
+
+                Ok(match remove_file(slot_path(slot)?) {
+                    Ok(()) => true,
+                    Err(err) if err.kind() == IOErrorKind::NotFound => false,
+                    Err(err) => {
+                        emit_diagnostic_warn!(format!(
+                            "failed to delete slot {} due to: {}",
+                            slot, err
+                        ));
+                        false
+                    }
+                })
+            })?,
+        )?;
+        table.set(
+            "slots",
+            lua.create_function(|_lua, ()| {
+                let mut slots = Vec::new();
+
+                // No directory means nothing has been saved yet.
+                if let Ok(entries) = read_dir(saves_dir()) {
+                    for entry in entries.flatten() {
+                        let path = entry.path();
+
+                        if path.extension().is_some_and(|ext| ext == SAVE_EXTENSION) {
+                            if let Some(slot) = path.file_stem().and_then(|stem| stem.to_str()) {
+                                slots.push(slot.to_owned());
+                            }
+                        }
+                    }
+                }
+
+                slots.sort();
+                Ok(slots)
+            })?,
+        )?;
+
+        Ok(table)
+    }
+
+    fn lua_functions() -> &'static [LuaMethodMeta] {
+        &[
+            LuaMethodMeta {
+                name: "configure",
+                doc: "Sets the `version`, `compress` and `key` options of the saves.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "options",
+                    ty: "table",
+                }],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "add_migration",
+                doc: "Adds the migration of the values saved at `version` to the next version. It may modify the values in place or return new ones.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "version",
+                        ty: "integer",
+                    },
+                    LuaParamMeta {
+                        name: "migration",
+                        ty: "fun(values: table): table?",
+                    },
+                ],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "save",
+                doc: "Saves the values to a slot, with the whole scene if `scene` is `true` or the tree of the given entity. Returns whether it could be saved.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "slot",
+                        ty: "string",
+                    },
+                    LuaParamMeta {
+                        name: "values",
+                        ty: "table",
+                    },
+                    LuaParamMeta {
+                        name: "scene",
+                        ty: "boolean|Entity|nil",
+                    },
+                ],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "load",
+                doc: "Loads a slot, instantiating its scene under `parent`. Returns the values and the roots of the scene, or nothing if it could not be loaded.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "slot",
+                        ty: "string",
+                    },
+                    LuaParamMeta {
+                        name: "parent",
+                        ty: "Entity?",
+                    },
+                ],
+                returns: Some("table?, Entity[]?"),
+            },
+            LuaMethodMeta {
+                name: "exists",
+                doc: "Returns whether a slot has been saved.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "slot",
+                    ty: "string",
+                }],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "delete",
+                doc: "Deletes a slot. Returns whether it existed.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "slot",
+                    ty: "string",
+                }],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "slots",
+                doc: "Returns the names of the saved slots, sorted.",
+                is_static: true,
+                params: &[],
+                returns: Some("string[]"),
+            },
+        ]
+    }
+}
+
+fn save<'lua>(
+    lua: &'lua Lua,
+    values: LuaTable<'lua>,
+    scene: Option<LuaValue<'lua>>,
+) -> LuaResult<(SaveFile, SaveOptions)> {
+    let (scene, entity_ids) = match scene {
+        None | Some(LuaValue::Nil) | Some(LuaValue::Boolean(false)) => (None, HashMap::new()),
+        Some(LuaValue::Boolean(true)) => {
+            let (scene, ids) = capture_scene_with_ids(use_context(), None);
+            (Some(scene), ids)
+        }
+        Some(root) => {
+            let root = Entity::from_lua(root, lua)?;
+            let (scene, ids) = capture_scene_with_ids(use_context(), Some(root.0));
+            (Some(scene), ids)
+        }
+    };
+
+    let mut encoder = SavedValueEncoder::new(&entity_ids);
+    let mut saved_values = BTreeMap::new();
+
+    for pair in values.pairs::<LuaString, LuaValue>() {
+        let (name, value) = pair?;
+        let name = name.to_str()?;
+        let value = encoder.encode(value).map_err(|err| {
+            LuaError::external(format!("cannot save value {} due to: {}", name, err))
+        })?;
+        saved_values.insert(name.to_owned(), value);
+    }
+
+    for warning in encoder.take_warnings() {
+        emit_diagnostic_warn!(warning);
+    }
+
+    let config = config(lua)?;
+    let save = SaveFile {
+        schema_version: config.get("version")?,
+        scene,
+        values: saved_values,
+        tables: encoder.finish(),
+    };
+    let options = SaveOptions {
+        compress: config.get("compress")?,
+        key: config.get("key")?,
+    };
+
+    Ok((save, options))
+}
+
+/// Instantiates the scene of the save and restores its values, migrating them to the current version.
+/// Returns the values and the roots of the scene.
+fn load<'lua>(
+    lua: &'lua Lua,
+    save: SaveFile,
+    parent: Option<Entity>,
+) -> LuaResult<(LuaTable<'lua>, Vec<Entity>)> {
+    let config = config(lua)?;
+    let version = config.get::<_, u32>("version")?;
+
+    if version < save.schema_version {
+        return Err(LuaError::external(format!(
+            "the save is of version {}, which is newer than the current version {}",
+            save.schema_version, version
+        )));
+    }
+
+    let (roots, entities) = match &save.scene {
+        Some(scene) => {
+            instantiate_scene_with_ids(use_context(), scene, parent.map(|parent| parent.0), lua)
+                .map_err(|err| LuaError::external(err.to_string()))?
+        }
+        None => (Vec::new(), HashMap::new()),
+    };
+
+    let result = restore_values(lua, &save, &entities)
+        .and_then(|values| migrate(&config, values, save.schema_version, version));
+
+    match result {
+        Ok(values) => Ok((values, roots.into_iter().map(Entity::new).collect())),
+        Err(err) => {
+            for root in roots {
+                destroy_entity(use_context(), root, lua);
+            }
+
+            Err(err)
+        }
+    }
+}
+
+fn restore_values<'lua>(
+    lua: &'lua Lua,
+    save: &SaveFile,
+    entities: &HashMap<u32, specs::Entity>,
+) -> LuaResult<LuaTable<'lua>> {
+    let decoder = SavedValueDecoder::new(lua, &save.tables, entities)?;
+    let values = lua.create_table()?;
+
+    for (name, value) in &save.values {
+        values.set(name.as_str(), decoder.decode(value)?)?;
+    }
+
+    Ok(values)
+}
+
+/// Runs the migrations from `from` up to `to`, in order. A migration may modify the values in place or return
+/// new ones; versions without a migration leave the values as they are.
+fn migrate<'lua>(
+    config: &LuaTable<'lua>,
+    mut values: LuaTable<'lua>,
+    from: u32,
+    to: u32,
+) -> LuaResult<LuaTable<'lua>> {
+    let migrations = config.get::<_, LuaTable>("migrations")?;
+
+    for version in from..to {
+        if let Some(migration) = migrations.get::<_, Option<LuaFunction>>(version)? {
+            if let Some(migrated) = migration
+                .call::<_, Option<LuaTable>>(values.clone())
+                .map_err(|err| {
+                    LuaError::external(format!(
+                        "migration from version {} failed due to: {}",
+                        version, err
+                    ))
+                })?
+            {
+                values = migrated;
+            }
+        }
+    }
+
+    Ok(values)
+}
+
+fn config(lua: &Lua) -> LuaResult<LuaTable> {
+    match lua.named_registry_value::<_, Option<LuaTable>>(CONFIG_KEY)? {
+        Some(config) => Ok(config),
+        None => {
+            let config = lua.create_table()?;
+            config.set("version", 0)?;
+            config.set("compress", false)?;
+            config.set("migrations", lua.create_table()?)?;
+            lua.set_named_registry_value(CONFIG_KEY, config.clone())?;
+            Ok(config)
+        }
+    }
+}
+
+fn saves_dir() -> PathBuf {
+    use_context().user_dir().join("saves")
+}
+
+/// Returns the path of the slot, rejecting names that could escape the save directory.
+fn slot_path(slot: &str) -> LuaResult<PathBuf> {
+    user_file_path(saves_dir(), "slot", slot, SAVE_EXTENSION).map_err(LuaError::external)
+}
@@ -0,0 +1,165 @@
+use crate::{
+    emit_diagnostic_warn,
+    engine::use_context,
+    scene::{capture_scene, instantiate_scene, Scene, SceneError},
+    script::{api::LuaApiTable, entity::Entity, LuaMethodMeta, LuaParamMeta},
+    util::user_file_path,
+};
+use mlua::prelude::*;
+use std::path::PathBuf;
+
+pub struct SceneModule;
+
+impl LuaApiTable for SceneModule {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        table.set(
+            "save",
+            lua.create_function(|_lua, (name, root): (LuaString, Option<Entity>)| {
+                let path = user_scene_path(name.to_str()?)?;
+                let scene = capture_scene(use_context(), root.map(|root| root.0));
+
+                Ok(match scene.save(&path) {
+                    Ok(()) => true,
+                    Err(err) => {
+                        emit_diagnostic_warn!(format!(
+                            "failed to save scene to {} due to: {}",
+                            path.display(),
+                            err
+                        ));
+                        false
+                    }
+                })
+            })?,
+        )?;
+        table.set(
+            "load",
+            lua.create_function(|lua, (name, parent): (LuaString, Option<Entity>)| {
+                let path = user_scene_path(name.to_str()?)?;
+
+                Ok(
+                    match Scene::load(&path).and_then(|scene| instantiate(lua, &scene, parent)) {
+                        Ok(roots) => Some(roots),
+                        Err(err) => {
+                            emit_diagnostic_warn!(format!(
+                                "failed to load scene from {} due to: {}",
+                                path.display(),
+                                err
+                            ));
+                            None
+                        }
+                    },
+                )
+            })?,
+        )?;
+        table.set(
+            "to_json",
+            lua.create_function(|_lua, root: Option<Entity>| {
+                capture_scene(use_context(), root.map(|root| root.0))
+                    .to_json()
+                    .map_err(|err| LuaError::external(err.to_string()))
+            })?,
+        )?;
+        table.set(
+            "from_json",
+            lua.create_function(|lua, (json, parent): (LuaString, Option<Entity>)| {
+                Ok(
+                    match Scene::from_json(json.to_str()?)
+                        .and_then(|scene| instantiate(lua, &scene, parent))
+                    {
+                        Ok(roots) => Some(roots),
+                        Err(err) => {
+                            emit_diagnostic_warn!(format!("failed to load scene due to: {}", err));
+                            None
+                        }
+                    },
+                )
+            })?,
+        )?;
+
+        Ok(table)
+    }
+
+    fn lua_functions() -> &'static [LuaMethodMeta] {
+        &[
+            LuaMethodMeta {
+                name: "save",
+                doc: "Saves the tree of `root`, or the whole scene, to the user directory. Returns whether it could be saved.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "name",
+                        ty: "string",
+                    },
+                    LuaParamMeta {
+                        name: "root",
+                        ty: "Entity?",
+                    },
+                ],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "load",
+                doc: "Instantiates a scene saved with `save` under `parent`. Returns its roots, or `nil` if it could not be loaded.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "name",
+                        ty: "string",
+                    },
+                    LuaParamMeta {
+                        name: "parent",
+                        ty: "Entity?",
+                    },
+                ],
+                returns: Some("Entity[]?"),
+            },
+            LuaMethodMeta {
+                name: "to_json",
+                doc: "Returns the tree of `root`, or the whole scene, as JSON.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "root",
+                    ty: "Entity?",
+                }],
+                returns: Some("string"),
+            },
+            LuaMethodMeta {
+                name: "from_json",
+                doc: "Instantiates a scene from JSON under `parent`. Returns its roots, or `nil` if it could not be loaded.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "json",
+                        ty: "string",
+                    },
+                    LuaParamMeta {
+                        name: "parent",
+                        ty: "Entity?",
+                    },
+                ],
+                returns: Some("Entity[]?"),
+            },
+        ]
+    }
+}
+
+fn instantiate(
+    lua: &Lua,
+    scene: &Scene,
+    parent: Option<Entity>,
+) -> Result<Vec<Entity>, SceneError> {
+    let roots = instantiate_scene(use_context(), scene, parent.map(|parent| parent.0), lua)?;
+    Ok(roots.into_iter().map(Entity::new).collect())
+}
+
+fn user_scene_path(name: &str) -> LuaResult<PathBuf> {
+    user_file_path(
+        use_context().user_dir().join("scenes"),
+        "scene",
+        name,
+        "json",
+    )
+    .map_err(LuaError::external)
+}
@@ -34,6 +34,20 @@ impl LuaApiTable for Screen {
                 Ok(screen_mgr.scale_factor())
             })?,
         )?;
+        table.set(
+            "vsync",
+            lua.create_function(|_lua, ()| {
+                let render_mgr = use_context().render_mgr();
+                Ok(render_mgr.vsync())
+            })?,
+        )?;
+        table.set(
+            "set_vsync",
+            lua.create_function(|_lua, vsync: bool| {
+                use_context().render_mgr_mut().set_vsync(vsync);
+                Ok(())
+            })?,
+        )?;
 
         Ok(table)
     }
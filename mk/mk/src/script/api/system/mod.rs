@@ -0,0 +1,210 @@
+use crate::{
+    diagnostic::{report_script_error, ScriptError},
+    engine::use_context,
+    script::{
+        api::LuaApiTable,
+        entity::{Entity, EntityQuery},
+        LuaMethodMeta, LuaParamMeta,
+    },
+    system::SystemOptions,
+    EngineContext,
+};
+use mlua::prelude::*;
+
+mod system_stage;
+
+pub use system_stage::*;
+
+pub struct SystemModule;
+
+impl LuaApiTable for SystemModule {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        table.set("SystemStage", SystemStage::create_api_table(lua)?)?;
+        table.set(
+            "add",
+            lua.create_function(|lua, params: LuaTable| {
+                let name = params.get::<_, String>("name")?;
+                let stage = params
+                    .get::<_, Option<SystemStage>>("stage")?
+                    .unwrap_or(SystemStage::Update);
+                let query = EntityQuery::new(
+                    params.get("query")?,
+                    params
+                        .get::<_, Option<Vec<String>>>("without")?
+                        .unwrap_or_default(),
+                )?;
+                let run = lua.create_registry_value(params.get::<_, LuaFunction>("run")?)?;
+                let options = SystemOptions {
+                    before: params
+                        .get::<_, Option<Vec<String>>>("before")?
+                        .unwrap_or_default(),
+                    after: params
+                        .get::<_, Option<Vec<String>>>("after")?
+                        .unwrap_or_default(),
+                };
+                let system_name = name.clone();
+
+                use_context()
+                    .system_mgr()
+                    .add_thread_local_fn(
+                        stage,
+                        name,
+                        move |context| run_lua_system(context, &system_name, &query, &run),
+                        options,
+                    )
+                    .map_err(|err| LuaError::external(err.to_string()))
+            })?,
+        )?;
+        table.set(
+            "remove",
+            lua.create_function(|_lua, name: LuaString| {
+                Ok(use_context().system_mgr().remove_system(name.to_str()?))
+            })?,
+        )?;
+        table.set(
+            "names",
+            lua.create_function(|_lua, stage: SystemStage| {
+                Ok(use_context().system_mgr().system_names(stage))
+            })?,
+        )?;
+
+        Ok(table)
+    }
+
+    fn lua_functions() -> &'static [LuaMethodMeta] {
+        &[
+            LuaMethodMeta {
+                name: "add",
+                doc: "Adds a system calling `run` with each entity matching its query, from a table of `name`, `query`, `run` and optionally `stage`, `without`, `before` and `after`.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "params",
+                    ty: "table",
+                }],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "remove",
+                doc: "Removes a system. Returns whether it existed.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "name",
+                    ty: "string",
+                }],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "names",
+                doc: "Returns the names of the systems of a stage, in the order they run.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "stage",
+                    ty: "SystemStage",
+                }],
+                returns: Some("string[]"),
+            },
+        ]
+    }
+}
+
+/// Calls the function of the system with each entity matching its query. An error stops the system for the frame.
+fn run_lua_system(context: &EngineContext, name: &str, query: &EntityQuery, run: &LuaRegistryKey) {
+    let lua = context.script_mgr().lua();
+    let result = lua.registry_value::<LuaFunction>(run).and_then(|run| {
+        call_for_each(
+            &run,
+            query.entities(context),
+            context.time_mgr().dt_f64(),
+            |entity| context.entity_mgr().is_destroyed(entity),
+        )
+    });
+
+    if let Err(err) = result {
+        report_script_error(
+            format!("system {}", name),
+            &ScriptError::from_lua_error(&err),
+        );
+    }
+}
+
+/// Calls the function with each of the entities and the delta time, stopping at the first error.
+fn call_for_each(
+    run: &LuaFunction,
+    entities: Vec<specs::Entity>,
+    dt: f64,
+    is_destroyed: impl Fn(specs::Entity) -> bool,
+) -> LuaResult<()> {
+    for entity in entities {
+        // Entities destroyed by the system while iterating are skipped.
+        if is_destroyed(entity) {
+            continue;
+        }
+
+        run.call::<_, ()>((Entity::new(entity), dt))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use specs::{Builder, World, WorldExt};
+    use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+    #[test]
+    fn errors_stop_the_failing_system_only() {
+        let lua = Lua::new();
+        let mut world = World::new();
+        let entities = (0..3)
+            .map(|_| world.create_entity().build())
+            .collect::<Vec<_>>();
+        let (failing, counting) = lua
+            .load(
+                r#"
+                count = 0
+                return function() error("system failed") end, function() count = count + 1 end
+                "#,
+            )
+            .eval::<(LuaFunction, LuaFunction)>()
+            .unwrap();
+
+        let err = call_for_each(&failing, entities.clone(), 0f64, |_| false).unwrap_err();
+        assert!(err.to_string().contains("system failed"));
+
+        call_for_each(&counting, entities, 0f64, |_| false).unwrap();
+        assert_eq!(lua.globals().get::<_, i64>("count").unwrap(), 3);
+    }
+
+    #[test]
+    fn entities_destroyed_while_running_are_skipped() {
+        let lua = Lua::new();
+        let mut world = World::new();
+        let entities = (0..3)
+            .map(|_| world.create_entity().build())
+            .collect::<Vec<_>>();
+        let destroyed = Rc::new(RefCell::new(HashSet::new()));
+        let visited = Rc::new(RefCell::new(Vec::new()));
+
+        let run = {
+            let destroyed = destroyed.clone();
+            let visited = visited.clone();
+            let last = entities[2];
+            lua.create_function(move |_lua, (entity, _dt): (LuaAnyUserData, f64)| {
+                visited.borrow_mut().push(entity.borrow::<Entity>()?.0);
+                // The first entity visited destroys the last one.
+                destroyed.borrow_mut().insert(last);
+                Ok(())
+            })
+            .unwrap()
+        };
+
+        call_for_each(&run, entities.clone(), 0f64, |entity| {
+            destroyed.borrow().contains(&entity)
+        })
+        .unwrap();
+        assert_eq!(*visited.borrow(), entities[..2]);
+    }
+}
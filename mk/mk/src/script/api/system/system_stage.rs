@@ -0,0 +1,31 @@
+use crate::script::{api::LuaApiTable, register_lua_enum, LuaEnumMeta, LuaEnumMetaProvider};
+use mlua::prelude::*;
+
+pub type SystemStage = crate::system::SystemStage;
+
+impl LuaApiTable for SystemStage {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        register_lua_enum::<Self>(lua);
+        let table = lua.create_table()?;
+
+        table.set("PreUpdate", Self::PreUpdate)?;
+        table.set("Update", Self::Update)?;
+        table.set("PostUpdate", Self::PostUpdate)?;
+        table.set("PreRender", Self::PreRender)?;
+
+        Ok(table)
+    }
+}
+
+impl LuaUserData for SystemStage {}
+
+impl LuaEnumMetaProvider for SystemStage {
+    fn lua_enum_meta() -> LuaEnumMeta {
+        LuaEnumMeta {
+            name: "SystemStage",
+            doc: "The points of a frame at which registered systems run.",
+            variants: &["PreUpdate", "Update", "PostUpdate", "PreRender"],
+            are_strings: false,
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{
+    engine::use_context,
+    script::{
+        api::LuaApiTable, register_lua_class, FFIFunction, FFIThread, LuaMethodMeta, LuaParamMeta,
+        TaskHandle, TaskWait,
+    },
+};
+use mlua::prelude::*;
+
+pub struct TaskModule;
+
+impl LuaApiTable for TaskModule {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        register_lua_class::<TaskHandle>(lua);
+        let table = lua.create_table()?;
+
+        table.set(
+            "spawn",
+            lua.create_function(|lua, function: LuaFunction| {
+                let thread = FFIThread::new(lua, lua.create_thread(function)?)?;
+                Ok(use_context().task_mgr_mut().spawn(thread))
+            })?,
+        )?;
+        table.set(
+            "wait",
+            yielding(
+                lua,
+                lua.create_function(|_lua, seconds: f64| Ok(TaskWait::Seconds(seconds)))?,
+            )?,
+        )?;
+        table.set(
+            "wait_frames",
+            yielding(
+                lua,
+                lua.create_function(|_lua, frames: Option<u32>| {
+                    Ok(TaskWait::Frames(frames.unwrap_or(1)))
+                })?,
+            )?,
+        )?;
+        table.set(
+            "wait_event",
+            yielding(
+                lua,
+                lua.create_function(|_lua, event: LuaValue| {
+                    let name = match event {
+                        // Event tables, e.g. `mk.event.TurnBegin`.
+                        LuaValue::Table(table) => table.get::<_, String>("name")?,
+                        LuaValue::String(name) => name.to_str()?.to_owned(),
+                        _ => {
+                            return Err(LuaError::external(
+                                "expected an event table or an event name",
+                            ))
+                        }
+                    };
+                    Ok(TaskWait::Event(name))
+                })?,
+            )?,
+        )?;
+        table.set(
+            "wait_until",
+            yielding(
+                lua,
+                lua.create_function(|lua, predicate: LuaFunction| {
+                    Ok(TaskWait::Until(FFIFunction::new(lua, predicate)?))
+                })?,
+            )?,
+        )?;
+
+        Ok(table)
+    }
+
+    fn lua_functions() -> &'static [LuaMethodMeta] {
+        &[
+            LuaMethodMeta {
+                name: "spawn",
+                doc: "Runs a function as a task, which can wait with the other functions of `mk.task`.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "callback",
+                    ty: "function",
+                }],
+                returns: Some("TaskHandle"),
+            },
+            LuaMethodMeta {
+                name: "wait",
+                doc: "Waits for the given seconds of the scaled clock.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "seconds",
+                    ty: "number",
+                }],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "wait_frames",
+                doc: "Waits for the given number of frames, `1` by default.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "frames",
+                    ty: "integer?",
+                }],
+                returns: None,
+            },
+            LuaMethodMeta {
+                name: "wait_event",
+                doc: "Waits for the next event of the given table or name. Returns its parameters.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "event",
+                    ty: "table|string",
+                }],
+                returns: Some("table"),
+            },
+            LuaMethodMeta {
+                name: "wait_until",
+                doc: "Waits until the predicate returns `true`, checked once per frame.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "predicate",
+                    ty: "fun(): boolean",
+                }],
+                returns: None,
+            },
+        ]
+    }
+}
+
+/// Wraps a function returning a `TaskWait` into one that yields it from the running task.
+fn yielding<'lua>(lua: &'lua Lua, make_wait: LuaFunction<'lua>) -> LuaResult<LuaFunction<'lua>> {
+    lua.load(
+        r#"
+local yield, make_wait = coroutine.yield, ...
+return function(...)
+  return yield(make_wait(...))
+end
+"#,
+    )
+    .set_name("mk.task")?
+    .call(make_wait)
+}
@@ -1,4 +1,8 @@
-use crate::script::api::LuaApiTable;
+use crate::{
+    engine::use_context,
+    script::{api::LuaApiTable, register_lua_class, FFIFunction, LuaMethodMeta, LuaParamMeta},
+    time::TimerHandle,
+};
 use mlua::prelude::*;
 
 mod time;
@@ -9,10 +13,90 @@ pub struct TimeModule;
 
 impl LuaApiTable for TimeModule {
     fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        register_lua_class::<TimerHandle>(lua);
         let table = lua.create_table()?;
 
         table.set("Time", time::Time::create_api_table(lua)?)?;
+        table.set(
+            "after",
+            lua.create_function(
+                |lua, (delay, function, is_unscaled): (f64, LuaFunction, Option<bool>)| {
+                    if !delay.is_finite() {
+                        return Err(LuaError::external("delay must be finite"));
+                    }
+
+                    let function = FFIFunction::new(lua, function)?;
+                    Ok(use_context().timer_mgr_mut().after(
+                        delay,
+                        is_unscaled.unwrap_or(false),
+                        function,
+                    ))
+                },
+            )?,
+        )?;
+        table.set(
+            "every",
+            lua.create_function(
+                |lua, (interval, function, is_unscaled): (f64, LuaFunction, Option<bool>)| {
+                    if !interval.is_finite() || interval <= 0f64 {
+                        return Err(LuaError::external("interval must be finite and positive"));
+                    }
+
+                    let function = FFIFunction::new(lua, function)?;
+                    Ok(use_context().timer_mgr_mut().every(
+                        interval,
+                        is_unscaled.unwrap_or(false),
+                        function,
+                    ))
+                },
+            )?,
+        )?;
 
         Ok(table)
     }
+
+    fn lua_functions() -> &'static [LuaMethodMeta] {
+        &[
+            LuaMethodMeta {
+                name: "after",
+                doc: "Calls a function once after the given delay, in seconds of the scaled clock unless `is_unscaled`.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "delay",
+                        ty: "number",
+                    },
+                    LuaParamMeta {
+                        name: "callback",
+                        ty: "function",
+                    },
+                    LuaParamMeta {
+                        name: "is_unscaled",
+                        ty: "boolean?",
+                    },
+                ],
+                returns: Some("TimerHandle"),
+            },
+            LuaMethodMeta {
+                name: "every",
+                doc: "Calls a function repeatedly at the given interval, in seconds of the scaled clock unless `is_unscaled`.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "interval",
+                        ty: "number",
+                    },
+                    LuaParamMeta {
+                        name: "callback",
+                        ty: "function",
+                    },
+                    LuaParamMeta {
+                        name: "is_unscaled",
+                        ty: "boolean?",
+                    },
+                ],
+                returns: Some("TimerHandle"),
+            },
+        ]
+    }
 }
@@ -1,10 +1,18 @@
-use crate::{engine::use_context, script::api::LuaApiTable};
+use crate::{
+    engine::use_context,
+    script::{
+        api::LuaApiTable, register_lua_class, LuaClassMeta, LuaMethodMeta, LuaParamMeta,
+        LuaUserDataMetaProvider,
+    },
+    time::MIN_FRAME_RATE_CAP,
+};
 use mlua::prelude::*;
 
 pub struct Time;
 
 impl LuaApiTable for Time {
     fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        register_lua_class::<Self>(lua);
         let table = lua.create_table()?;
 
         table.set(
@@ -21,7 +29,255 @@ impl LuaApiTable for Time {
                 Ok(time_mgr.dt_f64())
             })?,
         )?;
+        table.set(
+            "unscaled_time",
+            lua.create_function(|_lua, ()| {
+                let time_mgr = use_context().time_mgr();
+                Ok(time_mgr.unscaled_time_f64())
+            })?,
+        )?;
+        table.set(
+            "unscaled_dt",
+            lua.create_function(|_lua, ()| {
+                let time_mgr = use_context().time_mgr();
+                Ok(time_mgr.unscaled_dt_f64())
+            })?,
+        )?;
+        table.set(
+            "scale",
+            lua.create_function(|_lua, ()| {
+                let time_mgr = use_context().time_mgr();
+                Ok(time_mgr.scale())
+            })?,
+        )?;
+        table.set(
+            "set_scale",
+            lua.create_function(|_lua, scale: f64| {
+                if !scale.is_finite() || scale < 0f64 {
+                    return Err(LuaError::external(
+                        "time scale must be finite and not negative",
+                    ));
+                }
+
+                use_context().time_mgr_mut().set_scale(scale);
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "is_paused",
+            lua.create_function(|_lua, ()| {
+                let time_mgr = use_context().time_mgr();
+                Ok(time_mgr.is_paused())
+            })?,
+        )?;
+        table.set(
+            "set_paused",
+            lua.create_function(|_lua, is_paused: bool| {
+                use_context().time_mgr_mut().set_paused(is_paused);
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "fixed_dt",
+            lua.create_function(|_lua, ()| {
+                let time_mgr = use_context().time_mgr();
+                Ok(time_mgr.fixed_dt())
+            })?,
+        )?;
+        table.set(
+            "set_fixed_rate",
+            lua.create_function(|_lua, rate: Option<f64>| {
+                if let Some(rate) = rate {
+                    if !rate.is_finite() || rate <= 0f64 {
+                        return Err(LuaError::external("fixed rate must be finite and positive"));
+                    }
+                }
+
+                use_context().time_mgr_mut().set_fixed_rate(rate);
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "max_fixed_steps",
+            lua.create_function(|_lua, ()| {
+                let time_mgr = use_context().time_mgr();
+                Ok(time_mgr.max_fixed_steps())
+            })?,
+        )?;
+        table.set(
+            "set_max_fixed_steps",
+            lua.create_function(|_lua, max_fixed_steps: u32| {
+                use_context()
+                    .time_mgr_mut()
+                    .set_max_fixed_steps(max_fixed_steps);
+                Ok(())
+            })?,
+        )?;
+        table.set(
+            "alpha",
+            lua.create_function(|_lua, ()| {
+                let time_mgr = use_context().time_mgr();
+                Ok(time_mgr.alpha())
+            })?,
+        )?;
+        table.set(
+            "frame_rate_cap",
+            lua.create_function(|_lua, ()| {
+                let time_mgr = use_context().time_mgr();
+                Ok(time_mgr
+                    .frame_interval()
+                    .map(|interval| 1f64 / interval.as_secs_f64()))
+            })?,
+        )?;
+        table.set(
+            "set_frame_rate_cap",
+            lua.create_function(|_lua, fps: Option<f64>| {
+                if let Some(fps) = fps {
+                    if !fps.is_finite() || fps < MIN_FRAME_RATE_CAP {
+                        return Err(LuaError::external(format!(
+                            "frame rate cap must be finite and at least {}",
+                            MIN_FRAME_RATE_CAP
+                        )));
+                    }
+                }
+
+                use_context().time_mgr_mut().set_frame_rate_cap(fps);
+                Ok(())
+            })?,
+        )?;
 
         Ok(table)
     }
 }
+
+impl LuaUserDataMetaProvider for Time {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "Time",
+            doc: "The clocks of the engine. Scaled time follows the time scale and stops while paused.",
+            fields: &[],
+            methods: &[
+                LuaMethodMeta {
+                    name: "time",
+                    doc: "Returns the scaled time elapsed.",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("number"),
+                },
+                LuaMethodMeta {
+                    name: "dt",
+                    doc: "Returns the scaled duration of the last frame; `0` while paused.",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("number"),
+                },
+                LuaMethodMeta {
+                    name: "unscaled_time",
+                    doc: "Returns the real time elapsed, unaffected by the time scale and pausing.",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("number"),
+                },
+                LuaMethodMeta {
+                    name: "unscaled_dt",
+                    doc: "Returns the real duration of the last frame, unaffected by the time scale and pausing.",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("number"),
+                },
+                LuaMethodMeta {
+                    name: "scale",
+                    doc: "Returns how fast the scaled clock runs relative to the real time.",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("number"),
+                },
+                LuaMethodMeta {
+                    name: "set_scale",
+                    doc: "Sets how fast the scaled clock runs relative to the real time, e.g. `0.5` for slow motion.",
+                    is_static: true,
+                    params: &[LuaParamMeta {
+                        name: "scale",
+                        ty: "number",
+                    }],
+                    returns: None,
+                },
+                LuaMethodMeta {
+                    name: "is_paused",
+                    doc: "Returns whether the scaled clock is stopped.",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("boolean"),
+                },
+                LuaMethodMeta {
+                    name: "set_paused",
+                    doc: "Stops or resumes the scaled clock. Fixed steps are not run while paused.",
+                    is_static: true,
+                    params: &[LuaParamMeta {
+                        name: "is_paused",
+                        ty: "boolean",
+                    }],
+                    returns: None,
+                },
+                LuaMethodMeta {
+                    name: "fixed_dt",
+                    doc: "Returns the delta of a fixed step, or `nil` if fixed steps are disabled.",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("number?"),
+                },
+                LuaMethodMeta {
+                    name: "set_fixed_rate",
+                    doc: "Enables fixed steps at the given rate in Hz, or disables them with `nil`.",
+                    is_static: true,
+                    params: &[LuaParamMeta {
+                        name: "rate",
+                        ty: "number?",
+                    }],
+                    returns: None,
+                },
+                LuaMethodMeta {
+                    name: "max_fixed_steps",
+                    doc: "Returns the maximum number of fixed steps run in a frame.",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("integer"),
+                },
+                LuaMethodMeta {
+                    name: "set_max_fixed_steps",
+                    doc: "Sets the maximum number of fixed steps run in a frame.",
+                    is_static: true,
+                    params: &[LuaParamMeta {
+                        name: "max_fixed_steps",
+                        ty: "integer",
+                    }],
+                    returns: None,
+                },
+                LuaMethodMeta {
+                    name: "alpha",
+                    doc: "Returns how far the current frame is between the last fixed step and the next one, in range `[0, 1)`. Always `1` if fixed steps are disabled.",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("number"),
+                },
+                LuaMethodMeta {
+                    name: "frame_rate_cap",
+                    doc: "Returns the frame rate cap in frames per second, or `nil` if the frame rate is not capped.",
+                    is_static: true,
+                    params: &[],
+                    returns: Some("number?"),
+                },
+                LuaMethodMeta {
+                    name: "set_frame_rate_cap",
+                    doc: "Caps the frame rate to the given frames per second, or uncaps it with `nil`.",
+                    is_static: true,
+                    params: &[LuaParamMeta {
+                        name: "fps",
+                        ty: "number?",
+                    }],
+                    returns: None,
+                },
+            ],
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{
+    engine::use_context,
+    script::{api::LuaApiTable, entity::Entity, LuaMethodMeta, LuaParamMeta},
+    turn::TURN_ENERGY_THRESHOLD,
+};
+use mlua::prelude::*;
+
+pub struct TurnModule;
+
+impl LuaApiTable for TurnModule {
+    fn create_api_table<'lua>(lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table()?;
+
+        table.set("ENERGY_THRESHOLD", TURN_ENERGY_THRESHOLD)?;
+        table.set(
+            "add_actor",
+            lua.create_function(|_lua, (entity, speed): (Entity, u32)| {
+                Ok(use_context().turn_mgr_mut().add_actor(entity.0, speed))
+            })?,
+        )?;
+        table.set(
+            "remove_actor",
+            lua.create_function(|_lua, entity: Entity| {
+                Ok(use_context().turn_mgr_mut().remove_actor(entity.0))
+            })?,
+        )?;
+        table.set(
+            "has_actor",
+            lua.create_function(|_lua, entity: Entity| {
+                Ok(use_context().turn_mgr().contains(entity.0))
+            })?,
+        )?;
+        table.set(
+            "speed",
+            lua.create_function(|_lua, entity: Entity| {
+                Ok(use_context().turn_mgr().speed(entity.0))
+            })?,
+        )?;
+        table.set(
+            "set_speed",
+            lua.create_function(|_lua, (entity, speed): (Entity, u32)| {
+                Ok(use_context().turn_mgr_mut().set_speed(entity.0, speed))
+            })?,
+        )?;
+        table.set(
+            "energy",
+            lua.create_function(|_lua, entity: Entity| {
+                Ok(use_context().turn_mgr().energy(entity.0))
+            })?,
+        )?;
+        table.set(
+            "current",
+            lua.create_function(|_lua, ()| {
+                Ok(use_context().turn_mgr().current().map(Entity::new))
+            })?,
+        )?;
+        table.set(
+            "turn",
+            lua.create_function(|_lua, ()| Ok(use_context().turn_mgr().turn()))?,
+        )?;
+        table.set(
+            "end_turn",
+            lua.create_function(|_lua, cost: Option<u32>| {
+                Ok(use_context()
+                    .turn_mgr_mut()
+                    .end_turn(cost.unwrap_or(TURN_ENERGY_THRESHOLD))
+                    .map(Entity::new))
+            })?,
+        )?;
+        table.set(
+            "is_paused",
+            lua.create_function(|_lua, ()| Ok(use_context().turn_mgr().is_paused()))?,
+        )?;
+        table.set(
+            "set_paused",
+            lua.create_function(|_lua, is_paused: bool| {
+                use_context().turn_mgr_mut().set_paused(is_paused);
+                Ok(())
+            })?,
+        )?;
+
+        Ok(table)
+    }
+
+    fn lua_functions() -> &'static [LuaMethodMeta] {
+        &[
+            LuaMethodMeta {
+                name: "add_actor",
+                doc: "Registers an entity taking turns with no energy. Returns `false` if it is already registered.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "entity",
+                        ty: "Entity",
+                    },
+                    LuaParamMeta {
+                        name: "speed",
+                        ty: "integer",
+                    },
+                ],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "remove_actor",
+                doc: "Unregisters an entity. Returns whether it was registered.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "entity",
+                    ty: "Entity",
+                }],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "has_actor",
+                doc: "Returns whether an entity is registered.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "entity",
+                    ty: "Entity",
+                }],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "speed",
+                doc: "Returns the speed of an entity, or `nil` if it is not registered.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "entity",
+                    ty: "Entity",
+                }],
+                returns: Some("integer?"),
+            },
+            LuaMethodMeta {
+                name: "set_speed",
+                doc: "Sets the speed of an entity. Returns whether it is registered.",
+                is_static: true,
+                params: &[
+                    LuaParamMeta {
+                        name: "entity",
+                        ty: "Entity",
+                    },
+                    LuaParamMeta {
+                        name: "speed",
+                        ty: "integer",
+                    },
+                ],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "energy",
+                doc: "Returns the energy of an entity, or `nil` if it is not registered.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "entity",
+                    ty: "Entity",
+                }],
+                returns: Some("integer?"),
+            },
+            LuaMethodMeta {
+                name: "current",
+                doc: "Returns the entity taking its turn, if any.",
+                is_static: true,
+                params: &[],
+                returns: Some("Entity?"),
+            },
+            LuaMethodMeta {
+                name: "turn",
+                doc: "Returns the number of turns taken.",
+                is_static: true,
+                params: &[],
+                returns: Some("integer"),
+            },
+            LuaMethodMeta {
+                name: "end_turn",
+                doc: "Ends the current turn, spending `cost` energy, `ENERGY_THRESHOLD` by default. Returns the entity whose turn ended.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "cost",
+                    ty: "integer?",
+                }],
+                returns: Some("Entity?"),
+            },
+            LuaMethodMeta {
+                name: "is_paused",
+                doc: "Returns whether turns are paused.",
+                is_static: true,
+                params: &[],
+                returns: Some("boolean"),
+            },
+            LuaMethodMeta {
+                name: "set_paused",
+                doc: "Pauses or resumes turns.",
+                is_static: true,
+                params: &[LuaParamMeta {
+                    name: "is_paused",
+                    ty: "boolean",
+                }],
+                returns: None,
+            },
+        ]
+    }
+}
@@ -0,0 +1,91 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, Error, ErrorKind, Result, Write};
+
+/// A request sent by the client, e.g. `setBreakpoints`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DebugRequest {
+    pub seq: i64,
+    pub command: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// Reads a message framed with a `Content-Length` header.
+/// Returns `None` once the stream is closed.
+pub fn read_debug_message(reader: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut content_length = None;
+    let mut header = String::new();
+
+    loop {
+        header.clear();
+
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let content_length = content_length
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "missing Content-Length header"))?;
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+}
+
+/// Writes a message framed with a `Content-Length` header.
+pub fn write_debug_message(writer: &mut impl Write, message: &Value) -> Result<()> {
+    let content = message.to_string();
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()
+}
+
+pub fn debug_response(seq: i64, request: &DebugRequest, body: Value) -> Value {
+    json!({
+        "seq": seq,
+        "type": "response",
+        "request_seq": request.seq,
+        "success": true,
+        "command": request.command,
+        "body": body,
+    })
+}
+
+pub fn debug_error_response(seq: i64, request: &DebugRequest, message: &str) -> Value {
+    json!({
+        "seq": seq,
+        "type": "response",
+        "request_seq": request.seq,
+        "success": false,
+        "command": request.command,
+        "message": message,
+    })
+}
+
+pub fn debug_event(seq: i64, event: &str, body: Value) -> Value {
+    json!({
+        "seq": seq,
+        "type": "event",
+        "event": event,
+        "body": body,
+    })
+}
@@ -0,0 +1,106 @@
+use super::{read_debug_message, write_debug_message, DebugRequest};
+use anyhow::{Context, Result};
+use crossbeam::channel::{unbounded, Receiver};
+use serde_json::Value;
+use std::{
+    io::BufReader,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    thread,
+};
+
+/// What the server thread received from the client.
+pub enum DebugMessage {
+    /// A client attached. Messages are written to the stream.
+    Connected(TcpStream),
+    Request(DebugRequest),
+    Disconnected,
+}
+
+/// Accepts a debug client at a time on a background thread, and forwards its requests.
+pub struct DebugServer {
+    address: SocketAddr,
+    receiver: Receiver<DebugMessage>,
+}
+
+impl DebugServer {
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self> {
+        let listener = TcpListener::bind(address).with_context(|| "failed to bind debug server")?;
+        let address = listener.local_addr()?;
+        let (sender, receiver) = unbounded();
+
+        thread::Builder::new()
+            .name("debug-server".to_owned())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(..) => continue,
+                    };
+                    let writer = match stream.try_clone() {
+                        Ok(writer) => writer,
+                        Err(..) => continue,
+                    };
+
+                    if sender.send(DebugMessage::Connected(writer)).is_err() {
+                        return;
+                    }
+
+                    let mut reader = BufReader::new(stream);
+
+                    // Messages that are not requests, e.g. responses to reverse requests, are ignored.
+                    while let Ok(Some(message)) = read_debug_message(&mut reader) {
+                        let request = match serde_json::from_value::<DebugRequest>(message) {
+                            Ok(request) => request,
+                            Err(..) => continue,
+                        };
+
+                        if sender.send(DebugMessage::Request(request)).is_err() {
+                            return;
+                        }
+                    }
+
+                    if sender.send(DebugMessage::Disconnected).is_err() {
+                        return;
+                    }
+                }
+            })
+            .with_context(|| "failed to spawn debug server thread")?;
+
+        Ok(Self { address, receiver })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn try_recv(&self) -> Option<DebugMessage> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Blocks until a message arrives. Returns `None` if the server thread stopped.
+    pub fn recv(&self) -> Option<DebugMessage> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// The attached client, with the sequence number of the messages sent to it.
+pub struct DebugClient {
+    stream: TcpStream,
+    seq: i64,
+}
+
+impl DebugClient {
+    pub fn new(stream: TcpStream) -> Self {
+        Self { stream, seq: 0 }
+    }
+
+    pub fn next_seq(&mut self) -> i64 {
+        self.seq += 1;
+        self.seq
+    }
+
+    /// Sends the message. A client that went away is detected by the server thread.
+    pub fn send(&mut self, message: &Value) {
+        let _ = write_debug_message(&mut self.stream, message);
+    }
+}
@@ -0,0 +1,782 @@
+use super::{
+    debug_error_response, debug_event, debug_response, DebugClient, DebugMessage, DebugRequest,
+    DebugServer,
+};
+use crate::{
+    diagnostic::{pretty_print, ScriptError, CONSOLE_PRINT_DEPTH},
+    engine::try_use_context,
+    script::MODULE_ROOT,
+};
+use mlua::{prelude::*, Debug, DebugEvent, DebugSource, HookTriggers};
+use serde_json::{json, Value};
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap},
+    fs::canonicalize,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// Name of the registry value that holds the `debug` library, which is hidden from the scripts.
+pub const DEBUG_LIBRARY_REGISTRY_KEY: &str = "mk.debug";
+
+/// Scripts run on a single thread as far as the client is concerned.
+const THREAD_ID: i64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunMode {
+    Running,
+    /// Stops at the next line.
+    Pausing,
+    StepIn,
+    /// Stops at the next line whose stack is at most as deep as the given depth.
+    StepOver(usize),
+    /// Stops at the next line whose stack is shallower than the given depth.
+    StepOut(usize),
+}
+
+/// What a `variablesReference` handed to the client refers to. Valid until resumed.
+enum Variables {
+    Locals(usize),
+    Upvalues(usize),
+    Table(LuaRegistryKey),
+}
+
+/// Serves a subset of the Debug Adapter Protocol for the Lua state: breakpoints, stepping,
+/// stack frames, locals and upvalues, and evaluation.
+///
+/// While stopped, the debugger blocks the game inside the hook until the client resumes it.
+pub struct Debugger {
+    server: DebugServer,
+    client: Option<DebugClient>,
+    is_configured: bool,
+    source_base: PathBuf,
+    /// Lines to stop at, keyed by chunk path, e.g. `scripts/entry.lua`.
+    breakpoints: HashMap<String, BTreeSet<u32>>,
+    mode: RunMode,
+    variables: Vec<Variables>,
+}
+
+impl Debugger {
+    /// Creates a debugger serving the client of the server.
+    /// Stack frames report the paths of the chunks relative to the source base.
+    pub fn new(server: DebugServer, source_base: impl Into<PathBuf>) -> Self {
+        let source_base = source_base.into();
+        let source_base = canonicalize(&source_base).unwrap_or(source_base);
+
+        Self {
+            server,
+            client: None,
+            is_configured: false,
+            source_base,
+            breakpoints: HashMap::new(),
+            mode: RunMode::Running,
+            variables: Vec::new(),
+        }
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.server.address()
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.client.is_some()
+    }
+
+    /// Returns `true` once the attached client has sent its breakpoints.
+    pub fn is_configured(&self) -> bool {
+        self.is_configured
+    }
+
+    /// Handles the requests received since the last poll. Called once per frame,
+    /// so that the client is served while no script runs.
+    pub fn poll(&mut self, lua: &Lua) {
+        while let Some(message) = self.server.try_recv() {
+            self.handle_message(lua, message, false);
+        }
+    }
+
+    fn on_line(&mut self, lua: &Lua, debug: &Debug) {
+        self.poll(lua);
+
+        let reason = match self.mode {
+            RunMode::Running => None,
+            RunMode::Pausing => Some("pause"),
+            RunMode::StepIn => Some("step"),
+            RunMode::StepOver(depth) => (stack_depth(lua) <= depth).then_some("step"),
+            RunMode::StepOut(depth) => (stack_depth(lua) < depth).then_some("step"),
+        };
+        let reason = reason.or_else(|| self.is_breakpoint(debug).then_some("breakpoint"));
+
+        if let Some(reason) = reason {
+            self.stop(lua, reason);
+        }
+    }
+
+    fn is_breakpoint(&self, debug: &Debug) -> bool {
+        if self.breakpoints.is_empty() {
+            return false;
+        }
+
+        let line = debug.curr_line();
+        let source = debug.source();
+        let path = match source.source.and_then(|source| source.strip_prefix(b"@")) {
+            Some(path) => path,
+            None => return false,
+        };
+
+        std::str::from_utf8(path)
+            .ok()
+            .and_then(|path| self.breakpoints.get(path))
+            .is_some_and(|lines| u32::try_from(line).is_ok_and(|line| lines.contains(&line)))
+    }
+
+    /// Blocks until the client resumes or goes away.
+    fn stop(&mut self, lua: &Lua, reason: &str) {
+        self.mode = RunMode::Running;
+        self.send_event(
+            "stopped",
+            json!({
+                "reason": reason,
+                "threadId": THREAD_ID,
+                "allThreadsStopped": true,
+            }),
+        );
+
+        while let Some(message) = self.server.recv() {
+            if self.handle_message(lua, message, true) {
+                break;
+            }
+        }
+
+        self.variables.clear();
+        lua.expire_registry_values();
+    }
+
+    /// Returns `true` if the message resumes the execution.
+    fn handle_message(&mut self, lua: &Lua, message: DebugMessage, is_stopped: bool) -> bool {
+        match message {
+            DebugMessage::Connected(stream) => {
+                self.client = Some(DebugClient::new(stream));
+                false
+            }
+            DebugMessage::Request(request) => {
+                match self.handle_request(lua, &request, is_stopped) {
+                    Ok((body, is_resumed)) => {
+                        self.respond(&request, body);
+
+                        if request.command == "initialize" {
+                            self.send_event("initialized", json!({}));
+                        } else if request.command == "disconnect" {
+                            self.detach();
+                        }
+
+                        is_resumed
+                    }
+                    Err(err) => {
+                        self.fail(&request, &err);
+                        false
+                    }
+                }
+            }
+            DebugMessage::Disconnected => {
+                self.detach();
+                true
+            }
+        }
+    }
+
+    /// Handles the request, and returns the body of the response and whether it resumes the execution.
+    fn handle_request(
+        &mut self,
+        lua: &Lua,
+        request: &DebugRequest,
+        is_stopped: bool,
+    ) -> Result<(Value, bool), String> {
+        let arguments = &request.arguments;
+
+        match request.command.as_str() {
+            "initialize" => Ok((
+                json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsEvaluateForHovers": true,
+                    "supportsTerminateRequest": false,
+                }),
+                false,
+            )),
+            "launch" | "attach" | "setExceptionBreakpoints" => Ok((json!({}), false)),
+            "configurationDone" => {
+                self.is_configured = true;
+                Ok((json!({}), false))
+            }
+            "setBreakpoints" => Ok((self.set_breakpoints(arguments), false)),
+            "threads" => Ok((
+                json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] }),
+                false,
+            )),
+            "pause" => {
+                self.mode = RunMode::Pausing;
+                Ok((json!({}), false))
+            }
+            "disconnect" => Ok((json!({}), true)),
+            "evaluate" => {
+                let level = match arguments["frameId"].as_u64() {
+                    Some(frame_id) if is_stopped => Some(frame_level(frame_id)?),
+                    Some(..) => return Err("not stopped".to_owned()),
+                    None => None,
+                };
+                let expression = arguments["expression"]
+                    .as_str()
+                    .ok_or("missing expression")?;
+                self.evaluate(lua, expression, level)
+                    .map(|body| (body, false))
+                    .map_err(|err| ScriptError::from_lua_error(&err).message)
+            }
+            _ if !is_stopped => Err("not stopped".to_owned()),
+            "continue" => Ok((json!({ "allThreadsContinued": true }), true)),
+            "next" => {
+                self.mode = RunMode::StepOver(stack_depth(lua));
+                Ok((json!({}), true))
+            }
+            "stepIn" => {
+                self.mode = RunMode::StepIn;
+                Ok((json!({}), true))
+            }
+            "stepOut" => {
+                self.mode = RunMode::StepOut(stack_depth(lua));
+                Ok((json!({}), true))
+            }
+            "stackTrace" => Ok((self.stack_trace(lua), false)),
+            "scopes" => {
+                let level = frame_level(arguments["frameId"].as_u64().unwrap_or_default())?;
+                Ok((self.scopes(level), false))
+            }
+            "variables" => {
+                let reference = arguments["variablesReference"].as_u64().unwrap_or_default();
+                self.variables(lua, reference)
+                    .map(|body| (body, false))
+                    .map_err(|err| err.to_string())
+            }
+            command => Err(format!("unsupported request '{}'", command)),
+        }
+    }
+
+    fn set_breakpoints(&mut self, arguments: &Value) -> Value {
+        let source = &arguments["source"];
+        let path = source["path"]
+            .as_str()
+            .or_else(|| source["name"].as_str())
+            .unwrap_or_default();
+        let lines = arguments["breakpoints"]
+            .as_array()
+            .map(|breakpoints| {
+                breakpoints
+                    .iter()
+                    .filter_map(|breakpoint| breakpoint["line"].as_u64())
+                    .filter_map(|line| u32::try_from(line).ok())
+                    .collect::<BTreeSet<_>>()
+            })
+            .unwrap_or_default();
+        let breakpoints = lines
+            .iter()
+            .map(|line| json!({ "verified": true, "line": line }))
+            .collect::<Vec<_>>();
+        let path = self.chunk_path(path);
+
+        if lines.is_empty() {
+            self.breakpoints.remove(&path);
+        } else {
+            self.breakpoints.insert(path, lines);
+        }
+
+        json!({ "breakpoints": breakpoints })
+    }
+
+    fn stack_trace(&self, lua: &Lua) -> Value {
+        let frames = (0..)
+            .map_while(|level| lua.inspect_stack(level).map(|debug| (level, debug)))
+            .map(|(level, debug)| {
+                let source = debug.source();
+                let name = match debug.names().name {
+                    Some(name) => String::from_utf8_lossy(name).into_owned(),
+                    None if source.what == Some(b"main") => "main chunk".to_owned(),
+                    None => "?".to_owned(),
+                };
+
+                json!({
+                    "id": level + 1,
+                    "name": name,
+                    "line": debug.curr_line().max(0),
+                    "column": 1,
+                    "source": self.source(&source),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        json!({ "stackFrames": frames, "totalFrames": frames.len() })
+    }
+
+    fn source(&self, source: &DebugSource) -> Value {
+        if source.what == Some(b"C") {
+            return Value::Null;
+        }
+
+        match source.source.and_then(|source| source.strip_prefix(b"@")) {
+            Some(path) => {
+                let path = self
+                    .source_base
+                    .join(String::from_utf8_lossy(path).as_ref());
+                json!({
+                    "name": path.file_name().map(|name| name.to_string_lossy()),
+                    "path": path.to_string_lossy(),
+                })
+            }
+            None => json!({
+                "name": source.short_src.map(String::from_utf8_lossy),
+            }),
+        }
+    }
+
+    fn scopes(&mut self, level: usize) -> Value {
+        let locals = self.allocate(Variables::Locals(level));
+        let upvalues = self.allocate(Variables::Upvalues(level));
+
+        json!({
+            "scopes": [
+                { "name": "Locals", "variablesReference": locals, "expensive": false },
+                { "name": "Upvalues", "variablesReference": upvalues, "expensive": false },
+            ]
+        })
+    }
+
+    fn variables(&mut self, lua: &Lua, reference: u64) -> LuaResult<Value> {
+        let pairs = match usize::try_from(reference)
+            .ok()
+            .and_then(|reference| self.variables.get(reference.checked_sub(1)?))
+        {
+            Some(Variables::Locals(level)) => frame_locals(lua, *level)?,
+            Some(Variables::Upvalues(level)) => frame_upvalues(lua, *level)?,
+            Some(Variables::Table(table)) => lua
+                .registry_value::<LuaTable>(table)?
+                .pairs::<LuaValue, LuaValue>()
+                .map(|pair| pair.map(|(key, value)| (variable_name(&key), value)))
+                .collect::<LuaResult<Vec<_>>>()?,
+            None => return Err(LuaError::external("invalid variables reference")),
+        };
+        let variables = pairs
+            .into_iter()
+            .map(|(name, value)| {
+                let (value, type_name, reference) = self.describe(lua, value)?;
+                Ok(json!({
+                    "name": name,
+                    "value": value,
+                    "type": type_name,
+                    "variablesReference": reference,
+                }))
+            })
+            .collect::<LuaResult<Vec<_>>>()?;
+
+        Ok(json!({ "variables": variables }))
+    }
+
+    fn evaluate(&mut self, lua: &Lua, expression: &str, level: Option<usize>) -> LuaResult<Value> {
+        let env = match level {
+            Some(level) => frame_env(lua, level)?,
+            None => lua.globals(),
+        };
+        let function = match lua
+            .load(&format!("return {}", expression))
+            .set_name("=evaluate")?
+            .set_environment(env.clone())?
+            .into_function()
+        {
+            Ok(function) => function,
+            Err(..) => lua
+                .load(expression)
+                .set_name("=evaluate")?
+                .set_environment(env)?
+                .into_function()?,
+        };
+        let mut values = function.call::<_, LuaMultiValue>(())?.into_vec();
+
+        if values.len() == 1 {
+            let (result, _, reference) = self.describe(lua, values.remove(0))?;
+            return Ok(json!({ "result": result, "variablesReference": reference }));
+        }
+
+        let result = values
+            .iter()
+            .map(|value| pretty_print(try_use_context(), value, 0))
+            .collect::<Vec<_>>()
+            .join("\t");
+        Ok(json!({ "result": result, "variablesReference": 0 }))
+    }
+
+    /// Returns the preview and type of the value, and a reference to expand it if it is a table.
+    fn describe(&mut self, lua: &Lua, value: LuaValue) -> LuaResult<(String, &'static str, usize)> {
+        let type_name = value.type_name();
+
+        match value {
+            LuaValue::Table(table) => {
+                let preview = pretty_print(
+                    try_use_context(),
+                    &LuaValue::Table(table.clone()),
+                    CONSOLE_PRINT_DEPTH - 1,
+                );
+                let reference = self.allocate(Variables::Table(lua.create_registry_value(table)?));
+                Ok((preview, type_name, reference))
+            }
+            value => Ok((pretty_print(try_use_context(), &value, 0), type_name, 0)),
+        }
+    }
+
+    fn allocate(&mut self, variables: Variables) -> usize {
+        self.variables.push(variables);
+        self.variables.len()
+    }
+
+    /// Converts a path sent by the client into the name of its chunk, e.g. `scripts/entry.lua`.
+    fn chunk_path(&self, path: &str) -> String {
+        let path = Path::new(path);
+        let path = canonicalize(path).unwrap_or_else(|_| path.to_owned());
+
+        if let Ok(relative) = path.strip_prefix(&self.source_base) {
+            return relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+        }
+
+        // The client may see the assets under another directory.
+        let path = path.to_string_lossy().replace('\\', "/");
+        match path.rfind(&format!("/{}/", MODULE_ROOT)) {
+            Some(index) => path[index + 1..].to_owned(),
+            None => path,
+        }
+    }
+
+    fn detach(&mut self) {
+        self.client = None;
+        self.is_configured = false;
+        self.breakpoints.clear();
+        self.mode = RunMode::Running;
+    }
+
+    fn respond(&mut self, request: &DebugRequest, body: Value) {
+        if let Some(client) = &mut self.client {
+            let seq = client.next_seq();
+            client.send(&debug_response(seq, request, body));
+        }
+    }
+
+    fn fail(&mut self, request: &DebugRequest, message: &str) {
+        if let Some(client) = &mut self.client {
+            let seq = client.next_seq();
+            client.send(&debug_error_response(seq, request, message));
+        }
+    }
+
+    fn send_event(&mut self, event: &str, body: Value) {
+        if let Some(client) = &mut self.client {
+            let seq = client.next_seq();
+            client.send(&debug_event(seq, event, body));
+        }
+    }
+}
+
+/// Hides the `debug` library from the scripts, and stops at the breakpoints of the debugger.
+/// The Lua state must have been created with the `debug` library.
+pub fn install_debugger(lua: &Lua, debugger: Rc<RefCell<Debugger>>) -> LuaResult<()> {
+    let globals = lua.globals();
+    let debug = globals.get::<_, LuaTable>("debug")?;
+    lua.set_named_registry_value(DEBUG_LIBRARY_REGISTRY_KEY, debug)?;
+    globals.raw_set("debug", LuaNil)?;
+    globals
+        .get::<_, LuaTable>("package")?
+        .get::<_, LuaTable>("loaded")?
+        .raw_set("debug", LuaNil)?;
+
+    // Coroutines inherit the hook when created, so it stays set rather than being toggled.
+    lua.set_hook(HookTriggers::every_line(), move |lua, debug| {
+        if debug.event() != DebugEvent::Line {
+            return Ok(());
+        }
+
+        // The debugger is busy if it runs a script outside of the hook, e.g. to evaluate.
+        if let Ok(mut debugger) = debugger.try_borrow_mut() {
+            debugger.on_line(lua, &debug);
+        }
+
+        Ok(())
+    })
+}
+
+fn debug_library(lua: &Lua) -> LuaResult<LuaTable> {
+    lua.named_registry_value(DEBUG_LIBRARY_REGISTRY_KEY)
+}
+
+fn frame_level(frame_id: u64) -> Result<usize, String> {
+    usize::try_from(frame_id)
+        .ok()
+        .and_then(|frame_id| frame_id.checked_sub(1))
+        .ok_or_else(|| "invalid frame id".to_owned())
+}
+
+fn stack_depth(lua: &Lua) -> usize {
+    (0..)
+        .take_while(|&level| lua.inspect_stack(level).is_some())
+        .count()
+}
+
+// The functions of the `debug` library are called from the hook, one level below the stopped frame.
+
+fn frame_locals(lua: &Lua, level: usize) -> LuaResult<Vec<(String, LuaValue)>> {
+    let getlocal = debug_library(lua)?.get::<_, LuaFunction>("getlocal")?;
+    let mut locals = Vec::new();
+
+    for index in 1.. {
+        let (name, value) = getlocal.call::<_, (Option<String>, LuaValue)>((level + 1, index))?;
+        let name = match name {
+            Some(name) => name,
+            None => break,
+        };
+
+        // Names in parentheses are internal, e.g. `(for state)`.
+        if !name.starts_with('(') {
+            locals.push((name, value));
+        }
+    }
+
+    Ok(locals)
+}
+
+fn frame_upvalues(lua: &Lua, level: usize) -> LuaResult<Vec<(String, LuaValue)>> {
+    let debug = debug_library(lua)?;
+    let function = debug
+        .get::<_, LuaFunction>("getinfo")?
+        .call::<_, LuaTable>((level + 1, "f"))?
+        .get::<_, LuaValue>("func")?;
+    let getupvalue = debug.get::<_, LuaFunction>("getupvalue")?;
+    let mut upvalues = Vec::new();
+
+    for index in 1.. {
+        let (name, value) =
+            getupvalue.call::<_, (Option<String>, LuaValue)>((function.clone(), index))?;
+
+        match name {
+            Some(name) => upvalues.push((name, value)),
+            None => break,
+        }
+    }
+
+    Ok(upvalues)
+}
+
+/// Returns an environment that reads the locals and upvalues of the frame,
+/// then the globals of the frame. Assignments to other names go to the globals.
+fn frame_env(lua: &Lua, level: usize) -> LuaResult<LuaTable> {
+    let upvalues = frame_upvalues(lua, level)?;
+    let locals = frame_locals(lua, level)?;
+    let globals = upvalues
+        .iter()
+        .find_map(|(name, value)| match (name.as_str(), value) {
+            ("_ENV", LuaValue::Table(env)) => Some(env.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| lua.globals());
+    let env = lua.create_table()?;
+
+    // Later locals shadow earlier ones, and locals shadow upvalues.
+    for (name, value) in upvalues.into_iter().chain(locals) {
+        if name != "_ENV" {
+            env.raw_set(name, value)?;
+        }
+    }
+
+    let metatable = lua.create_table()?;
+    metatable.set("__index", globals.clone())?;
+    metatable.set("__newindex", globals)?;
+    env.set_metatable(Some(metatable));
+    Ok(env)
+}
+
+fn variable_name(key: &LuaValue) -> String {
+    match key {
+        LuaValue::String(key) => key.to_string_lossy().into_owned(),
+        key => format!(
+            "[{}]",
+            pretty_print(try_use_context(), key, CONSOLE_PRINT_DEPTH)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::script::{read_debug_message, write_debug_message};
+    use mlua::StdLib;
+    use std::{io::BufReader, net::TcpStream, thread};
+
+    /// Stands in for an editor.
+    struct TestClient {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+        seq: i64,
+        events: Vec<Value>,
+    }
+
+    impl TestClient {
+        fn connect(address: SocketAddr) -> Self {
+            let writer = TcpStream::connect(address).unwrap();
+            let reader = BufReader::new(writer.try_clone().unwrap());
+            Self {
+                reader,
+                writer,
+                seq: 0,
+                events: Vec::new(),
+            }
+        }
+
+        fn request(&mut self, command: &str, arguments: Value) -> Value {
+            self.seq += 1;
+            let seq = self.seq;
+            write_debug_message(
+                &mut self.writer,
+                &json!({
+                    "seq": seq,
+                    "type": "request",
+                    "command": command,
+                    "arguments": arguments,
+                }),
+            )
+            .unwrap();
+
+            loop {
+                let message = read_debug_message(&mut self.reader).unwrap().unwrap();
+
+                if message["type"] == "response" && message["request_seq"] == seq {
+                    assert_eq!(message["success"], true, "{}", message);
+                    return message["body"].clone();
+                }
+
+                self.events.push(message);
+            }
+        }
+
+        fn wait_event(&mut self, event: &str) -> Value {
+            if let Some(index) = self
+                .events
+                .iter()
+                .position(|message| message["event"] == event)
+            {
+                return self.events.remove(index)["body"].clone();
+            }
+
+            loop {
+                let message = read_debug_message(&mut self.reader).unwrap().unwrap();
+
+                if message["event"] == event {
+                    return message["body"].clone();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn debugger_stops_at_breakpoint() {
+        let lua =
+            unsafe { Lua::unsafe_new_with(StdLib::ALL_SAFE | StdLib::DEBUG, LuaOptions::new()) };
+        let server = DebugServer::bind("127.0.0.1:0").unwrap();
+        let address = server.address();
+        let debugger = Rc::new(RefCell::new(Debugger::new(server, "assets")));
+        install_debugger(&lua, debugger.clone()).unwrap();
+
+        let client = thread::spawn(move || {
+            let mut client = TestClient::connect(address);
+            client.request("initialize", json!({ "adapterID": "mk" }));
+            client.wait_event("initialized");
+            client.request(
+                "setBreakpoints",
+                json!({
+                    "source": { "path": "/game/assets/scripts/test.lua" },
+                    "breakpoints": [{ "line": 4 }],
+                }),
+            );
+            client.request("configurationDone", json!({}));
+
+            assert_eq!(client.wait_event("stopped")["reason"], "breakpoint");
+            let frames = client.request("stackTrace", json!({ "threadId": THREAD_ID }));
+            let frame = &frames["stackFrames"][0];
+            assert_eq!(frame["name"], "double");
+            assert_eq!(frame["line"], 4);
+            assert!(frame["source"]["path"]
+                .as_str()
+                .unwrap()
+                .ends_with("test.lua"));
+
+            let scopes = client.request("scopes", json!({ "frameId": frame["id"] }));
+            let locals = client.request(
+                "variables",
+                json!({ "variablesReference": scopes["scopes"][0]["variablesReference"] }),
+            );
+            assert_eq!(locals["variables"][0]["name"], "value");
+            assert_eq!(locals["variables"][1]["name"], "x");
+            assert_eq!(locals["variables"][1]["value"], "42");
+
+            let upvalues = client.request(
+                "variables",
+                json!({ "variablesReference": scopes["scopes"][1]["variablesReference"] }),
+            );
+            assert_eq!(upvalues["variables"][0]["name"], "offset");
+            assert_eq!(upvalues["variables"][0]["value"], "1");
+
+            let result = client.request(
+                "evaluate",
+                json!({ "expression": "x * 2 + offset", "frameId": frame["id"] }),
+            );
+            assert_eq!(result["result"], "85");
+
+            client.request("next", json!({ "threadId": THREAD_ID }));
+            assert_eq!(client.wait_event("stopped")["reason"], "step");
+            let frames = client.request("stackTrace", json!({ "threadId": THREAD_ID }));
+            assert_eq!(frames["stackFrames"][0]["line"], 5);
+
+            client.request("stepOut", json!({ "threadId": THREAD_ID }));
+            assert_eq!(client.wait_event("stopped")["reason"], "step");
+            let frames = client.request("stackTrace", json!({ "threadId": THREAD_ID }));
+            assert_eq!(frames["stackFrames"][0]["name"], "main chunk");
+            assert_eq!(frames["stackFrames"][0]["line"], 8);
+
+            client.request("continue", json!({ "threadId": THREAD_ID }));
+        });
+
+        while !debugger.borrow().is_configured() {
+            debugger.borrow_mut().poll(&lua);
+            thread::yield_now();
+        }
+
+        lua.load(
+            r#"local offset = 1
+            local function double(value)
+              local x = value + offset - 1
+              local y = x * 2
+              return y
+            end
+            result = double(42)
+            done = true"#,
+        )
+        .set_name("@scripts/test.lua")
+        .unwrap()
+        .exec()
+        .unwrap();
+
+        client.join().unwrap();
+        assert_eq!(lua.globals().get::<_, i64>("result").unwrap(), 84);
+        assert!(matches!(
+            lua.globals().get::<_, LuaValue>("debug").unwrap(),
+            LuaNil
+        ));
+    }
+}
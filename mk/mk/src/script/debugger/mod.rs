@@ -0,0 +1,7 @@
+mod debug_protocol;
+mod debug_server;
+mod lua_debugger;
+
+pub use debug_protocol::*;
+pub use debug_server::*;
+pub use lua_debugger::*;
@@ -0,0 +1,106 @@
+use super::{
+    LuaClassMeta, LuaEnumMeta, LuaEnumMetaProvider, LuaEventMeta, LuaEventMetaProvider,
+    LuaMethodMeta, LuaUserDataMetaProvider,
+};
+use crate::script::api::{LuaApiTable, Module};
+use mlua::prelude::*;
+
+/// The part of the Lua API described by the `codegen` macros and the hand-written metadata.
+#[derive(Default)]
+pub struct LuaApiMeta {
+    pub namespaces: Vec<LuaNamespaceMeta>,
+    /// Events emitted to entities, listened with `entity:listen`.
+    pub entity_events: Vec<LuaEventMeta>,
+}
+
+/// A namespace under `mk`, e.g. `structure` for `mk.structure`.
+pub struct LuaNamespaceMeta {
+    pub name: &'static str,
+    /// Functions set on the namespace table, e.g. `mk.time.after`.
+    pub functions: Vec<&'static LuaMethodMeta>,
+    pub classes: Vec<LuaClassMeta>,
+    pub enums: Vec<LuaEnumMeta>,
+    /// Events listened with `mk.event.<name>.listen`.
+    pub events: Vec<LuaEventMeta>,
+}
+
+impl LuaNamespaceMeta {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            functions: Vec::new(),
+            classes: Vec::new(),
+            enums: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+            && self.classes.is_empty()
+            && self.enums.is_empty()
+            && self.events.is_empty()
+    }
+}
+
+/// Builds the `mk` table into a fresh Lua state and collects the metadata of every type
+/// registered into it, so that the description can't drift from what scripts actually get.
+pub fn lua_api_meta() -> LuaResult<LuaApiMeta> {
+    let lua = Lua::new();
+    lua.set_app_data(LuaApiMeta::default());
+    Module::create_api_table(&lua)?;
+
+    let mut meta = lua.remove_app_data::<LuaApiMeta>().unwrap_or_default();
+    meta.namespaces.retain(|namespace| !namespace.is_empty());
+    Ok(meta)
+}
+
+/// Starts the namespace the following types are registered into.
+/// Like the other `register_lua_*` functions, it does nothing unless the metadata is being collected.
+pub fn register_lua_namespace(lua: &Lua, name: &'static str) {
+    if let Some(mut meta) = lua.app_data_mut::<LuaApiMeta>() {
+        meta.namespaces.push(LuaNamespaceMeta::new(name));
+    }
+}
+
+pub fn register_lua_functions(lua: &Lua, functions: &'static [LuaMethodMeta]) {
+    with_namespace(lua, |namespace| namespace.functions.extend(functions));
+}
+
+pub fn register_lua_class<T>(lua: &Lua)
+where
+    T: LuaUserDataMetaProvider,
+{
+    with_namespace(lua, |namespace| namespace.classes.push(T::lua_class_meta()));
+}
+
+pub fn register_lua_enum<T>(lua: &Lua)
+where
+    T: LuaEnumMetaProvider,
+{
+    with_namespace(lua, |namespace| namespace.enums.push(T::lua_enum_meta()));
+}
+
+pub fn register_lua_event<T>(lua: &Lua)
+where
+    T: LuaEventMetaProvider,
+{
+    with_namespace(lua, |namespace| namespace.events.push(T::lua_event_meta()));
+}
+
+pub fn register_lua_entity_event<T>(lua: &Lua)
+where
+    T: LuaEventMetaProvider,
+{
+    if let Some(mut meta) = lua.app_data_mut::<LuaApiMeta>() {
+        meta.entity_events.push(T::lua_event_meta());
+    }
+}
+
+fn with_namespace(lua: &Lua, f: impl FnOnce(&mut LuaNamespaceMeta)) {
+    if let Some(mut meta) = lua.app_data_mut::<LuaApiMeta>() {
+        if let Some(namespace) = meta.namespaces.last_mut() {
+            f(namespace);
+        }
+    }
+}
@@ -0,0 +1,479 @@
+use super::{LuaApiMeta, LuaClassMeta, LuaEnumMeta, LuaEventMeta, LuaFieldMeta, LuaMethodMeta};
+use anyhow::Context;
+use std::{fmt::Write, fs, path::Path};
+
+const GENERATED_NOTICE: &str =
+    "Generated from the `codegen` metadata by `cargo run --manifest-path mk/Cargo.toml -p mk --example lua-api`. Do not edit.";
+
+/// Renders the API as a LuaLS (EmmyLua) definition file.
+pub fn lua_annotations(meta: &LuaApiMeta) -> String {
+    let mut out = String::new();
+
+    writeln!(out, "---@meta").unwrap();
+    writeln!(out, "-- {}", GENERATED_NOTICE).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "---@class EventHandler").unwrap();
+    writeln!(out, "---@class EntityEventHandler").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "mk = mk or {{}}").unwrap();
+
+    for namespace in &meta.namespaces {
+        let table = format!("mk.{}", namespace.name);
+
+        writeln!(out).unwrap();
+        writeln!(out, "{} = {{}}", table).unwrap();
+
+        for function in &namespace.functions {
+            writeln!(out).unwrap();
+            write_method_annotations(&mut out, function);
+            writeln!(
+                out,
+                "function {}.{}({}) end",
+                table,
+                function.name,
+                param_names(function)
+            )
+            .unwrap();
+        }
+
+        for class in &namespace.classes {
+            write_class_annotations(&mut out, &table, class);
+        }
+
+        for enum_meta in &namespace.enums {
+            write_enum_annotations(&mut out, &table, enum_meta);
+        }
+
+        for event in &namespace.events {
+            write_event_annotations(&mut out, &table, event);
+        }
+    }
+
+    if !meta.entity_events.is_empty() {
+        writeln!(out).unwrap();
+
+        for event in &meta.entity_events {
+            write_doc_lines(&mut out, event.doc);
+            write_class_header(&mut out, event.name, event.fields);
+            writeln!(out).unwrap();
+        }
+
+        writeln!(out, "---@alias EntityEventName").unwrap();
+
+        for event in &meta.entity_events {
+            writeln!(out, "---| '\"{}\"' # {}", event.event_name, event.name).unwrap();
+        }
+    }
+
+    out
+}
+
+fn write_class_annotations(out: &mut String, table: &str, class: &LuaClassMeta) {
+    writeln!(out).unwrap();
+    write_doc_lines(out, class.doc);
+    write_class_header(out, class.name, class.fields);
+    writeln!(out, "local {} = {{}}", class.name).unwrap();
+
+    for method in class.methods.iter().filter(|method| !method.is_static) {
+        writeln!(out).unwrap();
+        write_method_annotations(out, method);
+        writeln!(
+            out,
+            "function {}:{}({}) end",
+            class.name,
+            method.name,
+            param_names(method)
+        )
+        .unwrap();
+    }
+
+    let static_methods = class
+        .methods
+        .iter()
+        .filter(|method| method.is_static)
+        .collect::<Vec<_>>();
+
+    // Types without functions, e.g. components, are only reached through other values.
+    if static_methods.is_empty() {
+        return;
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "---@class {}.{}", table, class.name).unwrap();
+    writeln!(out, "{}.{} = {{}}", table, class.name).unwrap();
+
+    for method in static_methods {
+        writeln!(out).unwrap();
+        write_method_annotations(out, method);
+        writeln!(
+            out,
+            "function {}.{}.{}({}) end",
+            table,
+            class.name,
+            method.name,
+            param_names(method)
+        )
+        .unwrap();
+    }
+}
+
+fn write_enum_annotations(out: &mut String, table: &str, enum_meta: &LuaEnumMeta) {
+    writeln!(out).unwrap();
+    write_doc_lines(out, enum_meta.doc);
+
+    if enum_meta.are_strings {
+        writeln!(out, "---@alias {}", enum_meta.name).unwrap();
+
+        for variant in enum_meta.variants {
+            writeln!(out, "---| '\"{}\"'", variant).unwrap();
+        }
+
+        return;
+    }
+
+    writeln!(out, "---@class {}", enum_meta.name).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "---@class {}.{}", table, enum_meta.name).unwrap();
+
+    for variant in enum_meta.variants {
+        writeln!(out, "---@field {} {}", variant, enum_meta.name).unwrap();
+    }
+
+    writeln!(out, "{}.{} = {{}}", table, enum_meta.name).unwrap();
+}
+
+fn write_event_annotations(out: &mut String, table: &str, event: &LuaEventMeta) {
+    writeln!(out).unwrap();
+    write_doc_lines(out, event.doc);
+    write_class_header(out, event.name, event.fields);
+    writeln!(out).unwrap();
+    writeln!(out, "---@class {}.{}", table, event.name).unwrap();
+    writeln!(out, "---@field name string").unwrap();
+    writeln!(out, "{}.{} = {{}}", table, event.name).unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "---@param handler fun(name: string, event: {})",
+        event.name
+    )
+    .unwrap();
+    writeln!(out, "---@return EventHandler").unwrap();
+    writeln!(out, "function {}.{}.listen(handler) end", table, event.name).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "---@param handler EventHandler").unwrap();
+    writeln!(
+        out,
+        "function {}.{}.unlisten(handler) end",
+        table, event.name
+    )
+    .unwrap();
+}
+
+fn write_class_header(out: &mut String, name: &str, fields: &[LuaFieldMeta]) {
+    writeln!(out, "---@class {}", name).unwrap();
+
+    for field in fields {
+        let readonly = if field.is_readonly {
+            "(read-only) "
+        } else {
+            ""
+        };
+        let doc = first_doc_line(field.doc);
+        let line = format!("---@field {} {} {}{}", field.name, field.ty, readonly, doc);
+        writeln!(out, "{}", line.trim_end()).unwrap();
+    }
+}
+
+fn write_method_annotations(out: &mut String, method: &LuaMethodMeta) {
+    write_doc_lines(out, method.doc);
+
+    for param in method.params {
+        writeln!(out, "---@param {} {}", param.name, param.ty).unwrap();
+    }
+
+    if let Some(returns) = method.returns {
+        writeln!(out, "---@return {}", returns).unwrap();
+    }
+}
+
+fn write_doc_lines(out: &mut String, doc: &str) {
+    for line in doc.lines() {
+        writeln!(out, "{}", format!("--- {}", line).trim_end()).unwrap();
+    }
+}
+
+fn first_doc_line(doc: &str) -> &str {
+    doc.lines().next().unwrap_or_default()
+}
+
+fn param_names(method: &LuaMethodMeta) -> String {
+    method
+        .params
+        .iter()
+        .map(|param| param.name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn signature(method: &LuaMethodMeta) -> String {
+    let params = method
+        .params
+        .iter()
+        .map(|param| format!("{}: {}", param.name, param.ty))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    match method.returns {
+        Some(returns) => format!("{}({}): {}", method.name, params, returns),
+        None => format!("{}({})", method.name, params),
+    }
+}
+
+/// Renders the API as mdBook pages, returned as `(file name, content)` pairs.
+pub fn lua_api_pages(meta: &LuaApiMeta) -> Vec<(String, String)> {
+    let mut pages = Vec::with_capacity(meta.namespaces.len() + 1);
+
+    for namespace in &meta.namespaces {
+        let table = format!("mk.{}", namespace.name);
+        let mut out = String::new();
+
+        writeln!(out, "# {}", table).unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "<!-- {} -->", GENERATED_NOTICE).unwrap();
+
+        if !namespace.functions.is_empty() {
+            writeln!(out).unwrap();
+            writeln!(out, "## Functions").unwrap();
+
+            for function in &namespace.functions {
+                writeln!(out).unwrap();
+                writeln!(out, "### `{}.{}`", table, signature(function)).unwrap();
+                write_doc_paragraph(&mut out, function.doc);
+            }
+        }
+
+        for class in &namespace.classes {
+            write_class_page(&mut out, &table, class);
+        }
+
+        for enum_meta in &namespace.enums {
+            write_enum_page(&mut out, &table, enum_meta);
+        }
+
+        for event in &namespace.events {
+            writeln!(out).unwrap();
+            writeln!(out, "## {}.{}", table, event.name).unwrap();
+            write_doc_paragraph(&mut out, event.doc);
+            writeln!(out).unwrap();
+            writeln!(
+                out,
+                "Listened with `{}.{}.listen(function(name, event) end)`.",
+                table, event.name
+            )
+            .unwrap();
+            write_field_table(&mut out, event.fields);
+        }
+
+        pages.push((format!("{}.md", table), out));
+    }
+
+    if !meta.entity_events.is_empty() {
+        let mut out = String::new();
+
+        writeln!(out, "# Entity events").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "<!-- {} -->", GENERATED_NOTICE).unwrap();
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "Entity events are listened with `entity:listen(name, function(entity, name, event, target) end)`, where `target` is the entity the event was emitted on. See [mk.entity](../mk.entity.md#entity-events) for how they propagate."
+        )
+        .unwrap();
+
+        for event in &meta.entity_events {
+            writeln!(out).unwrap();
+            writeln!(out, "## {}", event.event_name).unwrap();
+            write_doc_paragraph(&mut out, event.doc);
+            write_field_table(&mut out, event.fields);
+        }
+
+        pages.push(("entity-events.md".to_owned(), out));
+    }
+
+    pages
+}
+
+fn write_class_page(out: &mut String, table: &str, class: &LuaClassMeta) {
+    let (static_methods, methods): (Vec<_>, Vec<_>) =
+        class.methods.iter().partition(|method| method.is_static);
+
+    writeln!(out).unwrap();
+
+    if static_methods.is_empty() {
+        writeln!(out, "## {}", class.name).unwrap();
+    } else {
+        writeln!(out, "## {}.{}", table, class.name).unwrap();
+    }
+
+    write_doc_paragraph(out, class.doc);
+    write_field_table(out, class.fields);
+
+    if !static_methods.is_empty() {
+        writeln!(out).unwrap();
+        writeln!(out, "### Functions").unwrap();
+
+        for method in static_methods {
+            writeln!(out).unwrap();
+            writeln!(out, "#### `{}.{}.{}`", table, class.name, signature(method)).unwrap();
+            write_doc_paragraph(out, method.doc);
+        }
+    }
+
+    if !methods.is_empty() {
+        writeln!(out).unwrap();
+        writeln!(out, "### Methods").unwrap();
+
+        for method in methods {
+            writeln!(out).unwrap();
+            writeln!(out, "#### `{}:{}`", class.name, signature(method)).unwrap();
+            write_doc_paragraph(out, method.doc);
+        }
+    }
+}
+
+fn write_enum_page(out: &mut String, table: &str, enum_meta: &LuaEnumMeta) {
+    writeln!(out).unwrap();
+
+    if enum_meta.are_strings {
+        writeln!(out, "## {}", enum_meta.name).unwrap();
+    } else {
+        writeln!(out, "## {}.{}", table, enum_meta.name).unwrap();
+    }
+
+    write_doc_paragraph(out, enum_meta.doc);
+    writeln!(out).unwrap();
+    writeln!(out, "| Variant |").unwrap();
+    writeln!(out, "| ------- |").unwrap();
+
+    for variant in enum_meta.variants {
+        if enum_meta.are_strings {
+            writeln!(out, "| `\"{}\"` |", variant).unwrap();
+        } else {
+            writeln!(out, "| `{}` |", variant).unwrap();
+        }
+    }
+}
+
+fn write_field_table(out: &mut String, fields: &[LuaFieldMeta]) {
+    if fields.is_empty() {
+        return;
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "| Field | Type | Description |").unwrap();
+    writeln!(out, "| ----- | ---- | ----------- |").unwrap();
+
+    for field in fields {
+        let readonly = if field.is_readonly {
+            " (read-only)"
+        } else {
+            ""
+        };
+        writeln!(
+            out,
+            "| `{}` | `{}` | {}{} |",
+            field.name,
+            field.ty,
+            first_doc_line(field.doc),
+            readonly
+        )
+        .unwrap();
+    }
+}
+
+fn write_doc_paragraph(out: &mut String, doc: &str) {
+    if doc.is_empty() {
+        return;
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "{}", doc).unwrap();
+}
+
+/// Writes the LuaLS definition file and the mdBook pages generated from `meta`.
+pub fn write_lua_api(
+    meta: &LuaApiMeta,
+    annotations_path: impl AsRef<Path>,
+    docs_dir: impl AsRef<Path>,
+) -> anyhow::Result<()> {
+    let annotations_path = annotations_path.as_ref();
+    let docs_dir = docs_dir.as_ref();
+
+    if let Some(parent) = annotations_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("unable to create {}", parent.display()))?;
+    }
+
+    fs::write(annotations_path, lua_annotations(meta))
+        .with_context(|| format!("unable to write {}", annotations_path.display()))?;
+
+    fs::create_dir_all(docs_dir)
+        .with_context(|| format!("unable to create {}", docs_dir.display()))?;
+
+    for (name, content) in lua_api_pages(meta) {
+        let path = docs_dir.join(name);
+        fs::write(&path, content).with_context(|| format!("unable to write {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::script::lua_api_meta;
+    use std::path::PathBuf;
+
+    const REGENERATE: &str =
+        "regenerate it with `cargo run --manifest-path mk/Cargo.toml -p mk --example lua-api`";
+
+    fn repo_path(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../..")
+            .join(path)
+    }
+
+    #[test]
+    fn committed_lua_api_is_up_to_date() {
+        let meta = lua_api_meta().unwrap();
+
+        let annotations = fs::read_to_string(repo_path("types/mk.lua")).unwrap();
+        assert!(
+            annotations == lua_annotations(&meta),
+            "types/mk.lua is stale; {}",
+            REGENERATE
+        );
+
+        let docs_dir = repo_path("docs/src/scripting/generated");
+        let pages = lua_api_pages(&meta);
+
+        for (name, content) in &pages {
+            let committed = fs::read_to_string(docs_dir.join(name)).unwrap_or_default();
+            assert!(
+                &committed == content,
+                "docs/src/scripting/generated/{} is stale; {}",
+                name,
+                REGENERATE
+            );
+        }
+
+        let mut committed_names = fs::read_dir(&docs_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        let mut names = pages.into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        committed_names.sort();
+        names.sort();
+        assert_eq!(committed_names, names, "{}", REGENERATE);
+    }
+}
@@ -0,0 +1,65 @@
+//! Descriptions of the types exposed to Lua, registered by the `codegen` macros.
+//! Type names follow the LuaLS annotation syntax, e.g. `number?` or `Vec2[]`.
+
+pub struct LuaFieldMeta {
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub doc: &'static str,
+    pub is_readonly: bool,
+}
+
+pub struct LuaParamMeta {
+    pub name: &'static str,
+    pub ty: &'static str,
+}
+
+pub struct LuaMethodMeta {
+    pub name: &'static str,
+    pub doc: &'static str,
+    /// Static methods are called on the API table, e.g. `mk.structure.Vec2.new(1, 2)`.
+    pub is_static: bool,
+    pub params: &'static [LuaParamMeta],
+    pub returns: Option<&'static str>,
+}
+
+/// A userdata type, registered by `LuaUserData` and `lua_user_data_method`, or a table type
+/// registered by `LuaStruct` and `LuaExpose`.
+pub struct LuaClassMeta {
+    pub name: &'static str,
+    pub doc: &'static str,
+    pub fields: &'static [LuaFieldMeta],
+    pub methods: &'static [LuaMethodMeta],
+}
+
+/// An enum whose variants are userdata, registered by `LuaEnum`, or strings, registered by `LuaExpose`.
+pub struct LuaEnumMeta {
+    pub name: &'static str,
+    pub doc: &'static str,
+    pub variants: &'static [&'static str],
+    /// String variants are passed by name and have no API table.
+    pub are_strings: bool,
+}
+
+/// An event whose fields are passed to Lua handlers as a table, registered by `Event`.
+pub struct LuaEventMeta {
+    pub name: &'static str,
+    pub event_name: &'static str,
+    pub doc: &'static str,
+    pub fields: &'static [LuaFieldMeta],
+}
+
+pub trait LuaUserDataMetaProvider {
+    fn lua_class_meta() -> LuaClassMeta;
+}
+
+pub trait LuaMethodMetaProvider {
+    fn lua_methods() -> &'static [LuaMethodMeta];
+}
+
+pub trait LuaEnumMetaProvider {
+    fn lua_enum_meta() -> LuaEnumMeta;
+}
+
+pub trait LuaEventMetaProvider {
+    fn lua_event_meta() -> LuaEventMeta;
+}
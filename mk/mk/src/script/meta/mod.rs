@@ -0,0 +1,7 @@
+mod lua_api_meta;
+mod lua_api_writer;
+mod lua_meta;
+
+pub use lua_api_meta::*;
+pub use lua_api_writer::*;
+pub use lua_meta::*;
@@ -1,9 +1,17 @@
 mod api;
 mod conversion;
+mod debugger;
 mod ffi;
+mod meta;
+mod module_loader;
 mod script_manager;
+mod task_manager;
 
 pub use api::*;
 pub use conversion::*;
+pub use debugger::*;
 pub use ffi::*;
+pub use meta::*;
+pub use module_loader::*;
 pub use script_manager::*;
+pub use task_manager::*;
@@ -0,0 +1,495 @@
+use anyhow::{anyhow, Context, Result};
+use mlua::prelude::*;
+use res::{
+    asset_loader::ResourceLoader, decoder::TextDecoder, decoder::TextDecoderOutput, ResourcesMeta,
+};
+use std::{
+    cell::RefCell, collections::HashMap, ffi::c_void, fs::read, io::ErrorKind, path::PathBuf,
+    rc::Rc, sync::Arc,
+};
+
+/// Directory of the asset source that modules are resolved from.
+pub const MODULE_ROOT: &str = "scripts";
+
+/// Modules a sandboxed module cannot `require`.
+const SANDBOX_DENIED_MODULES: [&str; 5] = ["_G", "debug", "io", "os", "package"];
+
+/// Libraries a sandboxed module gets copies of, so that changing them does not change them for the other modules.
+const SANDBOX_COPIED_LIBRARIES: [&str; 6] = ["coroutine", "math", "mk", "string", "table", "utf8"];
+
+/// Where `require` reads Lua modules from.
+pub trait ModuleSource {
+    /// Reads the file at the path, relative to the asset base and separated with `/`.
+    /// Returns `None` if there is no such file.
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>>;
+}
+
+/// Reads modules from a loose asset directory.
+pub struct DirModuleSource {
+    base: PathBuf,
+}
+
+impl DirModuleSource {
+    pub fn new(base: impl Into<PathBuf>) -> Self {
+        Self { base: base.into() }
+    }
+}
+
+impl ModuleSource for DirModuleSource {
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.base.join(path);
+
+        match read(&path) {
+            Ok(content) => Ok(Some(content)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+}
+
+/// Reads modules from a packed `res` archive.
+/// Modules are `text` resources named after their path relative to the asset base.
+pub struct ResModuleSource {
+    loader: ResourceLoader,
+    meta: ResourcesMeta,
+}
+
+impl ResModuleSource {
+    pub fn new(mut loader: ResourceLoader, meta: ResourcesMeta) -> Self {
+        loader.add_decoder(Box::new(TextDecoder));
+        Self { loader, meta }
+    }
+}
+
+impl ModuleSource for ResModuleSource {
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let resource = match self.meta.resource_names.get(path) {
+            Some(&index) => &self.meta.resources[index],
+            None => return Ok(None),
+        };
+        let output = self
+            .loader
+            .load(resource)
+            .map_err(|err| anyhow!("failed to load resource {}: {:?}", path, err))?;
+
+        match output.downcast_ref::<TextDecoderOutput>() {
+            Some(output) => Ok(Some(output.content.clone())),
+            None => Err(anyhow!("resource {} is not a text", path)),
+        }
+    }
+}
+
+/// Resolves `require` from a `ModuleSource`, and caches the loaded chunks.
+///
+/// A module name is a path under `MODULE_ROOT` whose segments are separated with `.` or `/`,
+/// e.g. `editor.theme` refers to `scripts/editor/theme.lua` or `scripts/editor/theme/init.lua`.
+pub struct ModuleLoader {
+    source: Box<dyn ModuleSource>,
+    chunks: HashMap<String, Arc<LuaRegistryKey>>,
+    sandboxed_prefixes: Vec<String>,
+    sandbox: Option<LuaRegistryKey>,
+    /// Paths of the modules being run, outermost first.
+    loading: Vec<String>,
+}
+
+impl ModuleLoader {
+    pub fn new(source: impl ModuleSource + 'static) -> Self {
+        Self {
+            source: Box::new(source),
+            chunks: HashMap::new(),
+            sandboxed_prefixes: Vec::new(),
+            sandbox: None,
+            loading: Vec::new(),
+        }
+    }
+
+    /// Replaces the source. Cached chunks are dropped.
+    pub fn set_source(&mut self, source: impl ModuleSource + 'static) {
+        self.source = Box::new(source);
+        self.chunks.clear();
+    }
+
+    /// Drops the cached chunks, so that modules are read again the next time they are required.
+    pub fn clear_cache(&mut self) {
+        self.chunks.clear();
+    }
+
+    /// Runs the modules under the prefix, e.g. `mods`, without the `io` and `os` libraries
+    /// and without the functions that load files.
+    pub fn sandbox(&mut self, prefix: impl Into<String>) {
+        self.sandboxed_prefixes.push(prefix.into());
+        self.chunks.clear();
+    }
+
+    pub fn is_sandboxed(&self, name: &str) -> bool {
+        let path = module_path(name);
+        self.sandboxed_prefixes.iter().any(|prefix| {
+            let prefix = module_path(prefix);
+            path == prefix || path.starts_with(&format!("{}/", prefix))
+        })
+    }
+
+    /// Returns an error naming the chain of modules if the module is already being run.
+    fn check_cycle(&self, name: &str) -> LuaResult<()> {
+        let path = module_path(name);
+
+        match self.loading.iter().position(|loading| loading == &path) {
+            Some(index) => Err(LuaError::external(format!(
+                "circular require: {} -> {}",
+                self.loading[index..].join(" -> "),
+                path
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Loads the module as a function, or returns `None` if it cannot be found.
+    /// The function is called with the name of the module and its path.
+    fn load(&mut self, lua: &Lua, name: &str) -> Result<Option<(Arc<LuaRegistryKey>, String)>> {
+        let path = module_path(name);
+        let candidates = [
+            format!("{}/{}.lua", MODULE_ROOT, path),
+            format!("{}/{}/init.lua", MODULE_ROOT, path),
+        ];
+
+        for candidate in candidates {
+            if let Some(chunk) = self.chunks.get(&candidate) {
+                return Ok(Some((chunk.clone(), candidate)));
+            }
+
+            let content = match self.source.read(&candidate)? {
+                Some(content) => content,
+                None => continue,
+            };
+            let mut chunk = lua.load(&content).set_name(format!("@{}", candidate))?;
+
+            if self.is_sandboxed(name) {
+                chunk = chunk.set_environment(self.sandbox_env(lua)?)?;
+            }
+
+            let chunk = Arc::new(lua.create_registry_value(chunk.into_function()?)?);
+            self.chunks.insert(candidate.clone(), chunk.clone());
+            return Ok(Some((chunk, candidate)));
+        }
+
+        Ok(None)
+    }
+
+    /// Returns a new environment for a sandboxed module, which reads the allowed globals.
+    fn sandbox_env<'lua>(&mut self, lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
+        let globals = match &self.sandbox {
+            Some(globals) => lua.registry_value::<LuaTable>(globals)?,
+            None => {
+                let globals = create_sandbox_globals(lua)?;
+                self.sandbox = Some(lua.create_registry_value(globals.clone())?);
+                globals
+            }
+        };
+
+        let env = lua.create_table()?;
+        let metatable = lua.create_table()?;
+        metatable.set("__index", globals)?;
+        env.set_metatable(Some(metatable));
+        Ok(env)
+    }
+}
+
+/// Installs the loader into `package.searchers`, replacing the searchers that read the file system.
+/// Requiring a module that is still being run, directly or not, raises an error.
+pub fn install_module_loader(lua: &Lua, loader: Rc<RefCell<ModuleLoader>>) -> LuaResult<()> {
+    let package = lua.globals().get::<_, LuaTable>("package")?;
+    let searchers = package.get::<_, LuaTable>("searchers")?;
+    let preload = searchers.get::<_, LuaFunction>(1)?;
+
+    let searcher = lua.create_function(move |lua, name: String| {
+        loader.borrow().check_cycle(&name)?;
+
+        let loaded = loader
+            .borrow_mut()
+            .load(lua, &name)
+            .map_err(LuaError::external)?;
+
+        match loaded {
+            Some((chunk, path)) => {
+                let loader = loader.clone();
+                let path_in_chain = module_path(&name);
+                let function = lua.create_function(move |lua, args: LuaMultiValue| {
+                    let chunk = lua.registry_value::<LuaFunction>(&chunk)?;
+
+                    // The loader is not borrowed while the chunk runs, as it may require other modules.
+                    loader.borrow_mut().loading.push(path_in_chain.clone());
+                    let result = chunk.call::<_, LuaMultiValue>(args);
+                    loader.borrow_mut().loading.pop();
+                    result
+                })?;
+
+                (function, path).to_lua_multi(lua)
+            }
+            None => {
+                format!("no module '{}' in the asset source", module_path(&name)).to_lua_multi(lua)
+            }
+        }
+    })?;
+
+    let new_searchers = lua.create_sequence_from([preload, searcher])?;
+    package.set("searchers", new_searchers)?;
+    package.set("path", "")?;
+    package.set("cpath", "")?;
+    Ok(())
+}
+
+/// Copies the globals, leaving out the ones that reach the file system or the process.
+/// The libraries are copied as well, so that a sandboxed module cannot change them for the others.
+fn create_sandbox_globals(lua: &Lua) -> LuaResult<LuaTable> {
+    let globals = lua.create_table()?;
+
+    for pair in lua.globals().pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair?;
+        globals.set(key, value)?;
+    }
+
+    for name in ["io", "dofile", "loadfile", "load", "package"] {
+        globals.set(name, LuaValue::Nil)?;
+    }
+
+    let mut copies = HashMap::new();
+    let libraries = lua.create_table()?;
+
+    for name in SANDBOX_COPIED_LIBRARIES {
+        if let LuaValue::Table(library) = globals.get(name)? {
+            let library = copy_table(lua, library, &mut copies)?;
+            globals.set(name, library.clone())?;
+            libraries.set(name, library)?;
+        }
+    }
+
+    // Strings share a metatable whose `__index` is `string`, so the sandbox sees a copy pointing to its own `string`.
+    let string_metatable = lua
+        .globals()
+        .get::<_, LuaFunction>("getmetatable")?
+        .call::<_, LuaTable>("")?;
+    let string_metatable =
+        lua.create_registry_value(copy_table(lua, string_metatable, &mut copies)?)?;
+    globals.set(
+        "getmetatable",
+        lua.create_function(move |lua, value: LuaValue| match value {
+            LuaValue::String(_) => lua.registry_value::<LuaValue>(&string_metatable),
+            value => lua
+                .globals()
+                .get::<_, LuaFunction>("getmetatable")?
+                .call::<_, LuaValue>(value),
+        })?,
+    )?;
+
+    // The standard libraries are also reachable through `require`.
+    let libraries = lua.create_registry_value(libraries)?;
+    globals.set(
+        "require",
+        lua.create_function(move |lua, name: String| {
+            if SANDBOX_DENIED_MODULES.contains(&name.as_str()) {
+                return Err(LuaError::external(format!(
+                    "module '{}' is not available in the sandbox",
+                    name
+                )));
+            }
+
+            if SANDBOX_COPIED_LIBRARIES.contains(&name.as_str()) {
+                return lua
+                    .registry_value::<LuaTable>(&libraries)?
+                    .get::<_, LuaValue>(name)?
+                    .to_lua_multi(lua);
+            }
+
+            lua.globals()
+                .get::<_, LuaFunction>("require")?
+                .call::<_, LuaMultiValue>(name)
+        })?,
+    )?;
+
+    // Only the functions of `os` that tell the time are kept.
+    let os = lua.globals().get::<_, LuaTable>("os")?;
+    let safe_os = lua.create_table()?;
+    for name in ["clock", "date", "difftime", "time"] {
+        safe_os.set(name, os.get::<_, LuaValue>(name)?)?;
+    }
+    globals.set("os", safe_os)?;
+    globals.set("_G", globals.clone())?;
+
+    Ok(globals)
+}
+
+/// Copies the table along with the tables in it and its metatable. Tables reached more than once are copied once,
+/// remembered in `copies` by the table they are copied from.
+fn copy_table<'lua>(
+    lua: &'lua Lua,
+    table: LuaTable<'lua>,
+    copies: &mut HashMap<*const c_void, LuaTable<'lua>>,
+) -> LuaResult<LuaTable<'lua>> {
+    if let Some(copy) = copies.get(&table.to_pointer()) {
+        return Ok(copy.clone());
+    }
+
+    let copy = lua.create_table()?;
+    copies.insert(table.to_pointer(), copy.clone());
+
+    for pair in table.clone().pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair?;
+        let value = match value {
+            LuaValue::Table(table) => LuaValue::Table(copy_table(lua, table, copies)?),
+            value => value,
+        };
+        copy.raw_set(key, value)?;
+    }
+
+    if let Some(metatable) = table.get_metatable() {
+        copy.set_metatable(Some(copy_table(lua, metatable, copies)?));
+    }
+
+    Ok(copy)
+}
+
+/// Converts a module name into a path under `MODULE_ROOT`, without the extension.
+fn module_path(name: &str) -> String {
+    let name = name.strip_suffix(".lua").unwrap_or(name);
+    name.replace('.', "/")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Serves modules from memory and counts the reads.
+    struct MemorySource {
+        files: HashMap<String, String>,
+        reads: Rc<Cell<usize>>,
+    }
+
+    impl ModuleSource for MemorySource {
+        fn read(&self, path: &str) -> Result<Option<Vec<u8>>> {
+            self.reads.set(self.reads.get() + 1);
+            Ok(self.files.get(path).map(|file| file.as_bytes().to_vec()))
+        }
+    }
+
+    fn setup(files: &[(&str, &str)]) -> (Lua, Rc<RefCell<ModuleLoader>>, Rc<Cell<usize>>) {
+        let lua = Lua::new();
+        let reads = Rc::new(Cell::new(0));
+        let loader = Rc::new(RefCell::new(ModuleLoader::new(MemorySource {
+            files: files
+                .iter()
+                .map(|(path, file)| (path.to_string(), file.to_string()))
+                .collect(),
+            reads: reads.clone(),
+        })));
+        install_module_loader(&lua, loader.clone()).unwrap();
+        (lua, loader, reads)
+    }
+
+    #[test]
+    fn names_resolve_to_files_or_init_files() {
+        let (lua, _, _) = setup(&[
+            ("scripts/editor/theme.lua", "return 'theme'"),
+            ("scripts/editor/init.lua", "return 'editor'"),
+            ("scripts/utils/keys.lua", "return ..."),
+        ]);
+
+        let require = |name: &str| {
+            lua.load(&format!("return require('{}')", name))
+                .eval::<String>()
+        };
+
+        assert_eq!(require("editor.theme").unwrap(), "theme");
+        assert_eq!(require("editor").unwrap(), "editor");
+        // The chunk is called with the name as given.
+        assert_eq!(require("utils/keys").unwrap(), "utils/keys");
+        assert!(require("editor.missing")
+            .unwrap_err()
+            .to_string()
+            .contains("no module 'editor/missing' in the asset source"));
+    }
+
+    #[test]
+    fn chunks_are_cached_until_cleared() {
+        let (lua, loader, reads) = setup(&[("scripts/counter.lua", "count = (count or 0) + 1")]);
+        let require_again = "package.loaded.counter = nil; require('counter')";
+
+        lua.load("require('counter'); require('counter')")
+            .exec()
+            .unwrap();
+        assert_eq!(reads.get(), 1);
+
+        lua.load(require_again).exec().unwrap();
+        assert_eq!(reads.get(), 1);
+        assert_eq!(lua.globals().get::<_, i64>("count").unwrap(), 2);
+
+        loader.borrow_mut().clear_cache();
+        lua.load(require_again).exec().unwrap();
+        assert_eq!(reads.get(), 2);
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        let (lua, loader, _) = setup(&[
+            ("scripts/a.lua", "require('b')"),
+            ("scripts/b.lua", "require('c')"),
+            ("scripts/c.lua", "require('b')"),
+        ]);
+
+        let err = lua.load("require('a')").exec().unwrap_err().to_string();
+        assert!(err.contains("circular require: b -> c -> b"), "{}", err);
+
+        // A failed require does not leave the modules marked as being run.
+        assert!(loader.borrow().loading.is_empty());
+    }
+
+    #[test]
+    fn sandboxed_modules_cannot_reach_io() {
+        let (lua, loader, _) = setup(&[
+            (
+                "scripts/mods/cheat.lua",
+                "return io == nil and os.execute == nil",
+            ),
+            ("scripts/trusted.lua", "return io ~= nil"),
+        ]);
+        loader.borrow_mut().sandbox("mods");
+
+        assert!(lua
+            .load("return require('mods.cheat')")
+            .eval::<bool>()
+            .unwrap());
+        assert!(lua
+            .load("return require('trusted')")
+            .eval::<bool>()
+            .unwrap());
+    }
+
+    #[test]
+    fn sandboxed_modules_cannot_change_libraries_of_others() {
+        let (lua, loader, _) = setup(&[
+            (
+                "scripts/mods/patch.lua",
+                r#"
+                string.x = "mod"
+                string.format = nil
+                getmetatable("").__index.upper = nil
+                return require("string") == string and string.x == "mod"
+                "#,
+            ),
+            (
+                "scripts/trusted.lua",
+                r#"return string.x == nil and string.format ~= nil and ("a"):upper() == "A""#,
+            ),
+        ]);
+        loader.borrow_mut().sandbox("mods");
+
+        assert!(lua
+            .load("return require('mods.patch')")
+            .eval::<bool>()
+            .unwrap());
+        assert!(lua
+            .load("return require('trusted')")
+            .eval::<bool>()
+            .unwrap());
+    }
+}
@@ -1,6 +1,14 @@
-use super::{LuaApiTable, Module};
+use super::{
+    install_debugger, install_module_loader, Debugger, LuaApiTable, Module, ModuleLoader,
+    ModuleSource,
+};
 use anyhow::{Context, Result};
 use mlua::prelude::*;
+use std::{
+    cell::{RefCell, RefMut},
+    net::SocketAddr,
+    rc::Rc,
+};
 
 pub trait LuaCallable<'lua, A, R> {
     fn call(&self, lua: &'lua Lua, args: A) -> Result<R>
@@ -41,20 +49,65 @@ where
 
 pub struct ScriptManager {
     lua: Lua,
+    module_loader: Rc<RefCell<ModuleLoader>>,
+    debugger: Option<Rc<RefCell<Debugger>>>,
 }
 
 impl ScriptManager {
-    pub fn new() -> Result<Self> {
-        let lua = Lua::new();
+    /// Creates the Lua state, whose `require` reads modules from the source.
+    /// If a debugger is given, the state is created with the `debug` library for it.
+    pub fn new(
+        module_source: impl ModuleSource + 'static,
+        debugger: Option<Debugger>,
+    ) -> Result<Self> {
+        let lua = match debugger {
+            // The library is hidden from the scripts by the debugger.
+            Some(..) => unsafe {
+                Lua::unsafe_new_with(LuaStdLib::ALL_SAFE | LuaStdLib::DEBUG, LuaOptions::new())
+            },
+            None => Lua::new(),
+        };
         lua.globals()
             .raw_set("mk", Module::create_api_table(&lua)?)?;
-        Ok(Self { lua })
+
+        let module_loader = Rc::new(RefCell::new(ModuleLoader::new(module_source)));
+        install_module_loader(&lua, module_loader.clone())
+            .with_context(|| "unable to install module loader")?;
+
+        let debugger = debugger.map(|debugger| Rc::new(RefCell::new(debugger)));
+        if let Some(debugger) = &debugger {
+            install_debugger(&lua, debugger.clone())
+                .with_context(|| "unable to install debugger")?;
+        }
+
+        Ok(Self {
+            lua,
+            module_loader,
+            debugger,
+        })
     }
 
     pub fn lua(&self) -> &Lua {
         &self.lua
     }
 
+    pub fn module_loader_mut(&self) -> RefMut<ModuleLoader> {
+        self.module_loader.borrow_mut()
+    }
+
+    pub fn debug_server_address(&self) -> Option<SocketAddr> {
+        self.debugger
+            .as_ref()
+            .map(|debugger| debugger.borrow().address())
+    }
+
+    /// Serves the debug client, if any, while no script runs.
+    pub fn poll_debugger(&self) {
+        if let Some(debugger) = &self.debugger {
+            debugger.borrow_mut().poll(&self.lua);
+        }
+    }
+
     pub fn append_api_table<T>(&self, name: impl AsRef<str>) -> Result<()>
     where
         T: LuaApiTable,
@@ -73,6 +126,36 @@ impl ScriptManager {
             .with_context(|| "unable to execute lua chunk")
     }
 
+    /// Evaluates the chunk as an expression, or as a statement if it is not one,
+    /// and returns its values.
+    pub fn evaluate<'lua>(&'lua self, chunk: impl AsRef<str>) -> Result<LuaMultiValue<'lua>> {
+        let chunk = chunk.as_ref();
+        let function = match self
+            .lua
+            .load(&format!("return {}", chunk))
+            .set_name(chunk)?
+            .into_function()
+        {
+            Ok(function) => function,
+            Err(..) => self.lua.load(chunk).set_name(chunk)?.into_function()?,
+        };
+
+        function
+            .call(())
+            .with_context(|| "unable to evaluate lua chunk")
+    }
+
+    /// Requires the module, e.g. `entry` for `scripts/entry.lua`.
+    pub fn execute_module(&self, name: impl AsRef<str>) -> Result<()> {
+        let name = name.as_ref();
+        self.lua
+            .globals()
+            .get::<_, LuaFunction>("require")?
+            .call::<_, LuaValue>(name)
+            .with_context(|| format!("unable to execute lua module {}", name))?;
+        Ok(())
+    }
+
     pub fn call<'lua, A, R>(
         &'lua self,
         callable: impl LuaCallable<'lua, A, R>,
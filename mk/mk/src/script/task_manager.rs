@@ -0,0 +1,434 @@
+use crate::{
+    diagnostic::{report_script_error, ScriptError},
+    engine::use_context,
+    event::EventHandler,
+    script::{
+        FFIFunction, FFIThread, LuaClassMeta, LuaFieldMeta, LuaMethodMeta, LuaUserDataMetaProvider,
+    },
+    EngineContext,
+};
+use mlua::prelude::*;
+use std::{collections::HashSet, sync::Arc};
+
+/// Refers to a task spawned with `TaskManager`. Stays valid after the task has ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskHandle(u64);
+
+impl LuaUserData for TaskHandle {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("is_running", |_lua, this| {
+            Ok(use_context().task_mgr().is_running(*this))
+        });
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("cancel", |_lua, this, ()| {
+            Ok(use_context().task_mgr_mut().cancel(*this))
+        });
+
+        methods.add_meta_method(LuaMetaMethod::ToString, |_lua, this, ()| {
+            Ok(format!("TaskHandle({})", this.0))
+        });
+    }
+}
+
+impl LuaUserDataMetaProvider for TaskHandle {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "TaskHandle",
+            doc: "Refers to a task spawned with `mk.task.spawn`. Stays valid after the task has ended.",
+            fields: &[LuaFieldMeta {
+                name: "is_running",
+                ty: "boolean",
+                doc: "Whether the task has neither finished nor been cancelled.",
+                is_readonly: true,
+            }],
+            methods: &[LuaMethodMeta {
+                name: "cancel",
+                doc: "Stops the task. Returns whether it was still running.",
+                is_static: false,
+                params: &[],
+                returns: Some("boolean"),
+            }],
+        }
+    }
+}
+
+/// What a task waits for, yielded by the `mk.task.wait*` functions.
+#[derive(Clone)]
+pub enum TaskWait {
+    /// Resumes after the given seconds of the scaled clock.
+    Seconds(f64),
+    /// Resumes after the given number of frames.
+    Frames(u32),
+    /// Resumes with the parameters of the next event with the given name.
+    Event(String),
+    /// Resumes once the function returns a truthy value. It is called once per frame.
+    Until(FFIFunction),
+}
+
+impl LuaUserData for TaskWait {}
+
+enum TaskState {
+    Seconds(f64),
+    Frames(u32),
+    Event {
+        name: String,
+        params: Option<Arc<LuaRegistryKey>>,
+    },
+    Until(FFIFunction),
+}
+
+impl From<TaskWait> for TaskState {
+    fn from(wait: TaskWait) -> Self {
+        match wait {
+            TaskWait::Seconds(seconds) => TaskState::Seconds(seconds),
+            TaskWait::Frames(frames) => TaskState::Frames(frames),
+            TaskWait::Event(name) => TaskState::Event { name, params: None },
+            TaskWait::Until(predicate) => TaskState::Until(predicate),
+        }
+    }
+}
+
+/// How a task should be resumed in this frame.
+enum TaskWake {
+    Resume(Option<Arc<LuaRegistryKey>>),
+    Check(FFIFunction),
+}
+
+struct Task {
+    handle: TaskHandle,
+    thread: FFIThread,
+    state: TaskState,
+}
+
+/// Runs Lua coroutines across frames. Tasks are resumed by `run_tasks`, once per frame.
+#[derive(Default)]
+pub struct TaskManager {
+    next_handle: u64,
+    tasks: Vec<Task>,
+    listened_events: HashSet<String>,
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the coroutine as a task. It is first resumed in the next task update.
+    pub fn spawn(&mut self, thread: FFIThread) -> TaskHandle {
+        let handle = TaskHandle(self.next_handle);
+        self.next_handle += 1;
+        self.tasks.push(Task {
+            handle,
+            thread,
+            state: TaskState::Frames(0),
+        });
+        handle
+    }
+
+    pub fn is_running(&self, handle: TaskHandle) -> bool {
+        self.tasks.iter().any(|task| task.handle == handle)
+    }
+
+    /// Stops the task without resuming it again. Returns `false` if it has already ended.
+    pub fn cancel(&mut self, handle: TaskHandle) -> bool {
+        let len = self.tasks.len();
+        self.tasks.retain(|task| task.handle != handle);
+        self.tasks.len() != len
+    }
+
+    /// Returns `true` if a handler for the event has to be added, which should call `notify_event`.
+    fn listen_event(&mut self, name: &str) -> bool {
+        self.listened_events.insert(name.to_owned())
+    }
+
+    /// Returns `true` if a task is waiting for the event and has not been notified of it yet.
+    fn is_waiting_for(&self, name: &str) -> bool {
+        self.tasks.iter().any(|task| {
+            matches!(
+                &task.state,
+                TaskState::Event { name: event_name, params: None } if event_name == name
+            )
+        })
+    }
+
+    /// Wakes the tasks waiting for the event.
+    fn notify_event(&mut self, name: &str, params: Arc<LuaRegistryKey>) {
+        for task in &mut self.tasks {
+            if let TaskState::Event {
+                name: event_name,
+                params: event_params @ None,
+            } = &mut task.state
+            {
+                if event_name == name {
+                    *event_params = Some(params.clone());
+                }
+            }
+        }
+    }
+
+    fn set_wait(&mut self, handle: TaskHandle, wait: TaskWait) {
+        if let Some(task) = self.tasks.iter_mut().find(|task| task.handle == handle) {
+            task.state = wait.into();
+        }
+    }
+
+    /// Advances every task and returns the ones that may be resumed.
+    fn update(&mut self, dt: f64) -> Vec<(TaskHandle, FFIThread, TaskWake)> {
+        let mut woken = Vec::new();
+
+        for task in &mut self.tasks {
+            let wake = match &mut task.state {
+                TaskState::Seconds(remaining) => {
+                    *remaining -= dt;
+                    (*remaining <= 0f64).then_some(TaskWake::Resume(None))
+                }
+                TaskState::Frames(remaining) => {
+                    *remaining = remaining.saturating_sub(1);
+                    (*remaining == 0).then_some(TaskWake::Resume(None))
+                }
+                TaskState::Event { params, .. } => {
+                    params.take().map(|params| TaskWake::Resume(Some(params)))
+                }
+                TaskState::Until(predicate) => Some(TaskWake::Check(predicate.clone())),
+            };
+
+            if let Some(wake) = wake {
+                woken.push((task.handle, task.thread.clone(), wake));
+            }
+        }
+
+        woken
+    }
+}
+
+/// Resumes every task whose wait is over. Errors are reported with the traceback of the task,
+/// which is then stopped.
+pub fn run_tasks(context: &EngineContext) {
+    let dt = context.time_mgr().dt_f64();
+    let woken = context.task_mgr_mut().update(dt);
+    let lua = context.script_mgr().lua();
+
+    for (handle, thread, wake) in woken {
+        // A task may have been cancelled by another one resumed before.
+        if !context.task_mgr().is_running(handle) {
+            continue;
+        }
+
+        if let Err(err) = resume_task(context, lua, handle, &thread, wake) {
+            context.task_mgr_mut().cancel(handle);
+            report_script_error("task", &ScriptError::from_lua_error(&err));
+        }
+    }
+}
+
+fn resume_task(
+    context: &EngineContext,
+    lua: &Lua,
+    handle: TaskHandle,
+    thread: &FFIThread,
+    wake: TaskWake,
+) -> LuaResult<()> {
+    let args = match wake {
+        TaskWake::Resume(Some(params)) => lua.registry_value::<LuaValue>(&params)?,
+        TaskWake::Resume(None) => LuaValue::Nil,
+        TaskWake::Check(predicate) => {
+            if !predicate.as_function(lua)?.call::<_, bool>(())? {
+                return Ok(());
+            }
+
+            LuaValue::Nil
+        }
+    };
+
+    let thread = thread.as_thread(lua)?;
+    let yielded = thread.resume::<_, LuaValue>(args)?;
+
+    if thread.status() != LuaThreadStatus::Resumable {
+        context.task_mgr_mut().cancel(handle);
+        return Ok(());
+    }
+
+    // Yielding anything else than a wait, e.g. `coroutine.yield()`, waits for the next frame.
+    let wait = match yielded {
+        LuaValue::UserData(userdata) if userdata.is::<TaskWait>() => {
+            userdata.borrow::<TaskWait>()?.clone()
+        }
+        _ => TaskWait::Frames(1),
+    };
+
+    if let TaskWait::Event(name) = &wait {
+        listen_event(context, name);
+    }
+
+    context.task_mgr_mut().set_wait(handle, wait);
+    Ok(())
+}
+
+/// Makes sure that the tasks waiting for the event are woken when it is emitted.
+/// The handler stays, but does nothing while no task is waiting for the event.
+fn listen_event(context: &EngineContext, name: &str) {
+    if !context.task_mgr_mut().listen_event(name) {
+        return;
+    }
+
+    let event_name = name.to_owned();
+    context.event_mgr().add_handler(
+        name,
+        EventHandler::native(move |event| {
+            let context = use_context();
+
+            if !context.task_mgr().is_waiting_for(&event_name) {
+                return Ok(());
+            }
+
+            let lua = context.script_mgr().lua();
+            let params = lua.create_registry_value(event.params_to_lua_table(lua)?)?;
+            context
+                .task_mgr_mut()
+                .notify_event(&event_name, Arc::new(params));
+            Ok(())
+        }),
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn spawn(lua: &Lua, task_mgr: &mut TaskManager) -> TaskHandle {
+        let function = lua.create_function(|_lua, ()| Ok(())).unwrap();
+        let thread = lua.create_thread(function).unwrap();
+        task_mgr.spawn(FFIThread::new(lua, thread).unwrap())
+    }
+
+    fn woken(task_mgr: &mut TaskManager, dt: f64) -> Vec<TaskHandle> {
+        task_mgr
+            .update(dt)
+            .into_iter()
+            .map(|(handle, _, _)| handle)
+            .collect()
+    }
+
+    #[test]
+    fn spawned_tasks_wake_in_the_next_update() {
+        let lua = Lua::new();
+        let mut task_mgr = TaskManager::new();
+        let task = spawn(&lua, &mut task_mgr);
+
+        assert_eq!(woken(&mut task_mgr, 0f64), vec![task]);
+    }
+
+    #[test]
+    fn frames_are_counted() {
+        let lua = Lua::new();
+        let mut task_mgr = TaskManager::new();
+        let task = spawn(&lua, &mut task_mgr);
+
+        task_mgr.set_wait(task, TaskWait::Frames(3));
+        assert!(woken(&mut task_mgr, 1f64).is_empty());
+        assert!(woken(&mut task_mgr, 1f64).is_empty());
+        assert_eq!(woken(&mut task_mgr, 1f64), vec![task]);
+    }
+
+    #[test]
+    fn seconds_follow_the_given_dt() {
+        let lua = Lua::new();
+        let mut task_mgr = TaskManager::new();
+        let task = spawn(&lua, &mut task_mgr);
+
+        // As with a time scale of 0.5 on frames of 0.5 seconds.
+        task_mgr.set_wait(task, TaskWait::Seconds(0.5f64));
+        assert!(woken(&mut task_mgr, 0.25f64).is_empty());
+        assert_eq!(woken(&mut task_mgr, 0.25f64), vec![task]);
+
+        // A paused clock never wakes the task.
+        task_mgr.set_wait(task, TaskWait::Seconds(0.5f64));
+        assert!(woken(&mut task_mgr, 0f64).is_empty());
+        assert!(woken(&mut task_mgr, 0f64).is_empty());
+    }
+
+    #[test]
+    fn event_params_are_passed_back() {
+        let lua = Lua::new();
+        let mut task_mgr = TaskManager::new();
+        let task = spawn(&lua, &mut task_mgr);
+        let other = spawn(&lua, &mut task_mgr);
+
+        task_mgr.set_wait(task, TaskWait::Event("hit".to_owned()));
+        task_mgr.set_wait(other, TaskWait::Event("miss".to_owned()));
+        assert!(woken(&mut task_mgr, 1f64).is_empty());
+        assert!(task_mgr.is_waiting_for("hit"));
+        assert!(!task_mgr.is_waiting_for("update"));
+
+        let params = lua.create_table().unwrap();
+        params.set("damage", 3).unwrap();
+        task_mgr.notify_event("hit", Arc::new(lua.create_registry_value(params).unwrap()));
+        // Once notified, further emissions are not needed until the task waits again.
+        assert!(!task_mgr.is_waiting_for("hit"));
+
+        let woken = task_mgr.update(1f64);
+        assert_eq!(woken.len(), 1);
+        assert_eq!(woken[0].0, task);
+
+        match &woken[0].2 {
+            TaskWake::Resume(Some(params)) => {
+                let params = lua.registry_value::<LuaTable>(params).unwrap();
+                assert_eq!(params.get::<_, i64>("damage").unwrap(), 3);
+            }
+            _ => panic!("expected the event params"),
+        }
+
+        // The params are handed back once.
+        assert!(task_mgr.update(1f64).is_empty());
+    }
+
+    #[test]
+    fn until_is_checked_every_frame() {
+        let lua = Lua::new();
+        let mut task_mgr = TaskManager::new();
+        let task = spawn(&lua, &mut task_mgr);
+        let predicate = lua
+            .load("return function() return done end")
+            .eval()
+            .unwrap();
+
+        task_mgr.set_wait(
+            task,
+            TaskWait::Until(FFIFunction::new(&lua, predicate).unwrap()),
+        );
+
+        for done in [false, true] {
+            lua.globals().set("done", done).unwrap();
+
+            let woken = task_mgr.update(1f64);
+            assert_eq!(woken.len(), 1);
+
+            match &woken[0].2 {
+                TaskWake::Check(predicate) => {
+                    let predicate = predicate.as_function(&lua).unwrap();
+                    assert_eq!(predicate.call::<_, bool>(()).unwrap(), done);
+                }
+                _ => panic!("expected the predicate to be checked"),
+            }
+        }
+    }
+
+    #[test]
+    fn cancel_during_update() {
+        let lua = Lua::new();
+        let mut task_mgr = TaskManager::new();
+        let first = spawn(&lua, &mut task_mgr);
+        let second = spawn(&lua, &mut task_mgr);
+
+        // As if the first task cancelled the second before its resumption.
+        assert_eq!(woken(&mut task_mgr, 1f64), vec![first, second]);
+        assert!(task_mgr.cancel(second));
+        assert!(!task_mgr.is_running(second));
+        assert!(!task_mgr.cancel(second));
+
+        task_mgr.set_wait(first, TaskWait::Frames(1));
+        assert_eq!(woken(&mut task_mgr, 1f64), vec![first]);
+    }
+}
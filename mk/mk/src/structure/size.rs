@@ -1,12 +1,13 @@
 use crate::script::UserDataOpsProvider;
 use codegen::{lua_user_data_method, no_except, ops_extra, ops_to_string, LuaUserData};
 use mlua::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::{
     fmt::Display,
     ops::{Div, DivAssign, Mul, MulAssign, Neg},
 };
 
-#[derive(LuaUserData, Default, Debug, Clone, Copy, PartialEq)]
+#[derive(LuaUserData, Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq)]
 #[impl_copy]
 pub struct Size {
     pub width: f32,
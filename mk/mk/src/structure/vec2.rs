@@ -2,12 +2,13 @@ use super::{Mat22, Vec3};
 use crate::script::UserDataOpsProvider;
 use codegen::{lua_user_data_method, no_except, ops_extra, ops_to_string, LuaUserData};
 use mlua::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::{
     fmt::Display,
     ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
 };
 
-#[derive(LuaUserData, Debug, Clone, Copy, PartialEq)]
+#[derive(LuaUserData, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 #[impl_copy]
 pub struct Vec2 {
     pub x: f32,
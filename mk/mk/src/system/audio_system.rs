@@ -1,6 +1,9 @@
 use crate::component::*;
 use specs::prelude::*;
 
+/// The name the audio system is registered under, in the pre-update stage.
+pub const AUDIO_SYSTEM: &str = "audio";
+
 pub struct AudioSystem;
 
 impl<'a> System<'a> for AudioSystem {
@@ -2,8 +2,16 @@
 mod audio_system;
 mod render_system;
 mod render_system_new;
+mod system_error;
+mod system_manager;
+mod system_stage;
+mod text_field_system;
 
 // pub use animate_single_animators::*;
 pub use audio_system::*;
 pub use render_system::*;
 pub use render_system_new::*;
+pub use system_error::*;
+pub use system_manager::*;
+pub use system_stage::*;
+pub use text_field_system::*;
@@ -8,7 +8,6 @@ use crate::{
     handles::{BufferHandle, PipelineHandle},
     structure::{Mat33, Vec2},
 };
-use fontdue::layout::{HorizontalAlign, VerticalAlign};
 use rayon::slice::ParallelSliceMut;
 use specs::prelude::*;
 use std::{
@@ -165,27 +164,7 @@ impl<'a> System<'a> for RenderSystem {
 
                 let matrix = transform_mgr.transform_world_matrix(transform.index());
 
-                let size = size.size;
-                let layout_size = renderer.compute_size();
-                let (horizontal_align, vertical_align) = (
-                    match renderer.config().horizontal_align {
-                        HorizontalAlign::Left => 0f32,
-                        HorizontalAlign::Center => 0.5f32,
-                        HorizontalAlign::Right => 1f32,
-                    },
-                    match renderer.config().vertical_align {
-                        VerticalAlign::Top => 0f32,
-                        VerticalAlign::Middle => 0.5f32,
-                        VerticalAlign::Bottom => 1f32,
-                    },
-                );
-                let overflow_offset =
-                    Vec2::new((size.width * 0.5) as f32, (size.height * 0.5) as f32);
-                let alignment_offset = Vec2::new(
-                    (size.width - layout_size.width) * horizontal_align,
-                    (size.height - layout_size.height) * vertical_align,
-                );
-                let offset = alignment_offset - overflow_offset;
+                let offset = renderer.layout_offset(size.size);
 
                 let layout = renderer.layout();
                 let glyphs = renderer.glyphs();
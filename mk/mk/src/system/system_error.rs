@@ -0,0 +1,9 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SystemError {
+    #[error("a system named {0} is already registered")]
+    DuplicatedName(String),
+    #[error("the ordering constraints of system {0} form a cycle")]
+    CyclicOrder(String),
+}
@@ -0,0 +1,544 @@
+use super::{SystemError, SystemStage};
+use crate::EngineContext;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use specs::prelude::*;
+use specs::{Dispatcher, DispatcherBuilder, RunningTime};
+use std::{
+    cell::{Cell, RefCell},
+    mem::replace,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+/// Controls where a system runs among the systems of its stage, passed to [`SystemManager::add_system`] and co.
+///
+/// Constraints naming systems that are not registered in the same stage are ignored.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SystemOptions {
+    /// The systems this system runs before.
+    pub before: Vec<String>,
+    /// The systems this system runs after.
+    pub after: Vec<String>,
+}
+
+impl SystemOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_before(mut self, name: impl Into<String>) -> Self {
+        self.before.push(name.into());
+        self
+    }
+
+    pub fn with_after(mut self, name: impl Into<String>) -> Self {
+        self.after.push(name.into());
+        self
+    }
+}
+
+/// Holds the systems registered by the engine, games and scripts, and runs them stage by stage.
+///
+/// Within a stage, systems run in an order satisfying their [`SystemOptions`], and otherwise in the order they were
+/// added. Consecutive systems added by [`SystemManager::add_system`] run in parallel on a thread pool wherever their
+/// system data does not conflict, so they must not reach the engine context. Thread-local systems run on the main
+/// thread, one at a time, and may use the engine context and scripts freely.
+///
+/// Systems can be added and removed at any time, even while a stage is running; the change applies from the next
+/// time the stage runs.
+pub struct SystemManager {
+    systems: RefCell<Vec<SystemEntry>>,
+    stages: [StageSchedule; 4],
+    pool: RefCell<Option<Arc<ThreadPool>>>,
+}
+
+impl SystemManager {
+    pub fn new() -> Self {
+        Self {
+            systems: RefCell::new(Vec::new()),
+            stages: Default::default(),
+            pool: RefCell::new(None),
+        }
+    }
+
+    /// Adds a system that may run in parallel with the other systems of the stage.
+    pub fn add_system<S>(
+        &self,
+        stage: SystemStage,
+        name: impl Into<String>,
+        system: S,
+        options: SystemOptions,
+    ) -> Result<(), SystemError>
+    where
+        S: for<'a> System<'a> + Send + 'static,
+    {
+        // The system is shared with every dispatcher built for the stage, as they are rebuilt whenever it changes.
+        let system = Arc::new(Mutex::new(SetUpOnce::new(system)));
+
+        self.add(
+            stage,
+            name.into(),
+            options,
+            SystemKind::Parallel(Box::new(move |builder, name, deps| {
+                builder.add(SharedSystem(system.clone()), name, deps);
+            })),
+        )
+    }
+
+    /// Adds a system that runs on the main thread.
+    pub fn add_thread_local_system<S>(
+        &self,
+        stage: SystemStage,
+        name: impl Into<String>,
+        system: S,
+        options: SystemOptions,
+    ) -> Result<(), SystemError>
+    where
+        S: for<'a> System<'a> + 'static,
+    {
+        self.add(
+            stage,
+            name.into(),
+            options,
+            SystemKind::ThreadLocal(Rc::new(RefCell::new(SpecsSystem(SetUpOnce::new(system))))),
+        )
+    }
+
+    /// Adds a function that runs on the main thread as a system. The world is not borrowed while it runs.
+    pub fn add_thread_local_fn(
+        &self,
+        stage: SystemStage,
+        name: impl Into<String>,
+        f: impl FnMut(&EngineContext) + 'static,
+        options: SystemOptions,
+    ) -> Result<(), SystemError> {
+        self.add(
+            stage,
+            name.into(),
+            options,
+            SystemKind::ThreadLocal(Rc::new(RefCell::new(FnSystem(f)))),
+        )
+    }
+
+    /// Removes the system with the given name. Returns `false` if there is none.
+    pub fn remove_system(&self, name: &str) -> bool {
+        let mut systems = self.systems.borrow_mut();
+
+        match systems.iter().position(|entry| entry.name == name) {
+            Some(index) => {
+                let entry = systems.remove(index);
+                self.stages[entry.stage.index()].is_dirty.set(true);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn has_system(&self, name: &str) -> bool {
+        self.systems.borrow().iter().any(|entry| entry.name == name)
+    }
+
+    /// Returns the names of the systems of the stage, in the order they run.
+    pub fn system_names(&self, stage: SystemStage) -> Vec<String> {
+        let systems = self.systems.borrow();
+
+        sort_stage(&systems, stage)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|index| systems[index].name.clone())
+            .collect()
+    }
+
+    /// Runs the systems of the stage.
+    pub fn run_stage(&self, context: &EngineContext, stage: SystemStage) {
+        let schedule = &self.stages[stage.index()];
+        let steps = if schedule.is_dirty.replace(false) {
+            None
+        } else {
+            schedule.steps.borrow_mut().take()
+        };
+        let mut steps = match steps {
+            Some(steps) => steps,
+            None => self.build_steps(&mut context.world_mut(), stage),
+        };
+
+        for step in &mut steps {
+            match step {
+                ScheduleStep::Parallel(dispatcher) => dispatcher.dispatch(&context.world()),
+                ScheduleStep::ThreadLocal(system) => system.borrow_mut().run(context),
+            }
+        }
+
+        // Systems added or removed while running made the steps stale.
+        if !schedule.is_dirty.get() {
+            *schedule.steps.borrow_mut() = Some(steps);
+        }
+    }
+
+    fn add(
+        &self,
+        stage: SystemStage,
+        name: String,
+        options: SystemOptions,
+        kind: SystemKind,
+    ) -> Result<(), SystemError> {
+        let mut systems = self.systems.borrow_mut();
+
+        if systems.iter().any(|entry| entry.name == name) {
+            return Err(SystemError::DuplicatedName(name));
+        }
+
+        systems.push(SystemEntry {
+            name,
+            stage,
+            options,
+            kind,
+        });
+
+        if sort_stage(&systems, stage).is_none() {
+            let entry = systems.pop().unwrap();
+            return Err(SystemError::CyclicOrder(entry.name));
+        }
+
+        self.stages[stage.index()].is_dirty.set(true);
+        Ok(())
+    }
+
+    /// Splits the systems of the stage into runs of parallel systems, each making a dispatcher, and thread-local
+    /// systems in between. Systems built for the first time are set up.
+    fn build_steps(&self, world: &mut World, stage: SystemStage) -> Vec<ScheduleStep> {
+        let systems = self.systems.borrow();
+        let order = sort_stage(&systems, stage).unwrap_or_default();
+        let mut steps = Vec::new();
+        let mut batch: Option<(DispatcherBuilder<'static, 'static>, Vec<usize>)> = None;
+
+        for index in order {
+            let entry = &systems[index];
+
+            match &entry.kind {
+                SystemKind::Parallel(add) => {
+                    let (builder, members) = batch.get_or_insert_with(|| {
+                        (DispatcherBuilder::new().with_pool(self.pool()), Vec::new())
+                    });
+                    // Systems of earlier steps have already run; only the order within the dispatcher is left.
+                    let deps = members
+                        .iter()
+                        .filter(|&&member| precedes(&systems[member], entry))
+                        .map(|&member| systems[member].name.as_str())
+                        .collect::<Vec<_>>();
+
+                    add(builder, &entry.name, &deps);
+                    members.push(index);
+                }
+                SystemKind::ThreadLocal(system) => {
+                    if let Some((builder, _)) = batch.take() {
+                        steps.push(ScheduleStep::Parallel(builder.build()));
+                    }
+
+                    steps.push(ScheduleStep::ThreadLocal(system.clone()));
+                }
+            }
+        }
+
+        if let Some((builder, _)) = batch {
+            steps.push(ScheduleStep::Parallel(builder.build()));
+        }
+
+        drop(systems);
+
+        for step in &mut steps {
+            match step {
+                ScheduleStep::Parallel(dispatcher) => dispatcher.setup(world),
+                ScheduleStep::ThreadLocal(system) => system.borrow_mut().setup(world),
+            }
+        }
+
+        steps
+    }
+
+    fn pool(&self) -> Arc<ThreadPool> {
+        self.pool
+            .borrow_mut()
+            .get_or_insert_with(|| {
+                Arc::new(
+                    ThreadPoolBuilder::new()
+                        .thread_name(|index| format!("mk-system-{}", index))
+                        .build()
+                        .expect("failed to create the thread pool for systems"),
+                )
+            })
+            .clone()
+    }
+}
+
+impl Default for SystemManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Default)]
+struct StageSchedule {
+    steps: RefCell<Option<Vec<ScheduleStep>>>,
+    is_dirty: Cell<bool>,
+}
+
+enum ScheduleStep {
+    Parallel(Dispatcher<'static, 'static>),
+    ThreadLocal(Rc<RefCell<dyn ThreadLocalSystem>>),
+}
+
+struct SystemEntry {
+    name: String,
+    stage: SystemStage,
+    options: SystemOptions,
+    kind: SystemKind,
+}
+
+type AddToDispatcher = Box<dyn Fn(&mut DispatcherBuilder<'static, 'static>, &str, &[&str])>;
+
+enum SystemKind {
+    Parallel(AddToDispatcher),
+    ThreadLocal(Rc<RefCell<dyn ThreadLocalSystem>>),
+}
+
+trait ThreadLocalSystem {
+    fn setup(&mut self, world: &mut World);
+    fn run(&mut self, context: &EngineContext);
+}
+
+/// Sets up the system only the first time, as the steps holding it are rebuilt whenever its stage changes.
+struct SetUpOnce<S> {
+    system: S,
+    is_set_up: bool,
+}
+
+impl<S> SetUpOnce<S>
+where
+    S: for<'a> System<'a>,
+{
+    fn new(system: S) -> Self {
+        Self {
+            system,
+            is_set_up: false,
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        if !replace(&mut self.is_set_up, true) {
+            self.system.setup(world);
+        }
+    }
+}
+
+struct SpecsSystem<S>(SetUpOnce<S>);
+
+impl<S> ThreadLocalSystem for SpecsSystem<S>
+where
+    S: for<'a> System<'a>,
+{
+    fn setup(&mut self, world: &mut World) {
+        self.0.setup(world);
+    }
+
+    fn run(&mut self, context: &EngineContext) {
+        self.0.system.run_now(&context.world());
+    }
+}
+
+struct FnSystem<F>(F);
+
+impl<F> ThreadLocalSystem for FnSystem<F>
+where
+    F: FnMut(&EngineContext),
+{
+    fn setup(&mut self, _world: &mut World) {}
+
+    fn run(&mut self, context: &EngineContext) {
+        (self.0)(context);
+    }
+}
+
+struct SharedSystem<S>(Arc<Mutex<SetUpOnce<S>>>);
+
+impl<'a, S> System<'a> for SharedSystem<S>
+where
+    S: for<'b> System<'b>,
+{
+    type SystemData = <S as System<'a>>::SystemData;
+
+    fn run(&mut self, data: Self::SystemData) {
+        self.0.lock().unwrap().system.run(data);
+    }
+
+    fn running_time(&self) -> RunningTime {
+        self.0.lock().unwrap().system.running_time()
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        self.0.lock().unwrap().setup(world);
+    }
+}
+
+fn precedes(a: &SystemEntry, b: &SystemEntry) -> bool {
+    a.options.before.contains(&b.name) || b.options.after.contains(&a.name)
+}
+
+/// Orders the systems of the stage by their constraints. Otherwise, systems keep the order they were added, with
+/// the systems that must run before one placed right ahead of it. Returns `None` if the constraints form a cycle.
+fn sort_stage(systems: &[SystemEntry], stage: SystemStage) -> Option<Vec<usize>> {
+    let members = systems
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.stage == stage)
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let mut states = vec![VisitState::Unvisited; systems.len()];
+    let mut order = Vec::with_capacity(members.len());
+
+    for &index in &members {
+        visit(systems, &members, index, &mut states, &mut order)?;
+    }
+
+    Some(order)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VisitState {
+    Unvisited,
+    Visiting,
+    Visited,
+}
+
+fn visit(
+    systems: &[SystemEntry],
+    members: &[usize],
+    index: usize,
+    states: &mut [VisitState],
+    order: &mut Vec<usize>,
+) -> Option<()> {
+    match states[index] {
+        VisitState::Visited => return Some(()),
+        VisitState::Visiting => return None,
+        VisitState::Unvisited => {}
+    }
+
+    states[index] = VisitState::Visiting;
+
+    for &other in members {
+        if other != index && precedes(&systems[other], &systems[index]) {
+            visit(systems, members, other, states, order)?;
+        }
+    }
+
+    states[index] = VisitState::Visited;
+    order.push(index);
+    Some(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn add(system_mgr: &SystemManager, name: &str, options: SystemOptions) {
+        system_mgr
+            .add_thread_local_fn(SystemStage::Update, name, |_| {}, options)
+            .unwrap();
+    }
+
+    #[test]
+    fn systems_follow_constraints_then_registration_order() {
+        let system_mgr = SystemManager::new();
+
+        add(&system_mgr, "a", SystemOptions::new());
+        add(&system_mgr, "b", SystemOptions::new().with_before("a"));
+        add(&system_mgr, "c", SystemOptions::new());
+        add(&system_mgr, "d", SystemOptions::new().with_after("missing"));
+        add(&system_mgr, "e", SystemOptions::new().with_before("b"));
+
+        assert_eq!(
+            system_mgr.system_names(SystemStage::Update),
+            ["e", "b", "a", "c", "d"]
+        );
+        assert!(system_mgr.system_names(SystemStage::PreUpdate).is_empty());
+    }
+
+    #[derive(Default)]
+    struct SetUps(Vec<&'static str>);
+
+    struct SetUpSystem(&'static str);
+
+    impl<'a> System<'a> for SetUpSystem {
+        type SystemData = ();
+
+        fn run(&mut self, _data: Self::SystemData) {}
+
+        fn setup(&mut self, world: &mut World) {
+            world
+                .entry::<SetUps>()
+                .or_insert_with(Default::default)
+                .0
+                .push(self.0);
+        }
+    }
+
+    #[test]
+    fn systems_are_set_up_once() {
+        let system_mgr = SystemManager::new();
+        let mut world = World::new();
+
+        system_mgr
+            .add_system(
+                SystemStage::Update,
+                "parallel",
+                SetUpSystem("parallel"),
+                SystemOptions::new(),
+            )
+            .unwrap();
+        system_mgr
+            .add_thread_local_system(
+                SystemStage::Update,
+                "thread-local",
+                SetUpSystem("thread-local"),
+                SystemOptions::new(),
+            )
+            .unwrap();
+        system_mgr.build_steps(&mut world, SystemStage::Update);
+
+        // The stage is rebuilt with the new system, reusing the others.
+        add(&system_mgr, "fn", SystemOptions::new());
+        let steps = system_mgr.build_steps(&mut world, SystemStage::Update);
+
+        assert_eq!(steps.len(), 3);
+        assert_eq!(world.fetch::<SetUps>().0, ["parallel", "thread-local"]);
+    }
+
+    #[test]
+    fn duplicated_names_and_cycles_are_rejected() {
+        let system_mgr = SystemManager::new();
+
+        add(&system_mgr, "a", SystemOptions::new().with_after("b"));
+
+        assert_eq!(
+            system_mgr.add_thread_local_fn(
+                SystemStage::PreRender,
+                "a",
+                |_| {},
+                SystemOptions::new()
+            ),
+            Err(SystemError::DuplicatedName("a".to_owned()))
+        );
+        assert_eq!(
+            system_mgr.add_thread_local_fn(
+                SystemStage::Update,
+                "b",
+                |_| {},
+                SystemOptions::new().with_after("a")
+            ),
+            Err(SystemError::CyclicOrder("b".to_owned()))
+        );
+        assert!(!system_mgr.has_system("b"));
+        assert!(system_mgr.remove_system("a"));
+        assert!(!system_mgr.remove_system("a"));
+    }
+}
@@ -0,0 +1,26 @@
+/// The points of a frame at which registered systems run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SystemStage {
+    /// Runs after input handling and UI layout, before the `PreUpdate` event.
+    PreUpdate,
+    /// Runs after the `Update` event, before tasks and turns.
+    Update,
+    /// Runs after the `PostUpdate` event, before destroyed entities are removed.
+    PostUpdate,
+    /// Runs after the `PreRender` event, before rendering.
+    PreRender,
+}
+
+impl SystemStage {
+    /// Every stage, in the order they run in a frame.
+    pub const ALL: [SystemStage; 4] = [
+        SystemStage::PreUpdate,
+        SystemStage::Update,
+        SystemStage::PostUpdate,
+        SystemStage::PreRender,
+    ];
+
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{component::*, engine::use_context, structure::Vec2};
+use specs::prelude::*;
+
+/// The name the text field system is registered under, in the pre-update stage.
+pub const TEXT_FIELD_SYSTEM: &str = "text_field";
+
+/// Refreshes the text of edited fields and places their caret and selection.
+pub struct TextFieldSystem;
+
+impl<'a> System<'a> for TextFieldSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, TextField>,
+        WriteStorage<'a, GlyphRenderer>,
+        WriteStorage<'a, Size>,
+        ReadStorage<'a, Transform>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, mut text_field, mut glyph_renderer, mut size, transform): Self::SystemData,
+    ) {
+        let context = use_context();
+        let focus = context.ui_event_mgr().focus;
+        let dt = context.time_mgr().dt();
+        let mut glyph_mgr = context.glyph_mgr_mut();
+        let mut render_mgr = context.render_mgr_mut();
+        let mut transform_mgr = context.transform_mgr_mut();
+
+        // Carets and selections are placed once the fields are no longer borrowed.
+        let mut placements = Vec::new();
+
+        for (entity, text_field, renderer) in
+            (&entities, &mut text_field, &mut glyph_renderer).join()
+        {
+            if text_field.is_dirty() {
+                let text = text_field.editor().display_text();
+                renderer.set_text(&mut glyph_mgr, &mut render_mgr, text);
+                text_field.reset_dirty();
+            }
+
+            let is_focused = focus == Some(entity);
+            let is_caret_visible = is_focused && text_field.update_blink(dt);
+            let editor = text_field.editor();
+            let field_size = size
+                .get(entity)
+                .map(|size| size.size)
+                .unwrap_or_else(crate::structure::Size::zero);
+            let offset = renderer.layout_offset(field_size);
+            let line_height = renderer.line_height();
+            // The layout grows downwards from its top; see `GlyphRenderer::layout_offset`.
+            let bottom = -offset.y - line_height;
+
+            if let Some(caret) = text_field.caret {
+                let x = offset.x + renderer.caret_x(editor.display_caret());
+                let width = if is_caret_visible {
+                    text_field.caret_width
+                } else {
+                    0f32
+                };
+                placements.push((
+                    caret,
+                    Vec2::new(x - width * 0.5f32, bottom),
+                    crate::structure::Size::new(width, line_height),
+                ));
+            }
+
+            if let Some(selection) = text_field.selection {
+                // The selection is hidden while composing, as the composition is shown instead.
+                match editor
+                    .selection()
+                    .filter(|_| is_focused && editor.composition().is_none())
+                {
+                    Some(range) => {
+                        let start = renderer.caret_x(range.start);
+                        let end = renderer.caret_x(range.end);
+                        placements.push((
+                            selection,
+                            Vec2::new(offset.x + start, bottom),
+                            crate::structure::Size::new(end - start, line_height),
+                        ));
+                    }
+                    None => placements.push((
+                        selection,
+                        Vec2::new(offset.x, bottom),
+                        crate::structure::Size::zero(),
+                    )),
+                }
+            }
+        }
+
+        for (entity, position, new_size) in placements {
+            if let Some(transform) = transform.get(entity) {
+                transform_mgr
+                    .allocator_mut()
+                    .transform_mut(transform.index())
+                    .position = position;
+                transform_mgr.hierarchy_mut().set_dirty(transform.index());
+            }
+
+            if let Some(size) = size.get_mut(entity) {
+                size.size = new_size;
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
 mod time_manager;
+mod timer_manager;
 
 pub use time_manager::*;
+pub use timer_manager::*;
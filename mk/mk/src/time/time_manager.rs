@@ -1,28 +1,51 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Fixed steps owed beyond this are dropped, so that a long stall does not freeze the game.
+pub const DEFAULT_MAX_FIXED_STEPS: u32 = 5;
+
+/// Lower frame rate caps are raised to this, so that the frame interval stays representable.
+pub const MIN_FRAME_RATE_CAP: f64 = 0.001;
 
 #[derive(Debug)]
 pub struct TimeManager {
-    begin: Instant,
-    time: Instant,
+    last: Instant,
+    time: f64,
     dt: f64,
+    unscaled_time: f64,
+    unscaled_dt: f64,
+    scale: f64,
+    is_paused: bool,
+    fixed_dt: Option<f64>,
+    max_fixed_steps: u32,
+    fixed_accumulator: f64,
+    fixed_steps: u32,
+    frame_interval: Option<Duration>,
 }
 
 impl TimeManager {
     pub fn new() -> TimeManager {
-        let now = Instant::now();
         TimeManager {
-            begin: now,
-            time: now,
+            last: Instant::now(),
+            time: 0f64,
             dt: 0f64,
+            unscaled_time: 0f64,
+            unscaled_dt: 0f64,
+            scale: 1f64,
+            is_paused: false,
+            fixed_dt: None,
+            max_fixed_steps: DEFAULT_MAX_FIXED_STEPS,
+            fixed_accumulator: 0f64,
+            fixed_steps: 0,
+            frame_interval: None,
         }
     }
 
     pub fn time(&self) -> f32 {
-        (self.time - self.begin).as_secs_f32()
+        self.time as f32
     }
 
     pub fn time_f64(&self) -> f64 {
-        (self.time - self.begin).as_secs_f64()
+        self.time
     }
 
     pub fn dt(&self) -> f32 {
@@ -33,9 +56,227 @@ impl TimeManager {
         self.dt
     }
 
+    /// Returns the real time elapsed, unaffected by the time scale and pausing.
+    pub fn unscaled_time(&self) -> f32 {
+        self.unscaled_time as f32
+    }
+
+    pub fn unscaled_time_f64(&self) -> f64 {
+        self.unscaled_time
+    }
+
+    /// Returns the real duration of the last frame, unaffected by the time scale and pausing.
+    pub fn unscaled_dt(&self) -> f32 {
+        self.unscaled_dt as f32
+    }
+
+    pub fn unscaled_dt_f64(&self) -> f64 {
+        self.unscaled_dt
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Sets how fast the scaled clock runs relative to the real time, e.g. `0.5` for slow motion.
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    /// Stops the scaled clock. Fixed steps are not run while paused.
+    pub fn set_paused(&mut self, is_paused: bool) {
+        self.is_paused = is_paused;
+    }
+
+    /// Returns the delta of a fixed step, or `None` if fixed steps are disabled.
+    pub fn fixed_dt(&self) -> Option<f64> {
+        self.fixed_dt
+    }
+
+    /// Enables fixed steps at the given rate in Hz, or disables them with `None`.
+    pub fn set_fixed_rate(&mut self, rate: Option<f64>) {
+        self.fixed_dt = rate.map(|rate| 1f64 / rate);
+        self.fixed_accumulator = 0f64;
+        self.fixed_steps = 0;
+    }
+
+    pub fn max_fixed_steps(&self) -> u32 {
+        self.max_fixed_steps
+    }
+
+    pub fn set_max_fixed_steps(&mut self, max_fixed_steps: u32) {
+        self.max_fixed_steps = max_fixed_steps;
+    }
+
+    /// Returns how many fixed steps are due in the current frame.
+    pub fn fixed_steps(&self) -> u32 {
+        self.fixed_steps
+    }
+
+    /// Returns how far the current frame is between the last fixed step and the next one,
+    /// in range `[0, 1)`. Rendering can interpolate fixed-step state with it.
+    /// Always `1` if fixed steps are disabled.
+    pub fn alpha(&self) -> f64 {
+        match self.fixed_dt {
+            Some(fixed_dt) => self.fixed_accumulator / fixed_dt,
+            None => 1f64,
+        }
+    }
+
+    /// Returns the minimum time between two frames, or `None` if the frame rate is not capped.
+    pub fn frame_interval(&self) -> Option<Duration> {
+        self.frame_interval
+    }
+
+    /// Caps the frame rate to the given frames per second, or uncaps it with `None`.
+    /// Caps below `MIN_FRAME_RATE_CAP` are raised to it.
+    pub fn set_frame_rate_cap(&mut self, fps: Option<f64>) {
+        self.frame_interval =
+            fps.map(|fps| Duration::from_secs_f64(1f64 / fps.max(MIN_FRAME_RATE_CAP)));
+    }
+
     pub fn update(&mut self) {
         let now = Instant::now();
-        self.dt = (now - self.time).as_secs_f64();
-        self.time = now;
+        self.tick((now - self.last).as_secs_f64());
+        self.last = now;
+    }
+
+    /// Advances the clocks by the given real delta instead of measuring it,
+    /// e.g. to replay a recording.
+    pub fn advance(&mut self, unscaled_dt: f64) {
+        self.tick(unscaled_dt);
+        self.last = Instant::now();
+    }
+
+    fn tick(&mut self, unscaled_dt: f64) {
+        self.unscaled_dt = unscaled_dt;
+        self.unscaled_time += unscaled_dt;
+
+        let dt = if self.is_paused {
+            0f64
+        } else {
+            unscaled_dt * self.scale
+        };
+        self.dt = dt;
+        self.time += dt;
+
+        if let Some(fixed_dt) = self.fixed_dt {
+            self.fixed_accumulator += dt;
+
+            let steps = (self.fixed_accumulator / fixed_dt).floor();
+            self.fixed_accumulator -= steps * fixed_dt;
+            self.fixed_steps = (steps as u32).min(self.max_fixed_steps);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tiny_frame_rate_caps_are_raised() {
+        let mut time_mgr = TimeManager::new();
+
+        time_mgr.set_frame_rate_cap(Some(1e-300));
+        assert_eq!(
+            time_mgr.frame_interval(),
+            Some(Duration::from_secs_f64(1f64 / MIN_FRAME_RATE_CAP))
+        );
+
+        time_mgr.set_frame_rate_cap(Some(60f64));
+        assert_eq!(
+            time_mgr.frame_interval(),
+            Some(Duration::from_secs_f64(1f64 / 60f64))
+        );
+
+        time_mgr.set_frame_rate_cap(None);
+        assert_eq!(time_mgr.frame_interval(), None);
+    }
+
+    #[test]
+    fn accumulated_time_runs_whole_fixed_steps() {
+        let mut time_mgr = TimeManager::new();
+        time_mgr.set_fixed_rate(Some(4f64));
+
+        time_mgr.advance(0.125f64);
+        assert_eq!(time_mgr.fixed_steps(), 0);
+
+        // Carries the 0.125s left from the last frame.
+        time_mgr.advance(0.5f64);
+        assert_eq!(time_mgr.fixed_steps(), 2);
+        assert_eq!(time_mgr.alpha(), 0.5f64);
+
+        time_mgr.advance(0.625f64);
+        assert_eq!(time_mgr.fixed_steps(), 3);
+        assert_eq!(time_mgr.alpha(), 0f64);
+    }
+
+    #[test]
+    fn steps_beyond_the_max_catch_up_are_dropped() {
+        let mut time_mgr = TimeManager::new();
+        time_mgr.set_fixed_rate(Some(4f64));
+        time_mgr.set_max_fixed_steps(3);
+
+        time_mgr.advance(2.125f64);
+        assert_eq!(time_mgr.fixed_steps(), 3);
+        assert_eq!(time_mgr.alpha(), 0.5f64);
+
+        // The dropped steps are not run later.
+        time_mgr.advance(0.125f64);
+        assert_eq!(time_mgr.fixed_steps(), 1);
+        assert_eq!(time_mgr.alpha(), 0f64);
+    }
+
+    #[test]
+    fn alpha_is_the_remainder_over_the_fixed_step() {
+        let mut time_mgr = TimeManager::new();
+        assert_eq!(time_mgr.alpha(), 1f64);
+
+        time_mgr.set_fixed_rate(Some(60f64));
+        let fixed_dt = time_mgr.fixed_dt().unwrap();
+        let mut elapsed = 0f64;
+        let mut steps = 0u32;
+
+        for frame in 0..1000 {
+            let dt = 0.001f64 * (frame % 37) as f64;
+            time_mgr.advance(dt);
+            elapsed += dt;
+            steps += time_mgr.fixed_steps();
+
+            let alpha = time_mgr.alpha();
+            assert!(
+                (0f64..1f64).contains(&alpha),
+                "alpha {} out of range",
+                alpha
+            );
+            assert!((elapsed - steps as f64 * fixed_dt - alpha * fixed_dt).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn no_fixed_steps_run_while_paused_or_stopped() {
+        let mut time_mgr = TimeManager::new();
+        time_mgr.set_fixed_rate(Some(4f64));
+        time_mgr.advance(0.125f64);
+
+        time_mgr.set_paused(true);
+        time_mgr.advance(1f64);
+        assert_eq!(time_mgr.fixed_steps(), 0);
+        assert_eq!(time_mgr.alpha(), 0.5f64);
+
+        time_mgr.set_paused(false);
+        time_mgr.set_scale(0f64);
+        time_mgr.advance(1f64);
+        assert_eq!(time_mgr.fixed_steps(), 0);
+        assert_eq!(time_mgr.alpha(), 0.5f64);
+
+        time_mgr.set_scale(1f64);
+        time_mgr.advance(0.125f64);
+        assert_eq!(time_mgr.fixed_steps(), 1);
     }
 }
@@ -0,0 +1,250 @@
+use crate::{
+    engine::use_context,
+    script::{FFIFunction, LuaClassMeta, LuaFieldMeta, LuaMethodMeta, LuaUserDataMetaProvider},
+};
+use mlua::prelude::*;
+
+/// Refers to a timer scheduled with `TimerManager`. Stays valid after the timer has ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle(u64);
+
+impl LuaUserData for TimerHandle {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("is_active", |_lua, this| {
+            Ok(use_context().timer_mgr().is_active(*this))
+        });
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("cancel", |_lua, this, ()| {
+            Ok(use_context().timer_mgr_mut().cancel(*this))
+        });
+
+        methods.add_meta_method(LuaMetaMethod::ToString, |_lua, this, ()| {
+            Ok(format!("TimerHandle({})", this.0))
+        });
+    }
+}
+
+impl LuaUserDataMetaProvider for TimerHandle {
+    fn lua_class_meta() -> LuaClassMeta {
+        LuaClassMeta {
+            name: "TimerHandle",
+            doc: "Refers to a timer scheduled with `mk.time.after` or `mk.time.every`. Stays valid after the timer has ended.",
+            fields: &[LuaFieldMeta {
+                name: "is_active",
+                ty: "boolean",
+                doc: "Whether the timer will still fire.",
+                is_readonly: true,
+            }],
+            methods: &[LuaMethodMeta {
+                name: "cancel",
+                doc: "Stops the timer. Returns whether it was still active.",
+                is_static: false,
+                params: &[],
+                returns: Some("boolean"),
+            }],
+        }
+    }
+}
+
+struct Timer {
+    handle: TimerHandle,
+    remaining: f64,
+    interval: Option<f64>,
+    is_unscaled: bool,
+    function: FFIFunction,
+    is_fired: bool,
+}
+
+/// Calls Lua functions once after a delay, or repeatedly at an interval.
+/// Timers follow the scaled clock unless created as unscaled.
+#[derive(Default)]
+pub struct TimerManager {
+    next_handle: u64,
+    timers: Vec<Timer>,
+}
+
+impl TimerManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedules the function to be called once after `delay` seconds.
+    pub fn after(&mut self, delay: f64, is_unscaled: bool, function: FFIFunction) -> TimerHandle {
+        self.schedule(delay, None, is_unscaled, function)
+    }
+
+    /// Schedules the function to be called every `interval` seconds until cancelled.
+    /// At most one call is made per frame; missed calls are dropped.
+    pub fn every(
+        &mut self,
+        interval: f64,
+        is_unscaled: bool,
+        function: FFIFunction,
+    ) -> TimerHandle {
+        self.schedule(interval, Some(interval), is_unscaled, function)
+    }
+
+    /// Returns `true` if the timer is still scheduled.
+    pub fn is_active(&self, handle: TimerHandle) -> bool {
+        self.timers.iter().any(|timer| timer.handle == handle)
+    }
+
+    /// Cancels the timer. Returns `false` if it has already ended.
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        let len = self.timers.len();
+        self.timers.retain(|timer| timer.handle != handle);
+        self.timers.len() != len
+    }
+
+    /// Advances every timer and returns the ones due in this frame.
+    /// The caller should call `finish_update` once every returned function has been called.
+    pub fn update(&mut self, dt: f64, unscaled_dt: f64) -> Vec<(TimerHandle, FFIFunction)> {
+        let mut due = Vec::new();
+
+        for timer in &mut self.timers {
+            timer.remaining -= if timer.is_unscaled { unscaled_dt } else { dt };
+
+            if 0f64 < timer.remaining {
+                continue;
+            }
+
+            match timer.interval {
+                Some(interval) => {
+                    timer.remaining += interval;
+
+                    if timer.remaining <= 0f64 {
+                        timer.remaining = interval;
+                    }
+                }
+                None => timer.is_fired = true,
+            }
+
+            due.push((timer.handle, timer.function.clone()));
+        }
+
+        due
+    }
+
+    /// Removes the one-shot timers fired by the last `update`.
+    pub fn finish_update(&mut self) {
+        self.timers.retain(|timer| !timer.is_fired);
+    }
+
+    fn schedule(
+        &mut self,
+        delay: f64,
+        interval: Option<f64>,
+        is_unscaled: bool,
+        function: FFIFunction,
+    ) -> TimerHandle {
+        let handle = TimerHandle(self.next_handle);
+        self.next_handle += 1;
+        self.timers.push(Timer {
+            handle,
+            remaining: delay,
+            interval,
+            is_unscaled,
+            function,
+            is_fired: false,
+        });
+        handle
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::time::TimeManager;
+
+    fn function(lua: &Lua) -> FFIFunction {
+        FFIFunction::new(lua, lua.create_function(|_lua, ()| Ok(())).unwrap()).unwrap()
+    }
+
+    fn due(timer_mgr: &mut TimerManager, time_mgr: &mut TimeManager, dt: f64) -> Vec<TimerHandle> {
+        time_mgr.advance(dt);
+
+        let due = timer_mgr
+            .update(time_mgr.dt_f64(), time_mgr.unscaled_dt_f64())
+            .into_iter()
+            .map(|(handle, _)| handle)
+            .collect();
+        timer_mgr.finish_update();
+        due
+    }
+
+    #[test]
+    fn after_follows_the_time_scale() {
+        let lua = Lua::new();
+        let mut time_mgr = TimeManager::new();
+        let mut timer_mgr = TimerManager::new();
+        let scaled = timer_mgr.after(1f64, false, function(&lua));
+        let unscaled = timer_mgr.after(1f64, true, function(&lua));
+
+        time_mgr.set_scale(0.5f64);
+        assert_eq!(due(&mut timer_mgr, &mut time_mgr, 1f64), vec![unscaled]);
+        assert!(timer_mgr.is_active(scaled));
+        assert!(!timer_mgr.is_active(unscaled));
+
+        assert_eq!(due(&mut timer_mgr, &mut time_mgr, 1f64), vec![scaled]);
+        assert!(!timer_mgr.is_active(scaled));
+    }
+
+    #[test]
+    fn every_stops_while_paused_unless_unscaled() {
+        let lua = Lua::new();
+        let mut time_mgr = TimeManager::new();
+        let mut timer_mgr = TimerManager::new();
+        let scaled = timer_mgr.every(0.5f64, false, function(&lua));
+        let unscaled = timer_mgr.every(0.5f64, true, function(&lua));
+
+        time_mgr.set_paused(true);
+        assert_eq!(due(&mut timer_mgr, &mut time_mgr, 0.5f64), vec![unscaled]);
+        assert_eq!(due(&mut timer_mgr, &mut time_mgr, 0.5f64), vec![unscaled]);
+
+        time_mgr.set_paused(false);
+        assert_eq!(
+            due(&mut timer_mgr, &mut time_mgr, 0.5f64),
+            vec![scaled, unscaled]
+        );
+    }
+
+    #[test]
+    fn every_drops_missed_calls() {
+        let lua = Lua::new();
+        let mut time_mgr = TimeManager::new();
+        let mut timer_mgr = TimerManager::new();
+        let timer = timer_mgr.every(0.25f64, false, function(&lua));
+
+        // A long frame fires the timer once, and the next call is a full interval away.
+        assert_eq!(due(&mut timer_mgr, &mut time_mgr, 1f64), vec![timer]);
+        assert!(due(&mut timer_mgr, &mut time_mgr, 0.125f64).is_empty());
+        assert_eq!(due(&mut timer_mgr, &mut time_mgr, 0.125f64), vec![timer]);
+
+        // A frame that is not long enough to miss a call keeps the remainder.
+        assert_eq!(due(&mut timer_mgr, &mut time_mgr, 0.375f64), vec![timer]);
+        assert_eq!(due(&mut timer_mgr, &mut time_mgr, 0.125f64), vec![timer]);
+    }
+
+    #[test]
+    fn cancel_during_update() {
+        let lua = Lua::new();
+        let mut timer_mgr = TimerManager::new();
+        let first = timer_mgr.after(0f64, false, function(&lua));
+        let second = timer_mgr.every(0.5f64, false, function(&lua));
+
+        let due = timer_mgr.update(1f64, 1f64);
+        assert_eq!(due.len(), 2);
+
+        // As if the first timer cancelled the second before its call.
+        assert!(timer_mgr.cancel(second));
+        assert!(!timer_mgr.is_active(second));
+        assert!(!timer_mgr.cancel(second));
+
+        timer_mgr.finish_update();
+        assert!(!timer_mgr.is_active(first));
+        assert!(!timer_mgr.cancel(first));
+        assert!(timer_mgr.update(1f64, 1f64).is_empty());
+    }
+}
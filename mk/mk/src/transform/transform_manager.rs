@@ -58,10 +58,10 @@ impl TransformManager {
         self.allocator.alloc_entity(transform, entity);
     }
 
+    /// Deallocates the transform and all of its children.
     pub fn dealloc(&mut self, transform: u32) {
-        self.allocator.dealloc(transform);
-
         for &transform in self.hierarchy.transform_and_children(transform) {
+            self.allocator.dealloc(transform);
             self.name_manager.remove_from_name_map(transform);
         }
 
@@ -140,3 +140,43 @@ impl Default for TransformManager {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dealloc_reuses_subtree() {
+        let mut transform_mgr = TransformManager::new();
+
+        let root = transform_mgr.alloc();
+        let parent = transform_mgr.alloc();
+        let child = transform_mgr.alloc();
+        transform_mgr.hierarchy_mut().set_parent(parent, Some(root));
+        transform_mgr
+            .hierarchy_mut()
+            .set_parent(child, Some(parent));
+        transform_mgr
+            .name_manager_mut()
+            .set_name(child, Some("child".into()));
+
+        transform_mgr.dealloc(parent);
+
+        assert_eq!(transform_mgr.hierarchy().ordered_transforms(), &[root]);
+        assert_eq!(transform_mgr.hierarchy().children(root), &[] as &[u32]);
+        assert!(transform_mgr
+            .name_manager()
+            .transforms_by_name("child")
+            .is_empty());
+
+        let mut reused = vec![transform_mgr.alloc(), transform_mgr.alloc()];
+        reused.sort_unstable();
+        assert_eq!(reused, vec![parent, child]);
+        assert_eq!(transform_mgr.alloc(), 3);
+
+        for &transform in &reused {
+            assert_eq!(transform_mgr.name_manager().name(transform), None);
+            assert_eq!(transform_mgr.hierarchy().parent(transform), None);
+        }
+    }
+}
@@ -0,0 +1,10 @@
+mod turn_scheduler;
+
+pub use turn_scheduler::*;
+
+/// Turns begun beyond this in a single frame wait for the next frame,
+/// so that actors ending their turns immediately cannot freeze the game.
+pub const MAX_TURNS_PER_FRAME: usize = 256;
+
+/// Schedules the turns of entities.
+pub type TurnManager = TurnScheduler<specs::Entity>;
@@ -0,0 +1,341 @@
+/// Energy an actor needs to take a turn. Also the cost of a regular action.
+pub const TURN_ENERGY_THRESHOLD: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Actor<A> {
+    id: A,
+    speed: u32,
+    /// Goes negative when an action costs more than the actor had.
+    energy: i64,
+    /// Registration order, used to break ties.
+    order: u64,
+}
+
+/// A turn that has been ended, waiting to be reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndedTurn<A> {
+    pub actor: A,
+    pub turn: u64,
+    pub cost: u32,
+}
+
+/// Energy-based turn scheduler.
+///
+/// Every actor gains its speed in energy per tick. Once an actor has at least
+/// `TURN_ENERGY_THRESHOLD`, it takes a turn, which lasts until `end_turn` is called
+/// with the energy spent by its action. Faster actors therefore act more often,
+/// and cheaper actions let an actor act again sooner.
+///
+/// When several actors are ready, the one with the most energy goes first;
+/// ties go to the actor registered first.
+#[derive(Debug, Clone)]
+pub struct TurnScheduler<A> {
+    actors: Vec<Actor<A>>,
+    current: Option<A>,
+    turn: u64,
+    next_order: u64,
+    is_paused: bool,
+    ended: Vec<EndedTurn<A>>,
+}
+
+impl<A> Default for TurnScheduler<A> {
+    fn default() -> Self {
+        Self {
+            actors: Vec::new(),
+            current: None,
+            turn: 0,
+            next_order: 0,
+            is_paused: false,
+            ended: Vec::new(),
+        }
+    }
+}
+
+impl<A> TurnScheduler<A>
+where
+    A: Copy + Eq,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, actor: A) -> bool {
+        self.actor(actor).is_some()
+    }
+
+    /// Registers the actor with no energy. Returns `false` if it is already registered.
+    pub fn add_actor(&mut self, actor: A, speed: u32) -> bool {
+        if self.contains(actor) {
+            return false;
+        }
+
+        self.actors.push(Actor {
+            id: actor,
+            speed,
+            energy: 0,
+            order: self.next_order,
+        });
+        self.next_order += 1;
+        true
+    }
+
+    /// Unregisters the actor. If it is taking its turn, the turn ends without being reported.
+    pub fn remove_actor(&mut self, actor: A) -> bool {
+        let len = self.actors.len();
+        self.actors.retain(|a| a.id != actor);
+
+        if self.current == Some(actor) {
+            self.current = None;
+        }
+
+        self.actors.len() != len
+    }
+
+    pub fn speed(&self, actor: A) -> Option<u32> {
+        self.actor(actor).map(|actor| actor.speed)
+    }
+
+    pub fn set_speed(&mut self, actor: A, speed: u32) -> bool {
+        match self.actor_mut(actor) {
+            Some(actor) => {
+                actor.speed = speed;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn energy(&self, actor: A) -> Option<i64> {
+        self.actor(actor).map(|actor| actor.energy)
+    }
+
+    /// Returns the actor taking its turn, if any.
+    pub fn current(&self) -> Option<A> {
+        self.current
+    }
+
+    /// Returns the number of turns begun so far.
+    pub fn turn(&self) -> u64 {
+        self.turn
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    /// While paused, no new turn begins. The current turn can still be ended.
+    pub fn set_paused(&mut self, is_paused: bool) {
+        self.is_paused = is_paused;
+    }
+
+    /// Begins the turn of the next ready actor, advancing time until one is ready.
+    /// Returns `None` if a turn is in progress, the scheduler is paused,
+    /// or no actor can ever become ready.
+    pub fn begin_turn(&mut self) -> Option<A> {
+        if self.current.is_some() || self.is_paused {
+            return None;
+        }
+
+        if !self.actors.iter().any(is_ready) {
+            let ticks = self
+                .actors
+                .iter()
+                .filter(|actor| actor.speed != 0)
+                .map(|actor| {
+                    let missing = i64::from(TURN_ENERGY_THRESHOLD) - actor.energy;
+                    (missing + i64::from(actor.speed) - 1) / i64::from(actor.speed)
+                })
+                .min()?;
+
+            for actor in &mut self.actors {
+                actor.energy += ticks * i64::from(actor.speed);
+            }
+        }
+
+        let actor = self
+            .actors
+            .iter()
+            .filter(|actor| is_ready(actor))
+            .max_by(|a, b| a.energy.cmp(&b.energy).then(b.order.cmp(&a.order)))?
+            .id;
+
+        self.turn += 1;
+        self.current = Some(actor);
+        Some(actor)
+    }
+
+    /// Ends the current turn, spending `cost` energy. Returns the actor whose turn has ended.
+    pub fn end_turn(&mut self, cost: u32) -> Option<A> {
+        let actor = self.current.take()?;
+
+        if let Some(a) = self.actor_mut(actor) {
+            a.energy -= i64::from(cost);
+        }
+
+        self.ended.push(EndedTurn {
+            actor,
+            turn: self.turn,
+            cost,
+        });
+        Some(actor)
+    }
+
+    /// Takes the turns ended since the last call.
+    pub fn take_ended_turns(&mut self) -> Vec<EndedTurn<A>> {
+        std::mem::take(&mut self.ended)
+    }
+
+    fn actor(&self, actor: A) -> Option<&Actor<A>> {
+        self.actors.iter().find(|a| a.id == actor)
+    }
+
+    fn actor_mut(&mut self, actor: A) -> Option<&mut Actor<A>> {
+        self.actors.iter_mut().find(|a| a.id == actor)
+    }
+}
+
+fn is_ready<A>(actor: &Actor<A>) -> bool {
+    i64::from(TURN_ENERGY_THRESHOLD) <= actor.energy
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Runs `count` turns, ending each one with a regular action.
+    fn run_turns(scheduler: &mut TurnScheduler<u32>, count: usize) -> Vec<u32> {
+        (0..count)
+            .map(|_| {
+                let actor = scheduler.begin_turn().unwrap();
+                scheduler.end_turn(TURN_ENERGY_THRESHOLD);
+                actor
+            })
+            .collect()
+    }
+
+    #[test]
+    fn equal_speeds_take_turns_in_registration_order() {
+        let mut scheduler = TurnScheduler::new();
+        scheduler.add_actor(1, 10);
+        scheduler.add_actor(2, 10);
+        scheduler.add_actor(3, 10);
+
+        assert_eq!(run_turns(&mut scheduler, 6), vec![1, 2, 3, 1, 2, 3]);
+        assert_eq!(scheduler.turn(), 6);
+    }
+
+    #[test]
+    fn faster_actors_act_more_often() {
+        let mut scheduler = TurnScheduler::new();
+        scheduler.add_actor(1, 10);
+        scheduler.add_actor(2, 20);
+
+        let turns = run_turns(&mut scheduler, 9);
+        assert_eq!(turns.iter().filter(|&&actor| actor == 1).count(), 3);
+        assert_eq!(turns.iter().filter(|&&actor| actor == 2).count(), 6);
+        assert_eq!(turns[0], 2);
+    }
+
+    #[test]
+    fn most_energy_goes_first() {
+        let mut scheduler = TurnScheduler::new();
+        scheduler.add_actor(1, 50);
+        scheduler.add_actor(2, 60);
+
+        // Both are ready after two ticks, but actor 2 overshoots the threshold further.
+        assert_eq!(scheduler.begin_turn(), Some(2));
+        assert_eq!(scheduler.energy(1), Some(100));
+        assert_eq!(scheduler.energy(2), Some(120));
+        scheduler.end_turn(TURN_ENERGY_THRESHOLD);
+        assert_eq!(scheduler.begin_turn(), Some(1));
+    }
+
+    #[test]
+    fn ties_go_to_earlier_registration() {
+        let mut scheduler = TurnScheduler::new();
+        scheduler.add_actor(7, 25);
+        scheduler.add_actor(3, 25);
+
+        assert_eq!(scheduler.begin_turn(), Some(7));
+        scheduler.end_turn(TURN_ENERGY_THRESHOLD);
+        assert_eq!(scheduler.begin_turn(), Some(3));
+    }
+
+    #[test]
+    fn waits_for_the_current_turn_to_end() {
+        let mut scheduler = TurnScheduler::new();
+        scheduler.add_actor(1, 10);
+        scheduler.add_actor(2, 10);
+
+        assert_eq!(scheduler.begin_turn(), Some(1));
+        // The player has not acted yet.
+        assert_eq!(scheduler.begin_turn(), None);
+        assert_eq!(scheduler.current(), Some(1));
+
+        assert_eq!(scheduler.end_turn(TURN_ENERGY_THRESHOLD), Some(1));
+        assert_eq!(scheduler.current(), None);
+        assert_eq!(scheduler.begin_turn(), Some(2));
+    }
+
+    #[test]
+    fn cheaper_actions_act_again_sooner() {
+        let mut scheduler = TurnScheduler::new();
+        scheduler.add_actor(1, 10);
+        scheduler.add_actor(2, 10);
+
+        assert_eq!(scheduler.begin_turn(), Some(1));
+        scheduler.end_turn(0);
+        // Actor 1 spent nothing and still has the most energy.
+        assert_eq!(scheduler.begin_turn(), Some(1));
+        scheduler.end_turn(TURN_ENERGY_THRESHOLD * 2);
+        assert_eq!(run_turns(&mut scheduler, 3), vec![2, 2, 1]);
+    }
+
+    #[test]
+    fn ended_turns_are_reported_once() {
+        let mut scheduler = TurnScheduler::new();
+        scheduler.add_actor(1, 50);
+
+        scheduler.begin_turn();
+        scheduler.end_turn(40);
+
+        assert_eq!(
+            scheduler.take_ended_turns(),
+            vec![EndedTurn {
+                actor: 1,
+                turn: 1,
+                cost: 40,
+            }]
+        );
+        assert!(scheduler.take_ended_turns().is_empty());
+    }
+
+    #[test]
+    fn removing_the_current_actor_ends_its_turn() {
+        let mut scheduler = TurnScheduler::new();
+        scheduler.add_actor(1, 10);
+        scheduler.add_actor(2, 10);
+
+        assert_eq!(scheduler.begin_turn(), Some(1));
+        assert!(scheduler.remove_actor(1));
+        assert_eq!(scheduler.current(), None);
+        assert!(scheduler.take_ended_turns().is_empty());
+        assert_eq!(scheduler.begin_turn(), Some(2));
+    }
+
+    #[test]
+    fn stalls_without_speed_or_while_paused() {
+        let mut scheduler = TurnScheduler::new();
+        assert_eq!(scheduler.begin_turn(), None);
+
+        scheduler.add_actor(1, 0);
+        assert_eq!(scheduler.begin_turn(), None);
+
+        scheduler.set_speed(1, 10);
+        scheduler.set_paused(true);
+        assert_eq!(scheduler.begin_turn(), None);
+
+        scheduler.set_paused(false);
+        assert_eq!(scheduler.begin_turn(), Some(1));
+    }
+}
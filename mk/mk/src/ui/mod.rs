@@ -1,3 +1,4 @@
+mod text_editor;
 mod ui_anchor;
 mod ui_element;
 mod ui_event_manager;
@@ -7,6 +8,7 @@ mod ui_manager;
 mod ui_margin;
 mod ui_scale_mode;
 
+pub use text_editor::*;
 pub use ui_anchor::*;
 pub use ui_element::*;
 pub use ui_event_manager::*;
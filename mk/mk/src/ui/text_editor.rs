@@ -0,0 +1,392 @@
+use crate::input::KeyCode;
+use std::ops::Range;
+
+/// Text being composed by an input method, shown at the caret until it is committed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextComposition {
+    pub text: String,
+    /// Caret inside the composition, in characters.
+    pub caret: Option<usize>,
+}
+
+/// What a key press did to the edited text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextEditAction {
+    None,
+    Moved,
+    Changed,
+    Submitted,
+    Copy(String),
+    Cut(String),
+    Paste,
+}
+
+/// Single-line editable text with a caret and an optional selection.
+/// Positions are counted in characters, not bytes.
+#[derive(Debug, Clone, Default)]
+pub struct TextEditor {
+    text: String,
+    caret: usize,
+    anchor: Option<usize>,
+    composition: Option<TextComposition>,
+    max_length: Option<usize>,
+}
+
+impl TextEditor {
+    pub fn new(text: impl Into<String>) -> Self {
+        let mut editor = Self::default();
+        editor.set_text(text);
+        editor
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text and moves the caret to its end.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = sanitize(&text.into());
+
+        if let Some(max_length) = self.max_length {
+            self.text = self.text.chars().take(max_length).collect();
+        }
+
+        self.caret = self.len();
+        self.anchor = None;
+    }
+
+    pub fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn max_length(&self) -> Option<usize> {
+        self.max_length
+    }
+
+    pub fn set_max_length(&mut self, max_length: Option<usize>) {
+        self.max_length = max_length;
+        let text = std::mem::take(&mut self.text);
+        self.set_text(text);
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    pub fn selection(&self) -> Option<Range<usize>> {
+        match self.anchor {
+            Some(anchor) if anchor != self.caret => {
+                Some(anchor.min(self.caret)..anchor.max(self.caret))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn selected_text(&self) -> Option<String> {
+        self.selection().map(|selection| {
+            self.text
+                .chars()
+                .skip(selection.start)
+                .take(selection.len())
+                .collect()
+        })
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.caret = self.len();
+    }
+
+    pub fn composition(&self) -> Option<&TextComposition> {
+        self.composition.as_ref()
+    }
+
+    pub fn set_composition(&mut self, composition: Option<TextComposition>) {
+        self.composition = composition.filter(|composition| !composition.text.is_empty());
+    }
+
+    /// Returns the text as it should be displayed, with the composition inserted at the caret.
+    pub fn display_text(&self) -> String {
+        match &self.composition {
+            Some(composition) => {
+                let index = self.byte_index(self.caret);
+                let mut text = self.text.clone();
+                text.insert_str(index, &composition.text);
+                text
+            }
+            None => self.text.clone(),
+        }
+    }
+
+    /// Returns the caret as displayed, accounting for the composition.
+    pub fn display_caret(&self) -> usize {
+        match &self.composition {
+            Some(composition) => {
+                self.caret
+                    + composition
+                        .caret
+                        .unwrap_or_else(|| composition.text.chars().count())
+            }
+            None => self.caret,
+        }
+    }
+
+    /// Inserts the text at the caret, replacing the selection.
+    /// Control characters are dropped. Returns `true` if the text has changed.
+    pub fn insert(&mut self, text: &str) -> bool {
+        let removed = self.delete_selection();
+        let mut text = sanitize(text);
+
+        if let Some(max_length) = self.max_length {
+            let room = max_length.saturating_sub(self.len());
+            text = text.chars().take(room).collect();
+        }
+
+        if text.is_empty() {
+            return removed;
+        }
+
+        let index = self.byte_index(self.caret);
+        self.text.insert_str(index, &text);
+        self.caret += text.chars().count();
+        true
+    }
+
+    pub fn backspace(&mut self) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+
+        if self.caret == 0 {
+            return false;
+        }
+
+        self.caret -= 1;
+        let index = self.byte_index(self.caret);
+        self.text.remove(index);
+        true
+    }
+
+    pub fn delete(&mut self) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+
+        if self.caret == self.len() {
+            return false;
+        }
+
+        let index = self.byte_index(self.caret);
+        self.text.remove(index);
+        true
+    }
+
+    /// Moves the caret, extending the selection if `select` is set.
+    pub fn move_to(&mut self, caret: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.caret);
+        } else {
+            self.anchor = None;
+        }
+
+        self.caret = caret.min(self.len());
+    }
+
+    /// Applies an editing key. Letters are expected to come through `insert` instead.
+    pub fn handle_key(&mut self, key: KeyCode, shift: bool, control: bool) -> TextEditAction {
+        let moved = |editor: &mut Self, caret: usize| {
+            editor.move_to(caret, shift);
+            TextEditAction::Moved
+        };
+        let changed = |changed: bool| {
+            if changed {
+                TextEditAction::Changed
+            } else {
+                TextEditAction::None
+            }
+        };
+
+        match key {
+            KeyCode::Left => match self.selection() {
+                Some(selection) if !shift => moved(self, selection.start),
+                _ => moved(self, self.caret.saturating_sub(1)),
+            },
+            KeyCode::Right => match self.selection() {
+                Some(selection) if !shift => moved(self, selection.end),
+                _ => moved(self, self.caret + 1),
+            },
+            KeyCode::Home => moved(self, 0),
+            KeyCode::End => moved(self, self.len()),
+            KeyCode::Backspace => changed(self.backspace()),
+            KeyCode::Delete => changed(self.delete()),
+            KeyCode::Enter | KeyCode::NumpadEnter => TextEditAction::Submitted,
+            KeyCode::AlphaA if control => {
+                self.select_all();
+                TextEditAction::Moved
+            }
+            KeyCode::AlphaC if control => match self.selected_text() {
+                Some(text) => TextEditAction::Copy(text),
+                None => TextEditAction::None,
+            },
+            KeyCode::AlphaX if control => match self.selected_text() {
+                Some(text) => {
+                    self.delete_selection();
+                    TextEditAction::Cut(text)
+                }
+                None => TextEditAction::None,
+            },
+            KeyCode::AlphaV if control => TextEditAction::Paste,
+            _ => TextEditAction::None,
+        }
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        let selection = match self.selection() {
+            Some(selection) => selection,
+            None => {
+                self.anchor = None;
+                return false;
+            }
+        };
+
+        let range = self.byte_index(selection.start)..self.byte_index(selection.end);
+        self.text.replace_range(range, "");
+        self.caret = selection.start;
+        self.anchor = None;
+        true
+    }
+
+    fn byte_index(&self, caret: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(caret)
+            .map(|(index, _)| index)
+            .unwrap_or(self.text.len())
+    }
+}
+
+/// Keeps the text on a single line and drops other control characters.
+fn sanitize(text: &str) -> String {
+    text.chars()
+        .map(|c| if c == '\n' || c == '\t' { ' ' } else { c })
+        .filter(|c| !c.is_control())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn insert_at_caret() {
+        let mut editor = TextEditor::new("held");
+
+        editor.move_to(2, false);
+        assert!(editor.insert("l"));
+        assert_eq!(editor.text(), "helld");
+        assert_eq!(editor.caret(), 3);
+
+        editor.move_to(editor.len(), false);
+        assert!(editor.insert(" world\n"));
+        assert_eq!(editor.text(), "helld world ");
+        assert!(!editor.insert("\r"));
+    }
+
+    #[test]
+    fn insert_respects_max_length() {
+        let mut editor = TextEditor::new("abc");
+
+        editor.set_max_length(Some(4));
+        assert!(editor.insert("def"));
+        assert_eq!(editor.text(), "abcd");
+        assert!(!editor.insert("e"));
+
+        editor.set_max_length(Some(2));
+        assert_eq!(editor.text(), "ab");
+        assert_eq!(editor.caret(), 2);
+    }
+
+    #[test]
+    fn backspace_and_delete() {
+        let mut editor = TextEditor::new("abc");
+
+        editor.move_to(1, false);
+        assert!(editor.backspace());
+        assert_eq!(editor.text(), "bc");
+        assert_eq!(editor.caret(), 0);
+        assert!(!editor.backspace());
+
+        assert!(editor.delete());
+        assert_eq!(editor.text(), "c");
+        assert_eq!(editor.caret(), 0);
+
+        editor.move_to(1, false);
+        assert!(!editor.delete());
+    }
+
+    #[test]
+    fn selection_is_replaced_or_removed() {
+        let mut editor = TextEditor::new("hello world");
+
+        editor.move_to(0, false);
+        editor.move_to(5, true);
+        assert_eq!(editor.selection(), Some(0..5));
+        assert_eq!(editor.selected_text().as_deref(), Some("hello"));
+        assert!(editor.insert("goodbye"));
+        assert_eq!(editor.text(), "goodbye world");
+        assert_eq!(editor.caret(), 7);
+        assert_eq!(editor.selection(), None);
+
+        editor.move_to(editor.len(), true);
+        assert!(editor.delete());
+        assert_eq!(editor.text(), "goodbye");
+
+        editor.select_all();
+        assert_eq!(
+            editor.handle_key(KeyCode::AlphaX, false, true),
+            TextEditAction::Cut("goodbye".to_owned())
+        );
+        assert!(editor.is_empty());
+    }
+
+    #[test]
+    fn positions_count_characters_not_bytes() {
+        let mut editor = TextEditor::new("héllo");
+
+        assert_eq!(editor.len(), 5);
+        editor.move_to(2, false);
+        assert!(editor.insert("ü"));
+        assert_eq!(editor.text(), "héüllo");
+
+        assert!(editor.backspace());
+        assert!(editor.backspace());
+        assert_eq!(editor.text(), "hllo");
+
+        editor.set_text("日本語");
+        editor.move_to(1, false);
+        editor.move_to(2, true);
+        assert_eq!(editor.selected_text().as_deref(), Some("本"));
+        assert!(editor.insert("🎮"));
+        assert_eq!(editor.text(), "日🎮語");
+        assert!(editor.delete());
+        assert_eq!(editor.text(), "日🎮");
+    }
+
+    #[test]
+    fn composition_is_shown_at_the_caret() {
+        let mut editor = TextEditor::new("ab");
+
+        editor.move_to(1, false);
+        editor.set_composition(Some(TextComposition {
+            text: "にほ".to_owned(),
+            caret: None,
+        }));
+        assert_eq!(editor.display_text(), "aにほb");
+        assert_eq!(editor.display_caret(), 3);
+        assert_eq!(editor.text(), "ab");
+    }
+}
@@ -1,7 +1,8 @@
 use crate::structure::Vec2;
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct UIAnchor {
     pub min: Vec2,
     pub max: Vec2,
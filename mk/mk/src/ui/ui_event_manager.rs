@@ -1,13 +1,16 @@
-use crate::component::{Camera, Transform};
+use crate::component::{Camera, TextField, Transform};
+use crate::emit_diagnostic_warn;
 use crate::engine::use_context;
 use crate::event::NativeEvent;
+use crate::input::KeyCode;
 use crate::script::event::{
     UIDragBegin, UIDragDrop, UIDragEnd, UIFocusIn, UIFocusOut, UIMouseDown, UIMouseEnter,
-    UIMouseExit, UIMouseMove, UIMouseUp,
+    UIMouseExit, UIMouseMove, UIMouseUp, UITextChange, UITextSubmit,
 };
-use crate::structure::Vec2;
+use crate::structure::{Vec2, Vec3};
+use crate::ui::{TextComposition, TextEditAction};
 use specs::prelude::*;
-use winit::event::MouseButton;
+use winit::event::{ModifiersState, MouseButton};
 
 #[derive(Debug)]
 struct MouseDown {
@@ -28,6 +31,8 @@ pub struct UIEventManager {
     mouse_down: Option<MouseDown>,
     mouse_drag: Option<MouseDrag>,
     last_mouse_position: Option<Vec2>,
+    modifiers: ModifiersState,
+    paste_requested: bool,
 }
 
 impl UIEventManager {
@@ -35,6 +40,166 @@ impl UIEventManager {
         Self::default()
     }
 
+    /// Moves the focus to the entity, emitting focus events if it has changed.
+    pub fn set_focus(&mut self, entity: Option<Entity>) {
+        if self.focus == entity {
+            return;
+        }
+
+        if let Some(focus_entity) = std::mem::replace(&mut self.focus, entity) {
+            emit_event(focus_entity, &UIFocusOut);
+        }
+
+        if let Some(entity) = entity {
+            emit_event(entity, &UIFocusIn);
+        }
+    }
+
+    /// Drops every reference to the entity, without emitting any event. Called when the entity is destroyed.
+    pub fn forget_entity(&mut self, entity: Entity) {
+        if self.camera == Some(entity) {
+            self.camera = None;
+        }
+
+        if self.focus == Some(entity) {
+            self.focus = None;
+        }
+
+        if self.mouse_in == Some(entity) {
+            self.mouse_in = None;
+        }
+
+        if let Some(mouse_down) = &mut self.mouse_down {
+            if mouse_down.entity == Some(entity) {
+                mouse_down.entity = None;
+            }
+        }
+
+        if matches!(&self.mouse_drag, Some(mouse_drag) if mouse_drag.entity == entity) {
+            self.mouse_drag = None;
+        }
+    }
+
+    /// Returns `true` if the focused entity is a text field.
+    pub fn is_editing_text(&self) -> bool {
+        match self.focus {
+            Some(entity) => use_context()
+                .world()
+                .read_storage::<TextField>()
+                .contains(entity),
+            None => false,
+        }
+    }
+
+    /// Returns whether an input method is composing text for the focused text field.
+    pub fn is_composing_text(&self) -> bool {
+        self.with_focused_text_field(|text_field| text_field.editor().composition().is_some())
+            .map(|(_, composing)| composing)
+            .unwrap_or(false)
+    }
+
+    /// Returns the screen position of the focused entity, in logical pixels.
+    pub fn focus_position_in_screen(&self) -> Option<Vec2> {
+        let entity = self.focus?;
+        let context = use_context();
+        let world = context.world();
+        let transform_storage = world.read_storage::<Transform>();
+        let transform_mgr = context.transform_mgr();
+        let screen_mgr = context.screen_mgr();
+
+        let point_in_world = Vec3::new(0f32, 0f32, 1f32)
+            * transform_mgr.transform_world_matrix(transform_storage.get(entity)?.index());
+        let point_in_camera = match self.camera.and_then(|camera| transform_storage.get(camera)) {
+            Some(camera) => {
+                point_in_world
+                    * transform_mgr
+                        .transform_world_matrix(camera.index())
+                        .inversed()
+            }
+            None => point_in_world,
+        };
+
+        Some(Vec2::new(
+            point_in_camera.x + screen_mgr.width() as f32 * 0.5f32,
+            -point_in_camera.y + screen_mgr.height() as f32 * 0.5f32,
+        ))
+    }
+
+    pub fn handle_modifiers_changed(&mut self, modifiers: ModifiersState) {
+        self.modifiers = modifiers;
+    }
+
+    /// Inserts typed or committed text into the focused text field.
+    pub fn handle_text_input(&mut self, text: &str) {
+        let changed = self.with_focused_text_field(|text_field| {
+            let editor = text_field.editor_mut();
+            editor.set_composition(None);
+            editor.insert(text).then(|| editor.text().to_owned())
+        });
+
+        if let Some((entity, Some(text))) = changed {
+            emit_event(entity, &UITextChange { text });
+        }
+    }
+
+    /// Shows the text being composed by an input method in the focused text field.
+    pub fn handle_text_composition(&mut self, composition: Option<TextComposition>) {
+        self.with_focused_text_field(|text_field| {
+            text_field.editor_mut().set_composition(composition);
+        });
+    }
+
+    /// Applies an editing key to the focused text field.
+    pub fn handle_key_down(&mut self, key: KeyCode) {
+        let shift = self.modifiers.shift();
+        // Command on macOS, control elsewhere.
+        let control = self.modifiers.ctrl() || self.modifiers.logo();
+        let result = self.with_focused_text_field(|text_field| {
+            if text_field.editor().composition().is_some() {
+                // The input method owns the keys while composing.
+                return (TextEditAction::None, String::new());
+            }
+
+            let action = text_field.editor_mut().handle_key(key, shift, control);
+            (action, text_field.editor().text().to_owned())
+        });
+        let (entity, (action, text)) = match result {
+            Some(result) => result,
+            None => return,
+        };
+
+        match action {
+            TextEditAction::None | TextEditAction::Moved => {}
+            TextEditAction::Changed => emit_event(entity, &UITextChange { text }),
+            TextEditAction::Submitted => emit_event(entity, &UITextSubmit { text }),
+            TextEditAction::Copy(selected) => set_clipboard_text(selected),
+            TextEditAction::Cut(selected) => {
+                set_clipboard_text(selected);
+                emit_event(entity, &UITextChange { text });
+            }
+            TextEditAction::Paste => self.paste_requested = true,
+        }
+    }
+
+    /// Returns whether a paste has been requested since the last call. The clipboard is not read
+    /// here, so that the pasted text can be recorded as an input and given to `handle_text_input`.
+    pub fn take_paste_request(&mut self) -> bool {
+        std::mem::take(&mut self.paste_requested)
+    }
+
+    fn with_focused_text_field<R>(
+        &self,
+        f: impl FnOnce(&mut TextField) -> R,
+    ) -> Option<(Entity, R)> {
+        let entity = self.focus?;
+        let result = use_context()
+            .world()
+            .write_storage::<TextField>()
+            .get_mut(entity)
+            .map(f)?;
+        Some((entity, result))
+    }
+
     pub fn handle_mouse_exit(&mut self) {
         if let Some(mouse_in_entity) = self.mouse_in.take() {
             emit_event(mouse_in_entity, &UIMouseExit);
@@ -160,13 +325,7 @@ impl UIEventManager {
                     button,
                 });
 
-                if let Some(focus_entity) = self.focus {
-                    if entity != focus_entity {
-                        self.focus = Some(entity);
-                        emit_event(focus_entity, &UIFocusOut);
-                        emit_event(entity, &UIFocusIn);
-                    }
-                }
+                self.set_focus(Some(entity));
 
                 emit_event(
                     entity,
@@ -263,14 +422,38 @@ impl UIEventManager {
     }
 }
 
+pub fn clipboard_text() -> Option<String> {
+    match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+        Ok(text) => Some(text),
+        Err(err) => {
+            emit_diagnostic_warn!(format!("failed to read the clipboard due to: {}", err));
+            None
+        }
+    }
+}
+
+fn set_clipboard_text(text: String) {
+    if let Err(err) = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text)) {
+        emit_diagnostic_warn!(format!("failed to write the clipboard due to: {}", err));
+    }
+}
+
+/// Emits a UI event on the entity. Every UI event but `ui-mouse-enter` and `ui-mouse-exit` propagates through
+/// the parents of the entity, so that containers can handle the events of their elements.
 fn emit_event<T>(entity: Entity, event: &T)
 where
     T: NativeEvent,
 {
     let context = use_context();
-    context.entity_event_mgr().emit(
-        crate::script::entity::Entity::new(entity),
-        event,
-        context.script_mgr().lua(),
-    );
+    let entity = crate::script::entity::Entity::new(entity);
+    let lua = context.script_mgr().lua();
+
+    // Entering and exiting concern the entity alone, since the mouse may still be in its parents.
+    let name = <T as NativeEvent>::name();
+    if name == <UIMouseEnter as NativeEvent>::name() || name == <UIMouseExit as NativeEvent>::name()
+    {
+        context.entity_event_mgr().emit(entity, event, lua);
+    } else {
+        context.entity_event_mgr().emit_bubbling(entity, event, lua);
+    }
 }
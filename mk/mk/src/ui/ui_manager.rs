@@ -258,3 +258,33 @@ impl Default for UIManager {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dealloc_reuses_index() {
+        let mut world = World::new();
+        let mut ui_mgr = UIManager::new();
+
+        let first = ui_mgr.begin_alloc();
+        ui_mgr.fin_alloc(first, world.create_entity().build());
+        let second = ui_mgr.begin_alloc();
+        ui_mgr.fin_alloc(second, world.create_entity().build());
+        ui_mgr.element_mut(first).set_order_index(3);
+        ui_mgr.element_mut(first).mark_as_dirty();
+
+        ui_mgr.dealloc(first);
+        assert!(!ui_mgr.element(first).is_dirty());
+
+        let entity = world.create_entity().build();
+        let reused = ui_mgr.begin_alloc();
+        ui_mgr.fin_alloc(reused, entity);
+
+        assert_eq!(reused, first);
+        assert_eq!(ui_mgr.entity(reused), entity);
+        assert_eq!(ui_mgr.element(reused).order_index(), 0);
+        assert_eq!(ui_mgr.begin_alloc(), 2);
+    }
+}
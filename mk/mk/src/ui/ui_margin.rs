@@ -1,7 +1,8 @@
 use crate::structure::{Size, Vec2};
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct UIMargin {
     pub left: f32,
     pub right: f32,
@@ -0,0 +1,26 @@
+use std::{
+    fs::{create_dir_all, rename, File},
+    io::{Result as IOResult, Write},
+    path::Path,
+};
+
+/// Writes the contents to a temporary file next to the destination and renames it over,
+/// so that the destination is never left half-written. Parent directories are created as needed.
+pub fn write_atomic(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> IOResult<()> {
+    let path = path.as_ref();
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+
+    {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+    }
+
+    rename(&tmp_path, path)
+}
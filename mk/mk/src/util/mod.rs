@@ -1,3 +1,7 @@
+mod atomic_write;
 mod box_id;
+mod user_file;
 
+pub use atomic_write::*;
 pub use box_id::*;
+pub use user_file::*;
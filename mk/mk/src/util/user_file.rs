@@ -0,0 +1,32 @@
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid {kind} name '{name}'; only letters, digits, '-' and '_' are allowed")]
+pub struct InvalidFileName {
+    /// What the name is for, e.g. `slot`.
+    pub kind: &'static str,
+    pub name: String,
+}
+
+/// Returns the path of the named file in the directory, rejecting names that could escape it.
+/// `kind` tells what the name is for in the error, e.g. `slot`.
+pub fn user_file_path(
+    dir: impl AsRef<Path>,
+    kind: &'static str,
+    name: &str,
+    extension: &str,
+) -> Result<PathBuf, InvalidFileName> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(InvalidFileName {
+            kind,
+            name: name.to_owned(),
+        });
+    }
+
+    Ok(dir.as_ref().join(name).with_extension(extension))
+}
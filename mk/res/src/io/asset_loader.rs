@@ -1,9 +1,8 @@
+use super::cipher::{mask_key_nonce, new_cipher, KEY_NONCE_SIZE};
 use crate::io::read_file_all;
 use crate::{chunk_to_filename, Resource};
-use aes::cipher::generic_array::GenericArray;
-use aes::cipher::{FromBlockCipher, NewBlockCipher, StreamCipher, StreamCipherSeek};
-use aes::{Aes256, Aes256Ctr};
-use argon2::{hash_raw, Config, Error as Argon2Error};
+use aes::cipher::{StreamCipher, StreamCipherSeek};
+use argon2::Error as Argon2Error;
 use downcast_rs::{impl_downcast, Downcast};
 use std::collections::HashMap;
 use std::error::Error;
@@ -35,8 +34,7 @@ impl From<DecoderError> for ResourceLoadError {
 }
 
 pub struct ResourceLoader {
-    key: Vec<u8>,
-    nonce: Vec<u8>,
+    key_nonce: [u8; KEY_NONCE_SIZE],
     base_path: PathBuf,
     decoders: HashMap<String, Box<dyn ResourceDecoder>>,
 }
@@ -54,66 +52,12 @@ impl ResourceLoader {
         let first_chunk = OpenOptions::new()
             .read(true)
             .open(base_path.join(chunk_to_filename(0)))?;
-        let key_nonce = &mut [0u8; 48];
+        let key_nonce = &mut [0u8; KEY_NONCE_SIZE];
         read_file_all(&first_chunk, 0, key_nonce)?;
-
-        let mut config = Config::default();
-        config.hash_length = 48;
-        let hash = hash_raw(key.as_ref(), salt.as_ref(), &config)
-            .map_err(|err| ResourceLoadError::KeySaltHashError(err))?;
-
-        key_nonce[0] ^= hash[0];
-        key_nonce[1] ^= hash[1];
-        key_nonce[2] ^= hash[2];
-        key_nonce[3] ^= hash[3];
-        key_nonce[4] ^= hash[4];
-        key_nonce[5] ^= hash[5];
-        key_nonce[6] ^= hash[6];
-        key_nonce[7] ^= hash[7];
-        key_nonce[8] ^= hash[8];
-        key_nonce[9] ^= hash[9];
-        key_nonce[10] ^= hash[10];
-        key_nonce[11] ^= hash[11];
-        key_nonce[12] ^= hash[12];
-        key_nonce[13] ^= hash[13];
-        key_nonce[14] ^= hash[14];
-        key_nonce[15] ^= hash[15];
-        key_nonce[16] ^= hash[16];
-        key_nonce[17] ^= hash[17];
-        key_nonce[18] ^= hash[18];
-        key_nonce[19] ^= hash[19];
-        key_nonce[20] ^= hash[20];
-        key_nonce[21] ^= hash[21];
-        key_nonce[22] ^= hash[22];
-        key_nonce[23] ^= hash[23];
-        key_nonce[24] ^= hash[24];
-        key_nonce[25] ^= hash[25];
-        key_nonce[26] ^= hash[26];
-        key_nonce[27] ^= hash[27];
-        key_nonce[28] ^= hash[28];
-        key_nonce[29] ^= hash[29];
-        key_nonce[30] ^= hash[30];
-        key_nonce[31] ^= hash[31];
-        key_nonce[32] ^= hash[32];
-        key_nonce[33] ^= hash[33];
-        key_nonce[34] ^= hash[34];
-        key_nonce[35] ^= hash[35];
-        key_nonce[36] ^= hash[36];
-        key_nonce[37] ^= hash[37];
-        key_nonce[38] ^= hash[38];
-        key_nonce[39] ^= hash[39];
-        key_nonce[40] ^= hash[40];
-        key_nonce[41] ^= hash[41];
-        key_nonce[42] ^= hash[42];
-        key_nonce[43] ^= hash[43];
-        key_nonce[44] ^= hash[44];
-        key_nonce[45] ^= hash[45];
-        key_nonce[46] ^= hash[46];
-        key_nonce[47] ^= hash[47];
+        mask_key_nonce(key, salt, key_nonce).map_err(ResourceLoadError::KeySaltHashError)?;
 
         Ok(Self {
-            key: key_nonce[..32].to_vec(),
-            nonce: key_nonce[32..].to_vec(),
+            key_nonce: *key_nonce,
             base_path,
             decoders: HashMap::new(),
         })
@@ -146,10 +90,7 @@ impl ResourceLoader {
             read += chunk.size as usize;
         }
 
-        let mut cipher = Aes256Ctr::from_block_cipher(
-            Aes256::new(GenericArray::from_slice(&self.key)),
-            GenericArray::from_slice(&self.nonce),
-        );
+        let mut cipher = new_cipher(&self.key_nonce);
         cipher.seek(res.cipher_offset);
         cipher.apply_keystream(&mut content);
 
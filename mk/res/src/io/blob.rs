@@ -0,0 +1,81 @@
+use super::cipher::{mask_key_nonce, new_cipher, KEY_NONCE_SIZE};
+use aes::cipher::StreamCipher;
+use argon2::Error as Argon2Error;
+use brotli::{CompressorWriter, DecompressorWriter};
+use rand::prelude::*;
+use rand::{Error as RandError, Fill};
+use std::io::{Error as IOError, Write};
+
+#[derive(Debug)]
+pub enum BlobError {
+    TooShort,
+    CipherKeyGenError(RandError),
+    KeySaltHashError(Argon2Error),
+}
+
+/// Compresses the content with brotli, as the text encoder does.
+pub fn compress_blob(content: &[u8], q: u32, lgwin: u32) -> Vec<u8> {
+    let mut result = vec![];
+
+    {
+        let mut writer = CompressorWriter::new(&mut result, 4096, q, lgwin);
+        // Writing to a vector cannot fail.
+        writer.write_all(content).unwrap();
+    }
+
+    result
+}
+
+pub fn decompress_blob(content: &[u8]) -> Result<Vec<u8>, IOError> {
+    let mut result = vec![];
+
+    {
+        let mut writer = DecompressorWriter::new(&mut result, 4096);
+        writer.write_all(content)?;
+        writer.flush()?;
+    }
+
+    Ok(result)
+}
+
+/// Encrypts the content with AES-256 in CTR mode, under a random key and nonce.
+/// As with resource chunks, the key and nonce are stored at the start of the blob, masked with the hash of `key` and `salt`.
+pub fn encrypt_blob(
+    key: impl AsRef<[u8]>,
+    salt: impl AsRef<[u8]>,
+    content: &[u8],
+) -> Result<Vec<u8>, BlobError> {
+    let key_nonce = &mut [0u8; KEY_NONCE_SIZE];
+    key_nonce
+        .try_fill(&mut thread_rng())
+        .map_err(BlobError::CipherKeyGenError)?;
+
+    let masked_key_nonce = &mut key_nonce.clone();
+    mask_key_nonce(key, salt, masked_key_nonce).map_err(BlobError::KeySaltHashError)?;
+
+    let mut result = Vec::with_capacity(KEY_NONCE_SIZE + content.len());
+    result.extend_from_slice(masked_key_nonce);
+    result.extend_from_slice(content);
+
+    new_cipher(key_nonce).apply_keystream(&mut result[KEY_NONCE_SIZE..]);
+    Ok(result)
+}
+
+/// Decrypts a blob made by [`encrypt_blob`]. A wrong key or salt is not detected; it yields garbage.
+pub fn decrypt_blob(
+    key: impl AsRef<[u8]>,
+    salt: impl AsRef<[u8]>,
+    blob: &[u8],
+) -> Result<Vec<u8>, BlobError> {
+    if blob.len() < KEY_NONCE_SIZE {
+        return Err(BlobError::TooShort);
+    }
+
+    let key_nonce = &mut [0u8; KEY_NONCE_SIZE];
+    key_nonce.copy_from_slice(&blob[..KEY_NONCE_SIZE]);
+    mask_key_nonce(key, salt, key_nonce).map_err(BlobError::KeySaltHashError)?;
+
+    let mut result = blob[KEY_NONCE_SIZE..].to_vec();
+    new_cipher(key_nonce).apply_keystream(&mut result);
+    Ok(result)
+}
@@ -0,0 +1,34 @@
+use aes::cipher::generic_array::GenericArray;
+use aes::cipher::{FromBlockCipher, NewBlockCipher};
+use aes::{Aes256, Aes256Ctr};
+use argon2::{hash_raw, Config, Error as Argon2Error};
+
+/// Size of the key and nonce at the start of encrypted content, a 32-byte key and a 16-byte nonce.
+pub(crate) const KEY_NONCE_SIZE: usize = 48;
+
+/// Masks the key and nonce with the argon2 hash of `key` and `salt`. Masking them again unmasks them.
+pub(crate) fn mask_key_nonce(
+    key: impl AsRef<[u8]>,
+    salt: impl AsRef<[u8]>,
+    key_nonce: &mut [u8; KEY_NONCE_SIZE],
+) -> Result<(), Argon2Error> {
+    let config = Config {
+        hash_length: KEY_NONCE_SIZE as u32,
+        ..Config::default()
+    };
+    let hash = hash_raw(key.as_ref(), salt.as_ref(), &config)?;
+
+    for (byte, hash) in key_nonce.iter_mut().zip(hash) {
+        *byte ^= hash;
+    }
+
+    Ok(())
+}
+
+/// Creates the AES-256 cipher in CTR mode for the unmasked key and nonce.
+pub(crate) fn new_cipher(key_nonce: &[u8; KEY_NONCE_SIZE]) -> Aes256Ctr {
+    Aes256Ctr::from_block_cipher(
+        Aes256::new(GenericArray::from_slice(&key_nonce[..32])),
+        GenericArray::from_slice(&key_nonce[32..]),
+    )
+}
@@ -3,6 +3,12 @@ pub mod asset_loader;
 #[cfg(feature = "asset_loader")]
 pub mod decoder;
 
+#[cfg(feature = "blob")]
+pub mod blob;
+
+#[cfg(any(feature = "asset_loader", feature = "blob", feature = "writer"))]
+mod cipher;
+
 #[cfg(feature = "meta_loader")]
 pub mod meta_loader;
 
@@ -1,11 +1,10 @@
+use super::cipher::{mask_key_nonce, new_cipher, KEY_NONCE_SIZE};
 use crate::{
     chunk_to_filename, Resource, ResourceChunk, ResourceHash, ResourceHashAlgorithm, ResourceMeta,
     ResourceUUID, ResourcesMeta,
 };
-use aes::cipher::generic_array::GenericArray;
-use aes::cipher::{FromBlockCipher, NewBlockCipher, StreamCipher};
-use aes::{Aes256, Aes256Ctr};
-use argon2::{hash_raw, Config, Error as Argon2Error};
+use aes::cipher::StreamCipher;
+use argon2::Error as Argon2Error;
 use byteorder::{ByteOrder, LittleEndian};
 use crc32fast::Hasher as Crc32Hasher;
 use memmap2::{Mmap, MmapOptions};
@@ -13,7 +12,7 @@ use rand::prelude::*;
 use rand::{Error as RandError, Fill};
 use rayon::prelude::*;
 use sha256::digest_bytes as sha256_digest_bytes;
-use std::cmp::min;
+use std::cmp::{max, min};
 use std::collections::HashMap;
 use std::error::Error;
 use std::fs::{create_dir_all, metadata, remove_dir_all, File, OpenOptions};
@@ -107,87 +106,22 @@ impl ResourceWriter {
             })
             .collect::<Result<Vec<_>, ResourceWriteError>>()?;
 
-        let mut config = Config::default();
-        config.hash_length = 48;
-        let hash = hash_raw(key.as_ref(), salt.as_ref(), &config)
-            .map_err(|err| ResourceWriteError::KeySaltHashError(err))?;
-
-        let key = &mut [0u8; 32];
-        let nonce = &mut [0u8; 16];
-
-        {
-            let mut rng = thread_rng();
-            key.try_fill(&mut rng)
-                .map_err(|err| ResourceWriteError::CipherKeyGenError(err))?;
-            nonce
-                .try_fill(&mut rng)
-                .map_err(|err| ResourceWriteError::CipherKeyGenError(err))?;
-        }
+        let key_nonce = &mut [0u8; KEY_NONCE_SIZE];
+        key_nonce
+            .try_fill(&mut thread_rng())
+            .map_err(ResourceWriteError::CipherKeyGenError)?;
 
-        let secure_key = &mut key.clone();
-        let secure_nonce = &mut nonce.clone();
-
-        secure_key[0] ^= hash[0];
-        secure_key[1] ^= hash[1];
-        secure_key[2] ^= hash[2];
-        secure_key[3] ^= hash[3];
-        secure_key[4] ^= hash[4];
-        secure_key[5] ^= hash[5];
-        secure_key[6] ^= hash[6];
-        secure_key[7] ^= hash[7];
-        secure_key[8] ^= hash[8];
-        secure_key[9] ^= hash[9];
-        secure_key[10] ^= hash[10];
-        secure_key[11] ^= hash[11];
-        secure_key[12] ^= hash[12];
-        secure_key[13] ^= hash[13];
-        secure_key[14] ^= hash[14];
-        secure_key[15] ^= hash[15];
-        secure_key[16] ^= hash[16];
-        secure_key[17] ^= hash[17];
-        secure_key[18] ^= hash[18];
-        secure_key[19] ^= hash[19];
-        secure_key[20] ^= hash[20];
-        secure_key[21] ^= hash[21];
-        secure_key[22] ^= hash[22];
-        secure_key[23] ^= hash[23];
-        secure_key[24] ^= hash[24];
-        secure_key[25] ^= hash[25];
-        secure_key[26] ^= hash[26];
-        secure_key[27] ^= hash[27];
-        secure_key[28] ^= hash[28];
-        secure_key[29] ^= hash[29];
-        secure_key[30] ^= hash[30];
-        secure_key[31] ^= hash[31];
-
-        secure_nonce[0] ^= hash[32];
-        secure_nonce[1] ^= hash[33];
-        secure_nonce[2] ^= hash[34];
-        secure_nonce[3] ^= hash[35];
-        secure_nonce[4] ^= hash[36];
-        secure_nonce[5] ^= hash[37];
-        secure_nonce[6] ^= hash[38];
-        secure_nonce[7] ^= hash[39];
-        secure_nonce[8] ^= hash[40];
-        secure_nonce[9] ^= hash[41];
-        secure_nonce[10] ^= hash[42];
-        secure_nonce[11] ^= hash[43];
-        secure_nonce[12] ^= hash[44];
-        secure_nonce[13] ^= hash[45];
-        secure_nonce[14] ^= hash[46];
-        secure_nonce[15] ^= hash[47];
-
-        let mut cipher = Aes256Ctr::from_block_cipher(
-            Aes256::new(GenericArray::from_slice(key)),
-            GenericArray::from_slice(nonce),
-        );
+        let mut cipher = new_cipher(key_nonce);
+        let secure_key_nonce = &mut key_nonce.clone();
+        mask_key_nonce(key, salt, secure_key_nonce)
+            .map_err(ResourceWriteError::KeySaltHashError)?;
 
         let mut chunk = 0;
         let mut chunk_offset = 0;
         let mut total_written = 0;
 
         let total_size = {
-            let mut size = 48;
+            let mut size = KEY_NONCE_SIZE as u64;
 
             for (_, _, encoded) in resources.iter() {
                 size += encoded.content.len() as u64;
@@ -201,7 +135,8 @@ impl ResourceWriter {
             if chunk_size == 0 {
                 total_size
             } else {
-                chunk_size
+                // The loader reads the key and nonce from the first chunk alone.
+                max(chunk_size, KEY_NONCE_SIZE as u64)
             }
         };
 
@@ -250,7 +185,7 @@ impl ResourceWriter {
                         chunk_offset..chunk_offset + len
                     }];
 
-                    range.copy_from_slice(&content[..len]);
+                    range.copy_from_slice(&content[content_offset..content_offset + len]);
 
                     if apply_cipher {
                         cipher.apply_keystream(range);
@@ -274,8 +209,7 @@ impl ResourceWriter {
                 Ok((cipher_offset_size, chunks))
             };
 
-        write_to_chunk(secure_key, false)?;
-        write_to_chunk(secure_nonce, false)?;
+        write_to_chunk(secure_key_nonce, false)?;
 
         let resources = resources
             .into_iter()
@@ -386,3 +320,81 @@ pub struct EncodedResource {
     pub meta: Option<ResourceMeta>,
     pub content: Mmap,
 }
+
+#[cfg(all(test, feature = "asset_loader"))]
+mod test {
+    use super::*;
+    use crate::asset_loader::ResourceLoader;
+    use crate::decoder::{TextDecoder, TextDecoderOutput};
+    use crate::encoder::TextEncoder;
+    use std::fs::write;
+
+    #[test]
+    fn written_resources_are_loaded_back() {
+        let dir = std::env::temp_dir().join(format!("res-round-trip-{}", std::process::id()));
+        let src_dir = dir.join("src");
+        let out_dir = dir.join("out");
+        create_dir_all(&src_dir).unwrap();
+
+        let contents = [noise(1, 100), noise(2, 300)];
+        let paths = contents
+            .iter()
+            .enumerate()
+            .map(|(index, content)| {
+                let path = src_dir.join(format!("{}.txt", index));
+                write(&path, content).unwrap();
+                path
+            })
+            .collect::<Vec<_>>();
+
+        let mut writer = ResourceWriter::new();
+        writer.add_encoder("text".to_owned(), Box::new(TextEncoder::default()));
+        // The contents do not compress, so they span several of the smallest chunks.
+        let meta = writer
+            .write(
+                "key",
+                "salt-of-the-test",
+                Some(16),
+                &out_dir,
+                [
+                    WritingResource {
+                        name: "short",
+                        ty: "text",
+                        path: &paths[0],
+                    },
+                    WritingResource {
+                        name: "long",
+                        ty: "text",
+                        path: &paths[1],
+                    },
+                ],
+            )
+            .unwrap();
+
+        let mut loader = ResourceLoader::new("key", "salt-of-the-test", &out_dir).unwrap();
+        loader.add_decoder(Box::new(TextDecoder));
+
+        for (name, content) in [("short", &contents[0]), ("long", &contents[1])] {
+            let res = &meta.resources[meta.resource_names[name]];
+            assert!(res.chunks.len() > 1);
+
+            let loaded = loader.load(res).unwrap();
+            let loaded = loaded.downcast_ref::<TextDecoderOutput>().unwrap();
+            assert_eq!(&loaded.content, content);
+        }
+
+        remove_dir_all(&dir).unwrap();
+    }
+
+    /// Returns pseudo-random bytes, which brotli cannot shrink below the chunk size.
+    fn noise(mut seed: u32, len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                seed as u8
+            })
+            .collect()
+    }
+}
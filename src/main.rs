@@ -13,7 +13,7 @@ fn main() -> Result<()> {
         768,
         true,
         current_dir()?.join("assets"),
-        "assets/scripts/entry.lua",
+        "entry",
         once_engine_initialized,
     )
     .block_on()
@@ -0,0 +1,1913 @@
+---@meta
+-- Generated from the `codegen` metadata by `cargo run --manifest-path mk/Cargo.toml -p mk --example lua-api`. Do not edit.
+
+---@class EventHandler
+---@class EntityEventHandler
+
+mk = mk or {}
+
+mk.component = {}
+
+--- Plays an audio clip.
+---@class ComponentAudioSource
+---@field is_playing boolean? (read-only)
+---@field volume number?
+---@field clip AudioClip?
+local ComponentAudioSource = {}
+
+--- Returns whether the entity still has the component.
+---@return boolean
+function ComponentAudioSource:is_exists() end
+
+function ComponentAudioSource:play() end
+
+function ComponentAudioSource:stop() end
+
+--- Renders the layers of its mask, in ascending `order`.
+---@class ComponentCamera
+---@field layer Layer?
+---@field order integer?
+---@field clear_mode ClearMode?
+---@field clear_color Color?
+local ComponentCamera = {}
+
+--- Returns whether the entity still has the component.
+---@return boolean
+function ComponentCamera:is_exists() end
+
+--- Marks an entity created by the engine for diagnostics, e.g. the console.
+---@class ComponentDiagnostic
+local ComponentDiagnostic = {}
+
+--- Returns whether the entity still has the component.
+---@return boolean
+function ComponentDiagnostic:is_exists() end
+
+--- Renders a text.
+---@class ComponentGlyphRenderer
+---@field layer Layer?
+---@field order integer?
+---@field color Color?
+---@field shader Shader?
+---@field thickness number?
+---@field smoothness number?
+---@field font Font?
+---@field font_size number?
+---@field text string?
+---@field config GlyphLayoutConfig?
+local ComponentGlyphRenderer = {}
+
+--- Returns whether the entity still has the component.
+---@return boolean
+function ComponentGlyphRenderer:is_exists() end
+
+--- Returns the size of the laid out text.
+---@return Size?
+function ComponentGlyphRenderer:compute_size() end
+
+--- The size of an entity, used by the renderers and UI elements.
+---@class ComponentSize
+---@field size Size?
+local ComponentSize = {}
+
+--- Returns whether the entity still has the component.
+---@return boolean
+function ComponentSize:is_exists() end
+
+--- Renders a sprite.
+---@class ComponentSpriteRenderer
+---@field layer Layer?
+---@field order integer?
+---@field color Color?
+---@field shader Shader?
+---@field sprite Sprite?
+local ComponentSpriteRenderer = {}
+
+--- Returns whether the entity still has the component.
+---@return boolean
+function ComponentSpriteRenderer:is_exists() end
+
+--- An editable text, rendered by the glyph renderer of the entity.
+---@class ComponentTextField
+---@field text string?
+---@field max_length integer?
+---@field caret_index integer?
+---@field selected_text string? (read-only)
+---@field caret Entity?
+---@field selection Entity?
+---@field caret_width number?
+---@field is_focused boolean (read-only)
+local ComponentTextField = {}
+
+--- Returns whether the entity still has the component.
+---@return boolean
+function ComponentTextField:is_exists() end
+
+function ComponentTextField:select_all() end
+
+function ComponentTextField:focus() end
+
+function ComponentTextField:blur() end
+
+--- Places the entity in the hierarchy; unprefixed fields are in world space.
+---@class ComponentTransform
+---@field parent ComponentTransform?
+---@field position Vec2
+---@field scale Vec2
+---@field angle number
+---@field local_position Vec2?
+---@field local_scale Vec2?
+---@field local_angle number?
+local ComponentTransform = {}
+
+--- Lays out the entity relative to its parent UI element.
+---@class ComponentUIElement
+---@field anchor UIAnchor?
+---@field margin UIMargin?
+---@field is_interactible boolean?
+---@field order_index integer?
+local ComponentUIElement = {}
+
+--- Returns whether the entity still has the component.
+---@return boolean
+function ComponentUIElement:is_exists() end
+
+--- Clips the rendering of its children to the entity.
+---@class ComponentUIMask
+---@field render_itself boolean?
+local ComponentUIMask = {}
+
+--- Returns whether the entity still has the component.
+---@return boolean
+function ComponentUIMask:is_exists() end
+
+--- Scales its children from a reference size to the screen.
+---@class ComponentUIScaler
+---@field mode UIScaleMode?
+---@field reference_size Size?
+local ComponentUIScaler = {}
+
+--- Returns whether the entity still has the component.
+---@return boolean
+function ComponentUIScaler:is_exists() end
+
+mk.console = {}
+
+--- Returns whether the console is shown.
+---@return boolean
+function mk.console.is_open() end
+
+--- Shows or hides the console.
+---@param is_open boolean
+function mk.console.set_open(is_open) end
+
+--- Shows the console if hidden and hides it otherwise. Returns whether it is now shown.
+---@return boolean
+function mk.console.toggle() end
+
+--- Runs a line as if it were typed into the console.
+---@param line string
+function mk.console.execute(line) end
+
+--- Completes the last name of a line. Returns the completed line and every candidate.
+---@param line string
+---@return string, string[]
+function mk.console.complete(line) end
+
+--- Steps back through the executed lines. Returns `nil` past the oldest one.
+---@return string?
+function mk.console.previous_history() end
+
+--- Steps forward through the executed lines. Returns `nil` past the newest one.
+---@return string?
+function mk.console.next_history() end
+
+--- Returns the output of the console, limited to the last `max_lines` lines if given.
+---@param max_lines integer?
+---@return string
+function mk.console.output(max_lines) end
+
+--- Appends a line to the output of the console.
+---@param text string
+function mk.console.print(text) end
+
+--- Clears the output of the console.
+function mk.console.clear() end
+
+--- Registers a command, called with the arguments typed after its name.
+---@param name string
+---@param callback function
+---@param help string?
+function mk.console.register_command(name, callback, help) end
+
+--- Unregisters a command. Returns whether it was registered.
+---@param name string
+---@return boolean
+function mk.console.unregister_command(name) end
+
+mk.diagnostic = {}
+
+--- Returns whether the game pauses when a script error is reported.
+---@return boolean
+function mk.diagnostic.pause_on_script_error() end
+
+--- Sets whether the game pauses when a script error is reported.
+---@param pause_on_script_error boolean
+function mk.diagnostic.set_pause_on_script_error(pause_on_script_error) end
+
+--- Returns the script error the game is paused on, if any.
+---@return Diagnostic?
+function mk.diagnostic.script_error() end
+
+--- Resumes the game paused on a script error. Returns whether it was paused.
+---@return boolean
+function mk.diagnostic.resume() end
+
+mk.entity = {}
+
+--- Returns an iterator over the entities having every component in `with` and none in `without`.
+---@param with string[]
+---@param without string[]?
+---@return fun(): Entity?
+function mk.entity.query(with, without) end
+
+--- An entity of the world; its components are reached through its fields.
+---@class Entity
+---@field name string? The name of its transform; `nil` without a transform.
+---@field audio_source ComponentAudioSource (read-only)
+---@field camera ComponentCamera (read-only)
+---@field diagnostic ComponentDiagnostic (read-only)
+---@field glyph_renderer ComponentGlyphRenderer (read-only)
+---@field size ComponentSize (read-only)
+---@field sprite_renderer ComponentSpriteRenderer (read-only)
+---@field text_field ComponentTextField (read-only)
+---@field transform ComponentTransform? (read-only) `nil` without a transform.
+---@field ui_element ComponentUIElement (read-only)
+---@field ui_mask ComponentUIMask (read-only)
+---@field ui_scaler ComponentUIScaler (read-only)
+local Entity = {}
+
+--- See the entity events for the arguments of the handler.
+---@param event_name EntityEventName|string
+---@param handler function|EntityEventHandler
+---@param options table?
+---@return EntityEventHandler
+function Entity:listen(event_name, handler, options) end
+
+---@param event_name string
+---@param handler EntityEventHandler
+function Entity:unlisten(event_name, handler) end
+
+--- Returns `false` once the entity is destroyed.
+---@return boolean
+function Entity:is_alive() end
+
+--- Destroys it with its children; `false` if already destroyed.
+---@return boolean
+function Entity:destroy() end
+
+--- Adds the component with the given name, replacing the existing one.
+---@param name string
+---@param params table?
+function Entity:add_component(name, params) end
+
+--- Returns `false` if the entity does not have the component.
+---@param name string
+---@return boolean
+function Entity:remove_component(name) end
+
+---@param name string
+---@return boolean
+function Entity:has_component(name) end
+
+--- Bubbles through the parents if asked; `true` if a handler stopped it.
+---@param event_name string
+---@param event table?
+---@param bubbles boolean?
+---@return boolean
+function Entity:emit(event_name, event, bubbles) end
+
+---@class mk.entity.Entity
+mk.entity.Entity = {}
+
+--- Returns the first entity with the given name.
+---@param name string
+---@return Entity?
+function mk.entity.Entity.find_by_name(name) end
+
+--- Returns every entity with the given name.
+---@param name string
+---@return Entity[]
+function mk.entity.Entity.find_all_by_name(name) end
+
+mk.event = {}
+
+--- Stops the event being dispatched from reaching the remaining handlers.
+function mk.event.consume() end
+
+--- Returns whether the event being dispatched has been consumed.
+---@return boolean
+function mk.event.is_consumed() end
+
+--- Stops the entity event being dispatched from reaching the ancestors of the current entity.
+function mk.event.stop_propagation() end
+
+--- Returns the phase of the entity event being dispatched, or `nil` outside entity events.
+---@return string?
+function mk.event.phase() end
+
+--- Defines an event emitted and listened from scripts, with fields mapping names to types such as `'Entity?'`.
+---@param name string
+---@param fields table<string, string>?
+---@return table
+function mk.event.define(name, fields) end
+
+---@class Diagnostic
+---@field level DiagnosticLevel (read-only)
+---@field message string (read-only)
+---@field sub_diagnostics SubDiagnostic[] (read-only)
+---@field traceback string? (read-only) The Lua stack traceback, for errors raised by scripts.
+---@field file string (read-only) The source file, or the script chunk for errors raised by scripts. Empty if unknown.
+---@field line integer (read-only)
+---@field column integer (read-only)
+
+---@class mk.event.Diagnostic
+---@field name string
+mk.event.Diagnostic = {}
+
+---@param handler fun(name: string, event: Diagnostic)
+---@return EventHandler
+function mk.event.Diagnostic.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.Diagnostic.unlisten(handler) end
+
+---@class KeyDown
+---@field key string (read-only)
+
+---@class mk.event.KeyDown
+---@field name string
+mk.event.KeyDown = {}
+
+---@param handler fun(name: string, event: KeyDown)
+---@return EventHandler
+function mk.event.KeyDown.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.KeyDown.unlisten(handler) end
+
+---@class KeyUp
+---@field key string (read-only)
+
+---@class mk.event.KeyUp
+---@field name string
+mk.event.KeyUp = {}
+
+---@param handler fun(name: string, event: KeyUp)
+---@return EventHandler
+function mk.event.KeyUp.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.KeyUp.unlisten(handler) end
+
+---@class TextInput
+---@field text string (read-only)
+
+---@class mk.event.TextInput
+---@field name string
+mk.event.TextInput = {}
+
+---@param handler fun(name: string, event: TextInput)
+---@return EventHandler
+function mk.event.TextInput.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.TextInput.unlisten(handler) end
+
+---@class TextComposition
+---@field text string (read-only)
+---@field caret integer? (read-only)
+
+---@class mk.event.TextComposition
+---@field name string
+mk.event.TextComposition = {}
+
+---@param handler fun(name: string, event: TextComposition)
+---@return EventHandler
+function mk.event.TextComposition.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.TextComposition.unlisten(handler) end
+
+--- Emitted when a rebinding started with `mk.input.rebind` ends. Only one of `key`, `mouse_button` and
+--- `gamepad_channel` is set, to the captured input; none is set if the rebinding has been cancelled.
+---@class InputRebound
+---@field action string (read-only)
+---@field trigger integer (read-only)
+---@field part string? (read-only)
+---@field key KeyCode? (read-only)
+---@field mouse_button MouseButton? (read-only)
+---@field gamepad_channel GamepadChannel? (read-only)
+---@field cancelled boolean (read-only)
+---@field rejected boolean (read-only) Whether the captured input could not be bound to the slot, e.g. a mouse button to a slot of a key axis.
+
+---@class mk.event.InputRebound
+---@field name string
+mk.event.InputRebound = {}
+
+---@param handler fun(name: string, event: InputRebound)
+---@return EventHandler
+function mk.event.InputRebound.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.InputRebound.unlisten(handler) end
+
+---@class GamepadConnected
+---@field gamepad integer (read-only)
+---@field name string (read-only)
+
+---@class mk.event.GamepadConnected
+---@field name string
+mk.event.GamepadConnected = {}
+
+---@param handler fun(name: string, event: GamepadConnected)
+---@return EventHandler
+function mk.event.GamepadConnected.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.GamepadConnected.unlisten(handler) end
+
+---@class GamepadDisconnected
+---@field gamepad integer (read-only)
+---@field name string (read-only)
+
+---@class mk.event.GamepadDisconnected
+---@field name string
+mk.event.GamepadDisconnected = {}
+
+---@param handler fun(name: string, event: GamepadDisconnected)
+---@return EventHandler
+function mk.event.GamepadDisconnected.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.GamepadDisconnected.unlisten(handler) end
+
+---@class PointerEnter
+
+---@class mk.event.PointerEnter
+---@field name string
+mk.event.PointerEnter = {}
+
+---@param handler fun(name: string, event: PointerEnter)
+---@return EventHandler
+function mk.event.PointerEnter.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.PointerEnter.unlisten(handler) end
+
+---@class PointerExit
+
+---@class mk.event.PointerExit
+---@field name string
+mk.event.PointerExit = {}
+
+---@param handler fun(name: string, event: PointerExit)
+---@return EventHandler
+function mk.event.PointerExit.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.PointerExit.unlisten(handler) end
+
+---@class PointerMove
+---@field pointer_x number (read-only)
+---@field pointer_y number (read-only)
+
+---@class mk.event.PointerMove
+---@field name string
+mk.event.PointerMove = {}
+
+---@param handler fun(name: string, event: PointerMove)
+---@return EventHandler
+function mk.event.PointerMove.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.PointerMove.unlisten(handler) end
+
+---@class PointerDown
+---@field button string (read-only)
+
+---@class mk.event.PointerDown
+---@field name string
+mk.event.PointerDown = {}
+
+---@param handler fun(name: string, event: PointerDown)
+---@return EventHandler
+function mk.event.PointerDown.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.PointerDown.unlisten(handler) end
+
+---@class PointerUp
+---@field button string (read-only)
+
+---@class mk.event.PointerUp
+---@field name string
+mk.event.PointerUp = {}
+
+---@param handler fun(name: string, event: PointerUp)
+---@return EventHandler
+function mk.event.PointerUp.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.PointerUp.unlisten(handler) end
+
+---@class PointerWheel
+---@field delta_x number (read-only)
+---@field delta_y number (read-only)
+
+---@class mk.event.PointerWheel
+---@field name string
+mk.event.PointerWheel = {}
+
+---@param handler fun(name: string, event: PointerWheel)
+---@return EventHandler
+function mk.event.PointerWheel.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.PointerWheel.unlisten(handler) end
+
+---@class PreUpdate
+---@field dt number (read-only)
+
+---@class mk.event.PreUpdate
+---@field name string
+mk.event.PreUpdate = {}
+
+---@param handler fun(name: string, event: PreUpdate)
+---@return EventHandler
+function mk.event.PreUpdate.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.PreUpdate.unlisten(handler) end
+
+--- Emitted zero or more times per frame, each time with the same fixed `dt`.
+---@class FixedUpdate
+---@field dt number (read-only)
+
+---@class mk.event.FixedUpdate
+---@field name string
+mk.event.FixedUpdate = {}
+
+---@param handler fun(name: string, event: FixedUpdate)
+---@return EventHandler
+function mk.event.FixedUpdate.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.FixedUpdate.unlisten(handler) end
+
+---@class Update
+---@field dt number (read-only)
+
+---@class mk.event.Update
+---@field name string
+mk.event.Update = {}
+
+---@param handler fun(name: string, event: Update)
+---@return EventHandler
+function mk.event.Update.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.Update.unlisten(handler) end
+
+---@class PostUpdate
+---@field dt number (read-only)
+
+---@class mk.event.PostUpdate
+---@field name string
+mk.event.PostUpdate = {}
+
+---@param handler fun(name: string, event: PostUpdate)
+---@return EventHandler
+function mk.event.PostUpdate.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.PostUpdate.unlisten(handler) end
+
+---@class TurnBegin
+---@field entity Entity (read-only)
+---@field turn integer (read-only)
+
+---@class mk.event.TurnBegin
+---@field name string
+mk.event.TurnBegin = {}
+
+---@param handler fun(name: string, event: TurnBegin)
+---@return EventHandler
+function mk.event.TurnBegin.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.TurnBegin.unlisten(handler) end
+
+---@class TurnEnd
+---@field entity Entity (read-only)
+---@field turn integer (read-only)
+---@field cost integer (read-only)
+
+---@class mk.event.TurnEnd
+---@field name string
+mk.event.TurnEnd = {}
+
+---@param handler fun(name: string, event: TurnEnd)
+---@return EventHandler
+function mk.event.TurnEnd.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.TurnEnd.unlisten(handler) end
+
+---@class PreRender
+---@field dt number (read-only)
+---@field alpha number (read-only)
+
+---@class mk.event.PreRender
+---@field name string
+mk.event.PreRender = {}
+
+---@param handler fun(name: string, event: PreRender)
+---@return EventHandler
+function mk.event.PreRender.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.PreRender.unlisten(handler) end
+
+---@class PostRender
+---@field dt number (read-only)
+
+---@class mk.event.PostRender
+---@field name string
+mk.event.PostRender = {}
+
+---@param handler fun(name: string, event: PostRender)
+---@return EventHandler
+function mk.event.PostRender.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.PostRender.unlisten(handler) end
+
+--- Emitted once when the window is closed, before the engine exits; the last chance to save the game.
+---@class Exit
+
+---@class mk.event.Exit
+---@field name string
+mk.event.Exit = {}
+
+---@param handler fun(name: string, event: Exit)
+---@return EventHandler
+function mk.event.Exit.listen(handler) end
+
+---@param handler EventHandler
+function mk.event.Exit.unlisten(handler) end
+
+mk.gfx = {}
+
+--- An RGBA color.
+---@class Color
+---@field r number The red channel, from 0 to 1.
+---@field g number The green channel, from 0 to 1.
+---@field b number The blue channel, from 0 to 1.
+---@field a number The alpha channel, from 0 to 1.
+local Color = {}
+
+---@class mk.gfx.Color
+mk.gfx.Color = {}
+
+--- An opaque color.
+---@param r number
+---@param g number
+---@param b number
+---@return Color
+function mk.gfx.Color.from_rgb(r, g, b) end
+
+---@param r number
+---@param g number
+---@param b number
+---@param a number
+---@return Color
+function mk.gfx.Color.from_rgba(r, g, b, a) end
+
+--- Parses a hex string such as `#RRGGBBAA`; raises if malformed.
+---@param hex string
+---@return Color
+function mk.gfx.Color.parse_hex(hex) end
+
+---@return Color
+function mk.gfx.Color.transparent() end
+
+---@return Color
+function mk.gfx.Color.black() end
+
+---@return Color
+function mk.gfx.Color.red() end
+
+---@return Color
+function mk.gfx.Color.green() end
+
+---@return Color
+function mk.gfx.Color.blue() end
+
+---@return Color
+function mk.gfx.Color.yellow() end
+
+---@return Color
+function mk.gfx.Color.magenta() end
+
+---@return Color
+function mk.gfx.Color.cyan() end
+
+---@return Color
+function mk.gfx.Color.white() end
+
+--- A bit mask of the layers a camera renders and a renderer is drawn on.
+---@class Layer
+local Layer = {}
+
+--- Returns the bits of the mask.
+---@return integer
+function Layer:get() end
+
+---@class mk.gfx.Layer
+mk.gfx.Layer = {}
+
+--- A mask from its bits.
+---@param layer integer
+---@return Layer
+function mk.gfx.Layer.new(layer) end
+
+--- A mask of no layers.
+---@return Layer
+function mk.gfx.Layer.none() end
+
+--- A mask of every layer.
+---@return Layer
+function mk.gfx.Layer.all() end
+
+--- Returns whether the two masks share a layer.
+---@param lhs Layer
+---@param rhs Layer
+---@return boolean
+function mk.gfx.Layer.has_overlap(lhs, rhs) end
+
+mk.input = {}
+
+--- Returns the action with the given name, or `nil` if there is none.
+---@param name string
+---@return InputAction?
+function mk.input.action(name) end
+
+--- Adds an action triggered by any of the given triggers, replacing the one of the same name.
+---@param name string
+---@param triggers table[]
+---@return InputAction
+function mk.input.add_action(name, triggers) end
+
+--- Removes an action. Returns whether it existed.
+---@param name string
+---@return boolean
+function mk.input.remove_action(name) end
+
+--- Applies the bindings of an asset. Returns whether it could be loaded.
+---@param path string
+---@return boolean
+function mk.input.load_bindings(path) end
+
+--- Binds the next pressed key, mouse button or gamepad channel to a trigger of an action; `trigger` starts from 1. Returns `false` if there is no such action.
+---@param name string
+---@param trigger integer
+---@param part string?
+---@return boolean
+function mk.input.rebind(name, trigger, part) end
+
+--- Cancels the rebinding in progress.
+function mk.input.cancel_rebind() end
+
+--- Returns whether a rebinding is in progress.
+---@return boolean
+function mk.input.is_rebinding() end
+
+--- Returns the names of the actions bound to an input, given as a `KeyCode` or a table of `key`, `mouse_button` or `gamepad_channel`.
+---@param input KeyCode|table
+---@return string[]
+function mk.input.actions_bound_to(input) end
+
+--- Returns the inputs bound to several actions, as tables of `actions` and one of `key`, `mouse_button` and `gamepad_channel`.
+---@return table[]
+function mk.input.conflicts() end
+
+--- Makes the current bindings the defaults restored by `reset_bindings`.
+function mk.input.set_default_bindings() end
+
+--- Restores the default bindings of an action, or of every action if no name is given. Returns `false` if there is no such action.
+---@param name string?
+---@return boolean
+function mk.input.reset_bindings(name) end
+
+--- Saves the current bindings to the user directory. Returns whether they could be saved.
+---@param name string
+---@return boolean
+function mk.input.save_bindings(name) end
+
+--- Applies the bindings saved with `save_bindings`. Returns whether they could be loaded.
+---@param name string
+---@return boolean
+function mk.input.load_user_bindings(name) end
+
+--- Returns the position of the mouse in the window, or `nil` if it is outside.
+---@return Vec2?
+function mk.input.mouse_position() end
+
+--- Returns the position of the mouse in the world seen by a camera, or `nil` if it is outside the window.
+---@param camera Entity
+---@return Vec2?
+function mk.input.mouse_world_position(camera) end
+
+--- Returns how far the mouse moved in the current frame.
+---@return Vec2
+function mk.input.mouse_delta() end
+
+--- Returns how far the mouse wheel scrolled in the current frame.
+---@return Vec2
+function mk.input.mouse_wheel() end
+
+--- Returns whether a mouse button is held.
+---@param button MouseButton
+---@return boolean
+function mk.input.is_mouse_down(button) end
+
+--- Returns whether a mouse button was pressed in the current frame.
+---@param button MouseButton
+---@return boolean
+function mk.input.is_mouse_pressed(button) end
+
+--- Returns whether a mouse button was released in the current frame.
+---@param button MouseButton
+---@return boolean
+function mk.input.is_mouse_released(button) end
+
+--- Returns whether the cursor is shown.
+---@return boolean
+function mk.input.is_cursor_visible() end
+
+--- Shows or hides the cursor.
+---@param visible boolean
+function mk.input.set_cursor_visible(visible) end
+
+--- Returns whether the cursor is locked to the window.
+---@return boolean
+function mk.input.is_cursor_locked() end
+
+--- Locks the cursor to the window or releases it.
+---@param locked boolean
+function mk.input.set_cursor_locked(locked) end
+
+--- Returns the connected gamepads, as tables of `id` and `name`.
+---@return table[]
+function mk.input.gamepads() end
+
+--- Returns the state of a channel of a gamepad, or `nil` if the gamepad is not connected.
+---@param id integer
+---@param channel GamepadChannel
+---@return boolean|number|Vec2|nil
+function mk.input.gamepad_channel(id, channel) end
+
+--- Returns the dead zones of the sticks and the triggers.
+---@return number, number
+function mk.input.dead_zones() end
+
+--- Sets the dead zones of the sticks and the triggers, each in range `[0, 1)`.
+---@param stick number
+---@param trigger number
+function mk.input.set_dead_zones(stick, trigger) end
+
+--- Rumbles a gamepad with a strength in range `[0, 1]`. Returns `false` if the gamepad is not connected.
+---@param id integer
+---@param strength number
+---@return boolean
+function mk.input.rumble(id, strength) end
+
+--- A reference to an action of the input map; its state is read when accessed.
+---@class InputAction
+---@field name string (read-only)
+---@field held boolean (read-only)
+---@field pressed boolean (read-only) `true` on the frame the action is pressed.
+---@field released boolean (read-only) `true` on the frame the action is released.
+---@field value number (read-only) The 1D reading of the strongest trigger.
+---@field vector Vec2 (read-only) The 2D reading of the strongest trigger; zero for keys.
+---@field triggers Trigger[] (read-only)
+local InputAction = {}
+
+---@class KeyCode
+
+---@class mk.input.KeyCode
+---@field Escape KeyCode
+---@field Space KeyCode
+---@field LeftShift KeyCode
+---@field LeftControl KeyCode
+---@field LeftAlt KeyCode
+---@field LeftSuper KeyCode
+---@field RightShift KeyCode
+---@field RightControl KeyCode
+---@field RightAlt KeyCode
+---@field RightSuper KeyCode
+---@field Num0 KeyCode
+---@field Num1 KeyCode
+---@field Num2 KeyCode
+---@field Num3 KeyCode
+---@field Num4 KeyCode
+---@field Num5 KeyCode
+---@field Num6 KeyCode
+---@field Num7 KeyCode
+---@field Num8 KeyCode
+---@field Num9 KeyCode
+---@field F1 KeyCode
+---@field F2 KeyCode
+---@field F3 KeyCode
+---@field F4 KeyCode
+---@field F5 KeyCode
+---@field F6 KeyCode
+---@field F7 KeyCode
+---@field F8 KeyCode
+---@field F9 KeyCode
+---@field F10 KeyCode
+---@field F11 KeyCode
+---@field F12 KeyCode
+---@field F13 KeyCode
+---@field F14 KeyCode
+---@field F15 KeyCode
+---@field F16 KeyCode
+---@field F17 KeyCode
+---@field F18 KeyCode
+---@field F19 KeyCode
+---@field F20 KeyCode
+---@field F21 KeyCode
+---@field F22 KeyCode
+---@field F23 KeyCode
+---@field F24 KeyCode
+---@field AlphaA KeyCode
+---@field AlphaB KeyCode
+---@field AlphaC KeyCode
+---@field AlphaD KeyCode
+---@field AlphaE KeyCode
+---@field AlphaF KeyCode
+---@field AlphaG KeyCode
+---@field AlphaH KeyCode
+---@field AlphaI KeyCode
+---@field AlphaJ KeyCode
+---@field AlphaK KeyCode
+---@field AlphaL KeyCode
+---@field AlphaM KeyCode
+---@field AlphaN KeyCode
+---@field AlphaO KeyCode
+---@field AlphaP KeyCode
+---@field AlphaQ KeyCode
+---@field AlphaR KeyCode
+---@field AlphaS KeyCode
+---@field AlphaT KeyCode
+---@field AlphaU KeyCode
+---@field AlphaV KeyCode
+---@field AlphaW KeyCode
+---@field AlphaX KeyCode
+---@field AlphaY KeyCode
+---@field AlphaZ KeyCode
+---@field PrintScreen KeyCode
+---@field ScrollLock KeyCode
+---@field Pause KeyCode
+---@field Insert KeyCode
+---@field Home KeyCode
+---@field PageUp KeyCode
+---@field Delete KeyCode
+---@field End KeyCode
+---@field PageDown KeyCode
+---@field Right KeyCode
+---@field Left KeyCode
+---@field Down KeyCode
+---@field Up KeyCode
+---@field NumLock KeyCode
+---@field NumpadDivide KeyCode
+---@field NumpadMultiply KeyCode
+---@field NumpadSubtract KeyCode
+---@field NumpadAdd KeyCode
+---@field NumpadEnter KeyCode
+---@field NumpadDecimal KeyCode
+---@field Numpad0 KeyCode
+---@field Numpad1 KeyCode
+---@field Numpad2 KeyCode
+---@field Numpad3 KeyCode
+---@field Numpad4 KeyCode
+---@field Numpad5 KeyCode
+---@field Numpad6 KeyCode
+---@field Numpad7 KeyCode
+---@field Numpad8 KeyCode
+---@field Numpad9 KeyCode
+---@field Grave KeyCode
+---@field Minus KeyCode
+---@field Equals KeyCode
+---@field Backspace KeyCode
+---@field Tab KeyCode
+---@field LeftBracket KeyCode
+---@field RightBracket KeyCode
+---@field Backslash KeyCode
+---@field CapsLock KeyCode
+---@field Semicolon KeyCode
+---@field Apostrophe KeyCode
+---@field Enter KeyCode
+---@field Comma KeyCode
+---@field Period KeyCode
+---@field Slash KeyCode
+mk.input.KeyCode = {}
+
+---@class MouseButton
+
+---@class mk.input.MouseButton
+---@field Left MouseButton
+---@field Right MouseButton
+---@field Middle MouseButton
+mk.input.MouseButton = {}
+
+--- A button, trigger or stick of a gamepad, named after the Xbox layout.
+---@class GamepadChannel
+
+---@class mk.input.GamepadChannel
+---@field South GamepadChannel
+---@field East GamepadChannel
+---@field North GamepadChannel
+---@field West GamepadChannel
+---@field LeftBumper GamepadChannel
+---@field RightBumper GamepadChannel
+---@field LeftTrigger GamepadChannel
+---@field RightTrigger GamepadChannel
+---@field Select GamepadChannel
+---@field Start GamepadChannel
+---@field Mode GamepadChannel
+---@field LeftThumb GamepadChannel
+---@field RightThumb GamepadChannel
+---@field DPadUp GamepadChannel
+---@field DPadDown GamepadChannel
+---@field DPadLeft GamepadChannel
+---@field DPadRight GamepadChannel
+---@field LeftStick GamepadChannel
+---@field RightStick GamepadChannel
+mk.input.GamepadChannel = {}
+
+mk.prefab = {}
+
+--- Builds the entity tree of a prefab, with the overrides merged into its template. Returns the root.
+---@param name string
+---@param overrides table?
+---@return Entity
+function mk.prefab.instantiate(name, overrides) end
+
+--- Returns a copy of the template of a prefab.
+---@param name string
+---@return table
+function mk.prefab.template(name) end
+
+--- Forgets the cached template of a prefab, or of every prefab if no name is given.
+---@param name string?
+function mk.prefab.reload(name) end
+
+mk.save = {}
+
+--- Sets the `version`, `compress` and `key` options of the saves.
+---@param options table
+function mk.save.configure(options) end
+
+--- Adds the migration of the values saved at `version` to the next version. It may modify the values in place or return new ones.
+---@param version integer
+---@param migration fun(values: table): table?
+function mk.save.add_migration(version, migration) end
+
+--- Saves the values to a slot, with the whole scene if `scene` is `true` or the tree of the given entity. Returns whether it could be saved.
+---@param slot string
+---@param values table
+---@param scene boolean|Entity|nil
+---@return boolean
+function mk.save.save(slot, values, scene) end
+
+--- Loads a slot, instantiating its scene under `parent`. Returns the values and the roots of the scene, or nothing if it could not be loaded.
+---@param slot string
+---@param parent Entity?
+---@return table?, Entity[]?
+function mk.save.load(slot, parent) end
+
+--- Returns whether a slot has been saved.
+---@param slot string
+---@return boolean
+function mk.save.exists(slot) end
+
+--- Deletes a slot. Returns whether it existed.
+---@param slot string
+---@return boolean
+function mk.save.delete(slot) end
+
+--- Returns the names of the saved slots, sorted.
+---@return string[]
+function mk.save.slots() end
+
+mk.scene = {}
+
+--- Saves the tree of `root`, or the whole scene, to the user directory. Returns whether it could be saved.
+---@param name string
+---@param root Entity?
+---@return boolean
+function mk.scene.save(name, root) end
+
+--- Instantiates a scene saved with `save` under `parent`. Returns its roots, or `nil` if it could not be loaded.
+---@param name string
+---@param parent Entity?
+---@return Entity[]?
+function mk.scene.load(name, parent) end
+
+--- Returns the tree of `root`, or the whole scene, as JSON.
+---@param root Entity?
+---@return string
+function mk.scene.to_json(root) end
+
+--- Instantiates a scene from JSON under `parent`. Returns its roots, or `nil` if it could not be loaded.
+---@param json string
+---@param parent Entity?
+---@return Entity[]?
+function mk.scene.from_json(json, parent) end
+
+mk.structure = {}
+
+---@class Mat22
+local Mat22 = {}
+
+---@return number[]
+function Mat22:elements() end
+
+---@param rhs Mat22
+function Mat22:set(rhs) end
+
+---@param index integer
+---@return Vec2
+function Mat22:row(index) end
+
+---@param index integer
+---@return Vec2
+function Mat22:column(index) end
+
+---@return number
+function Mat22:determinant() end
+
+function Mat22:inverse() end
+
+---@return Mat22
+function Mat22:inversed() end
+
+function Mat22:transpose() end
+
+---@return Mat22
+function Mat22:transposed() end
+
+---@return number[]
+function Mat22:into_elements() end
+
+---@class mk.structure.Mat22
+mk.structure.Mat22 = {}
+
+---@param elements number[]
+---@return Mat22
+function mk.structure.Mat22.new(elements) end
+
+---@return Mat22
+function mk.structure.Mat22.zero() end
+
+---@return Mat22
+function mk.structure.Mat22.identity() end
+
+---@class Mat33
+---@field elements number[]
+local Mat33 = {}
+
+---@return number[]
+function Mat33:elements() end
+
+---@param rhs Mat33
+function Mat33:set(rhs) end
+
+---@param index integer
+---@return Vec3
+function Mat33:row(index) end
+
+---@param index integer
+---@return Vec3
+function Mat33:column(index) end
+
+---@return number
+function Mat33:determinant() end
+
+---@return Mat33
+function Mat33:inverse() end
+
+---@return Mat33
+function Mat33:inversed() end
+
+---@return Mat33
+function Mat33:transpose() end
+
+---@return Mat33
+function Mat33:transposed() end
+
+---@return number[]
+function Mat33:into_elements() end
+
+---@class mk.structure.Mat33
+mk.structure.Mat33 = {}
+
+---@param elements number[]
+---@return Mat33
+function mk.structure.Mat33.new(elements) end
+
+---@return Mat33
+function mk.structure.Mat33.zero() end
+
+---@return Mat33
+function mk.structure.Mat33.identity() end
+
+---@param t Vec2
+---@return Mat33
+function mk.structure.Mat33.affine_translation(t) end
+
+---@param angle_degrees number
+---@return Mat33
+function mk.structure.Mat33.affine_rotation(angle_degrees) end
+
+---@param s Vec2
+---@return Mat33
+function mk.structure.Mat33.affine_scale(s) end
+
+---@param t Vec2
+---@param angle_degrees number
+---@param s Vec2
+---@return Mat33
+function mk.structure.Mat33.affine_srt(t, angle_degrees, s) end
+
+---@param t Vec2
+---@param angle_degrees number
+---@param s Vec2
+---@return Mat33
+function mk.structure.Mat33.affine_trs(t, angle_degrees, s) end
+
+---@class Size
+---@field width number
+---@field height number
+local Size = {}
+
+---@return number
+function Size:area() end
+
+---@class mk.structure.Size
+mk.structure.Size = {}
+
+---@param width number
+---@param height number
+---@return Size
+function mk.structure.Size.new(width, height) end
+
+---@return Size
+function mk.structure.Size.zero() end
+
+---@return Size
+function mk.structure.Size.one() end
+
+---@class Vec2
+---@field x number
+---@field y number
+local Vec2 = {}
+
+---@return number
+function Vec2:len() end
+
+---@return number
+function Vec2:len_square() end
+
+---@return Vec2
+function Vec2:norm() end
+
+---@param z number
+---@return Vec3
+function Vec2:to_vec3(z) end
+
+---@class mk.structure.Vec2
+mk.structure.Vec2 = {}
+
+---@param x number
+---@param y number
+---@return Vec2
+function mk.structure.Vec2.new(x, y) end
+
+---@param lhs Vec2
+---@param rhs Vec2
+---@return number
+function mk.structure.Vec2.distance(lhs, rhs) end
+
+---@param lhs Vec2
+---@param rhs Vec2
+---@return number
+function mk.structure.Vec2.distance_square(lhs, rhs) end
+
+---@param lhs Vec2
+---@param rhs Vec2
+---@return number
+function mk.structure.Vec2.dot(lhs, rhs) end
+
+---@param lhs Vec2
+---@param normal Vec2
+---@return Vec2
+function mk.structure.Vec2.project(lhs, normal) end
+
+---@param lhs Vec2
+---@param normal Vec2
+---@return number
+function mk.structure.Vec2.projected_len(lhs, normal) end
+
+---@param from Vec2
+---@param to Vec2
+---@return number
+function mk.structure.Vec2.angle(from, to) end
+
+---@param from Vec2
+---@param to Vec2
+---@return number
+function mk.structure.Vec2.angle_signed(from, to) end
+
+---@param lhs Vec2
+---@return Vec2
+function mk.structure.Vec2.perpendicular(lhs) end
+
+---@param lhs Vec2
+---@param normal Vec2
+---@return Vec2
+function mk.structure.Vec2.reflect(lhs, normal) end
+
+---@param from Vec2
+---@param to Vec2
+---@param t number
+---@return Vec2
+function mk.structure.Vec2.lerp(from, to, t) end
+
+---@param from Vec2
+---@param to Vec2
+---@param t number
+---@return Vec2
+function mk.structure.Vec2.lerp_unclamped(from, to, t) end
+
+---@param lhs Vec2
+---@return Vec2
+function mk.structure.Vec2.floor(lhs) end
+
+---@param lhs Vec2
+---@return Vec2
+function mk.structure.Vec2.round(lhs) end
+
+---@param lhs Vec2
+---@return Vec2
+function mk.structure.Vec2.ceil(lhs) end
+
+---@param lhs Vec2
+---@return Vec2
+function mk.structure.Vec2.abs(lhs) end
+
+---@param lhs Vec2
+---@return Vec2
+function mk.structure.Vec2.fract(lhs) end
+
+---@param lhs Vec2
+---@param n integer
+---@return Vec2
+function mk.structure.Vec2.powi(lhs, n) end
+
+---@param lhs Vec2
+---@param n number
+---@return Vec2
+function mk.structure.Vec2.powf(lhs, n) end
+
+---@param lhs Vec2
+---@return Vec2
+function mk.structure.Vec2.sqrt(lhs) end
+
+---@param lhs Vec2
+---@return Vec2
+function mk.structure.Vec2.exp(lhs) end
+
+---@param lhs Vec2
+---@return Vec2
+function mk.structure.Vec2.exp2(lhs) end
+
+---@param lhs Vec2
+---@return Vec2
+function mk.structure.Vec2.ln(lhs) end
+
+---@param lhs Vec2
+---@param base number
+---@return Vec2
+function mk.structure.Vec2.log(lhs, base) end
+
+---@param lhs Vec2
+---@return Vec2
+function mk.structure.Vec2.log2(lhs) end
+
+---@param lhs Vec2
+---@return Vec2
+function mk.structure.Vec2.log10(lhs) end
+
+---@param lhs Vec2
+---@param rhs Vec2
+---@return Vec2
+function mk.structure.Vec2.min(lhs, rhs) end
+
+---@param lhs Vec2
+---@param rhs Vec2
+---@return Vec2
+function mk.structure.Vec2.max(lhs, rhs) end
+
+---@param lhs Vec2
+---@param angle_degrees number
+---@return Vec2
+function mk.structure.Vec2.rotate(lhs, angle_degrees) end
+
+---@return Vec2
+function mk.structure.Vec2.zero() end
+
+---@return Vec2
+function mk.structure.Vec2.one() end
+
+---@return Vec2
+function mk.structure.Vec2.left() end
+
+---@return Vec2
+function mk.structure.Vec2.right() end
+
+---@return Vec2
+function mk.structure.Vec2.up() end
+
+---@return Vec2
+function mk.structure.Vec2.down() end
+
+---@class Vec3
+---@field x number
+---@field y number
+---@field z number
+local Vec3 = {}
+
+---@return number
+function Vec3:len() end
+
+---@return number
+function Vec3:len_square() end
+
+---@return Vec3
+function Vec3:norm() end
+
+---@return Vec2
+function Vec3:to_vec2() end
+
+---@class mk.structure.Vec3
+mk.structure.Vec3 = {}
+
+---@param x number
+---@param y number
+---@param z number
+---@return Vec3
+function mk.structure.Vec3.new(x, y, z) end
+
+---@param lhs Vec3
+---@param rhs Vec3
+---@return number
+function mk.structure.Vec3.distance(lhs, rhs) end
+
+---@param lhs Vec3
+---@param rhs Vec3
+---@return number
+function mk.structure.Vec3.distance_square(lhs, rhs) end
+
+---@param lhs Vec3
+---@param rhs Vec3
+---@return number
+function mk.structure.Vec3.dot(lhs, rhs) end
+
+---@param lhs Vec3
+---@param rhs Vec3
+---@return Vec3
+function mk.structure.Vec3.cross(lhs, rhs) end
+
+---@param lhs Vec3
+---@param normal Vec3
+---@return Vec3
+function mk.structure.Vec3.project(lhs, normal) end
+
+---@param lhs Vec3
+---@param normal Vec3
+---@return number
+function mk.structure.Vec3.projected_len(lhs, normal) end
+
+---@param from Vec3
+---@param to Vec3
+---@return number
+function mk.structure.Vec3.angle(from, to) end
+
+---@param from Vec3
+---@param to Vec3
+---@param normal Vec3
+---@return number
+function mk.structure.Vec3.angle_signed(from, to, normal) end
+
+---@param lhs Vec3
+---@param normal Vec3
+---@return Vec3
+function mk.structure.Vec3.reflect(lhs, normal) end
+
+---@param from Vec3
+---@param to Vec3
+---@param t number
+---@return Vec3
+function mk.structure.Vec3.lerp(from, to, t) end
+
+---@param from Vec3
+---@param to Vec3
+---@param t number
+---@return Vec3
+function mk.structure.Vec3.lerp_unclamped(from, to, t) end
+
+---@param lhs Vec3
+---@return Vec3
+function mk.structure.Vec3.floor(lhs) end
+
+---@param lhs Vec3
+---@return Vec3
+function mk.structure.Vec3.round(lhs) end
+
+---@param lhs Vec3
+---@return Vec3
+function mk.structure.Vec3.ceil(lhs) end
+
+---@param lhs Vec3
+---@return Vec3
+function mk.structure.Vec3.abs(lhs) end
+
+---@param lhs Vec3
+---@return Vec3
+function mk.structure.Vec3.fract(lhs) end
+
+---@param lhs Vec3
+---@param n integer
+---@return Vec3
+function mk.structure.Vec3.powi(lhs, n) end
+
+---@param lhs Vec3
+---@param n number
+---@return Vec3
+function mk.structure.Vec3.powf(lhs, n) end
+
+---@param lhs Vec3
+---@return Vec3
+function mk.structure.Vec3.sqrt(lhs) end
+
+---@param lhs Vec3
+---@return Vec3
+function mk.structure.Vec3.exp(lhs) end
+
+---@param lhs Vec3
+---@return Vec3
+function mk.structure.Vec3.exp2(lhs) end
+
+---@param lhs Vec3
+---@return Vec3
+function mk.structure.Vec3.ln(lhs) end
+
+---@param lhs Vec3
+---@param base number
+---@return Vec3
+function mk.structure.Vec3.log(lhs, base) end
+
+---@param lhs Vec3
+---@return Vec3
+function mk.structure.Vec3.log2(lhs) end
+
+---@param lhs Vec3
+---@return Vec3
+function mk.structure.Vec3.log10(lhs) end
+
+---@param lhs Vec3
+---@param rhs Vec3
+---@return Vec3
+function mk.structure.Vec3.min(lhs, rhs) end
+
+---@param lhs Vec3
+---@param rhs Vec3
+---@return Vec3
+function mk.structure.Vec3.max(lhs, rhs) end
+
+---@return Vec3
+function mk.structure.Vec3.zero() end
+
+---@return Vec3
+function mk.structure.Vec3.zero_one() end
+
+---@return Vec3
+function mk.structure.Vec3.one() end
+
+---@return Vec3
+function mk.structure.Vec3.left() end
+
+---@return Vec3
+function mk.structure.Vec3.right() end
+
+---@return Vec3
+function mk.structure.Vec3.up() end
+
+---@return Vec3
+function mk.structure.Vec3.down() end
+
+---@return Vec3
+function mk.structure.Vec3.forward() end
+
+---@return Vec3
+function mk.structure.Vec3.backward() end
+
+mk.system = {}
+
+--- Adds a system calling `run` with each entity matching its query, from a table of `name`, `query`, `run` and optionally `stage`, `without`, `before` and `after`.
+---@param params table
+function mk.system.add(params) end
+
+--- Removes a system. Returns whether it existed.
+---@param name string
+---@return boolean
+function mk.system.remove(name) end
+
+--- Returns the names of the systems of a stage, in the order they run.
+---@param stage SystemStage
+---@return string[]
+function mk.system.names(stage) end
+
+--- The points of a frame at which registered systems run.
+---@class SystemStage
+
+---@class mk.system.SystemStage
+---@field PreUpdate SystemStage
+---@field Update SystemStage
+---@field PostUpdate SystemStage
+---@field PreRender SystemStage
+mk.system.SystemStage = {}
+
+mk.task = {}
+
+--- Runs a function as a task, which can wait with the other functions of `mk.task`.
+---@param callback function
+---@return TaskHandle
+function mk.task.spawn(callback) end
+
+--- Waits for the given seconds of the scaled clock.
+---@param seconds number
+function mk.task.wait(seconds) end
+
+--- Waits for the given number of frames, `1` by default.
+---@param frames integer?
+function mk.task.wait_frames(frames) end
+
+--- Waits for the next event of the given table or name. Returns its parameters.
+---@param event table|string
+---@return table
+function mk.task.wait_event(event) end
+
+--- Waits until the predicate returns `true`, checked once per frame.
+---@param predicate fun(): boolean
+function mk.task.wait_until(predicate) end
+
+--- Refers to a task spawned with `mk.task.spawn`. Stays valid after the task has ended.
+---@class TaskHandle
+---@field is_running boolean (read-only) Whether the task has neither finished nor been cancelled.
+local TaskHandle = {}
+
+--- Stops the task. Returns whether it was still running.
+---@return boolean
+function TaskHandle:cancel() end
+
+mk.time = {}
+
+--- Calls a function once after the given delay, in seconds of the scaled clock unless `is_unscaled`.
+---@param delay number
+---@param callback function
+---@param is_unscaled boolean?
+---@return TimerHandle
+function mk.time.after(delay, callback, is_unscaled) end
+
+--- Calls a function repeatedly at the given interval, in seconds of the scaled clock unless `is_unscaled`.
+---@param interval number
+---@param callback function
+---@param is_unscaled boolean?
+---@return TimerHandle
+function mk.time.every(interval, callback, is_unscaled) end
+
+--- Refers to a timer scheduled with `mk.time.after` or `mk.time.every`. Stays valid after the timer has ended.
+---@class TimerHandle
+---@field is_active boolean (read-only) Whether the timer will still fire.
+local TimerHandle = {}
+
+--- Stops the timer. Returns whether it was still active.
+---@return boolean
+function TimerHandle:cancel() end
+
+--- The clocks of the engine. Scaled time follows the time scale and stops while paused.
+---@class Time
+local Time = {}
+
+---@class mk.time.Time
+mk.time.Time = {}
+
+--- Returns the scaled time elapsed.
+---@return number
+function mk.time.Time.time() end
+
+--- Returns the scaled duration of the last frame; `0` while paused.
+---@return number
+function mk.time.Time.dt() end
+
+--- Returns the real time elapsed, unaffected by the time scale and pausing.
+---@return number
+function mk.time.Time.unscaled_time() end
+
+--- Returns the real duration of the last frame, unaffected by the time scale and pausing.
+---@return number
+function mk.time.Time.unscaled_dt() end
+
+--- Returns how fast the scaled clock runs relative to the real time.
+---@return number
+function mk.time.Time.scale() end
+
+--- Sets how fast the scaled clock runs relative to the real time, e.g. `0.5` for slow motion.
+---@param scale number
+function mk.time.Time.set_scale(scale) end
+
+--- Returns whether the scaled clock is stopped.
+---@return boolean
+function mk.time.Time.is_paused() end
+
+--- Stops or resumes the scaled clock. Fixed steps are not run while paused.
+---@param is_paused boolean
+function mk.time.Time.set_paused(is_paused) end
+
+--- Returns the delta of a fixed step, or `nil` if fixed steps are disabled.
+---@return number?
+function mk.time.Time.fixed_dt() end
+
+--- Enables fixed steps at the given rate in Hz, or disables them with `nil`.
+---@param rate number?
+function mk.time.Time.set_fixed_rate(rate) end
+
+--- Returns the maximum number of fixed steps run in a frame.
+---@return integer
+function mk.time.Time.max_fixed_steps() end
+
+--- Sets the maximum number of fixed steps run in a frame.
+---@param max_fixed_steps integer
+function mk.time.Time.set_max_fixed_steps(max_fixed_steps) end
+
+--- Returns how far the current frame is between the last fixed step and the next one, in range `[0, 1)`. Always `1` if fixed steps are disabled.
+---@return number
+function mk.time.Time.alpha() end
+
+--- Returns the frame rate cap in frames per second, or `nil` if the frame rate is not capped.
+---@return number?
+function mk.time.Time.frame_rate_cap() end
+
+--- Caps the frame rate to the given frames per second, or uncaps it with `nil`.
+---@param fps number?
+function mk.time.Time.set_frame_rate_cap(fps) end
+
+mk.turn = {}
+
+--- Registers an entity taking turns with no energy. Returns `false` if it is already registered.
+---@param entity Entity
+---@param speed integer
+---@return boolean
+function mk.turn.add_actor(entity, speed) end
+
+--- Unregisters an entity. Returns whether it was registered.
+---@param entity Entity
+---@return boolean
+function mk.turn.remove_actor(entity) end
+
+--- Returns whether an entity is registered.
+---@param entity Entity
+---@return boolean
+function mk.turn.has_actor(entity) end
+
+--- Returns the speed of an entity, or `nil` if it is not registered.
+---@param entity Entity
+---@return integer?
+function mk.turn.speed(entity) end
+
+--- Sets the speed of an entity. Returns whether it is registered.
+---@param entity Entity
+---@param speed integer
+---@return boolean
+function mk.turn.set_speed(entity, speed) end
+
+--- Returns the energy of an entity, or `nil` if it is not registered.
+---@param entity Entity
+---@return integer?
+function mk.turn.energy(entity) end
+
+--- Returns the entity taking its turn, if any.
+---@return Entity?
+function mk.turn.current() end
+
+--- Returns the number of turns taken.
+---@return integer
+function mk.turn.turn() end
+
+--- Ends the current turn, spending `cost` energy, `ENERGY_THRESHOLD` by default. Returns the entity whose turn ended.
+---@param cost integer?
+---@return Entity?
+function mk.turn.end_turn(cost) end
+
+--- Returns whether turns are paused.
+---@return boolean
+function mk.turn.is_paused() end
+
+--- Pauses or resumes turns.
+---@param is_paused boolean
+function mk.turn.set_paused(is_paused) end
+
+--- Emitted on an entity once it has been built, propagating through its parents.
+---@class OnCreate
+
+--- Emitted on an entity and each of its children before they are destroyed. It does not propagate to their parents.
+---@class OnDestroy
+
+--- Emitted on an element when the mouse enters it. Unlike other UI events, it does not propagate.
+---@class UIMouseEnter
+---@field mouse_position Vec2 (read-only)
+
+--- Emitted on an element when the mouse exits it. Unlike other UI events, it does not propagate.
+---@class UIMouseExit
+
+--- Emitted on the element under the mouse when it moves, propagating through its parents.
+---@class UIMouseMove
+---@field mouse_position Vec2 (read-only)
+
+--- Emitted on the element under the mouse on a button press, propagating through its parents.
+---@class UIMouseDown
+---@field mouse_position Vec2 (read-only)
+---@field mouse_button string (read-only)
+
+--- Emitted on the element under the mouse on a button release, propagating through its parents.
+---@class UIMouseUp
+---@field mouse_position Vec2 (read-only)
+---@field mouse_button string (read-only)
+
+--- Emitted on an element when it starts being dragged, propagating through its parents.
+---@class UIDragBegin
+---@field mouse_position Vec2 (read-only)
+---@field mouse_button string (read-only)
+
+--- Emitted on the dragged element when the drag ends, propagating through its parents.
+---@class UIDragEnd
+
+--- Emitted on the element a dragged element is dropped on, propagating through its parents.
+---@class UIDragDrop
+---@field from Entity (read-only)
+---@field mouse_position Vec2 (read-only)
+---@field mouse_button string (read-only)
+
+--- Emitted on an element when it gains the focus, propagating through its parents.
+---@class UIFocusIn
+
+--- Emitted on an element when it loses the focus, propagating through its parents.
+---@class UIFocusOut
+
+--- Emitted on a text field when its text changes, propagating through its parents.
+---@class UITextChange
+---@field text string (read-only)
+
+--- Emitted on a text field when its text is submitted, propagating through its parents.
+---@class UITextSubmit
+---@field text string (read-only)
+
+---@alias EntityEventName
+---| '"entity-create"' # OnCreate
+---| '"entity-destroy"' # OnDestroy
+---| '"ui-mouse-enter"' # UIMouseEnter
+---| '"ui-mouse-exit"' # UIMouseExit
+---| '"ui-mouse-move"' # UIMouseMove
+---| '"ui-mouse-down"' # UIMouseDown
+---| '"ui-mouse-up"' # UIMouseUp
+---| '"ui-drag-begin"' # UIDragBegin
+---| '"ui-drag-end"' # UIDragEnd
+---| '"ui-drag-drop"' # UIDragDrop
+---| '"ui-focus-in"' # UIFocusIn
+---| '"ui-focus-out"' # UIFocusOut
+---| '"ui-text-change"' # UITextChange
+---| '"ui-text-submit"' # UITextSubmit